@@ -314,10 +314,72 @@ pub fn chunk_document(
     Ok(chunks)
 }
 
+/// Parse the chunk index from a `"source_path#chunk_index"` chunk ID.
+pub fn chunk_index_from_id(chunk_id: &str) -> Option<usize> {
+    chunk_id.rsplit_once('#')?.1.parse().ok()
+}
+
+/// Shortest shared text treated as sub-split overlap when joining chunks.
+/// Shorter matches (a shared trailing letter, say) are coincidental.
+const MIN_OVERLAP_CHARS: usize = 32;
+
+/// Concatenate the content of two consecutive chunks, dropping the longest
+/// suffix of `a` that `b` starts with (sub-split windows overlap by
+/// `overlap_tokens`). Chunks without such overlap are joined by a blank line.
+pub fn join_chunk_content(a: &str, b: &str) -> String {
+    let max = a.len().min(b.len());
+    let overlap = (MIN_OVERLAP_CHARS..=max)
+        .rev()
+        .filter(|&n| b.is_char_boundary(n) && a.is_char_boundary(a.len() - n))
+        .find(|&n| a.ends_with(&b[..n]))
+        .unwrap_or(0);
+    if overlap > 0 {
+        format!("{a}{}", &b[overlap..])
+    } else {
+        format!("{a}\n\n{b}")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn chunk_index_from_id_parses_suffix() {
+        assert_eq!(chunk_index_from_id("notes/a#b.md#12"), Some(12));
+        assert_eq!(chunk_index_from_id("a.md"), None);
+        assert_eq!(chunk_index_from_id("a.md#x"), None);
+    }
+
+    #[test]
+    fn join_chunk_content_drops_shared_text() {
+        let shared = "the overlapping window of tokens ";
+        let a = format!("first window, then {shared}");
+        let b = format!("{shared}and the rest");
+        assert_eq!(
+            join_chunk_content(&a, &b),
+            format!("first window, then {shared}and the rest")
+        );
+        // A coincidental short match is not treated as overlap.
+        assert_eq!(
+            join_chunk_content("second part", "third part"),
+            "second part\n\nthird part"
+        );
+    }
+
+    #[test]
+    fn join_chunk_content_of_real_sub_splits_restores_section() {
+        let section: String = (0..200).map(|i| format!("word{i} ")).collect();
+        let chunks = chunk_document(&make_file(&section, vec![]), 100, 20).unwrap();
+        assert!(chunks.len() > 1 && chunks.iter().all(|c| c.is_sub_split));
+        let joined = chunks[1..]
+            .iter()
+            .fold(chunks[0].content.clone(), |acc, c| {
+                join_chunk_content(&acc, &c.content)
+            });
+        assert_eq!(joined.trim(), section.trim());
+    }
+
     #[test]
     fn count_tokens_empty() {
         assert_eq!(count_tokens(""), 0);
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -5,7 +6,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::clustering::CustomClusterDef;
 use crate::error::Error;
-use crate::search::SearchMode;
+use crate::fts::{LexicalLanguage, LexicalSettings};
+use crate::search::{FusionMethod, SearchMode};
 
 // ---------------------------------------------------------------------------
 // YAML configuration types (intermediate deserialization target)
@@ -24,6 +26,8 @@ pub struct YamlConfig {
     pub watch: YamlWatch,
     pub index: YamlIndex,
     pub sources: YamlSources,
+    pub rerank: YamlRerank,
+    pub cache: YamlCache,
 }
 
 /// Embedding provider settings.
@@ -215,12 +219,21 @@ pub struct YamlSearch {
     pub min_score: f64,
     pub mode: String,
     pub rrf_k: f64,
+    /// Hybrid fusion method: `rrf` (default) or `linear`.
+    pub fusion: String,
+    /// Semantic weight α in [0.0, 1.0] for linear fusion; lexical weight is `1 - α`.
+    pub semantic_weight: f64,
     pub bm25_norm_k: f64,
     pub boost_links: bool,
     pub boost_hops: usize,
     pub expand_graph: usize,
     pub expand_limit: usize,
+    /// Default MMR lambda in [0.0, 1.0]; `None` disables diversification.
+    pub diversity: Option<f64>,
+    /// Default cap on results per file; `None` = unlimited.
+    pub max_per_file: Option<usize>,
     pub decay: YamlDecay,
+    pub lexical: YamlLexical,
 }
 
 impl Default for YamlSearch {
@@ -230,12 +243,43 @@ impl Default for YamlSearch {
             min_score: 0.0,
             mode: "hybrid".to_string(),
             rrf_k: 60.0,
+            fusion: "rrf".to_string(),
+            semantic_weight: 0.7,
             bm25_norm_k: 1.5,
             boost_links: false,
             boost_hops: 1,
             expand_graph: 0,
             expand_limit: 3,
+            diversity: None,
+            max_per_file: None,
             decay: YamlDecay::default(),
+            lexical: YamlLexical::default(),
+        }
+    }
+}
+
+/// Lexical (BM25) search settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YamlLexical {
+    /// Default text analysis language (`english`, `german`, `de`, `cjk`, ...).
+    pub language: String,
+    /// Path scope → language overrides; a note's frontmatter `lang` wins.
+    pub paths: BTreeMap<String, String>,
+    /// Remove stopwords for languages with a built-in stopword list.
+    pub stopwords: bool,
+    /// Per-field score multipliers keyed by `content`, `heading`,
+    /// `frontmatter` (all frontmatter values), or a frontmatter key.
+    pub boosts: BTreeMap<String, f32>,
+}
+
+impl Default for YamlLexical {
+    fn default() -> Self {
+        Self {
+            language: "english".to_string(),
+            paths: BTreeMap::new(),
+            stopwords: false,
+            boosts: BTreeMap::new(),
         }
     }
 }
@@ -374,6 +418,11 @@ pub struct YamlIndex {
     pub edge_embeddings: bool,
     pub edge_boost_weight: f64,
     pub edge_cluster_rebalance: usize,
+    pub wal_max_bytes: u64,
+    pub wal_max_age_secs: u64,
+    pub rescore_factor: usize,
+    pub snapshot_retention: usize,
+    pub snapshot_before_reindex: bool,
 }
 
 impl Default for YamlIndex {
@@ -384,6 +433,54 @@ impl Default for YamlIndex {
             edge_embeddings: true,
             edge_boost_weight: 0.15,
             edge_cluster_rebalance: 50,
+            wal_max_bytes: crate::index::storage::DEFAULT_WAL_MAX_BYTES,
+            wal_max_age_secs: crate::index::storage::DEFAULT_WAL_MAX_AGE_SECS,
+            rescore_factor: crate::index::storage::DEFAULT_RESCORE_FACTOR,
+            snapshot_retention: 5,
+            snapshot_before_reindex: true,
+        }
+    }
+}
+
+/// Reranker settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YamlRerank {
+    pub provider: String,
+    pub model: Option<String>,
+    pub endpoint: Option<String>,
+    pub top_n: usize,
+}
+
+impl Default for YamlRerank {
+    fn default() -> Self {
+        Self {
+            provider: "none".to_string(),
+            model: None,
+            endpoint: None,
+            top_n: 50,
+        }
+    }
+}
+
+/// On-disk embedding cache settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YamlCache {
+    /// Maximum cached query embeddings; 0 disables the query cache.
+    pub query_max_entries: usize,
+    /// Seconds before a cached query embedding expires; 0 = never.
+    pub query_ttl_secs: u64,
+    /// Reuse document embeddings by exact chunk text across re-ingests.
+    pub chunk_embeddings: bool,
+}
+
+impl Default for YamlCache {
+    fn default() -> Self {
+        Self {
+            query_max_entries: 1000,
+            query_ttl_secs: 7 * 24 * 60 * 60,
+            chunk_embeddings: true,
         }
     }
 }
@@ -440,11 +537,62 @@ impl FromStr for EmbeddingProviderType {
     }
 }
 
+/// Supported reranker backends.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum RerankProviderType {
+    /// Reranking disabled (default).
+    None,
+    /// Cohere-style `/rerank` API (also Jina and Voyage).
+    Cohere,
+    /// Hugging Face text-embeddings-inference `/rerank`.
+    Tei,
+    Mock,
+}
+
+impl FromStr for RerankProviderType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "" => Ok(Self::None),
+            "cohere" | "jina" | "voyage" => Ok(Self::Cohere),
+            "tei" => Ok(Self::Tei),
+            "mock" => Ok(Self::Mock),
+            other => Err(Error::Config(format!(
+                "unknown rerank provider '{other}': expected none, cohere, jina, voyage, tei, or mock"
+            ))),
+        }
+    }
+}
+
 /// Supported vector quantization types for the HNSW index.
+///
+/// `I8` and `B1` (one sign bit per dimension) keep full-precision copies of
+/// the vectors in a sidecar file next to the index, used for rescoring and
+/// wherever exact vectors are read back.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum VectorQuantization {
     F16,
     F32,
+    I8,
+    B1,
+}
+
+impl VectorQuantization {
+    /// Bytes one vector of `dimensions` takes in the HNSW index.
+    pub fn bytes_per_vector(&self, dimensions: usize) -> usize {
+        match self {
+            Self::F32 => dimensions * 4,
+            Self::F16 => dimensions * 2,
+            Self::I8 => dimensions,
+            Self::B1 => dimensions.div_ceil(8),
+        }
+    }
+
+    /// Whether full-precision vectors are kept in a sidecar file.
+    pub fn keeps_full_precision(&self) -> bool {
+        matches!(self, Self::I8 | Self::B1)
+    }
 }
 
 impl FromStr for VectorQuantization {
@@ -454,8 +602,10 @@ impl FromStr for VectorQuantization {
         match s.to_lowercase().as_str() {
             "f16" => Ok(Self::F16),
             "f32" => Ok(Self::F32),
+            "i8" => Ok(Self::I8),
+            "b1" => Ok(Self::B1),
             other => Err(Error::Config(format!(
-                "unknown vector quantization '{other}': expected f16 or f32"
+                "unknown vector quantization '{other}': expected f16, f32, i8 or b1"
             ))),
         }
     }
@@ -535,6 +685,10 @@ pub struct Config {
     pub search_min_score: f64,
     pub search_default_mode: SearchMode,
     pub search_rrf_k: f64,
+    /// Hybrid fusion method. Default: RRF.
+    pub search_fusion: FusionMethod,
+    /// Semantic weight α for linear fusion. Default: 0.7, range [0.0, 1.0].
+    pub search_semantic_weight: f64,
     /// BM25 saturation normalization constant. A BM25 score equal to this
     /// value maps to 0.5 after normalization. Higher = more compressed scores.
     pub bm25_norm_k: f64,
@@ -554,10 +708,57 @@ pub struct Config {
     pub search_expand_graph: usize,
     /// Maximum number of graph-expanded results to add. Default: 3, range 1–10.
     pub search_expand_limit: usize,
+    /// Default MMR diversification lambda. Default: None (disabled), range [0.0, 1.0].
+    pub search_diversity: Option<f64>,
+    /// Default maximum number of search results per file. Default: None (unlimited), must be > 0.
+    pub search_max_per_file: Option<usize>,
+    /// Lexical per-field score multipliers keyed by `content`, `heading`,
+    /// `frontmatter`, or a frontmatter key. Default: empty (headings 1.5, others 1.0).
+    pub search_lexical_boosts: BTreeMap<String, f32>,
+    /// Default lexical analysis language. Default: English.
+    pub search_lexical_language: LexicalLanguage,
+    /// Lexical language overrides by path scope. Default: empty.
+    pub search_lexical_paths: BTreeMap<String, LexicalLanguage>,
+    /// Whether lexical analysis removes stopwords. Default: false.
+    pub search_lexical_stopwords: bool,
+    /// Reranker backend applied to the top search candidates. Default: None (disabled).
+    pub rerank_provider: RerankProviderType,
+    /// Reranker model name (required for Cohere-style endpoints).
+    pub rerank_model: Option<String>,
+    /// Reranker endpoint URL. Defaults per provider when unset.
+    pub rerank_endpoint: Option<String>,
+    /// Number of top candidates sent to the reranker. Default: 50, range [1, 1000].
+    pub rerank_top_n: usize,
+    /// Maximum query embeddings kept under `.markdownvdb/cache/queries/`.
+    /// Default: 1000; 0 disables the query cache.
+    pub query_cache_max_entries: usize,
+    /// Seconds before a cached query embedding expires. Default: 7 days; 0 = never.
+    pub query_cache_ttl_secs: u64,
+    /// Whether ingestion reuses document embeddings from
+    /// `.markdownvdb/cache/embeddings/` by exact chunk text. Default: true.
+    pub chunk_embedding_cache: bool,
     /// Vector quantization type for the HNSW index. Default: F16.
     pub vector_quantization: VectorQuantization,
     /// Whether to compress the metadata region with zstd. Default: true.
     pub index_compression: bool,
+    /// Size in bytes at which the index write-ahead log is compacted into a
+    /// new base file. Default: 64 MiB; 0 disables the log (every save
+    /// rewrites the whole index).
+    pub index_wal_max_bytes: u64,
+    /// Age in seconds at which the index write-ahead log is compacted.
+    /// Default: 3600.
+    pub index_wal_max_age_secs: u64,
+    /// With `i8` or `b1` quantization, searches fetch this many times the
+    /// requested candidates from the HNSW index and re-rank them by exact
+    /// cosine similarity. Default: 4, range [0, 64]; 0 disables rescoring.
+    pub index_rescore_factor: usize,
+    /// Number of snapshots under `.markdownvdb/snapshots/` kept when a new
+    /// one is created or `mdvdb snapshot prune` runs. Default: 5; 0 keeps
+    /// every snapshot.
+    pub index_snapshot_retention: usize,
+    /// Whether `ingest --reindex` snapshots the index before replacing it.
+    /// Default: true.
+    pub index_snapshot_before_reindex: bool,
     /// Whether to compute and store edge embeddings. Default: true.
     pub edge_embeddings: bool,
     /// Weight for edge-based boost in search scoring. Default: 0.15, range [0.0, 1.0].
@@ -715,6 +916,15 @@ impl Config {
         Self::from_yaml(yaml_config, project_root)
     }
 
+    /// The index-time lexical analysis settings for the FTS index.
+    pub fn lexical_settings(&self) -> LexicalSettings {
+        LexicalSettings {
+            language: self.search_lexical_language,
+            paths: self.search_lexical_paths.clone(),
+            stopwords: self.search_lexical_stopwords,
+        }
+    }
+
     /// Validate constraint invariants on the loaded config.
     fn validate(&self) -> Result<(), Error> {
         if self.embedding_model.trim().is_empty() {
@@ -804,6 +1014,12 @@ impl Config {
         if self.search_rrf_k <= 0.0 {
             return Err(Error::Config("search_rrf_k must be > 0".into()));
         }
+        if !(0.0..=1.0).contains(&self.search_semantic_weight) {
+            return Err(Error::Config(format!(
+                "search_semantic_weight ({}) must be in [0.0, 1.0]",
+                self.search_semantic_weight
+            )));
+        }
         if self.bm25_norm_k <= 0.0 {
             return Err(Error::Config("bm25_norm_k must be > 0".into()));
         }
@@ -834,12 +1050,48 @@ impl Config {
                 self.search_expand_limit
             )));
         }
+        if let Some(lambda) = self.search_diversity {
+            if !(0.0..=1.0).contains(&lambda) {
+                return Err(Error::Config(format!(
+                    "search_diversity ({lambda}) must be in [0.0, 1.0]"
+                )));
+            }
+        }
+        if self.search_max_per_file == Some(0) {
+            return Err(Error::Config("search_max_per_file must be > 0".into()));
+        }
+        for (field, boost) in &self.search_lexical_boosts {
+            let valid_name = field.starts_with(|c: char| c.is_ascii_alphabetic())
+                && field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_name {
+                return Err(Error::Config(format!(
+                    "search_lexical_boosts field '{field}' must start with a letter and contain only letters, digits, and '_'"
+                )));
+            }
+            if !boost.is_finite() || *boost <= 0.0 {
+                return Err(Error::Config(format!(
+                    "search_lexical_boosts.{field} ({boost}) must be > 0"
+                )));
+            }
+        }
+        if !(1..=1000).contains(&self.rerank_top_n) {
+            return Err(Error::Config(format!(
+                "rerank_top_n ({}) must be in [1, 1000]",
+                self.rerank_top_n
+            )));
+        }
         if !(0.0..=1.0).contains(&self.edge_boost_weight) {
             return Err(Error::Config(format!(
                 "edge_boost_weight ({}) must be in [0.0, 1.0]",
                 self.edge_boost_weight
             )));
         }
+        if self.index_rescore_factor > 64 {
+            return Err(Error::Config(format!(
+                "index_rescore_factor ({}) must be in [0, 64]",
+                self.index_rescore_factor
+            )));
+        }
         if self.edge_cluster_rebalance == 0 {
             return Err(Error::Config("edge_cluster_rebalance must be > 0".into()));
         }
@@ -1195,6 +1447,12 @@ pub fn apply_env_overrides(yaml: &mut YamlConfig) {
     if let Some(v) = env_f64("MDVDB_SEARCH_RRF_K") {
         yaml.search.rrf_k = v;
     }
+    if let Some(v) = env_str("MDVDB_SEARCH_FUSION") {
+        yaml.search.fusion = v;
+    }
+    if let Some(v) = env_f64("MDVDB_SEARCH_SEMANTIC_WEIGHT") {
+        yaml.search.semantic_weight = v;
+    }
     if let Some(v) = env_f64("MDVDB_BM25_NORM_K") {
         yaml.search.bm25_norm_k = v;
     }
@@ -1210,6 +1468,38 @@ pub fn apply_env_overrides(yaml: &mut YamlConfig) {
     if let Some(v) = env_usize("MDVDB_SEARCH_EXPAND_LIMIT") {
         yaml.search.expand_limit = v;
     }
+    if let Some(v) = env_f64("MDVDB_SEARCH_DIVERSITY") {
+        yaml.search.diversity = Some(v);
+    }
+    if let Some(v) = env_usize("MDVDB_SEARCH_MAX_PER_FILE") {
+        yaml.search.max_per_file = Some(v);
+    }
+    if let Some(v) = env_str("MDVDB_SEARCH_LEXICAL_LANGUAGE") {
+        yaml.search.lexical.language = v;
+    }
+    // `scope=language` pairs, e.g. `notes/de=german,notes/ja=cjk`.
+    if let Some(v) = env_comma_list("MDVDB_SEARCH_LEXICAL_PATHS") {
+        yaml.search.lexical.paths = v
+            .iter()
+            .filter_map(|pair| {
+                let (scope, language) = pair.split_once('=')?;
+                Some((scope.trim().to_string(), language.trim().to_string()))
+            })
+            .collect();
+    }
+    if let Some(v) = env_bool("MDVDB_SEARCH_LEXICAL_STOPWORDS") {
+        yaml.search.lexical.stopwords = v;
+    }
+    // `field=boost` pairs, e.g. `title=3,tags=2`; unparsable pairs are ignored.
+    if let Some(v) = env_comma_list("MDVDB_SEARCH_LEXICAL_BOOSTS") {
+        yaml.search.lexical.boosts = v
+            .iter()
+            .filter_map(|pair| {
+                let (field, boost) = pair.split_once('=')?;
+                Some((field.trim().to_string(), boost.trim().parse().ok()?))
+            })
+            .collect();
+    }
 
     // Decay
     if let Some(v) = env_bool("MDVDB_SEARCH_DECAY") {
@@ -1289,6 +1579,46 @@ pub fn apply_env_overrides(yaml: &mut YamlConfig) {
     if let Some(v) = env_usize("MDVDB_EDGE_CLUSTER_REBALANCE") {
         yaml.index.edge_cluster_rebalance = v;
     }
+    if let Some(v) = env_u64("MDVDB_INDEX_WAL_MAX_BYTES") {
+        yaml.index.wal_max_bytes = v;
+    }
+    if let Some(v) = env_u64("MDVDB_INDEX_WAL_MAX_AGE_SECS") {
+        yaml.index.wal_max_age_secs = v;
+    }
+    if let Some(v) = env_usize("MDVDB_INDEX_RESCORE_FACTOR") {
+        yaml.index.rescore_factor = v;
+    }
+    if let Some(v) = env_usize("MDVDB_INDEX_SNAPSHOT_RETENTION") {
+        yaml.index.snapshot_retention = v;
+    }
+    if let Some(v) = env_bool("MDVDB_INDEX_SNAPSHOT_BEFORE_REINDEX") {
+        yaml.index.snapshot_before_reindex = v;
+    }
+
+    // Rerank
+    if let Some(v) = env_str("MDVDB_RERANK_PROVIDER") {
+        yaml.rerank.provider = v;
+    }
+    if let Some(v) = env_str("MDVDB_RERANK_MODEL") {
+        yaml.rerank.model = Some(v);
+    }
+    if let Some(v) = env_str("MDVDB_RERANK_ENDPOINT") {
+        yaml.rerank.endpoint = Some(v);
+    }
+    if let Some(v) = env_usize("MDVDB_RERANK_TOP_N") {
+        yaml.rerank.top_n = v;
+    }
+
+    // Cache
+    if let Some(v) = env_usize("MDVDB_QUERY_CACHE_MAX_ENTRIES") {
+        yaml.cache.query_max_entries = v;
+    }
+    if let Some(v) = env_u64("MDVDB_QUERY_CACHE_TTL_SECS") {
+        yaml.cache.query_ttl_secs = v;
+    }
+    if let Some(v) = env_bool("MDVDB_CHUNK_EMBEDDING_CACHE") {
+        yaml.cache.chunk_embeddings = v;
+    }
 
     // Sources
     if let Some(v) = env_comma_list("MDVDB_SOURCE_DIRS") {
@@ -1481,6 +1811,33 @@ pub fn migrate_dotenv_to_yaml(dotenv_path: &Path, yaml_path: &Path) -> Result<()
                     yaml.index.edge_cluster_rebalance = v;
                 }
             }
+            "MDVDB_INDEX_WAL_MAX_BYTES" => {
+                if let Ok(v) = value.parse() {
+                    yaml.index.wal_max_bytes = v;
+                }
+            }
+            "MDVDB_INDEX_WAL_MAX_AGE_SECS" => {
+                if let Ok(v) = value.parse() {
+                    yaml.index.wal_max_age_secs = v;
+                }
+            }
+            "MDVDB_INDEX_RESCORE_FACTOR" => {
+                if let Ok(v) = value.parse() {
+                    yaml.index.rescore_factor = v;
+                }
+            }
+            "MDVDB_INDEX_SNAPSHOT_RETENTION" => {
+                if let Ok(v) = value.parse() {
+                    yaml.index.snapshot_retention = v;
+                }
+            }
+            "MDVDB_INDEX_SNAPSHOT_BEFORE_REINDEX" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    yaml.index.snapshot_before_reindex = v;
+                } else {
+                    yaml.index.snapshot_before_reindex = value == "1" || value == "yes";
+                }
+            }
             "MDVDB_SOURCE_DIRS" => {
                 yaml.sources.dirs = value
                     .split(',')
@@ -1916,7 +2273,17 @@ impl Config {
     pub fn from_yaml(yaml: YamlConfig, _project_root: &Path) -> Result<Self, Error> {
         let embedding_provider = yaml.embedding.provider.parse::<EmbeddingProviderType>()?;
         let search_default_mode = yaml.search.mode.parse::<SearchMode>()?;
+        let search_fusion = yaml.search.fusion.parse::<FusionMethod>()?;
+        let search_lexical_language = yaml.search.lexical.language.parse::<LexicalLanguage>()?;
+        let search_lexical_paths = yaml
+            .search
+            .lexical
+            .paths
+            .iter()
+            .map(|(scope, language)| Ok((scope.clone(), language.parse::<LexicalLanguage>()?)))
+            .collect::<Result<BTreeMap<_, _>, Error>>()?;
         let vector_quantization = yaml.index.quantization.parse::<VectorQuantization>()?;
+        let rerank_provider = yaml.rerank.provider.parse::<RerankProviderType>()?;
 
         let openai_api_key = std::env::var("OPENAI_API_KEY").ok();
         let ollama_host =
@@ -1968,6 +2335,8 @@ impl Config {
             search_min_score: yaml.search.min_score,
             search_default_mode,
             search_rrf_k: yaml.search.rrf_k,
+            search_fusion,
+            search_semantic_weight: yaml.search.semantic_weight,
             bm25_norm_k: yaml.search.bm25_norm_k,
             search_decay_enabled: yaml.search.decay.enabled,
             search_decay_half_life: yaml.search.decay.half_life,
@@ -1977,8 +2346,26 @@ impl Config {
             search_boost_hops: yaml.search.boost_hops,
             search_expand_graph: yaml.search.expand_graph,
             search_expand_limit: yaml.search.expand_limit,
+            search_diversity: yaml.search.diversity,
+            search_max_per_file: yaml.search.max_per_file,
+            search_lexical_boosts: yaml.search.lexical.boosts,
+            search_lexical_language,
+            search_lexical_paths,
+            search_lexical_stopwords: yaml.search.lexical.stopwords,
+            rerank_provider,
+            rerank_model: yaml.rerank.model,
+            rerank_endpoint: yaml.rerank.endpoint,
+            rerank_top_n: yaml.rerank.top_n,
+            query_cache_max_entries: yaml.cache.query_max_entries,
+            query_cache_ttl_secs: yaml.cache.query_ttl_secs,
+            chunk_embedding_cache: yaml.cache.chunk_embeddings,
             vector_quantization,
             index_compression: yaml.index.compression,
+            index_wal_max_bytes: yaml.index.wal_max_bytes,
+            index_wal_max_age_secs: yaml.index.wal_max_age_secs,
+            index_rescore_factor: yaml.index.rescore_factor,
+            index_snapshot_retention: yaml.index.snapshot_retention,
+            index_snapshot_before_reindex: yaml.index.snapshot_before_reindex,
             edge_embeddings: yaml.index.edge_embeddings,
             edge_boost_weight: yaml.index.edge_boost_weight,
             edge_cluster_rebalance: yaml.index.edge_cluster_rebalance,
@@ -2137,6 +2524,11 @@ mod tests {
             "MDVDB_EDGE_EMBEDDINGS",
             "MDVDB_EDGE_BOOST_WEIGHT",
             "MDVDB_EDGE_CLUSTER_REBALANCE",
+            "MDVDB_INDEX_WAL_MAX_BYTES",
+            "MDVDB_INDEX_WAL_MAX_AGE_SECS",
+            "MDVDB_INDEX_RESCORE_FACTOR",
+            "MDVDB_INDEX_SNAPSHOT_RETENTION",
+            "MDVDB_INDEX_SNAPSHOT_BEFORE_REINDEX",
         ];
         // Save original values so we can restore them after the test
         let saved: Vec<(&str, Option<String>)> = vars_to_clear
@@ -2196,6 +2588,11 @@ mod tests {
         assert_eq!(config.search_expand_limit, 3);
         assert_eq!(config.vector_quantization, VectorQuantization::F16);
         assert!(config.index_compression);
+        assert_eq!(config.index_wal_max_bytes, 64 * 1024 * 1024);
+        assert_eq!(config.index_wal_max_age_secs, 3600);
+        assert_eq!(config.index_rescore_factor, 4);
+        assert_eq!(config.index_snapshot_retention, 5);
+        assert!(config.index_snapshot_before_reindex);
         assert!(config.edge_embeddings);
         assert_eq!(config.edge_boost_weight, 0.15);
         assert_eq!(config.edge_cluster_rebalance, 50);
@@ -2277,6 +2674,32 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("search_rrf_k"));
     }
 
+    #[test]
+    fn validation_rejects_semantic_weight_above_one() {
+        let _lock = ENV_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("MDVDB_SEARCH_SEMANTIC_WEIGHT", "1.5");
+        let result = Config::load(Path::new("/nonexistent"));
+        std::env::remove_var("MDVDB_SEARCH_SEMANTIC_WEIGHT");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("search_semantic_weight"));
+    }
+
+    #[test]
+    fn validation_rejects_unknown_fusion() {
+        let _lock = ENV_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("MDVDB_SEARCH_FUSION", "borda");
+        let result = Config::load(Path::new("/nonexistent"));
+        std::env::remove_var("MDVDB_SEARCH_FUSION");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("unknown fusion method"));
+    }
+
     #[test]
     fn parse_error_on_non_numeric() {
         // With the YAML pipeline, env var overrides silently ignore unparseable
@@ -2400,6 +2823,11 @@ mod tests {
             "MDVDB_EDGE_EMBEDDINGS",
             "MDVDB_EDGE_BOOST_WEIGHT",
             "MDVDB_EDGE_CLUSTER_REBALANCE",
+            "MDVDB_INDEX_WAL_MAX_BYTES",
+            "MDVDB_INDEX_WAL_MAX_AGE_SECS",
+            "MDVDB_INDEX_RESCORE_FACTOR",
+            "MDVDB_INDEX_SNAPSHOT_RETENTION",
+            "MDVDB_INDEX_SNAPSHOT_BEFORE_REINDEX",
         ] {
             std::env::remove_var(var);
         }
@@ -0,0 +1,410 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::chunker::{chunk_index_from_id, count_tokens, join_chunk_content};
+use crate::search::{GraphContextItem, SearchResponse, SearchResultChunk};
+
+/// Default token budget for `mdvdb context`.
+pub const DEFAULT_CONTEXT_BUDGET: usize = 4000;
+
+/// Default number of search results considered for packing. Packing needs
+/// more candidates than a typical result page to fill a budget.
+pub const DEFAULT_CONTEXT_CANDIDATES: usize = 50;
+
+/// A token-budgeted block of retrieved context, ready to paste into a prompt.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextPack {
+    /// The query the context was retrieved for.
+    pub query: String,
+    /// Token budget the pack was assembled under (cl100k_base).
+    pub budget: usize,
+    /// Tokens used by `text`; always `<= budget`.
+    pub tokens_used: usize,
+    /// Packed sections in relevance order. Adjacent chunks of the same file
+    /// are merged into one section.
+    pub sections: Vec<ContextSection>,
+    /// Number of candidate chunks that did not fit in the budget.
+    pub chunks_omitted: usize,
+    /// The rendered markdown block (citation headers plus content).
+    pub text: String,
+}
+
+/// One cited span of a source file within a [`ContextPack`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSection {
+    /// 1-based citation number, as shown in the rendered header.
+    pub citation: usize,
+    /// Relative path of the source file.
+    pub path: String,
+    /// Heading hierarchy of the first packed chunk.
+    pub heading_hierarchy: Vec<String>,
+    /// 1-based start line of the first packed chunk.
+    pub start_line: usize,
+    /// 1-based end line of the last packed chunk (inclusive).
+    pub end_line: usize,
+    /// IDs of the chunks merged into this section, in file order.
+    pub chunk_ids: Vec<String>,
+    /// Best search score among the merged chunks (`None` for graph context).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Result file this section was linked from (graph context only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_from: Option<String>,
+    /// Merged chunk content.
+    pub content: String,
+}
+
+/// A chunk selected for packing, before adjacent chunks are merged.
+struct Candidate<'a> {
+    /// Position in relevance order (ranked results first, then graph context).
+    rank: usize,
+    path: &'a str,
+    chunk_index: Option<usize>,
+    chunk: &'a SearchResultChunk,
+    score: Option<f64>,
+    linked_from: Option<&'a str>,
+}
+
+/// Pack search results (then graph context items) into `budget` tokens.
+///
+/// Candidates are taken greedily in relevance order; a chunk that does not
+/// fit is skipped so smaller, lower-ranked chunks can still fill the budget.
+/// Selected chunks with consecutive chunk indices in the same file are merged
+/// into one section (see [`join_chunk_content`]).
+/// Token counts use [`count_tokens`] on the rendered output.
+pub fn pack_context(query: &str, response: &SearchResponse, budget: usize) -> ContextPack {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut candidates: Vec<Candidate<'_>> = Vec::new();
+    for result in &response.results {
+        if seen.insert(&result.chunk.chunk_id) {
+            candidates.push(Candidate {
+                rank: candidates.len(),
+                path: &result.file.path,
+                chunk_index: chunk_index_from_id(&result.chunk.chunk_id),
+                chunk: &result.chunk,
+                score: Some(result.score),
+                linked_from: None,
+            });
+        }
+    }
+    for item in &response.graph_context {
+        if seen.insert(&item.chunk.chunk_id) {
+            candidates.push(graph_candidate(candidates.len(), item));
+        }
+    }
+
+    // Greedy selection on per-chunk rendered cost. Merging only removes
+    // headers and duplicated overlap, so the merged render is never larger.
+    let mut remaining = budget;
+    let mut selected: Vec<Candidate<'_>> = Vec::new();
+    let mut chunks_omitted = 0;
+    for candidate in candidates {
+        let cost = count_tokens(&render_section(
+            selected.len() + 1,
+            candidate.path,
+            &candidate.chunk.heading_hierarchy,
+            candidate.chunk.start_line,
+            candidate.chunk.end_line,
+            candidate.linked_from,
+            &candidate.chunk.content,
+        ));
+        if cost <= remaining {
+            remaining -= cost;
+            selected.push(candidate);
+        } else {
+            chunks_omitted += 1;
+        }
+    }
+
+    let mut sections = merge_adjacent(selected);
+    let mut text = render_pack(&sections);
+    let mut tokens_used = count_tokens(&text);
+    // Tokenization is not strictly additive across section boundaries; trim
+    // from the least relevant end in the rare case the join overshoots.
+    while tokens_used > budget {
+        let Some(dropped) = sections.pop() else {
+            break;
+        };
+        chunks_omitted += dropped.chunk_ids.len();
+        text = render_pack(&sections);
+        tokens_used = count_tokens(&text);
+    }
+
+    ContextPack {
+        query: query.to_string(),
+        budget,
+        tokens_used,
+        sections,
+        chunks_omitted,
+        text,
+    }
+}
+
+fn graph_candidate(rank: usize, item: &GraphContextItem) -> Candidate<'_> {
+    Candidate {
+        rank,
+        path: &item.file.path,
+        chunk_index: chunk_index_from_id(&item.chunk.chunk_id),
+        chunk: &item.chunk,
+        score: None,
+        linked_from: Some(&item.linked_from),
+    }
+}
+
+/// Merge selected chunks with consecutive indices in the same file, then
+/// order sections by their best-ranked chunk and number the citations.
+fn merge_adjacent(selected: Vec<Candidate<'_>>) -> Vec<ContextSection> {
+    let mut by_file: HashMap<&str, Vec<Candidate<'_>>> = HashMap::new();
+    for candidate in selected {
+        by_file.entry(candidate.path).or_default().push(candidate);
+    }
+
+    let mut ranked: Vec<(usize, ContextSection)> = Vec::new();
+    for (_, mut chunks) in by_file {
+        chunks.sort_by_key(|c| (c.chunk_index, c.rank));
+        let mut current: Option<(usize, Option<usize>, ContextSection)> = None;
+        for chunk in chunks {
+            if let Some((rank, last_index, section)) = current.as_mut() {
+                let adjacent = matches!(
+                    (*last_index, chunk.chunk_index),
+                    (Some(last), Some(next)) if next == last + 1
+                );
+                if adjacent && section.linked_from.as_deref() == chunk.linked_from {
+                    *rank = (*rank).min(chunk.rank);
+                    *last_index = chunk.chunk_index;
+                    section.end_line = section.end_line.max(chunk.chunk.end_line);
+                    section.chunk_ids.push(chunk.chunk.chunk_id.clone());
+                    section.score = match (section.score, chunk.score) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        (a, b) => a.or(b),
+                    };
+                    section.content = join_chunk_content(&section.content, &chunk.chunk.content);
+                    continue;
+                }
+            }
+            if let Some((rank, _, section)) = current.take() {
+                ranked.push((rank, section));
+            }
+            current = Some((chunk.rank, chunk.chunk_index, new_section(&chunk)));
+        }
+        if let Some((rank, _, section)) = current {
+            ranked.push((rank, section));
+        }
+    }
+
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, mut section))| {
+            section.citation = i + 1;
+            section
+        })
+        .collect()
+}
+
+fn new_section(candidate: &Candidate<'_>) -> ContextSection {
+    ContextSection {
+        citation: 0,
+        path: candidate.path.to_string(),
+        heading_hierarchy: candidate.chunk.heading_hierarchy.clone(),
+        start_line: candidate.chunk.start_line,
+        end_line: candidate.chunk.end_line,
+        chunk_ids: vec![candidate.chunk.chunk_id.clone()],
+        score: candidate.score,
+        linked_from: candidate.linked_from.map(str::to_string),
+        content: candidate.chunk.content.clone(),
+    }
+}
+
+fn render_section(
+    citation: usize,
+    path: &str,
+    heading_hierarchy: &[String],
+    start_line: usize,
+    end_line: usize,
+    linked_from: Option<&str>,
+    content: &str,
+) -> String {
+    let mut header = format!("### [{citation}] {path}:{start_line}-{end_line}");
+    if !heading_hierarchy.is_empty() {
+        header.push_str(" — ");
+        header.push_str(&heading_hierarchy.join(" > "));
+    }
+    if let Some(source) = linked_from {
+        header.push_str(&format!(" (linked from {source})"));
+    }
+    format!("{header}\n\n{}\n\n", content.trim_end())
+}
+
+fn render_pack(sections: &[ContextSection]) -> String {
+    sections
+        .iter()
+        .map(|s| {
+            render_section(
+                s.citation,
+                &s.path,
+                &s.heading_hierarchy,
+                s.start_line,
+                s.end_line,
+                s.linked_from.as_deref(),
+                &s.content,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{SearchResult, SearchResultFile, SearchTimings};
+
+    fn chunk(path: &str, index: usize, content: &str) -> SearchResultChunk {
+        SearchResultChunk {
+            chunk_id: format!("{path}#{index}"),
+            heading_hierarchy: vec!["Guide".into()],
+            content: content.into(),
+            start_line: index * 10 + 1,
+            end_line: index * 10 + 10,
+            before: vec![],
+            after: vec![],
+            section_chunk_ids: vec![],
+            snippet: None,
+            matches: vec![],
+        }
+    }
+
+    fn file(path: &str) -> SearchResultFile {
+        SearchResultFile {
+            path: path.into(),
+            frontmatter: None,
+            computed_fields: serde_json::Map::new(),
+            computed_field_errors: std::collections::BTreeMap::new(),
+            file_size: 0,
+            path_components: vec![path.into()],
+            modified_at: None,
+            relations: None,
+        }
+    }
+
+    fn result(path: &str, index: usize, content: &str, score: f64) -> SearchResult {
+        SearchResult {
+            score,
+            chunk: chunk(path, index, content),
+            file: file(path),
+            explain: None,
+        }
+    }
+
+    fn response(
+        results: Vec<SearchResult>,
+        graph_context: Vec<GraphContextItem>,
+    ) -> SearchResponse {
+        SearchResponse {
+            results,
+            graph_context,
+            timings: SearchTimings {
+                embed_secs: 0.0,
+                vector_search_secs: 0.0,
+                lexical_search_secs: 0.0,
+                fusion_secs: 0.0,
+                rerank_secs: 0.0,
+                assemble_secs: 0.0,
+                suggest_secs: 0.0,
+                total_secs: 0.0,
+                vector_strategy: None,
+                filter_selectivity: None,
+                cache_lookup_secs: 0.0,
+                cache_hit: None,
+            },
+            edge_results: Vec::new(),
+            facets: Default::default(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn packs_within_budget_and_skips_oversized_chunks() {
+        let big = "word ".repeat(500);
+        let resp = response(
+            vec![
+                result("a.md", 0, "alpha facts", 0.9),
+                result("b.md", 0, &big, 0.8),
+                result("c.md", 0, "gamma facts", 0.7),
+            ],
+            vec![],
+        );
+        let pack = pack_context("q", &resp, 100);
+        assert!(pack.tokens_used <= 100);
+        assert_eq!(pack.tokens_used, count_tokens(&pack.text));
+        let paths: Vec<&str> = pack.sections.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.md", "c.md"]);
+        assert_eq!(pack.chunks_omitted, 1);
+        assert!(pack
+            .text
+            .starts_with("### [1] a.md:1-10 — Guide\n\nalpha facts"));
+        assert!(pack.text.contains("### [2] c.md:1-10 — Guide"));
+    }
+
+    #[test]
+    fn merges_consecutive_chunks_of_same_file() {
+        let resp = response(
+            vec![
+                result("a.md", 2, "third part", 0.9),
+                result("b.md", 0, "other file", 0.8),
+                result("a.md", 1, "second part", 0.7),
+                result("a.md", 5, "far away", 0.6),
+            ],
+            vec![],
+        );
+        let pack = pack_context("q", &resp, 1000);
+        assert_eq!(pack.sections.len(), 3);
+        let merged = &pack.sections[0];
+        assert_eq!(merged.chunk_ids, vec!["a.md#1", "a.md#2"]);
+        assert_eq!((merged.start_line, merged.end_line), (11, 30));
+        assert_eq!(merged.content, "second part\n\nthird part");
+        assert_eq!(merged.score, Some(0.9));
+        assert_eq!(pack.sections[1].path, "b.md");
+        assert_eq!(pack.sections[2].chunk_ids, vec!["a.md#5"]);
+        assert_eq!(pack.sections[2].citation, 3);
+    }
+
+    #[test]
+    fn graph_context_fills_remaining_budget_without_duplicates() {
+        let linked = GraphContextItem {
+            chunk: chunk("linked.md", 0, "linked detail"),
+            file: file("linked.md"),
+            linked_from: "a.md".into(),
+            hop_distance: 1,
+            edge_context: None,
+            edge_relationship: None,
+        };
+        let duplicate = GraphContextItem {
+            chunk: chunk("a.md", 0, "alpha facts"),
+            file: file("a.md"),
+            linked_from: "b.md".into(),
+            hop_distance: 1,
+            edge_context: None,
+            edge_relationship: None,
+        };
+        let resp = response(
+            vec![result("a.md", 0, "alpha facts", 0.9)],
+            vec![linked, duplicate],
+        );
+        let pack = pack_context("q", &resp, 1000);
+        assert_eq!(pack.sections.len(), 2);
+        assert_eq!(pack.sections[1].linked_from.as_deref(), Some("a.md"));
+        assert_eq!(pack.sections[1].score, None);
+        assert!(pack.text.contains("(linked from a.md)"));
+    }
+
+    #[test]
+    fn zero_budget_packs_nothing() {
+        let resp = response(vec![result("a.md", 0, "alpha", 0.9)], vec![]);
+        let pack = pack_context("q", &resp, 0);
+        assert!(pack.sections.is_empty());
+        assert!(pack.text.is_empty());
+        assert_eq!(pack.chunks_omitted, 1);
+    }
+}
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::{Mutex, OnceLock};
 
 use serde::Serialize;
 
+use super::chunk_cache::{text_hash, ChunkEmbeddingCache, TextHash};
 use super::provider::{EmbeddingProvider, EmbeddingPurpose};
 
 static WORKING_BATCH_SIZES: OnceLock<Mutex<HashMap<String, usize>>> = OnceLock::new();
@@ -75,6 +77,47 @@ pub(crate) async fn embed_inputs_adaptively(
     Ok((embeddings, api_calls, estimated_input_tokens))
 }
 
+/// [`embed_inputs_adaptively`], serving inputs whose exact text is already in
+/// `cache` and storing the newly embedded vectors. Returns the vectors in
+/// input order, API calls, estimated input tokens, and cache hits.
+pub(crate) async fn embed_inputs_cached(
+    provider: &dyn EmbeddingProvider,
+    texts: Vec<String>,
+    cache: Option<&ChunkEmbeddingCache>,
+) -> crate::Result<(Vec<Vec<f32>>, usize, usize, usize)> {
+    let Some(cache) = cache else {
+        let (vectors, api_calls, tokens) = embed_inputs_adaptively(provider, texts).await?;
+        return Ok((vectors, api_calls, tokens, 0));
+    };
+    let hashes: Vec<TextHash> = texts.iter().map(|text| text_hash(text)).collect();
+    let mut cached = cache.lookup(
+        provider,
+        EmbeddingPurpose::Document,
+        &hashes.iter().copied().collect(),
+    );
+    let misses: Vec<usize> = (0..texts.len())
+        .filter(|&i| !cached.contains_key(&hashes[i]))
+        .collect();
+    let cache_hits = texts.len() - misses.len();
+    let miss_texts = misses.iter().map(|&i| texts[i].clone()).collect();
+    let (vectors, api_calls, tokens) = embed_inputs_adaptively(provider, miss_texts).await?;
+
+    let fresh: Vec<(TextHash, &[f32])> = misses
+        .iter()
+        .zip(&vectors)
+        .map(|(&i, vector)| (hashes[i], vector.as_slice()))
+        .collect();
+    cache.store(provider, EmbeddingPurpose::Document, &fresh);
+    for (&i, vector) in misses.iter().zip(&vectors) {
+        cached.insert(hashes[i], vector.clone());
+    }
+    let vectors = hashes
+        .iter()
+        .map(|hash| cached.get(hash).cloned().unwrap_or_default())
+        .collect();
+    Ok((vectors, api_calls, tokens, cache_hits))
+}
+
 /// A markdown chunk to be embedded.
 ///
 /// This is a temporary definition used until the chunking engine (Phase 3)
@@ -100,6 +143,8 @@ pub struct EmbeddingResult {
     pub api_calls: usize,
     /// Provider-independent local count of inputs successfully embedded.
     pub estimated_input_tokens: usize,
+    /// Chunks whose vectors came from the persistent embedding cache.
+    pub cache_hits: usize,
 }
 
 /// Monotonic progress emitted after each logical embedding batch completes.
@@ -130,6 +175,32 @@ pub async fn embed_chunks(
     current_hashes: &HashMap<PathBuf, String>,
     batch_size: usize,
     on_batch: Option<&(dyn Fn(&EmbeddingBatchProgress) + Send + Sync)>,
+) -> crate::Result<EmbeddingResult> {
+    embed_chunks_cached(
+        provider,
+        chunks,
+        existing_hashes,
+        current_hashes,
+        batch_size,
+        None,
+        on_batch,
+    )
+    .await
+}
+
+/// [`embed_chunks`] with a persistent content-addressed cache: changed
+/// chunks whose exact text was embedded before (in this vector space) are
+/// served from `cache` instead of the provider, and newly embedded vectors
+/// are added to it as each batch completes, so a failed run keeps the
+/// batches that succeeded before it.
+pub async fn embed_chunks_cached(
+    provider: &dyn EmbeddingProvider,
+    chunks: &[Chunk],
+    existing_hashes: &HashMap<PathBuf, String>,
+    current_hashes: &HashMap<PathBuf, String>,
+    batch_size: usize,
+    cache: Option<&ChunkEmbeddingCache>,
+    on_batch: Option<&(dyn Fn(&EmbeddingBatchProgress) + Send + Sync)>,
 ) -> crate::Result<EmbeddingResult> {
     let mut skipped = Vec::new();
     let mut to_embed: Vec<&Chunk> = Vec::new();
@@ -157,13 +228,44 @@ pub async fn embed_chunks(
         }
     }
 
+    // Serve chunks whose exact text is already cached.
+    let mut embeddings: HashMap<String, Vec<f32>> = HashMap::new();
+    let mut text_hashes: HashMap<&str, TextHash> = HashMap::new();
+    if let Some(cache) = cache {
+        text_hashes = to_embed
+            .iter()
+            .map(|chunk| (chunk.id.as_str(), text_hash(&chunk.content)))
+            .collect();
+        let cached = cache.lookup(
+            provider,
+            EmbeddingPurpose::Document,
+            &text_hashes.values().copied().collect(),
+        );
+        to_embed.retain(|chunk| match cached.get(&text_hashes[chunk.id.as_str()]) {
+            Some(vector) => {
+                embeddings.insert(chunk.id.clone(), vector.clone());
+                false
+            }
+            None => true,
+        });
+    }
+    let cache_hits = embeddings.len();
+
     if to_embed.is_empty() {
-        tracing::info!(skipped = skipped.len(), "all chunks skipped (no changes)");
+        tracing::info!(
+            skipped = skipped.len(),
+            cache_hits,
+            "all chunks skipped or cached"
+        );
+        if let Some(cb) = &on_batch {
+            cb(&EmbeddingBatchProgress::default());
+        }
         return Ok(EmbeddingResult {
-            embeddings: HashMap::new(),
+            embeddings,
             skipped,
             api_calls: 0,
             estimated_input_tokens: 0,
+            cache_hits,
         });
     }
 
@@ -219,11 +321,11 @@ pub async fn embed_chunks(
     }))
     .buffer_unordered(MAX_CONCURRENT);
 
-    let mut embeddings: HashMap<String, Vec<f32>> = HashMap::new();
     let mut api_calls: usize = 0;
     let mut completed_count: usize = 0;
     let mut completed_chunks: usize = 0;
     let mut estimated_input_tokens: usize = 0;
+    let mut stored: HashSet<TextHash> = HashSet::new();
 
     while let Some(result) = stream.next().await {
         let (_batch_idx, pairs, batch_api_calls, batch_estimated_input_tokens) = result?;
@@ -231,6 +333,18 @@ pub async fn embed_chunks(
         completed_count += 1;
         completed_chunks += pairs.len();
         estimated_input_tokens += batch_estimated_input_tokens;
+        // Cache each batch as it lands, so vectors already paid for survive a
+        // later batch failing or the run being interrupted.
+        if let Some(cache) = cache {
+            let fresh: Vec<(TextHash, &[f32])> = pairs
+                .iter()
+                .filter_map(|(id, vector)| {
+                    let hash = text_hashes[id.as_str()];
+                    stored.insert(hash).then_some((hash, vector.as_slice()))
+                })
+                .collect();
+            cache.store(provider, EmbeddingPurpose::Document, &fresh);
+        }
         for (id, vector) in pairs {
             embeddings.insert(id, vector);
         }
@@ -248,8 +362,9 @@ pub async fn embed_chunks(
     }
 
     tracing::info!(
-        embedded = embeddings.len(),
+        embedded = embeddings.len() - cache_hits,
         skipped = skipped.len(),
+        cache_hits,
         api_calls,
         "embedding complete"
     );
@@ -259,6 +374,7 @@ pub async fn embed_chunks(
         skipped,
         api_calls,
         estimated_input_tokens,
+        cache_hits,
     })
 }
 
@@ -0,0 +1,700 @@
+//! Content-addressed persistent cache of document embeddings.
+//!
+//! `embedding_body_hash` only avoids re-embedding files whose body did not
+//! change. Moving a file, `--reindex`, or a chunking-parameter change produces
+//! chunk texts that were very often embedded before. This cache remembers
+//! vectors by SHA-256 of the exact input text, so those runs only pay for new
+//! text.
+//!
+//! Each vector space — provider [`batch_cache_key`](EmbeddingProvider::batch_cache_key),
+//! dimensions, and purpose — gets one append-only file under
+//! `.markdownvdb/cache/embeddings/`: a small JSON header followed by
+//! fixed-size `(text hash, f32 vector)` records. Each cache handle keeps a
+//! text hash → record offset index per space, extended incrementally as
+//! records are appended, so a lookup reads only the records it asks for.
+//! Spaces are compacted automatically once most of their records are no
+//! longer referenced by the index. The cache is derived, disposable state:
+//! unreadable files are treated as empty and write failures only log a
+//! warning.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::embedding::provider::{EmbeddingProvider, EmbeddingPurpose};
+use crate::error::Error;
+use crate::index::Index;
+use crate::Result;
+
+const CHUNK_CACHE_MAGIC: &[u8; 8] = b"MDVDBEC1";
+
+/// Upper bound on the JSON header, so a corrupt length field cannot trigger
+/// a huge allocation.
+const MAX_HEADER_LEN: u64 = 64 * 1024;
+
+/// Upper bound on cached vector dimensions.
+const MAX_DIMENSIONS: usize = 1 << 16;
+
+/// A space is compacted automatically only once it holds more records than
+/// this, and more than twice the index's live embedding inputs.
+const COMPACT_MIN_RECORDS: u64 = 1024;
+
+/// SHA-256 digest of an embedding input text.
+pub type TextHash = [u8; 32];
+
+/// Hash an embedding input text for cache lookup.
+pub fn text_hash(text: &str) -> TextHash {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(text.as_bytes()));
+    hash
+}
+
+/// Identity of one cached vector space, stored as the file header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpaceHeader {
+    provider_key: String,
+    dimensions: usize,
+    purpose: String,
+    /// Changes whenever the file is rewritten, so offset indexes can tell a
+    /// rewrite from an append. 0 for files written before the field existed.
+    #[serde(default)]
+    generation: u64,
+}
+
+impl SpaceHeader {
+    fn same_space(&self, other: &SpaceHeader) -> bool {
+        self.provider_key == other.provider_key
+            && self.dimensions == other.dimensions
+            && self.purpose == other.purpose
+    }
+
+    fn with_new_generation(&self) -> SpaceHeader {
+        SpaceHeader {
+            generation: crate::index::storage::new_generation(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Usage of one cached vector space, reported by `mdvdb cache stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkCacheSpace {
+    /// Provider/model key the vectors were produced by.
+    pub provider_key: String,
+    /// Vector dimensionality.
+    pub dimensions: usize,
+    /// Embedding purpose (`document` for chunk and edge inputs).
+    pub purpose: String,
+    /// Number of cached vectors.
+    pub entries: usize,
+    /// Size of the cache file in bytes.
+    pub bytes: u64,
+}
+
+/// Text hash → record offset index of one space file.
+#[derive(Debug, Default)]
+struct SpaceOffsets {
+    generation: u64,
+    /// File length indexed so far; always on a record boundary.
+    scanned: u64,
+    offsets: HashMap<TextHash, u64>,
+}
+
+impl SpaceOffsets {
+    /// Index the complete records appended since the last scan, starting
+    /// over when the file was rewritten. Only hashes are read.
+    fn refresh(
+        &mut self,
+        file: &mut File,
+        header: &SpaceHeader,
+        header_len: u64,
+        file_len: u64,
+    ) -> Result<()> {
+        if self.generation != header.generation
+            || self.scanned < header_len
+            || self.scanned > file_len
+        {
+            *self = SpaceOffsets {
+                generation: header.generation,
+                scanned: header_len,
+                offsets: HashMap::new(),
+            };
+        }
+        let record_len = record_len(header.dimensions);
+        let end = file_len - (file_len - header_len) % record_len;
+        if self.scanned >= end {
+            return Ok(());
+        }
+        file.seek(SeekFrom::Start(self.scanned))?;
+        let mut reader = BufReader::new(file);
+        let mut hash = [0u8; 32];
+        while self.scanned < end {
+            reader.read_exact(&mut hash)?;
+            reader.seek_relative(record_len as i64 - 32)?;
+            self.offsets.insert(hash, self.scanned);
+            self.scanned += record_len;
+        }
+        Ok(())
+    }
+}
+
+/// Persistent text-hash → vector store under `.markdownvdb/cache/embeddings/`.
+///
+/// Clones share the in-memory offset indexes, so keep one handle alive for
+/// repeated lookups.
+#[derive(Debug, Clone)]
+pub struct ChunkEmbeddingCache {
+    dir: PathBuf,
+    offsets: Arc<Mutex<HashMap<PathBuf, SpaceOffsets>>>,
+}
+
+impl ChunkEmbeddingCache {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            dir: root.join(".markdownvdb").join("cache").join("embeddings"),
+            offsets: Arc::default(),
+        }
+    }
+
+    /// Cached vectors for the requested hashes in the provider's space.
+    /// Missing or unreadable entries are simply absent from the result.
+    pub fn lookup(
+        &self,
+        provider: &dyn EmbeddingProvider,
+        purpose: EmbeddingPurpose,
+        hashes: &HashSet<TextHash>,
+    ) -> HashMap<TextHash, Vec<f32>> {
+        if hashes.is_empty() {
+            return HashMap::new();
+        }
+        let header = space_header(provider, purpose);
+        let path = self.space_path(&header);
+        match self.lookup_space(&path, &header, hashes) {
+            Ok(found) => found,
+            Err(error) => {
+                warn!(path = %path.display(), %error, "ignoring unreadable embedding cache");
+                HashMap::new()
+            }
+        }
+    }
+
+    fn lookup_space(
+        &self,
+        path: &Path,
+        header: &SpaceHeader,
+        hashes: &HashSet<TextHash>,
+    ) -> Result<HashMap<TextHash, Vec<f32>>> {
+        let mut found = HashMap::new();
+        let Some((mut file, stored, header_len, file_len)) = open_space(path)? else {
+            return Ok(found);
+        };
+        if !stored.same_space(header) {
+            return Ok(found);
+        }
+        let mut spaces = self.offsets.lock();
+        let offsets = spaces.entry(path.to_path_buf()).or_default();
+        offsets.refresh(&mut file, &stored, header_len, file_len)?;
+
+        let mut record = vec![0u8; record_len(stored.dimensions) as usize];
+        for hash in hashes {
+            let Some(&offset) = offsets.offsets.get(hash) else {
+                continue;
+            };
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut record)?;
+            if record[..32] == hash[..] {
+                found.insert(*hash, decode_vector(&record[32..]));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Append freshly embedded vectors to the provider's space.
+    pub fn store(
+        &self,
+        provider: &dyn EmbeddingProvider,
+        purpose: EmbeddingPurpose,
+        entries: &[(TextHash, &[f32])],
+    ) {
+        let header = space_header(provider, purpose);
+        let entries: Vec<_> = entries
+            .iter()
+            .filter(|(_, vector)| vector.len() == header.dimensions)
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+        let path = self.space_path(&header);
+        if let Err(error) = self.append(&path, &header, &entries) {
+            warn!(path = %path.display(), %error, "embedding cache update failed");
+        }
+    }
+
+    fn append(
+        &self,
+        path: &Path,
+        header: &SpaceHeader,
+        entries: &[&(TextHash, &[f32])],
+    ) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let Some(_lock) = lock_space(path)? else {
+            debug!(path = %path.display(), "embedding cache busy; skipping store");
+            return Ok(());
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let file_len = file.metadata()?.len();
+        let header_len = match read_header(&mut BufReader::new(&file), file_len) {
+            Ok(Some((stored, header_len))) if stored.same_space(header) => header_len,
+            // New, foreign, or corrupt file: start the space over.
+            _ => {
+                file.set_len(0)?;
+                write_header(&mut file, &header.with_new_generation())?
+            }
+        };
+        // Drop a record torn by an interrupted append so new records stay
+        // aligned.
+        let record_len = record_len(header.dimensions);
+        let body_len = file.metadata()?.len() - header_len;
+        file.set_len(header_len + body_len - body_len % record_len)?;
+
+        let mut writer = BufWriter::new(&file);
+        for (hash, vector) in entries {
+            write_record(&mut writer, hash, vector)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Per-space usage of every cache file.
+    pub fn stats(&self) -> Result<Vec<ChunkCacheSpace>> {
+        let mut spaces = Vec::new();
+        for path in self.space_files()? {
+            let Ok(Some((_, header, header_len, bytes))) = open_space(&path) else {
+                continue;
+            };
+            let entries = ((bytes - header_len) / record_len(header.dimensions)) as usize;
+            spaces.push(ChunkCacheSpace {
+                provider_key: header.provider_key,
+                dimensions: header.dimensions,
+                purpose: header.purpose,
+                entries,
+                bytes,
+            });
+        }
+        spaces.sort_by(|a, b| a.provider_key.cmp(&b.provider_key));
+        Ok(spaces)
+    }
+
+    /// Rewrite every space keeping only vectors whose text hash is in `keep`
+    /// (deduplicated). Unreadable files are removed. Returns the number of
+    /// entries removed and bytes freed.
+    pub fn prune(&self, keep: &HashSet<TextHash>) -> Result<(usize, u64)> {
+        let (mut removed, mut freed) = (0, 0);
+        for path in self.space_files()? {
+            let Some(_lock) = lock_space(&path)? else {
+                return Err(cache_busy(&path));
+            };
+            let (space_removed, space_freed) = self.rewrite_space(&path, keep)?;
+            removed += space_removed;
+            freed += space_freed;
+        }
+        Ok((removed, freed))
+    }
+
+    /// Prune spaces holding more than twice `live_inputs` records (the
+    /// index's chunk and edge texts) down to the hashes in `keep`, which is
+    /// only computed when some space qualifies. Busy spaces are skipped.
+    /// Returns the number of entries removed.
+    pub fn compact_if_needed(
+        &self,
+        live_inputs: usize,
+        keep: impl FnOnce() -> HashSet<TextHash>,
+    ) -> Result<usize> {
+        let threshold = (live_inputs as u64)
+            .saturating_mul(2)
+            .max(COMPACT_MIN_RECORDS);
+        let mut bloated = Vec::new();
+        for path in self.space_files()? {
+            if let Ok(Some((_, header, header_len, file_len))) = open_space(&path) {
+                if (file_len - header_len) / record_len(header.dimensions) > threshold {
+                    bloated.push(path);
+                }
+            }
+        }
+        if bloated.is_empty() {
+            return Ok(0);
+        }
+        let keep = keep();
+        let mut removed = 0;
+        for path in bloated {
+            let Some(_lock) = lock_space(&path)? else {
+                debug!(path = %path.display(), "embedding cache busy; skipping compaction");
+                continue;
+            };
+            let (space_removed, freed) = self.rewrite_space(&path, &keep)?;
+            debug!(path = %path.display(), removed = space_removed, freed, "compacted embedding cache");
+            removed += space_removed;
+        }
+        Ok(removed)
+    }
+
+    /// [`compact_if_needed`](Self::compact_if_needed) against the inputs
+    /// `index` still references. Failures only log a warning.
+    pub(crate) fn compact_for_index(&self, index: &Index) {
+        let result = self.compact_if_needed(index.embedding_input_count(), || {
+            index.embedding_input_hashes()
+        });
+        if let Err(error) = result {
+            warn!(path = %self.dir.display(), %error, "embedding cache compaction failed");
+        }
+    }
+
+    /// Rewrite one space (under its lock) keeping only deduplicated records
+    /// whose hash is in `keep`; remove it when nothing is kept or it is
+    /// unreadable. Returns entries removed and bytes freed.
+    fn rewrite_space(&self, path: &Path, keep: &HashSet<TextHash>) -> Result<(usize, u64)> {
+        let before = fs::metadata(path)?.len();
+        let mut kept: Vec<u8> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut total = 0;
+        let header = read_space(path, |hash, vector| {
+            total += 1;
+            if keep.contains(&hash) && seen.insert(hash) {
+                kept.extend_from_slice(&hash);
+                kept.extend_from_slice(vector);
+            }
+        })
+        .ok()
+        .flatten();
+        let removed = total - seen.len();
+
+        match header {
+            Some(header) if !seen.is_empty() => {
+                let mut temporary = tempfile::NamedTempFile::new_in(&self.dir)?;
+                {
+                    let mut writer = BufWriter::new(temporary.as_file_mut());
+                    write_header(&mut writer, &header.with_new_generation())?;
+                    writer.write_all(&kept)?;
+                    writer.flush()?;
+                }
+                temporary
+                    .persist(path)
+                    .map_err(|error| Error::Io(error.error))?;
+                Ok((removed, before.saturating_sub(fs::metadata(path)?.len())))
+            }
+            _ => {
+                fs::remove_file(path)?;
+                let _ = fs::remove_file(path.with_extension("lock"));
+                Ok((removed, before))
+            }
+        }
+    }
+
+    /// Delete every cached vector. Returns the number of entries removed and
+    /// bytes freed.
+    pub fn clear(&self) -> Result<(usize, u64)> {
+        self.prune(&HashSet::new())
+    }
+
+    fn space_path(&self, header: &SpaceHeader) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(header.provider_key.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(header.dimensions.to_le_bytes());
+        hasher.update(b"\0");
+        hasher.update(header.purpose.as_bytes());
+        let digest = format!("{:x}", hasher.finalize());
+        self.dir.join(format!("{}.bin", &digest[..16]))
+    }
+
+    fn space_files(&self) -> Result<Vec<PathBuf>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(Error::Io(error)),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "bin") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn space_header(provider: &dyn EmbeddingProvider, purpose: EmbeddingPurpose) -> SpaceHeader {
+    SpaceHeader {
+        provider_key: provider.batch_cache_key(),
+        dimensions: provider.dimensions(),
+        purpose: format!("{purpose:?}").to_lowercase(),
+        generation: 0,
+    }
+}
+
+fn record_len(dimensions: usize) -> u64 {
+    (32 + dimensions * 4) as u64
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|value| f32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+        .collect()
+}
+
+/// Write the magic, header length, and JSON header. Returns bytes written.
+fn write_header(writer: &mut impl Write, header: &SpaceHeader) -> Result<u64> {
+    let json = serde_json::to_vec(header).map_err(|error| {
+        Error::Serialization(format!(
+            "failed to serialize embedding cache header: {error}"
+        ))
+    })?;
+    writer.write_all(CHUNK_CACHE_MAGIC)?;
+    writer.write_all(&(json.len() as u32).to_le_bytes())?;
+    writer.write_all(&json)?;
+    Ok((CHUNK_CACHE_MAGIC.len() + 4 + json.len()) as u64)
+}
+
+/// Read the header of a `file_len`-byte space file. `Ok(None)` for an empty,
+/// foreign, or implausible file: the header length must fit in the file and
+/// the dimensions must be sane before anything is allocated.
+fn read_header(reader: &mut impl Read, file_len: u64) -> Result<Option<(SpaceHeader, u64)>> {
+    let mut magic = [0u8; 8];
+    let mut len = [0u8; 4];
+    if reader.read_exact(&mut magic).is_err()
+        || &magic != CHUNK_CACHE_MAGIC
+        || reader.read_exact(&mut len).is_err()
+    {
+        return Ok(None);
+    }
+    let len = u64::from(u32::from_le_bytes(len));
+    let header_len = (magic.len() + 4) as u64 + len;
+    if len > MAX_HEADER_LEN || header_len > file_len {
+        return Ok(None);
+    }
+    let mut json = vec![0u8; len as usize];
+    reader.read_exact(&mut json)?;
+    Ok(serde_json::from_slice::<SpaceHeader>(&json)
+        .ok()
+        .filter(|header| (1..=MAX_DIMENSIONS).contains(&header.dimensions))
+        .map(|header| (header, header_len)))
+}
+
+/// Open a space file and read its header: `(file, header, header length,
+/// file length)`, or `None` when the file is missing or not a cache file.
+fn open_space(path: &Path) -> Result<Option<(File, SpaceHeader, u64, u64)>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(Error::Io(error)),
+    };
+    let file_len = file.metadata()?.len();
+    Ok(read_header(&mut file, file_len)?
+        .map(|(header, header_len)| (file, header, header_len, file_len)))
+}
+
+fn write_record(writer: &mut impl Write, hash: &TextHash, vector: &[f32]) -> Result<()> {
+    writer.write_all(hash)?;
+    for value in vector {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Stream every complete record of a space file through `visit` as its hash
+/// and raw little-endian vector bytes. A torn trailing record is ignored.
+/// Returns the header, or `None` when the file is missing or not a cache
+/// file.
+fn read_space(path: &Path, mut visit: impl FnMut(TextHash, &[u8])) -> Result<Option<SpaceHeader>> {
+    let Some((file, header, _, _)) = open_space(path)? else {
+        return Ok(None);
+    };
+    let mut reader = BufReader::new(file);
+    let mut record = vec![0u8; record_len(header.dimensions) as usize];
+    while reader.read_exact(&mut record).is_ok() {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&record[..32]);
+        visit(hash, &record[32..]);
+    }
+    Ok(Some(header))
+}
+
+#[must_use]
+struct SpaceLock {
+    _file: File,
+}
+
+/// Try to take the per-space lock. `Ok(None)` when another process holds it.
+fn lock_space(path: &Path) -> Result<Option<SpaceLock>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("lock"))?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(SpaceLock { _file: file })),
+        Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+        Err(std::fs::TryLockError::Error(error)) => Err(Error::Io(error)),
+    }
+}
+
+/// Error for a maintenance command that found a cache file locked.
+pub(super) fn cache_busy(path: &Path) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::WouldBlock,
+        format!("another mdvdb process is using cache '{}'", path.display()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::mock::MockProvider;
+
+    #[test]
+    fn stores_looks_up_and_prunes_by_text_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkEmbeddingCache::new(dir.path());
+        let provider = MockProvider::new(4);
+        let purpose = EmbeddingPurpose::Document;
+        let (a, b) = (text_hash("alpha"), text_hash("beta"));
+        let (va, vb) = ([1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0]);
+
+        cache.store(&provider, purpose, &[(a, &va[..])]);
+        cache.store(&provider, purpose, &[(b, &vb[..])]);
+        let found = cache.lookup(&provider, purpose, &HashSet::from([a, b]));
+        assert_eq!(found[&a], vec![1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(found[&b], vec![0.0, 1.0, 0.0, 0.0]);
+
+        // Another vector space never serves these vectors.
+        let other = MockProvider::new(8);
+        assert!(cache
+            .lookup(&other, purpose, &HashSet::from([a]))
+            .is_empty());
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].entries, 2);
+        assert_eq!(stats[0].purpose, "document");
+
+        let (removed, freed) = cache.prune(&HashSet::from([b])).unwrap();
+        assert_eq!(removed, 1);
+        assert!(freed > 0);
+        let found = cache.lookup(&provider, purpose, &HashSet::from([a, b]));
+        assert_eq!(found.keys().collect::<Vec<_>>(), vec![&b]);
+
+        assert_eq!(cache.clear().unwrap().0, 1);
+        assert!(cache.stats().unwrap().is_empty());
+    }
+
+    #[test]
+    fn torn_trailing_record_is_ignored_and_realigned() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkEmbeddingCache::new(dir.path());
+        let provider = MockProvider::new(2);
+        let purpose = EmbeddingPurpose::Document;
+        let (a, b) = (text_hash("alpha"), text_hash("beta"));
+
+        cache.store(&provider, purpose, &[(a, &[1.0, 2.0][..])]);
+        let path = cache.space_path(&space_header(&provider, purpose));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[7u8; 10]).unwrap();
+
+        cache.store(&provider, purpose, &[(b, &[3.0, 4.0][..])]);
+        let found = cache.lookup(&provider, purpose, &HashSet::from([a, b]));
+        assert_eq!(found[&a], vec![1.0, 2.0]);
+        assert_eq!(found[&b], vec![3.0, 4.0]);
+    }
+
+    #[test]
+    fn offset_index_follows_appends_and_rewrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkEmbeddingCache::new(dir.path());
+        let provider = MockProvider::new(2);
+        let purpose = EmbeddingPurpose::Document;
+        let (a, b) = (text_hash("alpha"), text_hash("beta"));
+
+        cache.store(&provider, purpose, &[(a, &[1.0, 2.0][..])]);
+        assert_eq!(
+            cache.lookup(&provider, purpose, &HashSet::from([a])).len(),
+            1
+        );
+        // Appended after the index was built: picked up incrementally.
+        cache.store(&provider, purpose, &[(b, &[3.0, 4.0][..])]);
+        let found = cache.lookup(&provider, purpose, &HashSet::from([a, b]));
+        assert_eq!(found[&b], vec![3.0, 4.0]);
+
+        // A rewrite moves records; the new generation resets the index.
+        cache.prune(&HashSet::from([b])).unwrap();
+        let found = cache.lookup(&provider, purpose, &HashSet::from([a, b]));
+        assert_eq!(found.keys().collect::<Vec<_>>(), vec![&b]);
+        assert_eq!(found[&b], vec![3.0, 4.0]);
+    }
+
+    #[test]
+    fn corrupt_header_length_is_ignored_without_allocating() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkEmbeddingCache::new(dir.path());
+        let provider = MockProvider::new(2);
+        let purpose = EmbeddingPurpose::Document;
+        let a = text_hash("alpha");
+
+        let path = cache.space_path(&space_header(&provider, purpose));
+        fs::create_dir_all(&cache.dir).unwrap();
+        let mut bytes = CHUNK_CACHE_MAGIC.to_vec();
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        assert!(cache
+            .lookup(&provider, purpose, &HashSet::from([a]))
+            .is_empty());
+        assert!(cache.stats().unwrap().is_empty());
+
+        // The next store starts the space over.
+        cache.store(&provider, purpose, &[(a, &[1.0, 2.0][..])]);
+        assert_eq!(
+            cache.lookup(&provider, purpose, &HashSet::from([a]))[&a],
+            vec![1.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn compacts_only_when_dead_records_dominate() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkEmbeddingCache::new(dir.path());
+        let provider = MockProvider::new(1);
+        let purpose = EmbeddingPurpose::Document;
+        let hashes: Vec<TextHash> = (0..COMPACT_MIN_RECORDS + 1)
+            .map(|i| text_hash(&i.to_string()))
+            .collect();
+        let entries: Vec<(TextHash, &[f32])> =
+            hashes.iter().map(|hash| (*hash, &[0.5f32][..])).collect();
+        cache.store(&provider, purpose, &entries);
+
+        let live = HashSet::from([hashes[0]]);
+        // Enough live inputs: nothing to do, and `keep` is never computed.
+        let removed = cache
+            .compact_if_needed(hashes.len(), || unreachable!("no space is bloated"))
+            .unwrap();
+        assert_eq!(removed, 0);
+
+        let removed = cache.compact_if_needed(1, || live.clone()).unwrap();
+        assert_eq!(removed, hashes.len() - 1);
+        assert_eq!(cache.stats().unwrap()[0].entries, 1);
+        let found = cache.lookup(&provider, purpose, &hashes.iter().copied().collect());
+        assert_eq!(found.keys().collect::<Vec<_>>(), vec![&hashes[0]]);
+    }
+}
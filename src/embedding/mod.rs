@@ -1,8 +1,10 @@
 pub mod batch;
 pub mod bedrock;
+pub mod chunk_cache;
 pub mod gemini;
 pub mod huggingface;
 pub mod mock;
 pub mod ollama;
 pub mod openai;
 pub mod provider;
+pub mod query_cache;
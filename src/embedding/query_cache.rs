@@ -0,0 +1,508 @@
+//! Persistent LRU cache of query embeddings.
+//!
+//! Repeated searches re-embed the same query text on every run. The cache
+//! keeps recent query vectors under `.markdownvdb/cache/queries/`, one small
+//! binary file per query named by a hash of the provider's
+//! [`batch_cache_key`](EmbeddingProvider::batch_cache_key), vector
+//! dimensions, embedding purpose, and exact query text. A lookup reads only
+//! the entries it asks for, and a hit records its recency by bumping the
+//! file's modification time rather than rewriting any vector. It is derived,
+//! disposable state: malformed entries are ignored, a busy lock skips
+//! eviction, and write failures only log a warning.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::embedding::chunk_cache::cache_busy;
+use crate::embedding::provider::{EmbeddingProvider, EmbeddingPurpose};
+use crate::error::Error;
+use crate::Result;
+
+const QUERY_CACHE_MAGIC: &[u8; 8] = b"MDVDBQC1";
+
+/// Entry file header: magic, creation time (unix seconds), dimensions.
+const ENTRY_HEADER_LEN: usize = 8 + 8 + 4;
+
+const ENTRY_EXTENSION: &str = "vec";
+
+/// Single-file layout used before per-entry files; removed by prune and clear.
+const LEGACY_FILE: &str = "queries.json";
+
+/// Locked, atomic access to `.markdownvdb/cache/queries/`.
+#[derive(Debug, Clone)]
+pub(crate) struct QueryEmbeddingCache {
+    dir: PathBuf,
+    max_entries: usize,
+    ttl_secs: u64,
+}
+
+/// One entry file as seen by maintenance.
+#[derive(Debug)]
+struct EntryFile {
+    path: PathBuf,
+    /// Unix seconds of the last hit or store (the file's mtime).
+    used_at: u64,
+    bytes: u64,
+}
+
+/// Query vectors in input order, with where each one came from.
+#[derive(Debug, Default)]
+pub(crate) struct QueryVectors {
+    pub vectors: Vec<Vec<f32>>,
+    /// Per input: `Some(true)` served from cache, `Some(false)` embedded,
+    /// `None` when no cache was consulted.
+    pub cache_hits: Vec<Option<bool>>,
+    pub cache_lookup_secs: f64,
+    pub embed_secs: f64,
+}
+
+impl QueryEmbeddingCache {
+    /// Open the cache for a project. Returns `None` when `max_entries` is 0.
+    pub(crate) fn new(root: &Path, max_entries: usize, ttl_secs: u64) -> Option<Self> {
+        (max_entries > 0).then(|| Self::open(root, max_entries, ttl_secs))
+    }
+
+    /// Open the cache directory for inspection and maintenance, even when
+    /// caching is disabled.
+    pub(crate) fn open(root: &Path, max_entries: usize, ttl_secs: u64) -> Self {
+        Self {
+            dir: root.join(".markdownvdb").join("cache").join("queries"),
+            max_entries,
+            ttl_secs,
+        }
+    }
+
+    /// Number of cached entries and their total size in bytes.
+    pub(crate) fn stats(&self) -> (usize, u64) {
+        let entries = self.entry_files();
+        (entries.len(), entries.iter().map(|entry| entry.bytes).sum())
+    }
+
+    /// Drop expired entries and entries beyond the size cap. Returns the
+    /// number removed.
+    pub(crate) fn prune(&self) -> Result<usize> {
+        let now = unix_now();
+        let removed = self.with_lock(|| {
+            self.remove_legacy_file();
+            let mut removed = 0;
+            let mut live = Vec::new();
+            for entry in self.entry_files() {
+                if read_created_at(&entry.path).is_none_or(|created| self.expired(created, now)) {
+                    removed += usize::from(remove_entry(&entry.path));
+                } else {
+                    live.push(entry);
+                }
+            }
+            removed + self.evict_excess(live)
+        })?;
+        removed.ok_or_else(|| cache_busy(&self.dir))
+    }
+
+    /// Delete every cached entry. Returns the number removed.
+    pub(crate) fn clear(&self) -> Result<usize> {
+        let removed = self.with_lock(|| {
+            self.remove_legacy_file();
+            self.entry_files()
+                .iter()
+                .filter(|entry| remove_entry(&entry.path))
+                .count()
+        })?;
+        removed.ok_or_else(|| cache_busy(&self.dir))
+    }
+
+    /// Cache key for one query text under a provider's vector space.
+    pub(crate) fn key(
+        provider: &dyn EmbeddingProvider,
+        purpose: EmbeddingPurpose,
+        text: &str,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(provider.batch_cache_key().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(provider.dimensions().to_le_bytes());
+        hasher.update(b"\0");
+        hasher.update(format!("{purpose:?}").as_bytes());
+        hasher.update(b"\0");
+        hasher.update(text.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Look up `keys`, marking hits as most recently used and dropping
+    /// expired entries.
+    pub(crate) fn lookup(&self, keys: &[String]) -> Vec<Option<Vec<f32>>> {
+        self.lookup_at(keys, unix_now())
+    }
+
+    /// Insert freshly embedded vectors, evicting least recently used entries
+    /// beyond the size cap.
+    pub(crate) fn store(&self, entries: Vec<(String, Vec<f32>)>) {
+        self.store_at(entries, unix_now());
+    }
+
+    fn lookup_at(&self, keys: &[String], now: u64) -> Vec<Option<Vec<f32>>> {
+        keys.iter()
+            .map(|key| {
+                let path = self.entry_path(key);
+                let (created_at, vector) = read_entry(&path)?;
+                if self.expired(created_at, now) {
+                    remove_entry(&path);
+                    return None;
+                }
+                if let Err(error) = touch(&path, now) {
+                    debug!(path = %path.display(), %error, "failed to mark query cache entry as used");
+                }
+                Some(vector)
+            })
+            .collect()
+    }
+
+    fn store_at(&self, new_entries: Vec<(String, Vec<f32>)>, now: u64) {
+        if new_entries.is_empty() {
+            return;
+        }
+        if let Err(error) = self.write_entries(&new_entries, now) {
+            warn!(path = %self.dir.display(), %error, "query embedding cache update failed");
+        }
+    }
+
+    /// Atomically write one file per entry, then evict beyond the size cap
+    /// unless another process holds the lock.
+    fn write_entries(&self, entries: &[(String, Vec<f32>)], now: u64) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        for (key, vector) in entries {
+            let mut temporary = tempfile::NamedTempFile::new_in(&self.dir)?;
+            temporary.write_all(&encode_entry(now, vector))?;
+            temporary.as_file().set_modified(unix_time(now))?;
+            temporary
+                .persist(self.entry_path(key))
+                .map_err(|error| Error::Io(error.error))?;
+        }
+        let evicted = self.with_lock(|| self.evict_excess(self.entry_files()))?;
+        if evicted.is_none() {
+            debug!(path = %self.dir.display(), "query embedding cache busy; skipping eviction");
+        }
+        Ok(())
+    }
+
+    fn expired(&self, created_at: u64, now: u64) -> bool {
+        self.ttl_secs > 0 && now.saturating_sub(created_at) >= self.ttl_secs
+    }
+
+    /// Remove the least recently used of `entries` beyond the size cap.
+    /// Returns the number removed.
+    fn evict_excess(&self, mut entries: Vec<EntryFile>) -> usize {
+        let excess = entries.len().saturating_sub(self.max_entries);
+        if excess == 0 {
+            return 0;
+        }
+        entries.sort_by(|a, b| a.used_at.cmp(&b.used_at).then_with(|| a.path.cmp(&b.path)));
+        entries[..excess]
+            .iter()
+            .filter(|entry| remove_entry(&entry.path))
+            .count()
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{ENTRY_EXTENSION}"))
+    }
+
+    /// Every entry file with its recency and size; no vectors are read.
+    fn entry_files(&self) -> Vec<EntryFile> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .filter(|entry| {
+                entry.path().extension().and_then(|e| e.to_str()) == Some(ENTRY_EXTENSION)
+            })
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                let used_at = meta
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |elapsed| elapsed.as_secs());
+                Some(EntryFile {
+                    path: entry.path(),
+                    used_at,
+                    bytes: meta.len(),
+                })
+            })
+            .collect()
+    }
+
+    fn remove_legacy_file(&self) {
+        if let Some(parent) = self.dir.parent() {
+            remove_entry(&parent.join(LEGACY_FILE));
+        }
+    }
+
+    /// Run `maintain` under the cache lock. Returns `Ok(None)` when another
+    /// process holds the lock.
+    fn with_lock<T>(&self, maintain: impl FnOnce() -> T) -> Result<Option<T>> {
+        fs::create_dir_all(&self.dir)?;
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.dir.with_extension("lock"))?;
+        match lock.try_lock() {
+            Ok(()) => Ok(Some(maintain())),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(error)) => Err(Error::Io(error)),
+        }
+    }
+}
+
+fn encode_entry(created_at: u64, vector: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(ENTRY_HEADER_LEN + vector.len() * 4);
+    bytes.extend_from_slice(QUERY_CACHE_MAGIC);
+    bytes.extend_from_slice(&created_at.to_le_bytes());
+    bytes.extend_from_slice(&(vector.len() as u32).to_le_bytes());
+    for value in vector {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Parse an entry header into `(created_at, dimensions)`.
+fn decode_header(header: &[u8]) -> Option<(u64, usize)> {
+    if header.len() < ENTRY_HEADER_LEN || &header[..8] != QUERY_CACHE_MAGIC {
+        return None;
+    }
+    let created_at = u64::from_le_bytes(header[8..16].try_into().ok()?);
+    let dimensions = u32::from_le_bytes(header[16..20].try_into().ok()?) as usize;
+    Some((created_at, dimensions))
+}
+
+/// Read one entry, or `None` when it is missing or malformed.
+fn read_entry(path: &Path) -> Option<(u64, Vec<f32>)> {
+    let bytes = fs::read(path).ok()?;
+    match decode_header(&bytes) {
+        Some((created_at, dimensions)) if bytes.len() - ENTRY_HEADER_LEN == dimensions * 4 => {
+            let vector = bytes[ENTRY_HEADER_LEN..]
+                .chunks_exact(4)
+                .map(|value| f32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+                .collect();
+            Some((created_at, vector))
+        }
+        _ => {
+            warn!(path = %path.display(), "ignoring malformed query embedding cache entry");
+            None
+        }
+    }
+}
+
+/// Read only an entry's creation time.
+fn read_created_at(path: &Path) -> Option<u64> {
+    let mut header = [0u8; ENTRY_HEADER_LEN];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+    decode_header(&header).map(|(created_at, _)| created_at)
+}
+
+/// Mark an entry as used at `now` without rewriting it.
+fn touch(path: &Path, now: u64) -> std::io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_modified(unix_time(now))
+}
+
+/// Delete one entry file. Returns whether it existed.
+fn remove_entry(path: &Path) -> bool {
+    match fs::remove_file(path) {
+        Ok(()) => true,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => false,
+        Err(error) => {
+            warn!(path = %path.display(), %error, "failed to remove query embedding cache entry");
+            false
+        }
+    }
+}
+
+/// Embed `texts` for [`EmbeddingPurpose::Query`], serving what it can from
+/// `cache` and embedding all misses in a single provider call.
+pub(crate) async fn embed_queries(
+    cache: Option<&QueryEmbeddingCache>,
+    provider: &dyn EmbeddingProvider,
+    texts: &[String],
+) -> Result<QueryVectors> {
+    if texts.is_empty() {
+        return Ok(QueryVectors::default());
+    }
+    let purpose = EmbeddingPurpose::Query;
+    let lookup_start = Instant::now();
+    let (keys, cached) = match cache {
+        Some(cache) => {
+            let keys: Vec<String> = texts
+                .iter()
+                .map(|text| QueryEmbeddingCache::key(provider, purpose, text))
+                .collect();
+            let cached = cache.lookup(&keys);
+            (keys, cached)
+        }
+        None => (Vec::new(), vec![None; texts.len()]),
+    };
+    let cache_lookup_secs = lookup_start.elapsed().as_secs_f64();
+
+    let missing: Vec<usize> = (0..texts.len()).filter(|&i| cached[i].is_none()).collect();
+    let (embedded, embed_secs) = if missing.is_empty() {
+        (Vec::new(), 0.0)
+    } else {
+        let embed_start = Instant::now();
+        let miss_texts: Vec<String> = missing.iter().map(|&i| texts[i].clone()).collect();
+        let vectors = provider.embed_batch_for(&miss_texts, purpose).await?;
+        if vectors.len() != miss_texts.len() {
+            return Err(Error::EmbeddingProvider(format!(
+                "provider returned {} query embeddings for {} queries",
+                vectors.len(),
+                miss_texts.len()
+            )));
+        }
+        (vectors, embed_start.elapsed().as_secs_f64())
+    };
+
+    if let Some(cache) = cache {
+        let fresh = missing
+            .iter()
+            .zip(&embedded)
+            .map(|(&i, vector)| (keys[i].clone(), vector.clone()))
+            .collect();
+        cache.store(fresh);
+    }
+
+    let cache_hits = cached
+        .iter()
+        .map(|vector| cache.map(|_| vector.is_some()))
+        .collect();
+    let mut embedded = embedded.into_iter();
+    let vectors = cached
+        .into_iter()
+        .map(|vector| vector.or_else(|| embedded.next()).unwrap_or_default())
+        .collect();
+    Ok(QueryVectors {
+        vectors,
+        cache_hits,
+        cache_lookup_secs,
+        embed_secs,
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+fn unix_time(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::mock::MockProvider;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn evicts_least_recently_used_and_expires_by_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = QueryEmbeddingCache::new(dir.path(), 2, 100).unwrap();
+
+        cache.store_at(
+            vec![("a".into(), vec![1.0]), ("b".into(), vec![2.0])],
+            1_000,
+        );
+        // Touch "a" so "b" becomes the eviction candidate.
+        assert_eq!(cache.lookup_at(&keys(&["a"]), 1_010), vec![Some(vec![1.0])]);
+        cache.store_at(vec![("c".into(), vec![3.0])], 1_020);
+        assert_eq!(
+            cache.lookup_at(&keys(&["a", "b", "c"]), 1_030),
+            vec![Some(vec![1.0]), None, Some(vec![3.0])]
+        );
+
+        // "a" was embedded at 1_000; at 1_100 it is past the 100s TTL.
+        assert_eq!(
+            cache.lookup_at(&keys(&["a", "c"]), 1_100),
+            vec![None, Some(vec![3.0])]
+        );
+    }
+
+    #[test]
+    fn lookup_only_bumps_recency_of_hit_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = QueryEmbeddingCache::new(dir.path(), 10, 0).unwrap();
+        cache.store_at(
+            vec![("a".into(), vec![1.0, 2.0]), ("b".into(), vec![3.0])],
+            1_000,
+        );
+        let bytes = fs::read(cache.entry_path("a")).unwrap();
+
+        assert_eq!(
+            cache.lookup_at(&keys(&["a", "missing"]), 1_050),
+            vec![Some(vec![1.0, 2.0]), None]
+        );
+        assert_eq!(fs::read(cache.entry_path("a")).unwrap(), bytes);
+        let mut used: Vec<(String, u64)> = cache
+            .entry_files()
+            .into_iter()
+            .map(|entry| {
+                let name = entry
+                    .path
+                    .file_stem()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned();
+                (name, entry.used_at)
+            })
+            .collect();
+        used.sort();
+        assert_eq!(used, vec![("a".into(), 1_050), ("b".into(), 1_000)]);
+    }
+
+    #[test]
+    fn malformed_cache_file_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = QueryEmbeddingCache::new(dir.path(), 10, 0).unwrap();
+        fs::create_dir_all(&cache.dir).unwrap();
+        fs::write(cache.entry_path("a"), b"not an entry").unwrap();
+
+        assert_eq!(cache.lookup(&keys(&["a"])), vec![None]);
+        cache.store(vec![("a".into(), vec![1.0])]);
+        assert_eq!(cache.lookup(&keys(&["a"])), vec![Some(vec![1.0])]);
+    }
+
+    #[tokio::test]
+    async fn embed_queries_serves_repeats_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = QueryEmbeddingCache::new(dir.path(), 10, 0).unwrap();
+        let provider = MockProvider::new(8);
+        let texts = keys(&["rust ownership", "tokio runtime"]);
+
+        let first = embed_queries(Some(&cache), &provider, &texts[..1])
+            .await
+            .unwrap();
+        assert_eq!(first.cache_hits, vec![Some(false)]);
+
+        let second = embed_queries(Some(&cache), &provider, &texts)
+            .await
+            .unwrap();
+        assert_eq!(second.cache_hits, vec![Some(true), Some(false)]);
+        assert_eq!(second.vectors[0], first.vectors[0]);
+        assert_eq!(second.vectors[1].len(), 8);
+
+        let uncached = embed_queries(None, &provider, &texts).await.unwrap();
+        assert_eq!(uncached.cache_hits, vec![None, None]);
+        assert_eq!(uncached.vectors, second.vectors);
+    }
+}
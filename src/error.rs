@@ -9,6 +9,12 @@ pub enum Error {
     #[error("shard error: {0}")]
     Shard(String),
 
+    #[error("snapshot error: {0}")]
+    Snapshot(String),
+
+    #[error("export error: {0}")]
+    Export(String),
+
     #[error("another mdvdb process is updating the project configuration: {}", path.display())]
     ConfigBusy { path: PathBuf },
 
@@ -21,6 +27,9 @@ pub enum Error {
     #[error("embedding provider error: {0}")]
     EmbeddingProvider(String),
 
+    #[error("reranker error: {0}")]
+    Reranker(String),
+
     #[error("markdown parse error in {}: {message}", path.display())]
     MarkdownParse { path: PathBuf, message: String },
 
@@ -74,6 +83,15 @@ pub enum Error {
 
     #[error("semantic edge error: {0}")]
     SemanticEdge(String),
+
+    #[error("invalid filter expression at position {position}: {message}")]
+    FilterExpression { position: usize, message: String },
+
+    #[error("invalid lexical query at position {position}: {message}")]
+    LexicalQuery { position: usize, message: String },
+
+    #[error("invalid regex '{pattern}': {message}")]
+    RegexPattern { pattern: String, message: String },
 }
 
 /// Convenience alias used throughout the crate.
@@ -96,6 +114,18 @@ mod tests {
         assert_eq!(err.to_string(), "shard error: invalid path");
     }
 
+    #[test]
+    fn snapshot_variant_formats() {
+        let err = Error::Snapshot("not found".into());
+        assert_eq!(err.to_string(), "snapshot error: not found");
+    }
+
+    #[test]
+    fn export_variant_formats() {
+        let err = Error::Export("unsupported version".into());
+        assert_eq!(err.to_string(), "export error: unsupported version");
+    }
+
     #[test]
     fn config_busy_variant_formats() {
         let err = Error::ConfigBusy {
@@ -126,6 +156,12 @@ mod tests {
         assert_eq!(err.to_string(), "embedding provider error: timeout");
     }
 
+    #[test]
+    fn reranker_variant_formats() {
+        let err = Error::Reranker("timeout".into());
+        assert_eq!(err.to_string(), "reranker error: timeout");
+    }
+
     #[test]
     fn markdown_parse_variant_formats() {
         let err = Error::MarkdownParse {
@@ -246,6 +282,39 @@ mod tests {
         assert_eq!(err.to_string(), "semantic edge error: invalid weight");
     }
 
+    #[test]
+    fn filter_expression_variant_formats() {
+        let err = Error::FilterExpression {
+            position: 7,
+            message: "expected a value".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid filter expression at position 7: expected a value"
+        );
+    }
+
+    #[test]
+    fn lexical_query_variant_formats() {
+        let err = Error::LexicalQuery {
+            position: 4,
+            message: "empty phrase".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid lexical query at position 4: empty phrase"
+        );
+    }
+
+    #[test]
+    fn regex_pattern_variant_formats() {
+        let err = Error::RegexPattern {
+            pattern: "ERR-(".into(),
+            message: "unclosed group".into(),
+        };
+        assert_eq!(err.to_string(), "invalid regex 'ERR-(': unclosed group");
+    }
+
     #[test]
     fn error_is_send_and_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
@@ -0,0 +1,895 @@
+//! Portable index export and import.
+//!
+//! `mdvdb export` writes the whole index to a directory that does not depend
+//! on the rkyv layout of the index file, so it can be moved between machines
+//! running different mdvdb versions and read by other tools:
+//!
+//! - `index.jsonl`: one JSON object per line, tagged by `"type"`. The first
+//!   line is the `header` (format version, embedding space, counts); it is
+//!   followed by every `file` and its `chunk`s, the `edge` vectors, and the
+//!   `schema`, `scoped_schemas`, `clusters`, `custom_clusters` and
+//!   `link_graph` state. Chunks and edges refer to their vector by row.
+//! - `vectors.npy`: a NumPy v1.0 array of little-endian `f32`, shaped
+//!   `(vector_count, dimensions)`.
+//!
+//! `mdvdb import` rebuilds the HNSW and FTS indexes from these files
+//! without calling the embedding provider. Readers ignore unknown fields and
+//! line types, so an export from a newer mdvdb of the same format version
+//! still imports.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use tracing::{info, warn};
+
+use crate::clustering::{ClusterState, CustomClusterState};
+use crate::error::Error;
+use crate::fts::{self, FtsIndex, LexicalSettings};
+use crate::index::state::Index;
+use crate::index::storage::WriteOptions;
+use crate::index::types::{
+    ComputedDependencySnapshot, ComputedFieldDiagnostic, ComputedFieldEntry, EmbeddingConfig,
+    StoredChunk, StoredFile,
+};
+use crate::index::wal::{WalChunk, WalOp, WalRecord, WalVector};
+use crate::links::LinkGraph;
+use crate::schema::{Schema, ScopedSchema};
+use crate::Result;
+
+const EXPORT_FORMAT: &str = "mdvdb.export";
+const EXPORT_VERSION: u32 = 1;
+const LINES_FILE: &str = "index.jsonl";
+const VECTORS_FILE: &str = "vectors.npy";
+const EMBEDDING_SPACE_FILE: &str = "embedding-space.json";
+const NPY_MAGIC: &[u8; 6] = b"\x93NUMPY";
+/// NumPy pads the array header so the data starts on this boundary.
+const NPY_ALIGNMENT: usize = 64;
+
+/// Summary of an export directory, returned by export and import.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportInfo {
+    /// The export directory.
+    pub path: PathBuf,
+    /// Export format version.
+    pub version: u32,
+    /// Version of the mdvdb that wrote the export.
+    pub mdvdb_version: String,
+    /// Embedding provider the exported vectors were produced by.
+    pub embedding_provider: String,
+    /// Embedding model the exported vectors were produced by.
+    pub embedding_model: String,
+    /// Dimensionality of the exported vectors.
+    pub embedding_dimensions: usize,
+    /// Number of exported documents.
+    pub document_count: usize,
+    /// Number of exported chunks.
+    pub chunk_count: usize,
+    /// Number of exported edge vectors.
+    pub edge_count: usize,
+    /// Total size of the export files in bytes.
+    pub bytes: u64,
+}
+
+/// First line of `index.jsonl`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportHeader {
+    format: String,
+    version: u32,
+    mdvdb_version: String,
+    /// Unix time the export was written.
+    exported_at: u64,
+    /// Unix time the exported index was last saved.
+    last_updated: u64,
+    embedding: EmbeddingConfig,
+    /// The project's embedding-space descriptor, if it had one.
+    #[serde(default)]
+    embedding_space: Option<serde_json::Value>,
+    vector_file: String,
+    vector_count: usize,
+    document_count: usize,
+    chunk_count: usize,
+    edge_count: usize,
+}
+
+/// One line of `index.jsonl`. Lines are read back by their `"type"` into
+/// the payload types directly.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportLine<'a> {
+    Header(&'a ExportHeader),
+    File(ExportFile),
+    Chunk(ExportChunk),
+    Edge(ExportEdge),
+    Schema { schema: &'a Schema },
+    ScopedSchemas { schemas: &'a [ScopedSchema] },
+    Clusters { state: &'a ClusterState },
+    CustomClusters { state: &'a CustomClusterState },
+    LinkGraph { graph: &'a LinkGraph },
+}
+
+#[derive(Deserialize)]
+struct LineType {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportFile {
+    path: String,
+    content_hash: String,
+    embedding_body_hash: String,
+    /// The stored frontmatter JSON, written verbatim so key order survives.
+    #[serde(default)]
+    frontmatter: Option<Box<RawValue>>,
+    file_size: u64,
+    indexed_at: u64,
+    #[serde(default)]
+    mtime: Option<u64>,
+    chunk_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    computed_fields: BTreeMap<String, ExportComputedField>,
+}
+
+/// A [`ComputedFieldEntry`] including the bookkeeping its public JSON omits,
+/// so module ownership survives the round trip.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportComputedField {
+    module: String,
+    definition_fingerprint: String,
+    #[serde(default)]
+    input_fingerprint: Option<String>,
+    #[serde(default)]
+    dependency_snapshot: ComputedDependencySnapshot,
+    #[serde(default)]
+    value_json: Option<String>,
+    #[serde(default)]
+    materialized_value_json: Option<String>,
+    #[serde(default)]
+    diagnostic: Option<ComputedFieldDiagnostic>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportChunk {
+    id: String,
+    source_path: String,
+    heading_hierarchy: Vec<String>,
+    content: String,
+    start_line: usize,
+    end_line: usize,
+    chunk_index: usize,
+    is_sub_split: bool,
+    /// Row in `vectors.npy`; absent for a chunk stored without a vector.
+    #[serde(default)]
+    vector: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportEdge {
+    id: String,
+    /// Row in `vectors.npy`.
+    vector: usize,
+}
+
+#[derive(Deserialize)]
+struct SchemaLine {
+    schema: Schema,
+}
+
+#[derive(Deserialize)]
+struct ScopedSchemasLine {
+    schemas: Vec<ScopedSchema>,
+}
+
+#[derive(Deserialize)]
+struct ClustersLine {
+    state: ClusterState,
+}
+
+#[derive(Deserialize)]
+struct CustomClustersLine {
+    state: CustomClusterState,
+}
+
+#[derive(Deserialize)]
+struct LinkGraphLine {
+    graph: LinkGraph,
+}
+
+/// Write `index` to the directory `dir`, which must be empty or missing.
+pub(crate) fn export(index: &Index, root: &Path, dir: &Path) -> Result<ExportInfo> {
+    if fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(Error::Export(format!(
+            "'{}' is not empty; choose a new directory",
+            dir.display()
+        )));
+    }
+    fs::create_dir_all(dir)?;
+
+    let embedding_space_path = root.join(".markdownvdb").join(EMBEDDING_SPACE_FILE);
+    let embedding_space = if embedding_space_path.is_file() {
+        let bytes = fs::read(&embedding_space_path)?;
+        Some(serde_json::from_slice(&bytes).map_err(|error| {
+            Error::Serialization(format!(
+                "failed to parse embedding-space descriptor '{}': {error}",
+                embedding_space_path.display()
+            ))
+        })?)
+    } else {
+        None
+    };
+
+    let status = index.status();
+    let writer = index.visit_full_record(
+        |counts| {
+            let header = ExportHeader {
+                format: EXPORT_FORMAT.to_string(),
+                version: EXPORT_VERSION,
+                mdvdb_version: env!("CARGO_PKG_VERSION").to_string(),
+                exported_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                last_updated: counts.last_updated,
+                embedding: status.embedding_config,
+                embedding_space,
+                vector_file: VECTORS_FILE.to_string(),
+                vector_count: counts.chunk_vectors + counts.edges,
+                document_count: counts.files,
+                chunk_count: counts.chunks,
+                edge_count: counts.edges,
+            };
+            ExportWriter::create(dir, header)
+        },
+        ExportWriter::write_op,
+    )?;
+    let header = writer.finish(dir)?;
+
+    let info = export_info(dir, &header)?;
+    info!(
+        path = %dir.display(),
+        documents = info.document_count,
+        chunks = info.chunk_count,
+        "exported index"
+    );
+    Ok(info)
+}
+
+/// Writes `vectors.npy` and a staged `index.jsonl` as the index is visited,
+/// one op at a time.
+struct ExportWriter {
+    header: ExportHeader,
+    vectors: BufWriter<File>,
+    lines: BufWriter<File>,
+    staged_lines: PathBuf,
+    next_row: usize,
+}
+
+impl ExportWriter {
+    fn create(dir: &Path, header: ExportHeader) -> Result<Self> {
+        let mut vectors = BufWriter::new(File::create(dir.join(VECTORS_FILE))?);
+        vectors.write_all(&npy_header(
+            header.vector_count,
+            header.embedding.dimensions,
+        ))?;
+        // `index.jsonl` is renamed into place last, so an interrupted export
+        // never looks complete.
+        let staged_lines = dir.join(format!("{LINES_FILE}.tmp"));
+        let mut lines = BufWriter::new(File::create(&staged_lines)?);
+        write_line(&mut lines, &ExportLine::Header(&header))?;
+        Ok(Self {
+            header,
+            vectors,
+            lines,
+            staged_lines,
+            next_row: 0,
+        })
+    }
+
+    /// Append `vector` to `vectors.npy`, returning its row.
+    fn add_vector(&mut self, vector: &[f32]) -> Result<usize> {
+        for value in vector {
+            self.vectors.write_all(&value.to_le_bytes())?;
+        }
+        self.next_row += 1;
+        Ok(self.next_row - 1)
+    }
+
+    fn write_op(&mut self, op: WalOp) -> Result<()> {
+        match &op {
+            WalOp::UpsertFile {
+                path,
+                file,
+                mtime,
+                chunks,
+            } => {
+                write_line(
+                    &mut self.lines,
+                    &ExportLine::File(export_file(path, file, *mtime)),
+                )?;
+                for chunk in chunks {
+                    let vector = match &chunk.vector {
+                        Some(vector) => Some(self.add_vector(vector)?),
+                        None => None,
+                    };
+                    write_line(
+                        &mut self.lines,
+                        &ExportLine::Chunk(export_chunk(&chunk.id, &chunk.chunk, vector)),
+                    )?;
+                }
+            }
+            WalOp::Edges { upserted, .. } => {
+                for edge in upserted {
+                    let row = self.add_vector(&edge.vector)?;
+                    write_line(
+                        &mut self.lines,
+                        &ExportLine::Edge(ExportEdge {
+                            id: edge.id.clone(),
+                            vector: row,
+                        }),
+                    )?;
+                }
+            }
+            WalOp::Schema(Some(schema)) => {
+                write_line(&mut self.lines, &ExportLine::Schema { schema })?;
+            }
+            WalOp::ScopedSchemas(Some(schemas)) => {
+                write_line(&mut self.lines, &ExportLine::ScopedSchemas { schemas })?;
+            }
+            WalOp::Clusters(Some(state)) => {
+                write_line(&mut self.lines, &ExportLine::Clusters { state })?;
+            }
+            WalOp::CustomClusters(Some(state)) => {
+                write_line(&mut self.lines, &ExportLine::CustomClusters { state })?;
+            }
+            WalOp::LinkGraph(Some(graph)) => {
+                write_line(&mut self.lines, &ExportLine::LinkGraph { graph })?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Sync both files and move `index.jsonl` into place.
+    fn finish(self, dir: &Path) -> Result<ExportHeader> {
+        // A vector that could not be read back would shift every later row.
+        if self.next_row != self.header.vector_count {
+            return Err(Error::Export(format!(
+                "only {} of the index's {} vectors could be read; run `mdvdb fsck`",
+                self.next_row, self.header.vector_count
+            )));
+        }
+        self.vectors
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        self.lines
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        fs::rename(&self.staged_lines, dir.join(LINES_FILE))?;
+        Ok(self.header)
+    }
+}
+
+/// Replace the index of the project at `root` with the export in `dir`.
+///
+/// The export is read and validated completely before anything is changed.
+/// The vector index is replaced first under an FTS reconciliation marker,
+/// so a crash part-way leaves a project whose next writable open rebuilds
+/// FTS from the imported vectors.
+pub(crate) fn import(
+    root: &Path,
+    dir: &Path,
+    write_options: WriteOptions,
+    lexical_settings: &LexicalSettings,
+) -> Result<ExportInfo> {
+    let (header, record) = read(dir)?;
+
+    let state_dir = root.join(".markdownvdb");
+    fs::create_dir_all(&state_dir)?;
+    let fts_path = state_dir.join("fts");
+    fts::ensure_no_writer(&fts_path)?;
+
+    fts::begin_reconciliation(root)?;
+    let index = Index::open_or_create_with_options(
+        &state_dir.join("index"),
+        &header.embedding,
+        write_options,
+    )?;
+    index.replace_with(&header.embedding, record)?;
+    index.save()?;
+
+    let fts_index = FtsIndex::open_or_create_with_settings(&fts_path, lexical_settings)?;
+    fts::rebuild_from_vector_index(&fts_index, &index)?;
+    drop(fts_index);
+
+    let embedding_space = state_dir.join(EMBEDDING_SPACE_FILE);
+    match &header.embedding_space {
+        Some(descriptor) => {
+            let mut temp = tempfile::NamedTempFile::new_in(&state_dir)?;
+            serde_json::to_writer_pretty(&mut temp, descriptor)
+                .map_err(|error| Error::Serialization(error.to_string()))?;
+            temp.write_all(b"\n")?;
+            temp.as_file().sync_all()?;
+            temp.persist(&embedding_space)
+                .map_err(|error| Error::Io(error.error))?;
+        }
+        None if embedding_space.is_file() => fs::remove_file(&embedding_space)?,
+        None => {}
+    }
+    fts::finish_reconciliation(root)?;
+
+    let info = export_info(dir, &header)?;
+    info!(
+        path = %dir.display(),
+        documents = info.document_count,
+        chunks = info.chunk_count,
+        "imported index"
+    );
+    Ok(info)
+}
+
+/// Read and validate the export in `dir` as the record rebuilding its index.
+fn read(dir: &Path) -> Result<(ExportHeader, WalRecord)> {
+    let lines_path = dir.join(LINES_FILE);
+    let file = File::open(&lines_path).map_err(|error| {
+        Error::Export(format!(
+            "cannot read '{}': {error}; is this an mdvdb export?",
+            lines_path.display()
+        ))
+    })?;
+    let mut lines = BufReader::new(file).lines().enumerate();
+
+    let parse_error = |number: usize, error: serde_json::Error| {
+        Error::Export(format!("{}:{}: {error}", lines_path.display(), number + 1))
+    };
+    let header: ExportHeader = match lines.next() {
+        Some((number, line)) => serde_json::from_str(&line?).map_err(|e| parse_error(number, e))?,
+        None => {
+            return Err(Error::Export(format!(
+                "'{}' is empty",
+                lines_path.display()
+            )))
+        }
+    };
+    if header.format != EXPORT_FORMAT {
+        return Err(Error::Export(format!(
+            "'{}' is not an mdvdb export (format '{}')",
+            lines_path.display(),
+            header.format
+        )));
+    }
+    if header.version > EXPORT_VERSION {
+        return Err(Error::Export(format!(
+            "export format v{} was written by mdvdb {}; this version reads up to v{EXPORT_VERSION}",
+            header.version, header.mdvdb_version
+        )));
+    }
+
+    // The header names the vector file; it must not lead out of `dir`.
+    let mut components = Path::new(&header.vector_file).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(Error::Export(format!(
+            "'{}' names vector file '{}'; expected a file name in the export directory",
+            lines_path.display(),
+            header.vector_file
+        )));
+    }
+    let vectors = NpyVectors::open(&dir.join(&header.vector_file))?;
+    if vectors.rows != header.vector_count || vectors.dims != header.embedding.dimensions {
+        return Err(Error::Export(format!(
+            "'{}' holds {} vectors of {} dimensions, the header expects {} of {}",
+            header.vector_file,
+            vectors.rows,
+            vectors.dims,
+            header.vector_count,
+            header.embedding.dimensions
+        )));
+    }
+
+    let mut files = Vec::new();
+    let mut chunks = HashMap::new();
+    let mut edges = Vec::new();
+    let mut state_ops = Vec::new();
+    for (number, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let LineType { kind } = serde_json::from_str(&line).map_err(|e| parse_error(number, e))?;
+        let parsed = match kind.as_str() {
+            "file" => serde_json::from_str(&line).map(|file: ExportFile| files.push(file)),
+            "chunk" => serde_json::from_str(&line).map(|chunk: ExportChunk| {
+                chunks.insert(chunk.id.clone(), chunk);
+            }),
+            "edge" => serde_json::from_str(&line).map(|edge: ExportEdge| edges.push(edge)),
+            "schema" => serde_json::from_str(&line)
+                .map(|l: SchemaLine| state_ops.push(WalOp::Schema(Some(l.schema)))),
+            "scoped_schemas" => serde_json::from_str(&line)
+                .map(|l: ScopedSchemasLine| state_ops.push(WalOp::ScopedSchemas(Some(l.schemas)))),
+            "clusters" => serde_json::from_str(&line)
+                .map(|l: ClustersLine| state_ops.push(WalOp::Clusters(Some(l.state)))),
+            "custom_clusters" => serde_json::from_str(&line)
+                .map(|l: CustomClustersLine| state_ops.push(WalOp::CustomClusters(Some(l.state)))),
+            "link_graph" => serde_json::from_str(&line)
+                .map(|l: LinkGraphLine| state_ops.push(WalOp::LinkGraph(Some(l.graph)))),
+            other => {
+                warn!(
+                    line = number + 1,
+                    kind = other,
+                    "ignoring unknown export line"
+                );
+                Ok(())
+            }
+        };
+        parsed.map_err(|e| parse_error(number, e))?;
+    }
+
+    let mut ops = Vec::with_capacity(files.len() + state_ops.len() + 1);
+    let mut claimed = HashSet::new();
+    for file in files {
+        let mut file_chunks = Vec::with_capacity(file.chunk_ids.len());
+        for id in &file.chunk_ids {
+            let chunk = chunks.get(id).ok_or_else(|| {
+                Error::Export(format!(
+                    "file '{}' references missing chunk '{id}'",
+                    file.path
+                ))
+            })?;
+            claimed.insert(id.clone());
+            file_chunks.push(WalChunk {
+                id: id.clone(),
+                chunk: StoredChunk {
+                    source_path: chunk.source_path.clone(),
+                    heading_hierarchy: chunk.heading_hierarchy.clone(),
+                    content: chunk.content.clone(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    chunk_index: chunk.chunk_index,
+                    is_sub_split: chunk.is_sub_split,
+                },
+                vector: chunk.vector.map(|row| vectors.row(row)).transpose()?,
+            });
+        }
+        let (path, stored, mtime) = stored_file(file);
+        ops.push(WalOp::UpsertFile {
+            path,
+            file: stored,
+            mtime,
+            chunks: file_chunks,
+        });
+    }
+    if let Some(id) = chunks.keys().find(|id| !claimed.contains(*id)) {
+        return Err(Error::Export(format!(
+            "chunk '{id}' does not belong to any exported file"
+        )));
+    }
+    if !edges.is_empty() {
+        let upserted = edges
+            .into_iter()
+            .map(|edge| {
+                Ok(WalVector {
+                    vector: vectors.row(edge.vector)?,
+                    id: edge.id,
+                })
+            })
+            .collect::<Result<_>>()?;
+        ops.push(WalOp::Edges {
+            upserted,
+            removed: Vec::new(),
+        });
+    }
+    ops.extend(state_ops);
+
+    let record = WalRecord {
+        last_updated: header.last_updated,
+        ops,
+    };
+    Ok((header, record))
+}
+
+fn export_info(dir: &Path, header: &ExportHeader) -> Result<ExportInfo> {
+    let bytes = fs::metadata(dir.join(LINES_FILE))?.len()
+        + fs::metadata(dir.join(&header.vector_file))?.len();
+    Ok(ExportInfo {
+        path: dir.to_path_buf(),
+        version: header.version,
+        mdvdb_version: header.mdvdb_version.clone(),
+        embedding_provider: header.embedding.provider.clone(),
+        embedding_model: header.embedding.model.clone(),
+        embedding_dimensions: header.embedding.dimensions,
+        document_count: header.document_count,
+        chunk_count: header.chunk_count,
+        edge_count: header.edge_count,
+        bytes,
+    })
+}
+
+fn write_line(out: &mut impl Write, line: &ExportLine<'_>) -> Result<()> {
+    serde_json::to_writer(&mut *out, line)
+        .map_err(|error| Error::Serialization(error.to_string()))?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+fn export_file(path: &str, file: &StoredFile, mtime: Option<u64>) -> ExportFile {
+    ExportFile {
+        path: path.to_string(),
+        content_hash: file.content_hash.clone(),
+        embedding_body_hash: file.embedding_body_hash.clone(),
+        frontmatter: file
+            .frontmatter
+            .clone()
+            .and_then(|json| RawValue::from_string(json).ok()),
+        file_size: file.file_size,
+        indexed_at: file.indexed_at,
+        mtime,
+        chunk_ids: file.chunk_ids.clone(),
+        computed_fields: file
+            .computed_fields
+            .iter()
+            .map(|(field, entry)| {
+                (
+                    field.clone(),
+                    ExportComputedField {
+                        module: entry.module.clone(),
+                        definition_fingerprint: entry.definition_fingerprint.clone(),
+                        input_fingerprint: entry.input_fingerprint.clone(),
+                        dependency_snapshot: entry.dependency_snapshot.clone(),
+                        value_json: entry.value_json.clone(),
+                        materialized_value_json: entry.materialized_value_json.clone(),
+                        diagnostic: entry.diagnostic.clone(),
+                    },
+                )
+            })
+            .collect(),
+    }
+}
+
+fn stored_file(file: ExportFile) -> (String, StoredFile, Option<u64>) {
+    let stored = StoredFile {
+        relative_path: file.path.clone(),
+        content_hash: file.content_hash,
+        embedding_body_hash: file.embedding_body_hash,
+        frontmatter: file.frontmatter.map(|json| json.get().to_string()),
+        file_size: file.file_size,
+        chunk_ids: file.chunk_ids,
+        indexed_at: file.indexed_at,
+        computed_fields: file
+            .computed_fields
+            .into_iter()
+            .map(|(field, entry)| {
+                (
+                    field,
+                    ComputedFieldEntry {
+                        module: entry.module,
+                        definition_fingerprint: entry.definition_fingerprint,
+                        input_fingerprint: entry.input_fingerprint,
+                        dependency_snapshot: entry.dependency_snapshot,
+                        value_json: entry.value_json,
+                        materialized_value_json: entry.materialized_value_json,
+                        diagnostic: entry.diagnostic,
+                    },
+                )
+            })
+            .collect(),
+    };
+    (file.path, stored, file.mtime)
+}
+
+fn export_chunk(id: &str, chunk: &StoredChunk, vector: Option<usize>) -> ExportChunk {
+    ExportChunk {
+        id: id.to_string(),
+        source_path: chunk.source_path.clone(),
+        heading_hierarchy: chunk.heading_hierarchy.clone(),
+        content: chunk.content.clone(),
+        start_line: chunk.start_line,
+        end_line: chunk.end_line,
+        chunk_index: chunk.chunk_index,
+        is_sub_split: chunk.is_sub_split,
+        vector,
+    }
+}
+
+/// NumPy v1.0 header for a C-order `<f4` array of `rows` x `dims`.
+fn npy_header(rows: usize, dims: usize) -> Vec<u8> {
+    let dict = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({rows}, {dims}), }}");
+    // Magic, version and header length precede the dict, which ends in a
+    // newline after the padding.
+    let unpadded = NPY_MAGIC.len() + 4 + dict.len() + 1;
+    let padding = (NPY_ALIGNMENT - unpadded % NPY_ALIGNMENT) % NPY_ALIGNMENT;
+    let header_len = (dict.len() + padding + 1) as u16;
+    let mut bytes = Vec::with_capacity(unpadded + padding);
+    bytes.extend_from_slice(NPY_MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&header_len.to_le_bytes());
+    bytes.extend_from_slice(dict.as_bytes());
+    bytes.resize(bytes.len() + padding, b' ');
+    bytes.push(b'\n');
+    bytes
+}
+
+/// A memory-mapped `.npy` file of little-endian `f32` rows.
+struct NpyVectors {
+    map: Mmap,
+    data_offset: usize,
+    rows: usize,
+    dims: usize,
+}
+
+impl NpyVectors {
+    fn open(path: &Path) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Error::Export(format!(
+                "'{}' is not a supported .npy file: {reason}",
+                path.display()
+            ))
+        };
+        let file = File::open(path)
+            .map_err(|error| Error::Export(format!("cannot read '{}': {error}", path.display())))?;
+        // Mapping an empty file fails, and an empty file is no array anyway.
+        if file.metadata()?.len() == 0 {
+            return Err(invalid("empty file"));
+        }
+        // Safety: the file is only read, and exports are not modified while
+        // they are imported.
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() < 10 || &map[..6] != NPY_MAGIC {
+            return Err(invalid("missing magic"));
+        }
+        let (header_start, header_len) = match map[6] {
+            1 => (10, u16::from_le_bytes([map[8], map[9]]) as usize),
+            2 | 3 if map.len() >= 12 => (
+                12,
+                u32::from_le_bytes([map[8], map[9], map[10], map[11]]) as usize,
+            ),
+            _ => return Err(invalid("unknown version")),
+        };
+        let data_offset = header_start + header_len;
+        let dict = map
+            .get(header_start..data_offset)
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+            .ok_or_else(|| invalid("truncated header"))?;
+        let (rows, dims) = parse_npy_dict(dict)
+            .ok_or_else(|| invalid("expected a C-order '<f4' array of shape (rows, dimensions)"))?;
+        let data_len = rows
+            .checked_mul(dims)
+            .and_then(|values| values.checked_mul(4))
+            .ok_or_else(|| invalid("shape is too large"))?;
+        if map.len().checked_sub(data_offset) != Some(data_len) {
+            return Err(invalid("data does not match its shape"));
+        }
+        Ok(Self {
+            map,
+            data_offset,
+            rows,
+            dims,
+        })
+    }
+
+    fn row(&self, row: usize) -> Result<Vec<f32>> {
+        if row >= self.rows {
+            return Err(Error::Export(format!(
+                "vector row {row} is out of range ({} rows)",
+                self.rows
+            )));
+        }
+        let start = self.data_offset + row * self.dims * 4;
+        let bytes = &self.map[start..start + self.dims * 4];
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+}
+
+/// Shape of a `.npy` header dict describing a 2-D C-order `<f4` array.
+fn parse_npy_dict(dict: &str) -> Option<(usize, usize)> {
+    let value_after = |key: &str| {
+        let start = dict.find(key)? + key.len();
+        Some(
+            dict[start..]
+                .trim_start()
+                .trim_start_matches(':')
+                .trim_start(),
+        )
+    };
+    let descr = value_after("'descr'")?;
+    if !(descr.starts_with("'<f4'") || descr.starts_with("\"<f4\"")) {
+        return None;
+    }
+    if !value_after("'fortran_order'")?.starts_with("False") {
+        return None;
+    }
+    let shape = value_after("'shape'")?.strip_prefix('(')?;
+    let shape = &shape[..shape.find(')')?];
+    let mut dims = shape
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<usize>().ok());
+    let shape = (dims.next()??, dims.next()??);
+    dims.next().is_none().then_some(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn npy_header_is_aligned_and_parses_back() {
+        for (rows, dims) in [(0, 8), (3, 1536), (123_456, 384)] {
+            let header = npy_header(rows, dims);
+            assert_eq!(header.len() % NPY_ALIGNMENT, 0);
+            assert_eq!(&header[..6], NPY_MAGIC);
+            assert_eq!(*header.last().unwrap(), b'\n');
+            let dict = std::str::from_utf8(&header[10..]).unwrap();
+            assert_eq!(parse_npy_dict(dict), Some((rows, dims)));
+        }
+    }
+
+    #[test]
+    fn npy_dict_rejects_other_layouts() {
+        for dict in [
+            "{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }",
+            "{'descr': '<f4', 'fortran_order': True, 'shape': (2, 3), }",
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (6,), }",
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (1, 2, 3), }",
+        ] {
+            assert_eq!(parse_npy_dict(dict), None, "{dict}");
+        }
+    }
+
+    #[test]
+    fn npy_rows_read_back_little_endian() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(VECTORS_FILE);
+        let mut bytes = npy_header(2, 3);
+        for value in [1.0f32, -2.5, 0.125, 4.0, 5.0, 6.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        fs::write(&path, &bytes).unwrap();
+        let vectors = NpyVectors::open(&path).unwrap();
+        assert_eq!((vectors.rows, vectors.dims), (2, 3));
+        assert_eq!(vectors.row(0).unwrap(), vec![1.0, -2.5, 0.125]);
+        assert_eq!(vectors.row(1).unwrap(), vec![4.0, 5.0, 6.0]);
+        assert!(vectors.row(2).is_err());
+
+        bytes.truncate(bytes.len() - 4);
+        fs::write(&path, &bytes).unwrap();
+        assert!(NpyVectors::open(&path).is_err());
+
+        // A shape whose byte size overflows is rejected, not a panic.
+        fs::write(&path, npy_header(usize::MAX / 2, 3)).unwrap();
+        assert!(NpyVectors::open(&path).is_err());
+    }
+
+    #[test]
+    fn frontmatter_round_trips_verbatim() {
+        let json = r#"{"zeta":1.50,"alpha":["b","a"]}"#;
+        let line = [
+            r#"{"type":"file","path":"a.md","content_hash":"h","embedding_body_hash":"b","#,
+            format!(r#""frontmatter":{json},"file_size":1,"indexed_at":2,"chunk_ids":[]}"#)
+                .as_str(),
+        ]
+        .concat();
+        let file: ExportFile = serde_json::from_str(&line).unwrap();
+        let (_, stored, _) = stored_file(file);
+        assert_eq!(stored.frontmatter.as_deref(), Some(json));
+
+        let exported = serde_json::to_string(&export_file("a.md", &stored, None)).unwrap();
+        assert!(
+            exported.contains(&format!(r#""frontmatter":{json}"#)),
+            "{exported}"
+        );
+    }
+}
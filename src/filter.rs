@@ -0,0 +1,576 @@
+//! Boolean filter expression language for search and collection queries.
+//!
+//! Parses expressions such as
+//! `status != draft and (year >= 2023 or tags contains rust)` into a
+//! [`MetadataFilter`] tree that the search engine and collection view evaluate
+//! against effective frontmatter.
+//!
+//! Grammar (keywords are case-insensitive):
+//!
+//! ```text
+//! expr       := or_expr
+//! or_expr    := and_expr (("or" | "||") and_expr)*
+//! and_expr   := not_expr (("and" | "&&") not_expr)*
+//! not_expr   := ("not" | "!") not_expr | primary
+//! primary    := "(" expr ")" | predicate
+//! predicate  := FIELD ("=" | "==" | "!=" | ">" | ">=" | "<" | "<=") VALUE
+//!             | FIELD ["not"] "contains" VALUE
+//!             | FIELD ["not"] "in" "(" VALUE ("," VALUE)* ")"
+//!             | FIELD ["not"] "exists"
+//! ```
+//!
+//! Unquoted values are typed like `--filter KEY=VALUE`: valid JSON numbers
+//! become numbers, exact lowercase `true`/`false` become booleans, anything
+//! else is a string. Quoted values (`"..."` or `'...'`) are always strings.
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::search::{CompareOp, MetadataFilter};
+
+/// Parse a filter expression into a [`MetadataFilter`].
+///
+/// Returns [`Error::FilterExpression`] with the byte offset of the offending
+/// token when the input is not a valid expression.
+pub fn parse_filter_expression(input: &str) -> Result<MetadataFilter> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        input_len: input.len(),
+    };
+    if parser.peek().is_none() {
+        return Err(syntax_error(0, "empty filter expression"));
+    }
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(syntax_error(
+            token.offset,
+            format!("unexpected {}", token.kind.describe()),
+        ));
+    }
+    Ok(expr)
+}
+
+fn syntax_error(position: usize, message: impl Into<String>) -> Error {
+    Error::FilterExpression {
+        position,
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Comma,
+    Op(CompareOp),
+    Eq,
+    Ne,
+    AndOp,
+    OrOp,
+    Bang,
+    /// A bare word (field name, keyword, or unquoted value).
+    Word(String),
+    /// A quoted string literal.
+    Quoted(String),
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            Self::LParen => "'('".to_string(),
+            Self::RParen => "')'".to_string(),
+            Self::Comma => "','".to_string(),
+            Self::Op(op) => format!("'{op}'"),
+            Self::Eq => "'='".to_string(),
+            Self::Ne => "'!='".to_string(),
+            Self::AndOp => "'&&'".to_string(),
+            Self::OrOp => "'||'".to_string(),
+            Self::Bang => "'!'".to_string(),
+            Self::Word(w) => format!("'{w}'"),
+            Self::Quoted(s) => format!("\"{s}\""),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Self::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    offset: usize,
+}
+
+/// Characters that terminate a bare word.
+fn is_word_boundary(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '(' | ')' | ',' | '=' | '!' | '<' | '>' | '"' | '\'' | '&' | '|'
+        )
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let two = input[offset..].get(..2).unwrap_or("");
+        let kind = match c {
+            '(' => {
+                chars.next();
+                TokenKind::LParen
+            }
+            ')' => {
+                chars.next();
+                TokenKind::RParen
+            }
+            ',' => {
+                chars.next();
+                TokenKind::Comma
+            }
+            '=' => {
+                chars.next();
+                if two == "==" {
+                    chars.next();
+                }
+                TokenKind::Eq
+            }
+            '!' => {
+                chars.next();
+                if two == "!=" {
+                    chars.next();
+                    TokenKind::Ne
+                } else {
+                    TokenKind::Bang
+                }
+            }
+            '>' | '<' => {
+                chars.next();
+                let inclusive = two.ends_with('=');
+                if inclusive {
+                    chars.next();
+                }
+                TokenKind::Op(match (c, inclusive) {
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    ('<', false) => CompareOp::Lt,
+                    _ => CompareOp::Le,
+                })
+            }
+            '&' | '|' => {
+                if two != "&&" && two != "||" {
+                    return Err(syntax_error(offset, format!("expected '{c}{c}'")));
+                }
+                chars.next();
+                chars.next();
+                if c == '&' {
+                    TokenKind::AndOp
+                } else {
+                    TokenKind::OrOp
+                }
+            }
+            '"' | '\'' => {
+                let quote = c;
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some((_, ch)) = chars.next() {
+                    if ch == '\\' {
+                        if let Some((_, escaped)) = chars.next() {
+                            value.push(escaped);
+                        }
+                    } else if ch == quote {
+                        closed = true;
+                        break;
+                    } else {
+                        value.push(ch);
+                    }
+                }
+                if !closed {
+                    return Err(syntax_error(offset, "unterminated string literal"));
+                }
+                TokenKind::Quoted(value)
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if is_word_boundary(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                TokenKind::Word(word)
+            }
+        };
+        tokens.push(Token { kind, offset });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    input_len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|t| t.kind.is_keyword(keyword))
+    }
+
+    fn current_offset(&self) -> usize {
+        self.peek().map(|t| t.offset).unwrap_or(self.input_len)
+    }
+
+    fn parse_or(&mut self) -> Result<MetadataFilter> {
+        let mut terms = vec![self.parse_and()?];
+        while self
+            .peek()
+            .is_some_and(|t| t.kind == TokenKind::OrOp || t.kind.is_keyword("or"))
+        {
+            self.advance();
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            MetadataFilter::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<MetadataFilter> {
+        let mut terms = vec![self.parse_not()?];
+        while self
+            .peek()
+            .is_some_and(|t| t.kind == TokenKind::AndOp || t.kind.is_keyword("and"))
+        {
+            self.advance();
+            terms.push(self.parse_not()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            MetadataFilter::And(terms)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<MetadataFilter> {
+        if self
+            .peek()
+            .is_some_and(|t| t.kind == TokenKind::Bang || t.kind.is_keyword("not"))
+        {
+            self.advance();
+            let inner = self.parse_not()?;
+            return Ok(MetadataFilter::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<MetadataFilter> {
+        let offset = self.current_offset();
+        match self.advance() {
+            Some(Token {
+                kind: TokenKind::LParen,
+                ..
+            }) => {
+                let inner = self.parse_or()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(inner)
+            }
+            Some(Token {
+                kind: TokenKind::Word(field),
+                ..
+            })
+            | Some(Token {
+                kind: TokenKind::Quoted(field),
+                ..
+            }) => self.parse_predicate(field),
+            Some(token) => Err(syntax_error(
+                token.offset,
+                format!("expected a field name, found {}", token.kind.describe()),
+            )),
+            None => Err(syntax_error(offset, "expected a field name")),
+        }
+    }
+
+    fn parse_predicate(&mut self, field: String) -> Result<MetadataFilter> {
+        let offset = self.current_offset();
+        let negated = if self.peek_keyword("not") {
+            self.advance();
+            true
+        } else {
+            false
+        };
+
+        let filter = match self.advance() {
+            Some(Token {
+                kind: TokenKind::Eq,
+                ..
+            }) if !negated => {
+                let value = self.parse_value()?;
+                return Ok(MetadataFilter::Equals { field, value });
+            }
+            Some(Token {
+                kind: TokenKind::Ne,
+                ..
+            }) if !negated => {
+                let value = self.parse_value()?;
+                return Ok(MetadataFilter::Not(Box::new(MetadataFilter::Equals {
+                    field,
+                    value,
+                })));
+            }
+            Some(Token {
+                kind: TokenKind::Op(op),
+                ..
+            }) if !negated => {
+                let value = self.parse_value()?;
+                return Ok(MetadataFilter::Compare { field, op, value });
+            }
+            Some(token) if token.kind.is_keyword("contains") => {
+                let value = self.parse_value()?;
+                MetadataFilter::Contains { field, value }
+            }
+            Some(token) if token.kind.is_keyword("exists") => MetadataFilter::Exists { field },
+            Some(token) if token.kind.is_keyword("in") => {
+                self.expect(TokenKind::LParen)?;
+                let mut values = vec![self.parse_value()?];
+                while self.peek().is_some_and(|t| t.kind == TokenKind::Comma) {
+                    self.advance();
+                    values.push(self.parse_value()?);
+                }
+                self.expect(TokenKind::RParen)?;
+                MetadataFilter::In { field, values }
+            }
+            Some(token) => {
+                return Err(syntax_error(
+                    token.offset,
+                    format!(
+                        "expected an operator after '{field}', found {}",
+                        token.kind.describe()
+                    ),
+                ))
+            }
+            None => {
+                return Err(syntax_error(
+                    offset,
+                    format!("expected an operator after '{field}'"),
+                ))
+            }
+        };
+
+        Ok(if negated {
+            MetadataFilter::Not(Box::new(filter))
+        } else {
+            filter
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        let offset = self.current_offset();
+        match self.advance() {
+            Some(Token {
+                kind: TokenKind::Quoted(s),
+                ..
+            }) => Ok(Value::String(s)),
+            Some(Token {
+                kind: TokenKind::Word(w),
+                ..
+            }) => Ok(typed_value(&w)),
+            Some(token) => Err(syntax_error(
+                token.offset,
+                format!("expected a value, found {}", token.kind.describe()),
+            )),
+            None => Err(syntax_error(offset, "expected a value")),
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<()> {
+        let offset = self.current_offset();
+        match self.advance() {
+            Some(token) if token.kind == kind => Ok(()),
+            Some(token) => Err(syntax_error(
+                token.offset,
+                format!(
+                    "expected {}, found {}",
+                    kind.describe(),
+                    token.kind.describe()
+                ),
+            )),
+            None => Err(syntax_error(
+                offset,
+                format!("expected {}", kind.describe()),
+            )),
+        }
+    }
+}
+
+/// Type an unquoted value the same way `--filter KEY=VALUE` does.
+fn typed_value(raw: &str) -> Value {
+    if raw == "true" {
+        Value::Bool(true)
+    } else if raw == "false" {
+        Value::Bool(false)
+    } else if let Ok(number) = serde_json::Number::from_str(raw) {
+        Value::Number(number)
+    } else {
+        Value::String(raw.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::evaluate_filters;
+    use serde_json::json;
+
+    fn matches(expr: &str, fm: &Value) -> bool {
+        let filter = parse_filter_expression(expr).unwrap();
+        evaluate_filters(&[filter], Some(fm))
+    }
+
+    #[test]
+    fn parses_simple_equality() {
+        let filter = parse_filter_expression("status = draft").unwrap();
+        assert!(matches!(
+            filter,
+            MetadataFilter::Equals { ref field, ref value } if field == "status" && value == &json!("draft")
+        ));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let filter = parse_filter_expression("a = 1 or b = 2 and c = 3").unwrap();
+        let MetadataFilter::Or(terms) = filter else {
+            panic!("expected Or at the root");
+        };
+        assert_eq!(terms.len(), 2);
+        assert!(matches!(terms[1], MetadataFilter::And(_)));
+    }
+
+    #[test]
+    fn evaluates_request_example() {
+        let expr = "status != draft and (year >= 2023 or tags contains rust)";
+        assert!(matches(
+            expr,
+            &json!({"status": "published", "year": 2021, "tags": ["rust"]})
+        ));
+        assert!(matches(
+            expr,
+            &json!({"status": "published", "year": 2024, "tags": []})
+        ));
+        assert!(!matches(
+            expr,
+            &json!({"status": "draft", "year": 2024, "tags": ["rust"]})
+        ));
+        assert!(!matches(
+            expr,
+            &json!({"status": "published", "year": 2020, "tags": ["go"]})
+        ));
+    }
+
+    #[test]
+    fn strict_and_inclusive_comparisons() {
+        let fm = json!({"year": 2023});
+        assert!(matches("year >= 2023", &fm));
+        assert!(!matches("year > 2023", &fm));
+        assert!(matches("year <= 2023", &fm));
+        assert!(!matches("year < 2023", &fm));
+        assert!(matches("year < 2024", &fm));
+    }
+
+    #[test]
+    fn in_list_and_not_in() {
+        let fm = json!({"status": "review"});
+        assert!(matches("status in (draft, review)", &fm));
+        assert!(!matches("status not in (draft, review)", &fm));
+        assert!(matches("status not in (published)", &fm));
+    }
+
+    #[test]
+    fn contains_matches_list_items_and_substrings() {
+        let fm = json!({"tags": ["rust", "cli"], "title": "Blue green deploys"});
+        assert!(matches("tags contains cli", &fm));
+        assert!(!matches("tags contains go", &fm));
+        assert!(matches("title contains 'green'", &fm));
+        assert!(matches("tags not contains go", &fm));
+    }
+
+    #[test]
+    fn not_and_exists() {
+        let fm = json!({"author": "Alice"});
+        assert!(matches("author exists", &fm));
+        assert!(matches("not reviewer exists", &fm));
+        assert!(matches("reviewer not exists", &fm));
+        assert!(matches("!(author = Bob)", &fm));
+    }
+
+    #[test]
+    fn negation_matches_missing_fields() {
+        assert!(matches("status != draft", &json!({})));
+    }
+
+    #[test]
+    fn symbolic_operators() {
+        let fm = json!({"a": 1, "b": 2});
+        assert!(matches("a == 1 && b == 2", &fm));
+        assert!(matches("a == 5 || b == 2", &fm));
+    }
+
+    #[test]
+    fn quoted_values_stay_strings() {
+        let filter = parse_filter_expression("year = \"2024\"").unwrap();
+        assert!(matches!(
+            filter,
+            MetadataFilter::Equals { ref value, .. } if value == &json!("2024")
+        ));
+    }
+
+    #[test]
+    fn keywords_are_case_insensitive() {
+        let fm = json!({"a": 1, "b": 2});
+        assert!(matches("a = 1 AND NOT b = 3", &fm));
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let err = parse_filter_expression("status = ").unwrap_err();
+        assert!(matches!(err, Error::FilterExpression { position: 9, .. }));
+
+        let err = parse_filter_expression("(a = 1").unwrap_err();
+        assert!(err.to_string().contains("expected ')'"));
+
+        let err = parse_filter_expression("a = 1 b = 2").unwrap_err();
+        assert!(matches!(err, Error::FilterExpression { position: 6, .. }));
+
+        let err = parse_filter_expression("title = 'open").unwrap_err();
+        assert!(err.to_string().contains("unterminated"));
+
+        assert!(parse_filter_expression("   ").is_err());
+        assert!(parse_filter_expression("a & b").is_err());
+    }
+}
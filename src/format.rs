@@ -3,19 +3,28 @@ use serde_json::Value;
 use std::time::SystemTime;
 
 use mdvdb::config::Config;
+use mdvdb::fts::SpellingSuggestion;
 use mdvdb::links::{LinkQueryResult, LinkState, NeighborhoodResult, OrphanFile, ResolvedLink};
 use mdvdb::schema::{FieldType, Schema};
-use mdvdb::search::{GraphContextItem, SearchResult};
+use mdvdb::search::{GraphContextItem, ScoreExplanation, SearchResult, SearchResultChunk};
+use mdvdb::snippet::SearchSnippet;
 use mdvdb::tree::{FileState, FileTree};
 use mdvdb::ClusterSummary;
 use mdvdb::CollectionResponse;
 use mdvdb::DocumentInfo;
+use mdvdb::ExportInfo;
+use mdvdb::FacetCounts;
 use mdvdb::GraphData;
 use mdvdb::IndexStatus;
 use mdvdb::IngestResult;
 use mdvdb::VaultInfo;
+use mdvdb::{CachePruneReport, CacheStats, CompactReport};
 use mdvdb::{CheckStatus, DoctorResult};
+use mdvdb::{FsckCheck, FsckReport};
 use mdvdb::{IngestPreview, PreviewFileStatus};
+use mdvdb::{SnapshotInfo, SnapshotReason};
+use mdvdb::{Suggestion, SuggestionKind};
+use mdvdb::{VectorQuantization, VectorStorageInfo};
 
 /// Format a timestamp as a human-readable relative time string.
 ///
@@ -200,11 +209,53 @@ pub fn print_search_results(results: &[SearchResult], query: &str) {
             size_str.dimmed()
         );
 
-        // Content preview (first 200 chars, dimmed)
-        let preview: String = r.chunk.content.chars().take(200).collect();
-        let preview = preview.replace('\n', " ");
-        if !preview.is_empty() {
-            println!("     {}", preview.dimmed());
+        if !r.chunk.section_chunk_ids.is_empty() {
+            println!(
+                "     {} {} chunks joined",
+                "Parent:".dimmed(),
+                r.chunk.section_chunk_ids.len()
+            );
+        }
+
+        if let Some(explain) = &r.explain {
+            print_score_explanation(explain);
+        }
+
+        if let Some(snippet) = &r.chunk.snippet {
+            // Best-matching fragment with highlighted terms (--snippets)
+            println!(
+                "     {} {}-{}  {}",
+                "Match:".dimmed(),
+                snippet.start_line,
+                snippet.end_line,
+                render_snippet(snippet)
+            );
+        } else if !r.chunk.matches.is_empty() {
+            // Matching lines with highlighted matches (--mode regex)
+            for (line, text) in render_match_lines(&r.chunk) {
+                println!("     {} {}", format!("{line}:").dimmed(), text);
+            }
+        } else {
+            // Content preview (first 200 chars, dimmed)
+            let preview: String = r.chunk.content.chars().take(200).collect();
+            let preview = preview.replace('\n', " ");
+            if !preview.is_empty() {
+                println!("     {}", preview.dimmed());
+            }
+        }
+
+        // Neighboring chunks (--window)
+        for (label, neighbors) in [("Before:", &r.chunk.before), ("After:", &r.chunk.after)] {
+            for n in neighbors {
+                let preview: String = n.content.chars().take(80).collect();
+                println!(
+                    "     {} {}-{}  {}",
+                    label.dimmed(),
+                    n.start_line,
+                    n.end_line,
+                    preview.replace('\n', " ").dimmed()
+                );
+            }
         }
 
         // Frontmatter key-value pairs
@@ -237,6 +288,179 @@ pub fn print_search_results(results: &[SearchResult], query: &str) {
     }
 }
 
+/// Render a snippet on one line with its highlighted terms in bold yellow.
+fn render_snippet(snippet: &SearchSnippet) -> String {
+    let mut out = String::new();
+    let mut pos = snippet.start;
+    for h in &snippet.highlights {
+        let plain = &snippet.text[pos - snippet.start..h.start - snippet.start];
+        let term = &snippet.text[h.start - snippet.start..h.end - snippet.start];
+        out.push_str(&plain.replace('\n', " "));
+        out.push_str(&term.yellow().bold().to_string());
+        pos = h.end;
+    }
+    out.push_str(&snippet.text[pos - snippet.start..].replace('\n', " "));
+    out
+}
+
+/// Each line of `chunk` holding a regex match, with its matches highlighted.
+/// Matches spanning lines are highlighted up to the end of their first line.
+fn render_match_lines(chunk: &SearchResultChunk) -> Vec<(usize, String)> {
+    let content = &chunk.content;
+    let mut lines = Vec::new();
+    let mut matches = chunk.matches.iter().peekable();
+    while let Some(first) = matches.next() {
+        let start = content[..first.start].rfind('\n').map_or(0, |p| p + 1);
+        let end = content[first.start..]
+            .find('\n')
+            .map_or(content.len(), |p| first.start + p);
+        let mut group = vec![first];
+        while let Some(m) = matches.next_if(|m| m.start < end) {
+            group.push(m);
+        }
+        let mut text = String::new();
+        let mut pos = start;
+        for m in group {
+            let m_end = m.end.min(end).max(m.start);
+            text.push_str(&content[pos..m.start]);
+            text.push_str(&content[m.start..m_end].yellow().bold().to_string());
+            pos = m_end;
+        }
+        text.push_str(&content[pos..end]);
+        lines.push((first.line, text));
+    }
+    lines
+}
+
+/// Print the per-signal score breakdown for one search result (`--explain`).
+///
+/// Signals that did not contribute are omitted; stages print in the order
+/// they were applied.
+fn print_score_explanation(e: &ScoreExplanation) {
+    let mut retrieval = Vec::new();
+    if let Some(score) = e.semantic_score {
+        let rank = e
+            .semantic_rank
+            .map(|r| format!(" (#{r})"))
+            .unwrap_or_default();
+        retrieval.push(format!("cosine={score:.4}{rank}"));
+    }
+    if let Some(score) = e.bm25_score {
+        let rank = e.bm25_rank.map(|r| format!(" (#{r})")).unwrap_or_default();
+        retrieval.push(format!("bm25={score:.4}{rank}"));
+    }
+    if e.rrf_semantic.is_some() || e.rrf_lexical.is_some() {
+        retrieval.push(format!(
+            "rrf={:.5}+{:.5}",
+            e.rrf_semantic.unwrap_or(0.0),
+            e.rrf_lexical.unwrap_or(0.0)
+        ));
+    }
+    retrieval.push(format!("retrieval={:.4}", e.retrieval_score));
+    println!("     {} {}", "Explain:".dimmed(), retrieval.join("  "));
+
+    let mut adjustments = Vec::new();
+    if let Some(multiplier) = e.decay_multiplier {
+        adjustments.push(format!("decay ×{multiplier:.4}"));
+    }
+    if let Some(boost) = &e.link_boost {
+        let edge = boost
+            .edge_similarity
+            .map(|sim| format!(", edge sim {sim:.4}"))
+            .unwrap_or_default();
+        adjustments.push(format!(
+            "link +{:.4} ({} hop{} from {}{}, ×{:.4})",
+            boost.delta,
+            boost.hops,
+            if boost.hops == 1 { "" } else { "s" },
+            boost.source,
+            edge,
+            boost.multiplier
+        ));
+    }
+    if let Some(score) = e.rerank_score {
+        adjustments.push(format!("rerank={score:.4}"));
+    }
+    adjustments.push(format!("final={:.4}", e.final_score));
+    println!("              {}", adjustments.join("  "));
+}
+
+/// Print facet value counts (`--facets`), most frequent values first.
+pub fn print_facets(facets: &FacetCounts) {
+    if facets.is_empty() {
+        return;
+    }
+    println!("\n  {} {}", "●".cyan().bold(), "Facets".bold());
+    for (field, counts) in facets {
+        let mut values: Vec<(&String, &usize)> = counts.iter().collect();
+        values.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let rendered: Vec<String> = values
+            .iter()
+            .map(|(value, count)| format!("{} {}", value, format!("({count})").dimmed()))
+            .collect();
+        if rendered.is_empty() {
+            println!("    {}  {}", field.cyan(), "(none)".dimmed());
+        } else {
+            println!("    {}  {}", field.cyan(), rendered.join("  "));
+        }
+    }
+}
+
+/// Print spelling suggestions for query words missing from the index, one
+/// line per word with candidates and their chunk counts.
+pub fn print_spelling_suggestions(suggestions: &[SpellingSuggestion]) {
+    if suggestions.is_empty() {
+        return;
+    }
+    println!("\n  {} {}", "?".yellow().bold(), "Did you mean".bold());
+    let mut terms: Vec<(&str, Vec<String>)> = Vec::new();
+    for s in suggestions {
+        let rendered = format!(
+            "{} {}",
+            s.suggestion.green(),
+            format!("({})", s.doc_freq).dimmed()
+        );
+        match terms.last_mut() {
+            Some((term, rendered_list)) if *term == s.term => rendered_list.push(rendered),
+            _ => terms.push((&s.term, vec![rendered])),
+        }
+    }
+    for (term, rendered) in terms {
+        println!("    {} → {}", term.yellow(), rendered.join("  "));
+    }
+}
+
+/// Print typeahead suggestions: kind, text, and the location to jump to.
+pub fn print_suggestions(suggestions: &[Suggestion], prefix: &str) {
+    if suggestions.is_empty() {
+        println!(
+            "  {} No titles or headings match {}",
+            "✗".red().bold(),
+            format!("\"{}\"", prefix).yellow()
+        );
+        return;
+    }
+    for s in suggestions {
+        let kind = match s.kind {
+            SuggestionKind::Title => format!("{:<7}", "title").green(),
+            SuggestionKind::Alias => format!("{:<7}", "alias").cyan(),
+            SuggestionKind::Heading => "heading".blue(),
+        };
+        let text = if s.heading_hierarchy.len() > 1 {
+            let parents = s.heading_hierarchy[..s.heading_hierarchy.len() - 1].join(" > ");
+            format!("{} {}", format!("{parents} >").dimmed(), s.text.bold())
+        } else {
+            s.text.bold().to_string()
+        };
+        println!(
+            "  {}  {}  {}",
+            kind,
+            text,
+            format!("{}:{}", s.path, s.line).dimmed()
+        );
+    }
+}
+
 /// Print graph context items grouped by hop distance.
 ///
 /// Shows a separator per hop level and for each item: an arrow, the linked-from
@@ -341,6 +565,17 @@ pub fn print_ingest_result(result: &IngestResult) {
         "Estimated input tokens:".dimmed(),
         result.estimated_input_tokens.to_string().yellow()
     );
+    if result.embeddings_cached > 0 {
+        println!(
+            "  {} {}",
+            "Cached embeddings:".dimmed(),
+            result.embeddings_cached.to_string().yellow()
+        );
+    }
+
+    if let Some(ref snapshot) = result.snapshot {
+        println!("  {}       {}", "Snapshot:".dimmed(), snapshot.yellow());
+    }
 
     if result.files_failed > 0 {
         println!(
@@ -486,6 +721,191 @@ pub fn print_status(status: &IndexStatus) {
     println!();
 }
 
+/// Print persistent embedding cache usage to stdout.
+pub fn print_cache_stats(stats: &CacheStats) {
+    println!("\n  {} {}\n", "●".cyan().bold(), "Embedding Cache".bold());
+    if stats.chunk_spaces.is_empty() {
+        println!("  {}", "No cached document embeddings".dimmed());
+    }
+    for space in &stats.chunk_spaces {
+        println!(
+            "  {} {} {}",
+            space.provider_key.bold(),
+            format!("({} dims, {})", space.dimensions, space.purpose).dimmed(),
+            format!(
+                "{} vectors · {}",
+                space.entries,
+                format_file_size(space.bytes)
+            )
+            .yellow()
+        );
+    }
+    println!(
+        "  {}    {}",
+        "Queries:".cyan(),
+        format!(
+            "{} vectors · {}",
+            stats.query_entries,
+            format_file_size(stats.query_bytes)
+        )
+        .yellow()
+    );
+    println!(
+        "  {}      {}",
+        "Total:".cyan(),
+        format_file_size(stats.total_bytes).yellow()
+    );
+    println!();
+}
+
+/// Print the outcome of `cache prune` or `cache clear` to stdout.
+pub fn print_cache_prune(report: &CachePruneReport, cleared: bool) {
+    let action = if cleared { "Cleared" } else { "Pruned" };
+    println!(
+        "  {} {} {} document and {} query embeddings ({} freed)",
+        "✓".green().bold(),
+        action,
+        report.chunk_entries_removed.to_string().yellow(),
+        report.query_entries_removed.to_string().yellow(),
+        format_file_size(report.bytes_freed)
+    );
+}
+
+/// One line describing a snapshot: ID, age, reason, contents and model.
+fn snapshot_line(info: &SnapshotInfo) -> String {
+    let reason = match info.reason {
+        SnapshotReason::Manual => "manual",
+        SnapshotReason::PreReindex => "before reindex",
+    };
+    format!(
+        "{} {} {} {}",
+        info.id.bold(),
+        format!(
+            "({}, {})",
+            format_timestamp(unix_to_system_time(info.created_at)),
+            reason
+        )
+        .dimmed(),
+        format!(
+            "{} docs · {} chunks · {}",
+            info.document_count,
+            info.chunk_count,
+            format_file_size(info.bytes)
+        )
+        .yellow(),
+        format!(
+            "{}/{} ({} dims)",
+            info.embedding_provider, info.embedding_model, info.embedding_dimensions
+        )
+        .dimmed()
+    )
+}
+
+/// Print the snapshot taken by `mdvdb snapshot create`.
+pub fn print_snapshot_created(info: &SnapshotInfo) {
+    println!(
+        "  {} Created snapshot {}",
+        "✓".green().bold(),
+        snapshot_line(info)
+    );
+}
+
+/// Print the snapshots listed by `mdvdb snapshot list`, newest first.
+pub fn print_snapshots(snapshots: &[SnapshotInfo]) {
+    println!("\n  {} {}\n", "●".cyan().bold(), "Index Snapshots".bold());
+    if snapshots.is_empty() {
+        println!("  {}", "No snapshots".dimmed());
+    }
+    for info in snapshots.iter().rev() {
+        println!("  {}", snapshot_line(info));
+    }
+    println!();
+}
+
+/// Print the snapshot installed by `mdvdb snapshot restore`.
+pub fn print_snapshot_restored(info: &SnapshotInfo) {
+    println!(
+        "  {} Restored snapshot {}",
+        "✓".green().bold(),
+        snapshot_line(info)
+    );
+    if info.fts_rebuild_required {
+        println!(
+            "  {} The full-text index is rebuilt from the restored vectors by the next ingest or watch",
+            "!".yellow().bold()
+        );
+    }
+}
+
+/// Print the snapshots deleted by `mdvdb snapshot prune`.
+pub fn print_snapshot_prune(removed: &[SnapshotInfo]) {
+    println!(
+        "  {} Pruned {} snapshots",
+        "✓".green().bold(),
+        removed.len().to_string().yellow()
+    );
+    for info in removed {
+        println!("    {}", info.id.dimmed());
+    }
+}
+
+/// Print the outcome of `mdvdb compact`.
+pub fn print_compact(report: &CompactReport) {
+    if report.wal_bytes == 0 {
+        println!(
+            "  {} Rewrote index ({}); the write-ahead log was empty",
+            "✓".green().bold(),
+            format_file_size(report.index_bytes)
+        );
+    } else {
+        println!(
+            "  {} Folded {} of write-ahead log into the index ({})",
+            "✓".green().bold(),
+            format_file_size(report.wal_bytes).yellow(),
+            format_file_size(report.index_bytes)
+        );
+    }
+}
+
+/// One line describing an export: contents, size and model.
+fn export_line(info: &ExportInfo) -> String {
+    format!(
+        "{} {} {}",
+        info.path.display().to_string().bold(),
+        format!(
+            "{} docs · {} chunks · {} edges · {}",
+            info.document_count,
+            info.chunk_count,
+            info.edge_count,
+            format_file_size(info.bytes)
+        )
+        .yellow(),
+        format!(
+            "{}/{} ({} dims)",
+            info.embedding_provider, info.embedding_model, info.embedding_dimensions
+        )
+        .dimmed()
+    )
+}
+
+/// Print the export written by `mdvdb export`.
+pub fn print_export(info: &ExportInfo) {
+    println!("  {} Exported {}", "✓".green().bold(), export_line(info));
+}
+
+/// Print the export installed by `mdvdb import`.
+pub fn print_import(info: &ExportInfo) {
+    println!("  {} Imported {}", "✓".green().bold(), export_line(info));
+    println!(
+        "  {}",
+        format!(
+            "written by mdvdb {} (export format v{})",
+            info.mdvdb_version, info.version
+        )
+        .dimmed()
+    );
+}
+
 /// Print vault/folder info with colored formatting to stdout.
 pub fn print_info(info: &VaultInfo) {
     let title = if info.is_whole_vault {
@@ -562,9 +982,82 @@ pub fn print_info(info: &VaultInfo) {
         info.embedding.model,
         info.embedding.dimensions
     );
+    print_vector_storage(&info.vector_storage);
     println!();
 }
 
+/// Print the vector quantization lines of `mdvdb info`: per-vector size
+/// against `f32`, the full-precision sidecar, rescoring, and recall.
+fn print_vector_storage(storage: &VectorStorageInfo) {
+    let label = |q: &VectorQuantization| format!("{q:?}").to_lowercase();
+    let percent = if storage.full_precision_bytes_per_vector == 0 {
+        100.0
+    } else {
+        100.0 * storage.bytes_per_vector as f64 / storage.full_precision_bytes_per_vector as f64
+    };
+    let pending = storage
+        .pending_quantization
+        .as_ref()
+        .map(|q| format!(", {} from the next save", label(q)))
+        .unwrap_or_default();
+    println!(
+        "    {}     {} ({} B/vector, {:.1}% of f32){}",
+        "Vectors:".dimmed(),
+        label(&storage.quantization).bold(),
+        storage.bytes_per_vector,
+        percent,
+        pending
+    );
+    if let Some(size) = storage.sidecar_file_size {
+        if storage.full_precision_available {
+            println!(
+                "    {}     {} (full-precision vectors)",
+                "Sidecar:".dimmed(),
+                format_file_size(size).yellow()
+            );
+        } else {
+            println!(
+                "    {}     {}",
+                "Sidecar:".dimmed(),
+                "missing or stale — reads and rescoring use quantized vectors; run `mdvdb ingest --reindex`"
+                    .red()
+            );
+        }
+    }
+    let rescoring = if storage.rescore_factor > 0 {
+        format!(
+            "{}× candidates, re-ranked by exact similarity",
+            storage.rescore_factor
+        )
+    } else if storage.sidecar_file_size.is_some() {
+        "off (ranked by quantized distances)".to_string()
+    } else {
+        "not needed".to_string()
+    };
+    println!("    {}   {}", "Rescoring:".dimmed(), rescoring);
+    match &storage.recall {
+        Some(recall) => {
+            let rescored = recall
+                .rescored
+                .map(|r| format!(", {:.1}% rescored", r * 100.0))
+                .unwrap_or_default();
+            println!(
+                "    {}      {} quantized{} (recall@{}, {} queries)",
+                "Recall:".dimmed(),
+                format!("{:.1}%", recall.quantized * 100.0).yellow(),
+                rescored,
+                recall.k,
+                recall.queries
+            );
+        }
+        None => println!(
+            "    {}      {}",
+            "Recall:".dimmed(),
+            "run `mdvdb info --recall` to measure".dimmed()
+        ),
+    }
+}
+
 /// Print document info with colored formatting to stdout.
 pub fn print_document(doc: &DocumentInfo) {
     println!("\n  {} {}\n", "●".cyan().bold(), doc.path.bold());
@@ -873,6 +1366,8 @@ pub fn print_collection(resp: &CollectionResponse) {
         }
     }
 
+    print_facets(&resp.facets);
+
     // Pagination footer.
     let shown = resp.rows.len();
     let footer = if shown == 0 {
@@ -1396,12 +1891,14 @@ pub fn print_config(config: &Config, user_config_path: Option<&std::path::Path>)
     );
 
     println!(
-        "  {}    mode={:?}, limit={}, min_score={}, rrf_k={}",
+        "  {}    mode={:?}, limit={}, min_score={}, fusion={}, rrf_k={}, semantic_weight={}",
         "Search:".cyan(),
         config.search_default_mode,
         config.search_default_limit,
         config.search_min_score,
-        config.search_rrf_k
+        config.search_fusion,
+        config.search_rrf_k,
+        config.search_semantic_weight
     );
 
     println!(
@@ -1457,6 +1954,80 @@ pub fn print_doctor(result: &DoctorResult) {
     );
 }
 
+/// Print the findings of `mdvdb fsck`, and what `--repair` did.
+pub fn print_fsck(report: &FsckReport) {
+    println!("\n  {} {}\n", "●".cyan().bold(), "mdvdb fsck".bold());
+
+    if let (Some(version), Some(quantization)) = (report.format_version, &report.quantization) {
+        println!(
+            "  {}      {}",
+            "Index:".cyan(),
+            format!(
+                "v{version} · {quantization:?} · {} docs · {} chunks · {} vectors",
+                report.document_count, report.chunk_count, report.vector_count
+            )
+            .yellow()
+        );
+    }
+    if let Some(count) = report.fts_document_count {
+        println!(
+            "  {}  {}",
+            "Full-text:".cyan(),
+            format!("{count} documents").yellow()
+        );
+    }
+    if report.format_version.is_some() {
+        println!();
+    }
+
+    if report.problems.is_empty() {
+        println!("  {} No problems found\n", "✓".green().bold());
+        return;
+    }
+    for problem in &report.problems {
+        let icon = if report.repaired && problem.repairable {
+            "✓".green().bold()
+        } else {
+            "✗".red().bold()
+        };
+        let check = match problem.check {
+            FsckCheck::Header => "header",
+            FsckCheck::Metadata => "metadata",
+            FsckCheck::Vectors => "vectors",
+            FsckCheck::Files => "files",
+            FsckCheck::FullText => "full-text",
+            FsckCheck::LinkGraph => "link graph",
+        };
+        println!("  {} {:<11} {}", icon, check, problem.detail.dimmed());
+    }
+    println!();
+
+    if report.repaired {
+        println!(
+            "  {} Repaired {} problem(s)",
+            "✓".green().bold(),
+            report.problems.len() - report.unresolved()
+        );
+    }
+    if !report.needs_reembedding.is_empty() {
+        let action = if report.repaired {
+            "run `mdvdb ingest` to re-embed"
+        } else {
+            "need re-embedding after `mdvdb fsck --repair`"
+        };
+        println!(
+            "  {} {} file(s) {}:",
+            "!".yellow().bold(),
+            report.needs_reembedding.len(),
+            action
+        );
+        for path in &report.needs_reembedding {
+            println!("      {path}");
+        }
+    }
+    println!();
+}
+
 /// Print a human-readable summary of graph data (nodes, edges, clusters).
 pub fn print_graph_summary(data: &GraphData) {
     let is_chunk = data.level == "chunk";
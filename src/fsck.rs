@@ -0,0 +1,334 @@
+//! Index integrity check and repair (`mdvdb fsck`).
+//!
+//! [`check`] validates the index file header, then loads the index, which
+//! validates the rkyv metadata with bytecheck and replays the write-ahead
+//! log. It cross-checks chunks, files, vectors, link graph sources and
+//! semantic edge targets (see [`Index::check_integrity`]) and compares the
+//! full-text document count with the chunk count.
+//!
+//! With `repair`, dangling entries are dropped, the full-text index is
+//! rebuilt from the vector index, and files whose vectors are missing are
+//! marked so the next ingest re-embeds them. A header or metadata that fails
+//! to load cannot be repaired; restore a snapshot or reindex instead.
+
+use std::path::Path;
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::config::VectorQuantization;
+use crate::error::{Error, Result};
+use crate::fts::{self, FtsIndex, LexicalSettings};
+use crate::index::state::IntegrityReport;
+use crate::index::storage::{self, WriteOptions};
+use crate::index::Index;
+
+/// Examples named per problem before the rest are counted.
+const MAX_EXAMPLES: usize = 5;
+
+/// Outcome of [`MarkdownVdb::fsck`](crate::MarkdownVdb::fsck). Counts
+/// describe the index as found, before any repair.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    /// Index file format version (`None` if the header is invalid).
+    pub format_version: Option<u32>,
+    /// Vector quantization recorded in the header.
+    pub quantization: Option<VectorQuantization>,
+    /// Files in the index.
+    pub document_count: usize,
+    /// Chunks in the index.
+    pub chunk_count: usize,
+    /// Vectors in the HNSW index, chunk and edge vectors alike.
+    pub vector_count: usize,
+    /// Documents in the full-text index (`None` if it could not be read).
+    pub fts_document_count: Option<u64>,
+    /// Problems found, in check order. Empty for a consistent index.
+    pub problems: Vec<FsckProblem>,
+    /// Whether `--repair` fixed the repairable problems.
+    pub repaired: bool,
+    /// Files whose vectors are missing; `mdvdb ingest` re-embeds them once
+    /// `--repair` has run.
+    pub needs_reembedding: Vec<String>,
+}
+
+impl FsckReport {
+    /// Problems left after this run: all of them unless repaired, else the
+    /// ones `--repair` cannot fix.
+    pub fn unresolved(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| !(self.repaired && problem.repairable))
+            .count()
+    }
+}
+
+/// A single inconsistency found by `mdvdb fsck`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckProblem {
+    /// The check that found it.
+    pub check: FsckCheck,
+    /// Human-readable description, naming up to five examples.
+    pub detail: String,
+    /// Whether `--repair` fixes it.
+    pub repairable: bool,
+}
+
+/// The checks run by `mdvdb fsck`, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsckCheck {
+    /// Magic bytes, format version, region bounds and quantization byte.
+    Header,
+    /// rkyv metadata validation, HNSW loading and write-ahead log replay.
+    Metadata,
+    /// Every chunk has a vector and every vector has a chunk or edge.
+    Vectors,
+    /// Files list exactly the chunks that belong to them.
+    Files,
+    /// Full-text documents match the chunks.
+    FullText,
+    /// Link graph sources and semantic edge targets are indexed files.
+    LinkGraph,
+}
+
+impl FsckProblem {
+    fn new(check: FsckCheck, detail: String) -> Self {
+        Self {
+            check,
+            detail,
+            repairable: true,
+        }
+    }
+
+    fn fatal(check: FsckCheck, detail: String) -> Self {
+        Self {
+            check,
+            detail: format!(
+                "{detail}; restore a snapshot (`mdvdb snapshot restore`) or rebuild with `mdvdb ingest --reindex`"
+            ),
+            repairable: false,
+        }
+    }
+}
+
+/// Check the index of the project at `root`, and repair it if `repair` is
+/// set. A header or metadata problem ends the check unrepaired.
+pub(crate) fn check(
+    root: &Path,
+    write_options: WriteOptions,
+    lexical_settings: &LexicalSettings,
+    repair: bool,
+) -> Result<FsckReport> {
+    let state_dir = root.join(".markdownvdb");
+    let index_path = state_dir.join("index");
+    let fts_path = state_dir.join("fts");
+    let mut report = FsckReport::default();
+
+    let header = match storage::read_header(&index_path) {
+        Ok(header) => header,
+        Err(error @ Error::IndexNotFound { .. }) => return Err(error),
+        Err(error) => {
+            report
+                .problems
+                .push(FsckProblem::fatal(FsckCheck::Header, error.to_string()));
+            return Ok(report);
+        }
+    };
+    report.format_version = Some(header.version);
+    report.quantization = Some(header.quantization);
+
+    let index = match Index::open_with_options(&index_path, write_options) {
+        Ok(index) => index,
+        Err(error) => {
+            report
+                .problems
+                .push(FsckProblem::fatal(FsckCheck::Metadata, error.to_string()));
+            return Ok(report);
+        }
+    };
+    let status = index.status();
+    report.document_count = status.document_count;
+    report.chunk_count = status.chunk_count;
+    report.vector_count = status.vector_count;
+
+    let integrity = index.check_integrity();
+    describe(&integrity, &mut report.problems);
+    report.needs_reembedding = integrity.files_to_reembed.clone();
+
+    let mut fts_readable = false;
+    if !fts_path.join("meta.json").is_file() {
+        report.problems.push(FsckProblem::new(
+            FsckCheck::FullText,
+            "full-text index is missing".into(),
+        ));
+    } else {
+        match FtsIndex::open_readonly_with_settings(&fts_path, lexical_settings)
+            .and_then(|fts_index| fts_index.num_docs())
+        {
+            Ok(count) => {
+                fts_readable = true;
+                report.fts_document_count = Some(count);
+                if count != status.chunk_count as u64 {
+                    report.problems.push(FsckProblem::new(
+                        FsckCheck::FullText,
+                        format!(
+                            "{count} full-text document(s) for {} chunk(s)",
+                            status.chunk_count
+                        ),
+                    ));
+                }
+            }
+            Err(error) => report.problems.push(FsckProblem::new(
+                FsckCheck::FullText,
+                format!("cannot read full-text index: {error}"),
+            )),
+        }
+    }
+    if fts::reconciliation_required(root)? {
+        report.problems.push(FsckProblem::new(
+            FsckCheck::FullText,
+            "an interrupted update left the full-text index unreconciled".into(),
+        ));
+    }
+
+    if !repair || report.problems.is_empty() {
+        return Ok(report);
+    }
+
+    if fts_readable {
+        fts::ensure_no_writer(&fts_path)?;
+    }
+    fts::begin_reconciliation(root)?;
+    if !integrity.is_clean() {
+        index.repair_integrity(&integrity);
+        index.save()?;
+    }
+    // Only an index Tantivy cannot open is deleted, and only while nothing
+    // else holds its writer lock; any other failure is reported as is.
+    let fts_index = match FtsIndex::open_or_create_with_settings(&fts_path, lexical_settings) {
+        Ok(fts_index) => fts_index,
+        Err(error @ (Error::IndexBusy { .. } | Error::Io(_))) => return Err(error),
+        Err(_) => fts::recreate_corrupt(&fts_path, lexical_settings)?,
+    };
+    fts::rebuild_from_vector_index(&fts_index, &index)?;
+    drop(fts_index);
+    fts::finish_reconciliation(root)?;
+    report.repaired = true;
+
+    info!(
+        problems = report.problems.len(),
+        reembed = report.needs_reembedding.len(),
+        "repaired index"
+    );
+    Ok(report)
+}
+
+/// Turn the findings of [`Index::check_integrity`] into problems.
+fn describe(integrity: &IntegrityReport, problems: &mut Vec<FsckProblem>) {
+    let mut push = |check, detail: Option<String>| {
+        if let Some(detail) = detail {
+            problems.push(FsckProblem::new(check, detail));
+        }
+    };
+    push(
+        FsckCheck::Vectors,
+        examples(
+            "chunk(s) without a vector",
+            &integrity.chunks_without_vectors,
+        ),
+    );
+    push(
+        FsckCheck::Vectors,
+        examples(
+            "vector(s) without a chunk or semantic edge",
+            &integrity.vectors_without_chunks,
+        ),
+    );
+    push(
+        FsckCheck::Vectors,
+        (integrity.unmapped_vectors > 0).then(|| {
+            format!(
+                "{} vector(s) in the HNSW index have no id",
+                integrity.unmapped_vectors
+            )
+        }),
+    );
+    let missing: Vec<String> = integrity
+        .missing_file_chunks
+        .iter()
+        .map(|(path, id)| format!("{path} → {id}"))
+        .collect();
+    push(
+        FsckCheck::Files,
+        examples("file reference(s) to a missing or foreign chunk", &missing),
+    );
+    push(
+        FsckCheck::Files,
+        examples(
+            "chunk(s) not listed by their file",
+            &integrity.unowned_chunks,
+        ),
+    );
+    push(
+        FsckCheck::LinkGraph,
+        examples(
+            "link source(s) that are not indexed files",
+            &integrity.dangling_link_sources,
+        ),
+    );
+    push(
+        FsckCheck::LinkGraph,
+        examples(
+            "semantic edge(s) to a file that is not indexed",
+            &integrity.dangling_edge_targets,
+        ),
+    );
+}
+
+/// `"{n} {what}: a, b, +3 more"`, or `None` without items.
+fn examples(what: &str, items: &[String]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let total = items.len();
+    let shown = &items[..total.min(MAX_EXAMPLES)];
+    let more = if total > MAX_EXAMPLES {
+        format!(", +{} more", total - MAX_EXAMPLES)
+    } else {
+        String::new()
+    };
+    Some(format!("{total} {what}: {}{more}", shown.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples_names_at_most_five_items() {
+        let items: Vec<String> = (0..7).map(|i| format!("n{i}.md#0")).collect();
+        assert_eq!(
+            examples("chunk(s) without a vector", &items).unwrap(),
+            "7 chunk(s) without a vector: n0.md#0, n1.md#0, n2.md#0, n3.md#0, n4.md#0, +2 more"
+        );
+        assert_eq!(
+            examples("chunk(s) not listed by their file", &items[..1]).unwrap(),
+            "1 chunk(s) not listed by their file: n0.md#0"
+        );
+        assert_eq!(examples("chunk(s) without a vector", &[]), None);
+    }
+
+    #[test]
+    fn unresolved_counts_only_unrepaired_problems() {
+        let mut report = FsckReport {
+            problems: vec![
+                FsckProblem::new(FsckCheck::FullText, "full-text index is missing".into()),
+                FsckProblem::fatal(FsckCheck::Header, "invalid magic bytes".into()),
+            ],
+            ..FsckReport::default()
+        };
+        assert_eq!(report.unresolved(), 2);
+        report.repaired = true;
+        assert_eq!(report.unresolved(), 1);
+    }
+}
@@ -1,19 +1,46 @@
+use std::collections::BTreeMap;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
+use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA, SINK_STATE};
+use serde::{Deserialize, Serialize};
 use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
+use tantivy::directory::error::LockError;
+use tantivy::directory::{Directory, MmapDirectory, INDEX_WRITER_LOCK};
+use tantivy::json_utils::JsonTermWriter;
+use tantivy::query::{BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query, QueryParser};
 use tantivy::schema::{
-    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, STRING,
+    Field, IndexRecordOption, JsonObjectOptions, Schema, TextFieldIndexing, TextOptions,
+    JSON_END_OF_PATH, STORED, STRING,
 };
-use tantivy::{Index, IndexWriter, ReloadPolicy, TantivyDocument};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::tokenizer::{
+    Language, LowerCaser, NgramTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    StopWordFilter, TextAnalyzer, TokenStream,
+};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument, Term};
+use tantivy_fst::Automaton;
 
 use crate::error::{Error, Result};
 use crate::index::state::Index as VectorIndex;
+use crate::lexical_query::{
+    parse_lexical_query, parse_lexical_query_lenient, LexicalClause, LexicalField, LexicalTerm,
+};
 
 const RECONCILIATION_MARKER: &str = "fts-reconcile-required";
 
+/// Default score multiplier for heading-hierarchy matches over content matches.
+const HEADING_BOOST: f32 = 1.5;
+
+/// Boost keys for the built-in fields; any other key names a frontmatter key.
+const CONTENT_BOOST_KEY: &str = "content";
+const HEADING_BOOST_KEY: &str = "heading";
+const FRONTMATTER_BOOST_KEY: &str = "frontmatter";
+
 fn reconciliation_marker_path(project_root: &Path) -> PathBuf {
     project_root
         .join(".markdownvdb")
@@ -61,6 +88,21 @@ pub(crate) fn begin_reconciliation(project_root: &Path) -> Result<()> {
     sync_directory(&state_dir)
 }
 
+/// Return whether the FTS index at `path` must be rebuilt: it was written
+/// with a different schema than this version uses, or its last commit used
+/// different [`LexicalSettings`]. A missing index is not outdated.
+pub(crate) fn index_outdated(path: &Path, settings: &LexicalSettings) -> Result<bool> {
+    if !path.join("meta.json").exists() {
+        return Ok(false);
+    }
+    let index = Index::open_in_dir(path).map_err(|e| Error::Fts(e.to_string()))?;
+    if index.schema() != build_schema().0 {
+        return Ok(true);
+    }
+    let metas = index.load_metas().map_err(|e| Error::Fts(e.to_string()))?;
+    Ok(metas.payload.as_deref() != Some(settings.fingerprint().as_str()))
+}
+
 /// Rebuild the complete FTS projection from the authoritative persisted vector
 /// index snapshot. A full replacement is required: a nonempty FTS index can be
 /// stale just as easily as an empty one.
@@ -87,7 +129,11 @@ pub(crate) fn rebuild_from_vector_index(fts_index: &FtsIndex, index: &VectorInde
             });
         }
         if !chunks.is_empty() {
-            fts_index.upsert_chunks(&path, &chunks)?;
+            let frontmatter = file
+                .frontmatter
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok());
+            fts_index.upsert_file(&path, &chunks, frontmatter.as_ref())?;
         }
     }
 
@@ -126,6 +172,255 @@ pub(crate) fn finish_reconciliation(project_root: &Path) -> Result<()> {
     }
 }
 
+/// Fail with [`Error::IndexBusy`] when another process holds the writer lock
+/// of the FTS index at `path`, e.g. before replacing the whole directory.
+/// A missing index has no writer.
+pub(crate) fn ensure_no_writer(path: &Path) -> Result<()> {
+    if !path.join("meta.json").exists() {
+        return Ok(());
+    }
+    let index = Index::open_in_dir(path).map_err(|e| Error::Fts(e.to_string()))?;
+    drop(FtsIndex::acquire_writer(&index, path)?);
+    Ok(())
+}
+
+/// Replace the FTS index at `path` with an empty one when it is corrupt,
+/// i.e. Tantivy cannot open it. Its files are deleted under the writer lock,
+/// so this fails with [`Error::IndexBusy`] while another process holds it,
+/// and with [`Error::Fts`] when the index opens fine (the problem lies
+/// elsewhere and deleting it would not help).
+pub(crate) fn recreate_corrupt(path: &Path, settings: &LexicalSettings) -> Result<FtsIndex> {
+    let error = match Index::open_in_dir(path) {
+        Ok(_) => {
+            return Err(Error::Fts(format!(
+            "full-text index '{}' is readable but cannot be opened for writing; not recreating it",
+            path.display()
+        )))
+        }
+        Err(error) => error,
+    };
+    let directory = MmapDirectory::open(path).map_err(|e| Error::Fts(e.to_string()))?;
+    let lock = directory
+        .acquire_lock(&INDEX_WRITER_LOCK)
+        .map_err(|e| match e {
+            LockError::LockBusy => Error::IndexBusy {
+                path: path.to_path_buf(),
+            },
+            other => Error::Fts(other.to_string()),
+        })?;
+    tracing::warn!(path = %path.display(), %error, "recreating corrupt FTS index");
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_name() == INDEX_WRITER_LOCK.filepath.as_os_str() {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    drop(lock);
+    FtsIndex::open_or_create_with_settings(path, settings)
+}
+
+/// Text analysis used for a note's lexical index entries.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LexicalLanguage {
+    Arabic,
+    Danish,
+    Dutch,
+    #[default]
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+    /// Chinese, Japanese, and Korean: overlapping character unigrams and
+    /// bigrams instead of words, since the scripts do not separate words.
+    Cjk,
+}
+
+impl LexicalLanguage {
+    /// Every supported language; each has its own set of index fields.
+    pub const ALL: [LexicalLanguage; 19] = [
+        Self::Arabic,
+        Self::Danish,
+        Self::Dutch,
+        Self::English,
+        Self::Finnish,
+        Self::French,
+        Self::German,
+        Self::Greek,
+        Self::Hungarian,
+        Self::Italian,
+        Self::Norwegian,
+        Self::Portuguese,
+        Self::Romanian,
+        Self::Russian,
+        Self::Spanish,
+        Self::Swedish,
+        Self::Tamil,
+        Self::Turkish,
+        Self::Cjk,
+    ];
+
+    /// ISO 639-1 code (`cjk` for the n-gram analyzer), used in field names.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Arabic => "ar",
+            Self::Danish => "da",
+            Self::Dutch => "nl",
+            Self::English => "en",
+            Self::Finnish => "fi",
+            Self::French => "fr",
+            Self::German => "de",
+            Self::Greek => "el",
+            Self::Hungarian => "hu",
+            Self::Italian => "it",
+            Self::Norwegian => "no",
+            Self::Portuguese => "pt",
+            Self::Romanian => "ro",
+            Self::Russian => "ru",
+            Self::Spanish => "es",
+            Self::Swedish => "sv",
+            Self::Tamil => "ta",
+            Self::Turkish => "tr",
+            Self::Cjk => "cjk",
+        }
+    }
+
+    /// The Tantivy stemmer language; `None` for the n-gram analyzer.
+    fn stemmer_language(self) -> Option<Language> {
+        Some(match self {
+            Self::Arabic => Language::Arabic,
+            Self::Danish => Language::Danish,
+            Self::Dutch => Language::Dutch,
+            Self::English => Language::English,
+            Self::Finnish => Language::Finnish,
+            Self::French => Language::French,
+            Self::German => Language::German,
+            Self::Greek => Language::Greek,
+            Self::Hungarian => Language::Hungarian,
+            Self::Italian => Language::Italian,
+            Self::Norwegian => Language::Norwegian,
+            Self::Portuguese => Language::Portuguese,
+            Self::Romanian => Language::Romanian,
+            Self::Russian => Language::Russian,
+            Self::Spanish => Language::Spanish,
+            Self::Swedish => Language::Swedish,
+            Self::Tamil => Language::Tamil,
+            Self::Turkish => Language::Turkish,
+            Self::Cjk => return None,
+        })
+    }
+}
+
+impl FromStr for LexicalLanguage {
+    type Err = Error;
+
+    /// Accepts English names (`german`) and ISO 639-1 codes with an optional
+    /// region (`de`, `de-AT`); `zh`, `ja`, `ko`, and `cjk` select the n-gram
+    /// analyzer.
+    fn from_str(s: &str) -> Result<Self> {
+        let lowered = s.trim().to_lowercase();
+        let base = lowered.split(['-', '_']).next().unwrap_or_default();
+        let language = match base {
+            "chinese" | "japanese" | "korean" | "zh" | "ja" | "ko" | "cjk" => Some(Self::Cjk),
+            "nb" | "nn" => Some(Self::Norwegian),
+            name => Self::ALL
+                .into_iter()
+                .find(|language| language.code() == name || language.to_string() == name),
+        };
+        language.ok_or_else(|| {
+            Error::Config(format!(
+                "unknown lexical language '{s}': expected a name like 'german', an ISO 639-1 code like 'de', or 'cjk'"
+            ))
+        })
+    }
+}
+
+impl std::fmt::Display for LexicalLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Arabic => "arabic",
+            Self::Danish => "danish",
+            Self::Dutch => "dutch",
+            Self::English => "english",
+            Self::Finnish => "finnish",
+            Self::French => "french",
+            Self::German => "german",
+            Self::Greek => "greek",
+            Self::Hungarian => "hungarian",
+            Self::Italian => "italian",
+            Self::Norwegian => "norwegian",
+            Self::Portuguese => "portuguese",
+            Self::Romanian => "romanian",
+            Self::Russian => "russian",
+            Self::Spanish => "spanish",
+            Self::Swedish => "swedish",
+            Self::Tamil => "tamil",
+            Self::Turkish => "turkish",
+            Self::Cjk => "cjk",
+        })
+    }
+}
+
+/// Index-time text analysis settings. Changing them requires rebuilding the
+/// index, which [`index_outdated`] detects through the commit payload.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LexicalSettings {
+    /// Language for notes without a frontmatter or path override.
+    pub language: LexicalLanguage,
+    /// Path scope → language; the most specific matching scope wins.
+    pub paths: BTreeMap<String, LexicalLanguage>,
+    /// Drop stopwords for languages Tantivy ships a stopword list for.
+    pub stopwords: bool,
+}
+
+impl LexicalSettings {
+    /// The language a note is indexed in: its frontmatter `lang` when that
+    /// names a supported language, else the most specific matching path
+    /// override, else the default.
+    pub fn language_for(
+        &self,
+        source_path: &str,
+        frontmatter: Option<&serde_json::Value>,
+    ) -> LexicalLanguage {
+        if let Some(language) = frontmatter
+            .and_then(|fm| fm.get("lang"))
+            .and_then(serde_json::Value::as_str)
+            .and_then(|lang| lang.parse().ok())
+        {
+            return language;
+        }
+        self.paths
+            .iter()
+            .filter(|(scope, _)| crate::path_util::path_is_in_scope(source_path, scope))
+            .max_by_key(|(scope, _)| scope.trim_end_matches('/').len())
+            .map(|(_, language)| *language)
+            .unwrap_or(self.language)
+    }
+
+    /// Stable serialization stored as the payload of every FTS commit.
+    fn fingerprint(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
 /// Data for a single chunk to be indexed in the FTS index.
 #[derive(Debug, Clone)]
 pub struct FtsChunkData {
@@ -148,12 +443,115 @@ pub struct FtsResult {
     pub score: f32,
 }
 
+/// A fragment of chunk text chosen by [`FtsHighlighter::snippet`]. Offsets
+/// are byte offsets into the highlighted text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtsSnippet {
+    /// Start of the fragment.
+    pub start: usize,
+    /// End of the fragment (exclusive).
+    pub end: usize,
+    /// Matched query terms within the fragment, in text order.
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// Maximum number of [`SpellingSuggestion`]s per query word.
+pub const SPELLING_SUGGESTIONS_PER_TERM: usize = 3;
+
+/// Spelling suggestions are only computed when no search result scores above
+/// this. In hybrid mode with RRF fusion a chunk ranked first by only one
+/// retriever scores 0.5.
+pub const SPELLING_SUGGESTION_MAX_SCORE: f64 = 0.5;
+
+/// A correction for a query word that occurs nowhere in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpellingSuggestion {
+    /// The query word, lowercased.
+    pub term: String,
+    /// An indexed word within edit distance of `term`.
+    pub suggestion: String,
+    /// Edit distance between `term` and `suggestion` (adjacent
+    /// transpositions count once).
+    pub distance: usize,
+    /// Number of indexed chunks containing `suggestion`.
+    pub doc_freq: u64,
+}
+
+/// Highlights the terms of one lexical query in chunk text. Created by
+/// [`FtsIndex::highlighter`].
+pub struct FtsHighlighter {
+    settings: LexicalSettings,
+    generators: Vec<(LexicalLanguage, SnippetGenerator)>,
+}
+
+impl FtsHighlighter {
+    /// The fragment of `text` (at most the highlighter's `max_chars` long)
+    /// with the most matched query terms, analyzed in the language the note
+    /// at `source_path` is indexed in. `None` when no term matches; fuzzy and
+    /// prefix clauses are not highlighted.
+    pub fn snippet(
+        &self,
+        source_path: &str,
+        frontmatter: Option<&serde_json::Value>,
+        text: &str,
+    ) -> Option<FtsSnippet> {
+        let language = self.settings.language_for(source_path, frontmatter);
+        let (_, generator) = self.generators.iter().find(|(l, _)| *l == language)?;
+        let snippet = generator.snippet(text);
+        if snippet.highlighted().is_empty() {
+            return None;
+        }
+        let fragment = snippet.fragment();
+        let start = text.find(fragment)?;
+        Some(FtsSnippet {
+            start,
+            end: start + fragment.len(),
+            highlights: snippet
+                .highlighted()
+                .iter()
+                .map(|range| start + range.start..start + range.end)
+                .collect(),
+        })
+    }
+}
+
 /// Schema field handles cached for the FTS index.
 struct FtsFields {
     chunk_id: Field,
     source_path: Field,
+    /// Text fields per language, in [`LexicalLanguage::ALL`] order.
+    languages: Vec<LanguageFields>,
+    /// Unstemmed, lowercased words of every chunk: the vocabulary spelling
+    /// suggestions are drawn from.
+    spelling: Field,
+}
+
+impl FtsFields {
+    fn language(&self, language: LexicalLanguage) -> &LanguageFields {
+        self.languages
+            .iter()
+            .find(|fields| fields.language == language)
+            .expect("schema has fields for every language")
+    }
+}
+
+/// The text fields of one language. A chunk is indexed into the fields of
+/// its note's language only.
+struct LanguageFields {
+    language: LexicalLanguage,
     content: Field,
     heading_hierarchy: Field,
+    /// JSON object with one path per top-level frontmatter key.
+    frontmatter: Field,
+    /// Every indexed frontmatter value, for unscoped queries.
+    frontmatter_all: Field,
+}
+
+/// One field, or frontmatter key path, that a clause is matched against.
+struct SearchTarget {
+    field: Field,
+    json_path: Option<String>,
+    boost: f32,
 }
 
 /// Wrapper around a Tantivy index for full-text search of chunks.
@@ -161,29 +559,92 @@ pub struct FtsIndex {
     index: Index,
     fields: FtsFields,
     writer: Option<parking_lot::Mutex<IndexWriter>>,
+    /// Shared by all searches; reloaded before each one.
+    reader: IndexReader,
+    settings: LexicalSettings,
+    field_boosts: BTreeMap<String, f32>,
 }
 
 impl FtsIndex {
     /// Open an existing Tantivy index or create a new one at the given directory.
     /// Acquires an exclusive writer lock — use [`open_readonly`] for read-only access.
+    ///
+    /// Uses the default [`LexicalSettings`]; see
+    /// [`open_or_create_with_settings`](Self::open_or_create_with_settings).
     pub fn open_or_create(path: &Path) -> Result<Self> {
+        Self::open_or_create_with_settings(path, &LexicalSettings::default())
+    }
+
+    /// Open or create the index at `path`, analyzing text with `settings`.
+    ///
+    /// An index built with an outdated schema is replaced by an empty one; the
+    /// caller is expected to have begun reconciliation so it gets rebuilt.
+    /// Likewise an index committed under different settings keeps its old
+    /// analysis until it is rebuilt (see [`index_outdated`]).
+    pub fn open_or_create_with_settings(path: &Path, settings: &LexicalSettings) -> Result<Self> {
         let (schema, fields) = build_schema();
 
         let index = if path.exists() && path.join("meta.json").exists() {
-            Index::open_in_dir(path).map_err(|e| Error::Fts(e.to_string()))?
+            let index = Index::open_in_dir(path).map_err(|e| Error::Fts(e.to_string()))?;
+            if index.schema() == schema {
+                index
+            } else {
+                // Taking the old writer proves no other process is using
+                // the index before it is deleted.
+                drop(Self::acquire_writer(&index, path)?);
+                drop(index);
+                tracing::warn!(path = %path.display(), "replacing FTS index with outdated schema");
+                std::fs::remove_dir_all(path)?;
+                std::fs::create_dir_all(path)?;
+                Index::create_in_dir(path, schema).map_err(|e| Error::Fts(e.to_string()))?
+            }
         } else {
             std::fs::create_dir_all(path)?;
             Index::create_in_dir(path, schema).map_err(|e| Error::Fts(e.to_string()))?
         };
 
+        register_tokenizers(&index, settings.stopwords)?;
+        let writer = Self::acquire_writer(&index, path)?;
+        let reader = Self::build_reader(&index)?;
+
+        Ok(Self {
+            index,
+            fields,
+            writer: Some(parking_lot::Mutex::new(writer)),
+            reader,
+            settings: settings.clone(),
+            field_boosts: BTreeMap::new(),
+        })
+    }
+
+    /// A reader that only reloads when asked, see [`searcher`](Self::searcher).
+    fn build_reader(index: &Index) -> Result<IndexReader> {
+        index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e: tantivy::TantivyError| Error::Fts(e.to_string()))
+    }
+
+    /// A searcher over the latest commit, made visible by reloading the shared
+    /// reader, whether the commit came from this process or another one.
+    fn searcher(&self) -> Result<Searcher> {
+        self.reader
+            .reload()
+            .map_err(|e| Error::Fts(e.to_string()))?;
+        Ok(self.reader.searcher())
+    }
+
+    /// Take the index's exclusive writer lock.
+    fn acquire_writer(index: &Index, path: &Path) -> Result<IndexWriter> {
         // A watcher or previous in-process writer can release Tantivy's lock a
         // moment after its owner is dropped. Brief retries make watcher
         // pause/run/restart and immediate reopen deterministic without hiding
         // a genuinely active writer.
         let mut attempts = 0;
-        let writer = loop {
+        loop {
             match index.writer(50_000_000) {
-                Ok(writer) => break writer,
+                Ok(writer) => return Ok(writer),
                 Err(tantivy::TantivyError::LockFailure(..)) if attempts < 9 => {
                     attempts += 1;
                     std::thread::sleep(std::time::Duration::from_millis(50));
@@ -195,55 +656,100 @@ impl FtsIndex {
                 }
                 Err(other) => return Err(Error::Fts(other.to_string())),
             }
-        };
-
-        Ok(Self {
-            index,
-            fields,
-            writer: Some(parking_lot::Mutex::new(writer)),
-        })
+        }
     }
 
     /// Open an existing Tantivy index in read-only mode (no writer lock acquired).
     /// Write operations (`upsert_chunks`, `remove_file`, `commit`, `delete_all`) will
     /// return an error if called on a read-only instance.
+    ///
+    /// Uses the default [`LexicalSettings`]; see
+    /// [`open_readonly_with_settings`](Self::open_readonly_with_settings).
     pub fn open_readonly(path: &Path) -> Result<Self> {
+        Self::open_readonly_with_settings(path, &LexicalSettings::default())
+    }
+
+    /// Open the index at `path` read-only, analyzing queries with `settings`.
+    ///
+    /// An index with an outdated schema is left on disk for the next writable
+    /// open to rebuild; until then searches run against an empty in-memory index.
+    pub fn open_readonly_with_settings(path: &Path, settings: &LexicalSettings) -> Result<Self> {
         let (schema, fields) = build_schema();
 
         let index = if path.exists() && path.join("meta.json").exists() {
-            Index::open_in_dir(path).map_err(|e| Error::Fts(e.to_string()))?
+            let index = Index::open_in_dir(path).map_err(|e| Error::Fts(e.to_string()))?;
+            if index.schema() == schema {
+                index
+            } else {
+                Index::create_in_ram(schema)
+            }
         } else {
             std::fs::create_dir_all(path)?;
             Index::create_in_dir(path, schema).map_err(|e| Error::Fts(e.to_string()))?
         };
+        register_tokenizers(&index, settings.stopwords)?;
+        let reader = Self::build_reader(&index)?;
 
         Ok(Self {
             index,
             fields,
             writer: None,
+            reader,
+            settings: settings.clone(),
+            field_boosts: BTreeMap::new(),
         })
     }
 
-    /// Upsert chunks for a given source file.
+    /// Set per-field score multipliers for lexical search, keyed by
+    /// `content`, `heading`, `frontmatter` (every frontmatter value), or a
+    /// top-level frontmatter key. Unlisted fields keep their defaults:
+    /// headings 1.5, everything else 1.0. Unscoped queries additionally
+    /// search each listed frontmatter key with its boost.
+    pub fn with_field_boosts(mut self, field_boosts: BTreeMap<String, f32>) -> Self {
+        self.field_boosts = field_boosts;
+        self
+    }
+
+    /// Upsert chunks for a given source file that has no frontmatter.
     ///
-    /// Deletes all existing chunks for the source path, then adds the new chunks.
-    /// Call [`commit`] after all upserts are done.
+    /// Equivalent to [`upsert_file`](Self::upsert_file) without frontmatter.
     pub fn upsert_chunks(&self, source_path: &str, chunks: &[FtsChunkData]) -> Result<()> {
+        self.upsert_file(source_path, chunks, None)
+    }
+
+    /// Upsert chunks for a given source file, indexing its frontmatter with
+    /// every chunk.
+    ///
+    /// Deletes all existing chunks for the source path, then adds the new chunks.
+    /// String and list-of-string frontmatter values are searchable by key and
+    /// through the catch-all field. Text is analyzed in the language chosen by
+    /// [`LexicalSettings::language_for`]. Call [`commit`] after all upserts are done.
+    pub fn upsert_file(
+        &self,
+        source_path: &str,
+        chunks: &[FtsChunkData],
+        frontmatter: Option<&serde_json::Value>,
+    ) -> Result<()> {
         let writer_mutex = self
             .writer
             .as_ref()
             .ok_or_else(|| Error::Fts("FTS index opened in read-only mode".into()))?;
+        let language = self.settings.language_for(source_path, frontmatter);
+        let fields = self.fields.language(language);
+        let template = self.frontmatter_document(fields, frontmatter)?;
         let writer = writer_mutex.lock();
         // Delete existing docs for this source path.
         let term = tantivy::Term::from_field_text(self.fields.source_path, source_path);
         writer.delete_term(term);
 
         for chunk in chunks {
-            let mut doc = TantivyDocument::new();
+            let mut doc = template.clone();
             doc.add_text(self.fields.chunk_id, &chunk.chunk_id);
             doc.add_text(self.fields.source_path, &chunk.source_path);
-            doc.add_text(self.fields.content, &chunk.content);
-            doc.add_text(self.fields.heading_hierarchy, &chunk.heading_hierarchy);
+            doc.add_text(fields.content, &chunk.content);
+            doc.add_text(fields.heading_hierarchy, &chunk.heading_hierarchy);
+            doc.add_text(self.fields.spelling, &chunk.content);
+            doc.add_text(self.fields.spelling, &chunk.heading_hierarchy);
             writer
                 .add_document(doc)
                 .map_err(|e| Error::Fts(e.to_string()))?;
@@ -251,6 +757,44 @@ impl FtsIndex {
         Ok(())
     }
 
+    /// A document holding only the indexable frontmatter values, used as the
+    /// starting point for each of the file's chunks.
+    fn frontmatter_document(
+        &self,
+        fields: &LanguageFields,
+        frontmatter: Option<&serde_json::Value>,
+    ) -> Result<TantivyDocument> {
+        let values = indexable_frontmatter(frontmatter);
+        if values.is_empty() {
+            return Ok(TantivyDocument::new());
+        }
+        let schema = self.index.schema();
+        let mut json = serde_json::Map::new();
+        json.insert(
+            schema.get_field_name(fields.frontmatter).to_string(),
+            serde_json::Value::Object(values.clone()),
+        );
+        let json = serde_json::Value::Object(json).to_string();
+        let mut doc =
+            TantivyDocument::parse_json(&schema, &json).map_err(|e| Error::Fts(e.to_string()))?;
+        for value in values.values() {
+            match value {
+                serde_json::Value::String(text) => {
+                    doc.add_text(fields.frontmatter_all, text);
+                    doc.add_text(self.fields.spelling, text);
+                }
+                serde_json::Value::Array(items) => {
+                    for text in items.iter().filter_map(serde_json::Value::as_str) {
+                        doc.add_text(fields.frontmatter_all, text);
+                        doc.add_text(self.fields.spelling, text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(doc)
+    }
+
     /// Remove all chunks for a given source file path.
     ///
     /// Call [`commit`] after removals are done.
@@ -267,30 +811,84 @@ impl FtsIndex {
 
     /// Search the FTS index for matching chunks.
     ///
-    /// Returns up to `limit` results sorted by BM25 score descending.
-    /// Empty queries return an empty vec.
+    /// `query_str` uses the [lexical query syntax](crate::lexical_query):
+    /// quoted phrases, `+required`/`-excluded` clauses, `word~1` fuzzy and
+    /// `word*` prefix matching, and `content:`/`heading:`/`<frontmatter key>:`
+    /// scoping. Returns up
+    /// to `limit` results sorted by score descending. Empty queries return an
+    /// empty vec; invalid syntax returns [`Error::LexicalQuery`].
     pub fn search(&self, query_str: &str, limit: usize) -> Result<Vec<FtsResult>> {
-        let query_str = query_str.trim();
-        if query_str.is_empty() {
-            return Ok(Vec::new());
-        }
+        self.search_clauses(&self.parse_query(query_str, true)?, limit)
+    }
 
-        let reader = self
-            .index
-            .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommitWithDelay)
-            .try_into()
-            .map_err(|e: tantivy::TantivyError| Error::Fts(e.to_string()))?;
+    /// Parse `query_str` as [lexical query syntax](crate::lexical_query),
+    /// scoping `key:` prefixes only to frontmatter keys present in the index.
+    ///
+    /// With `strict`, invalid syntax returns [`Error::LexicalQuery`];
+    /// otherwise it is [parsed leniently](parse_lexical_query_lenient).
+    pub fn parse_query(&self, query_str: &str, strict: bool) -> Result<Vec<LexicalClause>> {
+        let searcher = self.searcher()?;
+        let mut failure = None;
+        let is_key = |key: &str| match self.has_frontmatter_key(&searcher, key) {
+            Ok(found) => found,
+            Err(e) => {
+                failure.get_or_insert(e);
+                false
+            }
+        };
+        let clauses = if strict {
+            parse_lexical_query(query_str, is_key)?
+        } else {
+            parse_lexical_query_lenient(query_str, is_key)
+        };
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(clauses),
+        }
+    }
 
-        let searcher = reader.searcher();
+    /// Whether any indexed chunk has a value for the top-level frontmatter
+    /// `key`, in any language.
+    fn has_frontmatter_key(&self, searcher: &Searcher, key: &str) -> Result<bool> {
+        // JSON terms start with the path and its end marker.
+        let mut start = key.as_bytes().to_vec();
+        start.push(JSON_END_OF_PATH);
+        let mut end = key.as_bytes().to_vec();
+        end.push(JSON_END_OF_PATH + 1);
+        for fields in &self.fields.languages {
+            for segment in searcher.segment_readers() {
+                let inverted_index = segment
+                    .inverted_index(fields.frontmatter)
+                    .map_err(|e| Error::Fts(e.to_string()))?;
+                let mut terms = inverted_index
+                    .terms()
+                    .range()
+                    .ge(&start)
+                    .lt(&end)
+                    .into_stream()
+                    .map_err(|e| Error::Fts(e.to_string()))?;
+                if terms.advance() {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
 
-        let mut query_parser = QueryParser::for_index(
-            &self.index,
-            vec![self.fields.content, self.fields.heading_hierarchy],
-        );
-        query_parser.set_field_boost(self.fields.heading_hierarchy, 1.5);
+    /// Search with already parsed clauses, such as the lenient
+    /// [`parse_query`](Self::parse_query) of a hybrid query.
+    pub fn search_clauses(
+        &self,
+        clauses: &[LexicalClause],
+        limit: usize,
+    ) -> Result<Vec<FtsResult>> {
+        if clauses.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        let (query, _errors) = query_parser.parse_query_lenient(query_str);
+        let searcher = self.searcher()?;
+        let languages = self.indexed_languages(&searcher)?;
+        let query = self.compile_query(clauses, &languages)?;
 
         let top_docs = searcher
             .search(&query, &TopDocs::with_limit(limit))
@@ -320,6 +918,329 @@ impl FtsIndex {
         Ok(results)
     }
 
+    /// Prepare snippet highlighting for `query_str` (same syntax as
+    /// [`search`](Self::search), parsed leniently). Excluded clauses are never
+    /// highlighted; fragments are at most `max_chars` long.
+    pub fn highlighter(&self, query_str: &str, max_chars: usize) -> Result<FtsHighlighter> {
+        let clauses: Vec<LexicalClause> = self
+            .parse_query(query_str, false)?
+            .into_iter()
+            .filter(|clause| clause.occur != Occur::MustNot)
+            .collect();
+
+        let mut generators = Vec::new();
+        if !clauses.is_empty() {
+            let searcher = self.searcher()?;
+            for fields in self.indexed_languages(&searcher)? {
+                let query = self.compile_query(&clauses, &[fields])?;
+                let mut generator = SnippetGenerator::create(&searcher, &*query, fields.content)
+                    .map_err(|e| Error::Fts(e.to_string()))?;
+                generator.set_max_num_chars(max_chars);
+                generators.push((fields.language, generator));
+            }
+        }
+        Ok(FtsHighlighter {
+            settings: self.settings.clone(),
+            generators,
+        })
+    }
+
+    /// Suggest corrections for the words of `query_str` that occur nowhere in
+    /// the index (so lexical search finds nothing or only matches the other
+    /// words).
+    ///
+    /// Candidates are indexed words, unstemmed, within edit distance 1 of
+    /// words of 3–5 characters and 2 of longer words; shorter and numeric
+    /// words get none. Up to `per_term` candidates are returned per word,
+    /// ranked by document frequency, in query word order. Excluded, fuzzy
+    /// and prefix clauses are not corrected.
+    pub fn spelling_suggestions(
+        &self,
+        query_str: &str,
+        per_term: usize,
+    ) -> Result<Vec<SpellingSuggestion>> {
+        let mut analyzer = self
+            .index
+            .tokenizer_for_field(self.fields.spelling)
+            .map_err(|e| Error::Fts(e.to_string()))?;
+        let mut words: Vec<String> = Vec::new();
+        for clause in self.parse_query(query_str, false)? {
+            let text = match &clause.term {
+                _ if clause.occur == Occur::MustNot => continue,
+                LexicalTerm::Word(text) | LexicalTerm::Phrase { text, .. } => text,
+                LexicalTerm::Fuzzy { .. } | LexicalTerm::Prefix(_) => continue,
+            };
+            let mut stream = analyzer.token_stream(text);
+            while let Some(token) = stream.next() {
+                if !words.contains(&token.text) {
+                    words.push(token.text.clone());
+                }
+            }
+        }
+
+        let searcher = self.searcher()?;
+
+        let mut unknown: Vec<(String, LevenshteinDfa)> = Vec::new();
+        for word in words {
+            let max_distance = match word.chars().count() {
+                0..=2 => continue,
+                3..=5 => 1,
+                _ => 2,
+            };
+            if word.chars().all(char::is_numeric) {
+                continue;
+            }
+            let term = Term::from_field_text(self.fields.spelling, &word);
+            let doc_freq = searcher
+                .doc_freq(&term)
+                .map_err(|e| Error::Fts(e.to_string()))?;
+            if doc_freq == 0 {
+                let dfa = LevenshteinDfa::new(&word, max_distance);
+                unknown.push((word, dfa));
+            }
+        }
+        if unknown.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Candidate → (distance, doc_freq summed over segments), per word.
+        // The automaton only visits the parts of the term dictionary that
+        // can still lead to a word within range.
+        let mut candidates: Vec<BTreeMap<String, (usize, u64)>> =
+            vec![BTreeMap::new(); unknown.len()];
+        for segment in searcher.segment_readers() {
+            let inverted_index = segment
+                .inverted_index(self.fields.spelling)
+                .map_err(|e| Error::Fts(e.to_string()))?;
+            for ((_, dfa), found) in unknown.iter().zip(&mut candidates) {
+                let mut terms = inverted_index
+                    .terms()
+                    .search(dfa)
+                    .into_stream()
+                    .map_err(|e| Error::Fts(e.to_string()))?;
+                while terms.advance() {
+                    let (Ok(indexed), Some(distance)) =
+                        (std::str::from_utf8(terms.key()), dfa.distance(terms.key()))
+                    else {
+                        continue;
+                    };
+                    let entry = found
+                        .entry(indexed.to_string())
+                        .or_insert((usize::from(distance), 0));
+                    entry.1 += u64::from(terms.value().doc_freq);
+                }
+            }
+        }
+
+        let mut suggestions = Vec::new();
+        for ((term, _), found) in unknown.into_iter().zip(candidates) {
+            let mut found: Vec<(String, (usize, u64))> = found.into_iter().collect();
+            found.sort_by(|(a, (a_distance, a_freq)), (b, (b_distance, b_freq))| {
+                b_freq
+                    .cmp(a_freq)
+                    .then(a_distance.cmp(b_distance))
+                    .then(a.cmp(b))
+            });
+            suggestions.extend(found.into_iter().take(per_term).map(
+                |(suggestion, (distance, doc_freq))| SpellingSuggestion {
+                    term: term.clone(),
+                    suggestion,
+                    distance,
+                    doc_freq,
+                },
+            ));
+        }
+        Ok(suggestions)
+    }
+
+    /// Compile parsed lexical clauses into one boolean query. Words and
+    /// phrases go through Tantivy's [`QueryParser`] (so they are tokenized and
+    /// stemmed like the indexed text); fuzzy and prefix words become
+    /// [`FuzzyTermQuery`]s. Either way each clause matches any of its
+    /// [targets](Self::targets), scored with the target's boost.
+    fn compile_query(
+        &self,
+        clauses: &[LexicalClause],
+        languages: &[&LanguageFields],
+    ) -> Result<Box<dyn Query>> {
+        let query_parser = QueryParser::for_index(&self.index, Vec::new());
+        let schema = self.index.schema();
+
+        let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::with_capacity(clauses.len());
+        for clause in clauses {
+            let query: Box<dyn Query> = match &clause.term {
+                LexicalTerm::Word(text) | LexicalTerm::Phrase { text, .. } => {
+                    // Quoting hands the text to the tokenizer verbatim;
+                    // quotes and escapes inside it are only separators.
+                    let text: String = text
+                        .chars()
+                        .map(|c| if matches!(c, '"' | '\\') { ' ' } else { c })
+                        .collect();
+                    // Skip clauses that are only punctuation or stopwords.
+                    if !text.chars().any(char::is_alphanumeric)
+                        || !self.any_tokens(languages, &text)?
+                    {
+                        continue;
+                    }
+                    let slop = match &clause.term {
+                        LexicalTerm::Phrase { slop, .. } if *slop > 0 => format!("~{slop}"),
+                        _ => String::new(),
+                    };
+                    self.target_query(&clause.field, languages, |target| {
+                        let mut name = schema.get_field_name(target.field).to_string();
+                        if let Some(path) = &target.json_path {
+                            name = format!("{name}.{path}");
+                        }
+                        query_parser
+                            .parse_query(&format!("{name}:\"{text}\"{slop}"))
+                            .map_err(|e| Error::LexicalQuery {
+                                position: clause.position,
+                                message: e.to_string(),
+                            })
+                    })?
+                }
+                LexicalTerm::Fuzzy { word, distance } => {
+                    self.target_query(&clause.field, languages, |target| {
+                        let text = self.normalize(target.field, word)?;
+                        let term = target_term(target, &text);
+                        Ok(Box::new(FuzzyTermQuery::new(term, *distance, true)))
+                    })?
+                }
+                LexicalTerm::Prefix(prefix) => {
+                    self.target_query(&clause.field, languages, |target| {
+                        // Prefixes are only lowercased: stemming would cut a
+                        // partial word to a different root.
+                        let term = target_term(target, &prefix.to_lowercase());
+                        Ok(Box::new(FuzzyTermQuery::new_prefix(term, 0, true)))
+                    })?
+                }
+            };
+            subqueries.push((clause.occur, query));
+        }
+        Ok(Box::new(BooleanQuery::new(subqueries)))
+    }
+
+    /// Build `make(target)` for each target of `scope`, boosted and combined
+    /// so that matching any one target matches the clause.
+    fn target_query(
+        &self,
+        scope: &LexicalField,
+        languages: &[&LanguageFields],
+        make: impl Fn(&SearchTarget) -> Result<Box<dyn Query>>,
+    ) -> Result<Box<dyn Query>> {
+        let targets: Vec<SearchTarget> = languages
+            .iter()
+            .flat_map(|fields| self.targets(scope, fields))
+            .collect();
+        let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::with_capacity(targets.len());
+        for target in &targets {
+            let query = make(target)?;
+            subqueries.push((
+                Occur::Should,
+                Box::new(BoostQuery::new(query, target.boost)),
+            ));
+        }
+        Ok(Box::new(BooleanQuery::new(subqueries)))
+    }
+
+    /// The fields of one language a clause scoped to `scope` is matched against.
+    ///
+    /// Unscoped clauses search content, headings, the frontmatter catch-all,
+    /// and every frontmatter key that has a configured boost.
+    fn targets(&self, scope: &LexicalField, fields: &LanguageFields) -> Vec<SearchTarget> {
+        let boost =
+            |key: &str, default: f32| self.field_boosts.get(key).copied().unwrap_or(default);
+        let target = |field: Field, boost: f32| SearchTarget {
+            field,
+            json_path: None,
+            boost,
+        };
+        let key = |key: &str| SearchTarget {
+            field: fields.frontmatter,
+            json_path: Some(key.to_string()),
+            boost: boost(key, 1.0),
+        };
+        match scope {
+            LexicalField::Default => {
+                let mut targets = vec![
+                    target(fields.content, boost(CONTENT_BOOST_KEY, 1.0)),
+                    target(
+                        fields.heading_hierarchy,
+                        boost(HEADING_BOOST_KEY, HEADING_BOOST),
+                    ),
+                    target(fields.frontmatter_all, boost(FRONTMATTER_BOOST_KEY, 1.0)),
+                ];
+                targets.extend(
+                    self.field_boosts
+                        .keys()
+                        .filter(|name| {
+                            ![CONTENT_BOOST_KEY, HEADING_BOOST_KEY, FRONTMATTER_BOOST_KEY]
+                                .contains(&name.as_str())
+                        })
+                        .map(|name| key(name.as_str())),
+                );
+                targets
+            }
+            LexicalField::Content => {
+                vec![target(fields.content, boost(CONTENT_BOOST_KEY, 1.0))]
+            }
+            LexicalField::Heading => vec![target(
+                fields.heading_hierarchy,
+                boost(HEADING_BOOST_KEY, HEADING_BOOST),
+            )],
+            LexicalField::Frontmatter(name) if name == FRONTMATTER_BOOST_KEY => vec![target(
+                fields.frontmatter_all,
+                boost(FRONTMATTER_BOOST_KEY, 1.0),
+            )],
+            LexicalField::Frontmatter(name) => vec![key(name)],
+        }
+    }
+
+    /// The languages with indexed chunks. Queries only search their fields,
+    /// analyzing the query text once per language.
+    fn indexed_languages(&self, searcher: &Searcher) -> Result<Vec<&LanguageFields>> {
+        let mut languages = Vec::new();
+        for fields in &self.fields.languages {
+            for segment in searcher.segment_readers() {
+                let inverted_index = segment
+                    .inverted_index(fields.content)
+                    .map_err(|e| Error::Fts(e.to_string()))?;
+                if inverted_index.terms().num_terms() > 0 {
+                    languages.push(fields);
+                    break;
+                }
+            }
+        }
+        Ok(languages)
+    }
+
+    /// Whether any language's analyzer keeps a token of `text`.
+    fn any_tokens(&self, languages: &[&LanguageFields], text: &str) -> Result<bool> {
+        for fields in languages {
+            let mut analyzer = self
+                .index
+                .tokenizer_for_field(fields.content)
+                .map_err(|e| Error::Fts(e.to_string()))?;
+            if analyzer.token_stream(text).advance() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Run `word` through `field`'s tokenizer (lowercasing and stemming).
+    fn normalize(&self, field: Field, word: &str) -> Result<String> {
+        let mut analyzer = self
+            .index
+            .tokenizer_for_field(field)
+            .map_err(|e| Error::Fts(e.to_string()))?;
+        let mut stream = analyzer.token_stream(word);
+        Ok(match stream.next() {
+            Some(token) => token.text.clone(),
+            None => word.to_lowercase(),
+        })
+    }
+
     /// Commit all pending writes to the index and reload the reader.
     pub fn commit(&self) -> Result<()> {
         let writer_mutex = self
@@ -327,19 +1248,18 @@ impl FtsIndex {
             .as_ref()
             .ok_or_else(|| Error::Fts("FTS index opened in read-only mode".into()))?;
         let mut writer = writer_mutex.lock();
-        writer.commit().map_err(|e| Error::Fts(e.to_string()))?;
+        // Record the settings the committed documents were analyzed with.
+        let mut prepared = writer
+            .prepare_commit()
+            .map_err(|e| Error::Fts(e.to_string()))?;
+        prepared.set_payload(&self.settings.fingerprint());
+        prepared.commit().map_err(|e| Error::Fts(e.to_string()))?;
         Ok(())
     }
 
     /// Return the number of documents in the index.
     pub fn num_docs(&self) -> Result<u64> {
-        let reader = self
-            .index
-            .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommitWithDelay)
-            .try_into()
-            .map_err(|e: tantivy::TantivyError| Error::Fts(e.to_string()))?;
-        let searcher = reader.searcher();
+        let searcher = self.searcher()?;
         Ok(searcher.num_docs())
     }
 
@@ -358,19 +1278,53 @@ impl FtsIndex {
 }
 
 /// Build the Tantivy schema and return field handles.
+///
+/// The schema is the same for all settings: every language gets content,
+/// heading, and frontmatter fields analyzed by its own tokenizer (see
+/// [`register_tokenizers`]), plus one unstemmed `spelling` field shared by
+/// all languages.
 fn build_schema() -> (Schema, FtsFields) {
     let mut builder = Schema::builder();
 
     let chunk_id = builder.add_text_field("chunk_id", STRING | STORED);
     let source_path = builder.add_text_field("source_path", STRING | STORED);
 
-    // Content: indexed with English stemming, not stored (data lives in rkyv).
-    let text_indexing = TextFieldIndexing::default()
-        .set_tokenizer("en_stem")
-        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
-    let text_options = TextOptions::default().set_indexing_options(text_indexing);
-    let content = builder.add_text_field("content", text_options.clone());
-    let heading_hierarchy = builder.add_text_field("heading_hierarchy", text_options);
+    let mut languages = Vec::with_capacity(LexicalLanguage::ALL.len());
+    for language in LexicalLanguage::ALL {
+        let code = language.code();
+        // Text is indexed with positions, not stored (data lives in rkyv).
+        let text_indexing = TextFieldIndexing::default()
+            .set_tokenizer(&tokenizer_name(language))
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let text_options = TextOptions::default().set_indexing_options(text_indexing.clone());
+        let content = builder.add_text_field(&format!("content_{code}"), text_options.clone());
+        let heading_hierarchy =
+            builder.add_text_field(&format!("heading_hierarchy_{code}"), text_options.clone());
+
+        // Frontmatter: one JSON path per top-level key (`tags:postgres`) plus a
+        // catch-all field so unscoped queries match any value.
+        let json_options = JsonObjectOptions::default().set_indexing_options(text_indexing);
+        let frontmatter = builder.add_json_field(&format!("frontmatter_{code}"), json_options);
+        let frontmatter_all =
+            builder.add_text_field(&format!("frontmatter_all_{code}"), text_options);
+
+        languages.push(LanguageFields {
+            language,
+            content,
+            heading_hierarchy,
+            frontmatter,
+            frontmatter_all,
+        });
+    }
+
+    // Spelling suggestions only need document frequencies of surface forms.
+    let spelling_indexing = TextFieldIndexing::default()
+        .set_tokenizer(SPELLING_TOKENIZER)
+        .set_index_option(IndexRecordOption::Basic);
+    let spelling = builder.add_text_field(
+        "spelling",
+        TextOptions::default().set_indexing_options(spelling_indexing),
+    );
 
     let schema = builder.build();
     (
@@ -378,12 +1332,152 @@ fn build_schema() -> (Schema, FtsFields) {
         FtsFields {
             chunk_id,
             source_path,
-            content,
-            heading_hierarchy,
+            languages,
+            spelling,
         },
     )
 }
 
+const SPELLING_TOKENIZER: &str = "mdvdb_spelling";
+
+fn tokenizer_name(language: LexicalLanguage) -> String {
+    format!("mdvdb_{}", language.code())
+}
+
+/// Register each language's analyzer on `index`. Stemming languages split on
+/// non-alphanumerics, drop tokens over 40 bytes, lowercase, optionally remove
+/// stopwords, and stem; CJK text is split into character unigrams and bigrams.
+/// The spelling analyzer only splits, drops long tokens, and lowercases.
+fn register_tokenizers(index: &Index, stopwords: bool) -> Result<()> {
+    for language in LexicalLanguage::ALL {
+        let analyzer = match language.stemmer_language() {
+            Some(stemmer_language) => {
+                let builder = TextAnalyzer::builder(SimpleTokenizer::default())
+                    .filter(RemoveLongFilter::limit(40))
+                    .filter(LowerCaser);
+                let stop_words = if stopwords {
+                    StopWordFilter::new(stemmer_language)
+                } else {
+                    None
+                };
+                match stop_words {
+                    Some(stop_words) => builder
+                        .filter(stop_words)
+                        .filter(Stemmer::new(stemmer_language))
+                        .build(),
+                    None => builder.filter(Stemmer::new(stemmer_language)).build(),
+                }
+            }
+            None => {
+                let ngrams =
+                    NgramTokenizer::all_ngrams(1, 2).map_err(|e| Error::Fts(e.to_string()))?;
+                TextAnalyzer::builder(ngrams).filter(LowerCaser).build()
+            }
+        };
+        index
+            .tokenizers()
+            .register(&tokenizer_name(language), analyzer);
+    }
+    let spelling = TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .build();
+    index.tokenizers().register(SPELLING_TOKENIZER, spelling);
+    Ok(())
+}
+
+/// A Levenshtein automaton accepting the words within a few edits of a query
+/// word (an adjacent transposition counts as one), for walking a term
+/// dictionary or key set instead of comparing the word with every entry.
+pub(crate) struct LevenshteinDfa(DFA);
+
+impl LevenshteinDfa {
+    /// Accept words within `max_distance` edits of `word`, capped at 2.
+    pub(crate) fn new(word: &str, max_distance: u8) -> Self {
+        // Building the parametric automaton is the expensive step; it only
+        // depends on the distance.
+        static BUILDERS: [OnceLock<LevenshteinAutomatonBuilder>; 3] =
+            [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+        let max_distance = max_distance.min(2);
+        let builder = BUILDERS[usize::from(max_distance)]
+            .get_or_init(|| LevenshteinAutomatonBuilder::new(max_distance, true));
+        Self(builder.build_dfa(word))
+    }
+
+    /// The edit distance from the query word to `text`, or `None` when it
+    /// exceeds the maximum.
+    pub(crate) fn distance(&self, text: &[u8]) -> Option<u8> {
+        match self.0.eval(text) {
+            Distance::Exact(distance) => Some(distance),
+            Distance::AtLeast(_) => None,
+        }
+    }
+}
+
+impl Automaton for LevenshteinDfa {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+/// The term for `text` in `target`'s field, under its frontmatter key path.
+fn target_term(target: &SearchTarget, text: &str) -> Term {
+    match &target.json_path {
+        Some(path) => {
+            let mut term = Term::with_capacity(path.len() + text.len() + 8);
+            JsonTermWriter::from_field_and_json_path(target.field, path, false, &mut term)
+                .set_str(text);
+            term
+        }
+        None => Term::from_field_text(target.field, text),
+    }
+}
+
+/// Top-level frontmatter keys whose values are text: strings, and lists
+/// reduced to their string items. Numbers, booleans, and nested maps are
+/// left to metadata filters.
+fn indexable_frontmatter(
+    frontmatter: Option<&serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let Some(serde_json::Value::Object(map)) = frontmatter else {
+        return serde_json::Map::new();
+    };
+    map.iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(_) => value.clone(),
+                serde_json::Value::Array(items) => {
+                    let items: Vec<_> = items
+                        .iter()
+                        .filter(|item| item.is_string())
+                        .cloned()
+                        .collect();
+                    if items.is_empty() {
+                        return None;
+                    }
+                    serde_json::Value::Array(items)
+                }
+                _ => return None,
+            };
+            Some((key.clone(), value))
+        })
+        .collect()
+}
+
 /// Strip markdown formatting from content, returning plain text.
 ///
 /// Uses `pulldown-cmark` to parse and extract only text and code events.
@@ -426,6 +1520,20 @@ mod tests {
         assert!(!plain.contains("http"));
     }
 
+    #[test]
+    fn levenshtein_dfa_counts_transpositions_once() {
+        let distance =
+            |a: &str, b: &str, max: u8| LevenshteinDfa::new(a, max).distance(b.as_bytes());
+        assert_eq!(distance("timeout", "timeout", 2), Some(0));
+        assert_eq!(distance("timout", "timeout", 2), Some(1));
+        assert_eq!(distance("tiemout", "timeout", 2), Some(1));
+        assert_eq!(distance("postgress", "postgres", 1), Some(1));
+        assert_eq!(distance("kafka", "kafkaesque", 2), None);
+        assert_eq!(distance("gren", "grain", 1), None);
+        assert_eq!(distance("gren", "grain", 2), Some(2));
+        assert_eq!(distance("straße", "strasse", 2), Some(2));
+    }
+
     #[test]
     fn open_or_create_round_trip() {
         let dir = TempDir::new().unwrap();
@@ -663,4 +1771,122 @@ mod tests {
         assert!(fts_index.search("quokkas", 10).unwrap().is_empty());
         assert_eq!(fts_index.num_docs().unwrap(), 0);
     }
+
+    #[test]
+    fn outdated_schema_is_replaced_and_rebuilt_with_frontmatter() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let fts_path = root.join(".markdownvdb").join("fts");
+        std::fs::create_dir_all(&fts_path).unwrap();
+        std::fs::write(
+            root.join("doc.md"),
+            "---\ntags: [postgres]\n---\n# Pools\n\nConnection pooling tips.\n",
+        )
+        .unwrap();
+
+        // An index written before frontmatter fields existed.
+        let mut builder = Schema::builder();
+        builder.add_text_field("chunk_id", STRING | STORED);
+        builder.add_text_field("source_path", STRING | STORED);
+        Index::create_in_dir(&fts_path, builder.build()).unwrap();
+        let settings = LexicalSettings::default();
+        assert!(index_outdated(&fts_path, &settings).unwrap());
+
+        let file = parse_markdown_file(root, Path::new("doc.md")).unwrap();
+        let chunks = chunk_document(&file, 512, 0).unwrap();
+        let embedding_config = EmbeddingConfig {
+            provider: "test".into(),
+            model: "test".into(),
+            dimensions: 8,
+        };
+        let index =
+            VectorIndex::create(&root.join(".markdownvdb").join("index"), &embedding_config)
+                .unwrap();
+        index
+            .upsert(&file, &chunks, &vec![vec![1.0; 8]; chunks.len()])
+            .unwrap();
+
+        let readonly = FtsIndex::open_readonly(&fts_path).unwrap();
+        assert!(readonly.search("postgres", 10).unwrap().is_empty());
+        drop(readonly);
+
+        let fts_index = FtsIndex::open_or_create(&fts_path).unwrap();
+        rebuild_from_vector_index(&fts_index, &index).unwrap();
+        assert!(!index_outdated(&fts_path, &settings).unwrap());
+        assert_eq!(fts_index.search("tags:postgres", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn language_parses_names_and_codes_and_resolves_overrides() {
+        for (input, expected) in [
+            ("german", LexicalLanguage::German),
+            ("de-AT", LexicalLanguage::German),
+            ("ja", LexicalLanguage::Cjk),
+            ("zh_Hans", LexicalLanguage::Cjk),
+            ("NB", LexicalLanguage::Norwegian),
+        ] {
+            assert_eq!(
+                input.parse::<LexicalLanguage>().unwrap(),
+                expected,
+                "{input}"
+            );
+        }
+        assert!("klingon".parse::<LexicalLanguage>().is_err());
+        for language in LexicalLanguage::ALL {
+            assert_eq!(
+                language.to_string().parse::<LexicalLanguage>().unwrap(),
+                language
+            );
+        }
+
+        let settings = LexicalSettings {
+            language: LexicalLanguage::French,
+            paths: [
+                ("notes".to_string(), LexicalLanguage::German),
+                ("notes/ja/".to_string(), LexicalLanguage::Cjk),
+            ]
+            .into(),
+            stopwords: false,
+        };
+        assert_eq!(settings.language_for("a.md", None), LexicalLanguage::French);
+        assert_eq!(
+            settings.language_for("notes/a.md", None),
+            LexicalLanguage::German
+        );
+        assert_eq!(
+            settings.language_for("notes/ja/a.md", None),
+            LexicalLanguage::Cjk
+        );
+        let frontmatter = serde_json::json!({ "lang": "es" });
+        assert_eq!(
+            settings.language_for("notes/a.md", Some(&frontmatter)),
+            LexicalLanguage::Spanish
+        );
+        let unknown = serde_json::json!({ "lang": "klingon" });
+        assert_eq!(
+            settings.language_for("notes/a.md", Some(&unknown)),
+            LexicalLanguage::German
+        );
+    }
+
+    #[test]
+    fn changed_settings_mark_the_index_outdated() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fts_idx");
+        let english = LexicalSettings::default();
+        let german = LexicalSettings {
+            language: LexicalLanguage::German,
+            ..Default::default()
+        };
+
+        let idx = FtsIndex::open_or_create_with_settings(&path, &english).unwrap();
+        idx.commit().unwrap();
+        assert!(!index_outdated(&path, &english).unwrap());
+        assert!(index_outdated(&path, &german).unwrap());
+        drop(idx);
+
+        let idx = FtsIndex::open_or_create_with_settings(&path, &german).unwrap();
+        idx.commit().unwrap();
+        assert!(!index_outdated(&path, &german).unwrap());
+    }
 }
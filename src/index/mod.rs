@@ -1,12 +1,15 @@
 pub mod state;
 pub mod storage;
 pub mod types;
+pub(crate) mod vectors;
+pub(crate) mod wal;
 
 // Re-export key types for convenient access via `crate::index::*`
 pub use state::Index;
 pub use storage::WriteOptions;
 pub use types::{
-    EmbeddingConfig, IndexMetadata, IndexStatus, ScopedCounts, StoredChunk, StoredFile,
+    EmbeddingConfig, IndexMetadata, IndexStatus, RecallEstimate, ScopedCounts, StoredChunk,
+    StoredFile, VectorStorageInfo,
 };
 
 // Re-export clustering types for convenient access
@@ -1,21 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 
 use parking_lot::RwLock;
 use rayon::prelude::*;
-use usearch::Index as HnswIndex;
 
 use tracing::debug;
 
 use crate::chunker::Chunk;
 use crate::clustering::{ClusterState, CustomClusterState};
+use crate::embedding::chunk_cache::{text_hash, TextHash};
 use crate::error::{Error, Result};
 use crate::index::storage::{self, WriteOptions};
 use crate::index::types::{
-    ComputedFieldEntry, EmbeddingConfig, IndexMetadata, IndexStatus, ScopedCounts, StoredChunk,
-    StoredFile,
+    ComputedFieldEntry, EmbeddingConfig, IndexMetadata, IndexStatus, RecallEstimate, ScopedCounts,
+    StoredChunk, StoredFile, VectorStorageInfo,
 };
+use crate::index::vectors::{self, StoreBuilder, VectorStore};
+use crate::index::wal::{self, WalChunk, WalOp, WalRecord, WalVector};
 use crate::links::LinkGraph;
 use crate::parser::MarkdownFile;
 use crate::schema::Schema;
@@ -48,8 +51,59 @@ pub(crate) struct VectorSearchBatch {
     pub(crate) matches: Vec<Vec<(usize, f64)>>,
 }
 
+/// What [`Index::visit_full_record`] visits, counted before the first op.
+#[derive(Debug, Default)]
+pub(crate) struct RecordCounts {
+    pub(crate) files: usize,
+    pub(crate) chunks: usize,
+    /// Chunks with a vector.
+    pub(crate) chunk_vectors: usize,
+    /// Edge vectors.
+    pub(crate) edges: usize,
+    /// Unix time the index was last saved.
+    pub(crate) last_updated: u64,
+}
+
+/// Inconsistencies between an index's metadata, vectors and link graph,
+/// found by [`Index::check_integrity`]. Lists are sorted.
+#[derive(Debug, Default)]
+pub(crate) struct IntegrityReport {
+    /// Chunks with no vector in the HNSW index.
+    pub(crate) chunks_without_vectors: Vec<String>,
+    /// Ids with a vector that are neither a chunk nor a semantic edge.
+    pub(crate) vectors_without_chunks: Vec<String>,
+    /// Vectors in the HNSW index that no id maps to.
+    pub(crate) unmapped_vectors: usize,
+    /// `(file, chunk id)` pairs where a file lists a chunk that is missing
+    /// or belongs to another file.
+    pub(crate) missing_file_chunks: Vec<(String, String)>,
+    /// Chunks that no file lists.
+    pub(crate) unowned_chunks: Vec<String>,
+    /// Link graph sources (forward links and semantic edges) that are not
+    /// indexed files.
+    pub(crate) dangling_link_sources: Vec<String>,
+    /// Semantic edges whose target is not an indexed file. Forward links
+    /// may point anywhere, so only edges are checked.
+    pub(crate) dangling_edge_targets: Vec<String>,
+    /// Files with a chunk that has no vector or is missing; only
+    /// re-embedding them restores their vectors.
+    pub(crate) files_to_reembed: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub(crate) fn is_clean(&self) -> bool {
+        self.chunks_without_vectors.is_empty()
+            && self.vectors_without_chunks.is_empty()
+            && self.unmapped_vectors == 0
+            && self.missing_file_chunks.is_empty()
+            && self.unowned_chunks.is_empty()
+            && self.dangling_link_sources.is_empty()
+            && self.dangling_edge_targets.is_empty()
+    }
+}
+
 fn collect_vector_search_indices(
-    hnsw: &HnswIndex,
+    vectors: &VectorStore,
     key_to_id_index: &HashMap<u64, usize>,
     query: &[f32],
     limit: usize,
@@ -60,14 +114,12 @@ fn collect_vector_search_indices(
         return Ok(Vec::new());
     }
 
-    let results = hnsw
-        .search(query, search_limit.min(hnsw.size()))
-        .map_err(|e| Error::Serialization(format!("usearch search: {e}")))?;
+    let results = vectors.search(query, search_limit.min(vectors.size()))?;
 
-    let mut output = Vec::with_capacity(results.keys.len());
-    for (key, distance) in results.keys.iter().zip(results.distances.iter()) {
-        if let Some(id_index) = key_to_id_index.get(key) {
-            output.push((*id_index, 1.0 - *distance as f64));
+    let mut output = Vec::with_capacity(results.len());
+    for (key, distance) in results {
+        if let Some(id_index) = key_to_id_index.get(&key) {
+            output.push((*id_index, 1.0 - distance as f64));
         }
     }
 
@@ -97,13 +149,288 @@ fn chunk_id_projection(state: &IndexState) -> (Vec<&str>, HashMap<u64, usize>) {
     (ids, key_to_id_index)
 }
 
+/// Map the keys of ranked `(key, score)` matches back to chunk IDs, keeping
+/// their order. Only the matched keys are looked up.
+fn resolve_chunk_keys(state: &IndexState, matches: Vec<(u64, f64)>) -> Vec<(String, f64)> {
+    let mut ids: HashMap<u64, &str> = HashMap::with_capacity(matches.len());
+    for (key, _) in &matches {
+        ids.insert(*key, "");
+    }
+    for (id, key) in &state.id_to_key {
+        if let Some(slot) = ids.get_mut(key) {
+            *slot = id;
+        }
+    }
+    matches
+        .into_iter()
+        .filter_map(|(key, score)| {
+            let id = ids.get(&key).copied().filter(|id| !id.is_empty())?;
+            Some((id.to_string(), score))
+        })
+        .collect()
+}
+
+/// Average the stored vectors of `chunk_ids`, skipping chunks without one.
+fn mean_chunk_vector(state: &IndexState, chunk_ids: &[String], dims: usize) -> Option<Vec<f32>> {
+    let mut sum = vec![0.0f32; dims];
+    let mut count = 0usize;
+
+    for chunk_id in chunk_ids {
+        if let Some(&key) = state.id_to_key.get(chunk_id) {
+            if let Some(buf) = state.vectors.vector(key) {
+                for (s, v) in sum.iter_mut().zip(buf.iter()) {
+                    *s += v;
+                }
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+    let scale = 1.0 / count as f32;
+    for s in &mut sum {
+        *s *= scale;
+    }
+    Some(sum)
+}
+
+/// Insert `distance` into `best`, an ascending list of at most `k` distances.
+fn insert_nearest(best: &mut Vec<f32>, distance: f32, k: usize) {
+    if best.len() == k && best.last().is_some_and(|last| distance >= *last) {
+        return;
+    }
+    let at = best.partition_point(|d| *d <= distance);
+    best.insert(at, distance);
+    best.truncate(k);
+}
+
 /// Internal mutable state protected by the RwLock.
 struct IndexState {
     metadata: IndexMetadata,
-    hnsw: HnswIndex,
+    vectors: VectorStore,
     id_to_key: HashMap<String, u64>,
     next_key: u64,
     dirty: bool,
+    /// Changes not yet saved, recorded for the write-ahead log.
+    pending: PendingChanges,
+    /// Generation of the base file this state extends (0 for base files
+    /// written before generations existed, which are never appended to).
+    generation: u64,
+    /// Length of the write-ahead log this state has replayed or appended.
+    wal_len: u64,
+    /// Unix time the write-ahead log was started.
+    wal_created_at: u64,
+}
+
+impl IndexState {
+    fn new(metadata: IndexMetadata, vectors: VectorStore, generation: u64) -> Self {
+        Self {
+            metadata,
+            vectors,
+            id_to_key: HashMap::new(),
+            next_key: 0,
+            dirty: false,
+            pending: PendingChanges::default(),
+            generation,
+            wal_len: 0,
+            wal_created_at: 0,
+        }
+    }
+
+    /// Add `vector` under a fresh HNSW key, growing the index as needed.
+    fn add_vector(&mut self, id: String, vector: &[f32]) -> Result<()> {
+        let current_size = self.vectors.size();
+        if current_size + 1 > self.vectors.capacity() {
+            self.vectors
+                .reserve((current_size + 1).max(current_size * 2))?;
+        }
+        let key = self.next_key;
+        self.next_key += 1;
+        self.vectors.add(key, vector)?;
+        self.id_to_key.insert(id, key);
+        Ok(())
+    }
+
+    fn remove_vector(&mut self, id: &str) {
+        if let Some(key) = self.id_to_key.remove(id) {
+            let _ = self.vectors.remove(key);
+        }
+    }
+
+    fn vector(&self, id: &str) -> Option<Vec<f32>> {
+        let key = *self.id_to_key.get(id)?;
+        self.vectors.vector(key)
+    }
+
+    /// Remove a file entry, its chunks and chunk vectors, and its mtime.
+    fn remove_file_entry(&mut self, relative_path: &str) {
+        if let Some(file) = self.metadata.files.remove(relative_path) {
+            for chunk_id in &file.chunk_ids {
+                self.remove_vector(chunk_id);
+                self.metadata.chunks.remove(chunk_id);
+            }
+        }
+        if let Some(ref mut mtimes) = self.metadata.file_mtimes {
+            mtimes.remove(relative_path);
+        }
+    }
+
+    fn set_file(&mut self, path: String, file: StoredFile, mtime: Option<u64>) {
+        let mtimes = self.metadata.file_mtimes.get_or_insert_with(HashMap::new);
+        match mtime {
+            Some(mtime) => mtimes.insert(path.clone(), mtime),
+            None => mtimes.remove(&path),
+        };
+        self.metadata.files.insert(path, file);
+    }
+
+    /// Build the write-ahead log record for the pending changes.
+    fn wal_record(&self) -> WalRecord {
+        let pending = &self.pending;
+        let mut ops = Vec::new();
+
+        let mut paths: Vec<&String> = pending.chunks.union(&pending.files).collect();
+        paths.sort();
+        for path in paths {
+            let Some(file) = self.metadata.files.get(path) else {
+                ops.push(WalOp::RemoveFile { path: path.clone() });
+                continue;
+            };
+            let mtime = self
+                .metadata
+                .file_mtimes
+                .as_ref()
+                .and_then(|mtimes| mtimes.get(path))
+                .copied();
+            if pending.chunks.contains(path) {
+                let chunks = file
+                    .chunk_ids
+                    .iter()
+                    .filter_map(|id| {
+                        let chunk = self.metadata.chunks.get(id)?;
+                        Some(WalChunk {
+                            id: id.clone(),
+                            chunk: chunk.clone(),
+                            vector: self.vector(id),
+                        })
+                    })
+                    .collect();
+                ops.push(WalOp::UpsertFile {
+                    path: path.clone(),
+                    file: file.clone(),
+                    mtime,
+                    chunks,
+                });
+            } else {
+                ops.push(WalOp::UpdateFile {
+                    path: path.clone(),
+                    file: file.clone(),
+                    mtime,
+                });
+            }
+        }
+
+        if !pending.edges.is_empty() {
+            let mut edge_ids: Vec<&String> = pending.edges.iter().collect();
+            edge_ids.sort();
+            let mut upserted = Vec::new();
+            let mut removed = Vec::new();
+            for id in edge_ids {
+                match self.vector(id) {
+                    Some(vector) => upserted.push(WalVector {
+                        id: id.clone(),
+                        vector,
+                    }),
+                    None => removed.push(id.clone()),
+                }
+            }
+            ops.push(WalOp::Edges { upserted, removed });
+        }
+
+        let metadata = &self.metadata;
+        if pending.schema {
+            ops.push(WalOp::Schema(metadata.schema.clone()));
+        }
+        if pending.scoped_schemas {
+            ops.push(WalOp::ScopedSchemas(metadata.scoped_schemas.clone()));
+        }
+        if pending.clusters {
+            ops.push(WalOp::Clusters(metadata.cluster_state.clone()));
+        }
+        if pending.custom_clusters {
+            ops.push(WalOp::CustomClusters(metadata.custom_cluster_state.clone()));
+        }
+        if pending.link_graph {
+            ops.push(WalOp::LinkGraph(metadata.link_graph.clone()));
+        }
+
+        WalRecord {
+            last_updated: metadata.last_updated,
+            ops,
+        }
+    }
+
+    /// Replay one write-ahead log record on top of this state.
+    fn apply_wal_record(&mut self, record: WalRecord) -> Result<()> {
+        for op in record.ops {
+            match op {
+                WalOp::UpsertFile {
+                    path,
+                    file,
+                    mtime,
+                    chunks,
+                } => {
+                    self.remove_file_entry(&path);
+                    for chunk in chunks {
+                        if let Some(vector) = &chunk.vector {
+                            self.add_vector(chunk.id.clone(), vector)?;
+                        }
+                        self.metadata.chunks.insert(chunk.id, chunk.chunk);
+                    }
+                    self.set_file(path, file, mtime);
+                }
+                WalOp::UpdateFile { path, file, mtime } => self.set_file(path, file, mtime),
+                WalOp::RemoveFile { path } => self.remove_file_entry(&path),
+                WalOp::Edges { upserted, removed } => {
+                    for id in &removed {
+                        self.remove_vector(id);
+                    }
+                    for edge in upserted {
+                        self.remove_vector(&edge.id);
+                        self.add_vector(edge.id, &edge.vector)?;
+                    }
+                }
+                WalOp::Schema(schema) => self.metadata.schema = schema,
+                WalOp::ScopedSchemas(schemas) => self.metadata.scoped_schemas = schemas,
+                WalOp::Clusters(clusters) => self.metadata.cluster_state = clusters,
+                WalOp::CustomClusters(clusters) => self.metadata.custom_cluster_state = clusters,
+                WalOp::LinkGraph(graph) => self.metadata.link_graph = graph,
+            }
+        }
+        self.metadata.last_updated = record.last_updated;
+        Ok(())
+    }
+}
+
+/// Changes made since the last save, by what the write-ahead log has to
+/// record for them.
+#[derive(Default)]
+struct PendingChanges {
+    /// Files whose chunks and vectors were replaced, or that were removed.
+    chunks: HashSet<String>,
+    /// Files whose entry or mtime changed but whose chunks did not.
+    files: HashSet<String>,
+    /// Edge vectors added, replaced, or removed.
+    edges: HashSet<String>,
+    schema: bool,
+    scoped_schemas: bool,
+    clusters: bool,
+    custom_clusters: bool,
+    link_graph: bool,
+    /// The embedding space was reset, which only a full rewrite can record.
+    rewrite: bool,
 }
 
 /// Thread-safe handle to a memory-mapped index file.
@@ -111,6 +438,8 @@ pub struct Index {
     path: PathBuf,
     state: RwLock<IndexState>,
     write_options: WriteOptions,
+    /// Bumped by every in-memory change (see [`Index::revision`]).
+    revision: AtomicU64,
 }
 
 impl Index {
@@ -124,10 +453,7 @@ impl Index {
                 "cannot initialize an index with unresolved dimensions".into(),
             ));
         }
-        let hnsw = storage::create_hnsw(
-            config.dimensions,
-            storage::scalar_kind_for(&self.write_options.quantization),
-        )?;
+        let vectors = VectorStore::create(config.dimensions, &self.write_options)?;
         let mut state = self.state.write();
         state.metadata = IndexMetadata {
             chunks: HashMap::new(),
@@ -144,10 +470,12 @@ impl Index {
             scoped_schemas: None,
             custom_cluster_state: None,
         };
-        state.hnsw = hnsw;
+        state.vectors = vectors;
         state.id_to_key.clear();
         state.next_key = 0;
+        state.pending.rewrite = true;
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -178,12 +506,47 @@ impl Index {
         }
         let refreshed = Self::open_with_options(&self.path, self.write_options.clone())?;
         *state = refreshed.state.into_inner();
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(true)
     }
 
     /// Open an existing index file at the given path with explicit write options.
+    ///
+    /// Changes appended to the write-ahead log since the base file was
+    /// written are replayed on top of it.
     pub fn open_with_options(path: &Path, write_options: WriteOptions) -> Result<Self> {
-        let (metadata, hnsw) = storage::load_index(path)?;
+        // A writer compacting between reading the base file and its log
+        // would leave us with a base file missing the log's changes. The
+        // generation changes on every rewrite, so retry until it is stable.
+        let mut attempts = 0;
+        let state = loop {
+            let state = Self::load_state(path, &write_options)?;
+            attempts += 1;
+            if attempts == 3 || storage::read_generation(path).ok() == Some(state.generation) {
+                break state;
+            }
+            debug!(path = %path.display(), "index rewritten while loading, retrying");
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            state: RwLock::new(state),
+            write_options,
+            revision: AtomicU64::new(0),
+        })
+    }
+
+    /// Load the base file at `path` and replay its write-ahead log.
+    fn load_state(path: &Path, write_options: &WriteOptions) -> Result<IndexState> {
+        let (metadata, hnsw, header) = storage::load_index_with_header(path)?;
+        let generation = header.generation;
+        let vectors = VectorStore::load(
+            hnsw,
+            &header,
+            path,
+            metadata.embedding_config.dimensions,
+            write_options.rescore_factor,
+        )?;
 
         // Build id_to_key mapping and compute next_key from chunk IDs.
         // Sort chunk IDs alphabetically for deterministic key assignment,
@@ -216,9 +579,9 @@ impl Index {
         // After save() compaction, keys are assigned sequentially 0..total-1.
         // If metadata (chunks + semantic_edges) doesn't account for all entries
         // (e.g., orphaned edge vectors), next_key could be too low, causing
-        // "duplicate key" errors on subsequent adds. Use hnsw.size() as a
+        // "duplicate key" errors on subsequent adds. Use the HNSW size as a
         // lower bound since the max key is at most total-1 >= size-1.
-        let hnsw_size = hnsw.size() as u64;
+        let hnsw_size = vectors.size() as u64;
         if next_key < hnsw_size {
             debug!(
                 computed = next_key,
@@ -227,17 +590,28 @@ impl Index {
             next_key = hnsw_size;
         }
 
-        Ok(Self {
-            path: path.to_path_buf(),
-            state: RwLock::new(IndexState {
-                metadata,
-                hnsw,
-                id_to_key,
-                next_key,
-                dirty: false,
-            }),
-            write_options,
-        })
+        let mut state = IndexState::new(metadata, vectors, generation);
+        state.id_to_key = id_to_key;
+        state.next_key = next_key;
+
+        if generation != 0 {
+            if let Some(log) = wal::read(&wal::wal_path(path), generation)? {
+                debug!(records = log.records.len(), "replaying write-ahead log");
+                for record in log.records {
+                    state.apply_wal_record(record)?;
+                }
+                state.wal_len = log.valid_len;
+                state.wal_created_at = log.created_at;
+            }
+        }
+        Ok(state)
+    }
+
+    /// A counter bumped by every in-memory change to the index, including
+    /// reloads. Caches derived from the index compare it to detect staleness;
+    /// the value is not persisted.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
     }
 
     /// Create a new, empty index file at the given path with default write options.
@@ -271,23 +645,20 @@ impl Index {
             custom_cluster_state: None,
         };
 
-        let scalar_kind = storage::scalar_kind_for(&write_options.quantization);
-        let hnsw = storage::create_hnsw(config.dimensions, scalar_kind)?;
-        hnsw.reserve(10)
-            .map_err(|e| Error::Serialization(format!("usearch reserve: {e}")))?;
+        let vectors = VectorStore::create(config.dimensions, &write_options)?;
+        vectors.reserve(10)?;
 
-        storage::write_index(path, &metadata, &hnsw, &write_options)?;
+        let generation = storage::write_index(path, &metadata, vectors.hnsw(), &write_options)?;
+        // A log or vector sidecar left by a previous index at this path no
+        // longer applies.
+        wal::remove(&wal::wal_path(path))?;
+        vectors::remove_sidecars(path, None)?;
 
         Ok(Self {
             path: path.to_path_buf(),
-            state: RwLock::new(IndexState {
-                metadata,
-                hnsw,
-                id_to_key: HashMap::new(),
-                next_key: 0,
-                dirty: false,
-            }),
+            state: RwLock::new(IndexState::new(metadata, vectors, generation)),
             write_options,
+            revision: AtomicU64::new(0),
         })
     }
 
@@ -371,7 +742,7 @@ impl Index {
             if let Some(old_file) = state.metadata.files.remove(&relative_path) {
                 for chunk_id in &old_file.chunk_ids {
                     if let Some(key) = state.id_to_key.remove(chunk_id) {
-                        let _ = state.hnsw.remove(key);
+                        let _ = state.vectors.remove(key);
                     }
                     state.metadata.chunks.remove(chunk_id);
                 }
@@ -381,13 +752,10 @@ impl Index {
             };
 
         // Ensure HNSW has capacity for new vectors.
-        let current_size = state.hnsw.size();
+        let current_size = state.vectors.size();
         let needed = current_size + chunks.len();
-        if needed > state.hnsw.capacity() {
-            state
-                .hnsw
-                .reserve(needed.max(current_size * 2))
-                .map_err(|e| Error::Serialization(format!("usearch reserve: {e}")))?;
+        if needed > state.vectors.capacity() {
+            state.vectors.reserve(needed.max(current_size * 2))?;
         }
 
         // Insert new chunks.
@@ -398,10 +766,7 @@ impl Index {
             let key = state.next_key;
             state.next_key += 1;
 
-            state
-                .hnsw
-                .add(key, &embeddings[i])
-                .map_err(|e| Error::Serialization(format!("usearch add: {e}")))?;
+            state.vectors.add(key, &embeddings[i])?;
 
             let stored_chunk = StoredChunk::from(chunk);
             state.metadata.chunks.insert(chunk.id.clone(), stored_chunk);
@@ -416,8 +781,10 @@ impl Index {
             .get_or_insert_with(HashMap::new)
             .insert(relative_path.clone(), file.modified_at);
 
+        state.pending.chunks.insert(relative_path.clone());
         state.metadata.files.insert(relative_path, stored_file);
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -448,8 +815,10 @@ impl Index {
             .file_mtimes
             .get_or_insert_with(HashMap::new)
             .insert(relative_path.clone(), file.modified_at);
+        state.pending.files.insert(relative_path.clone());
         state.metadata.files.insert(relative_path, stored_file);
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -467,18 +836,15 @@ impl Index {
         // Remove old edge vectors with the same IDs.
         for (edge_id, _) in edges {
             if let Some(key) = state.id_to_key.remove(edge_id) {
-                let _ = state.hnsw.remove(key);
+                let _ = state.vectors.remove(key);
             }
         }
 
         // Ensure HNSW has capacity for new vectors.
-        let current_size = state.hnsw.size();
+        let current_size = state.vectors.size();
         let needed = current_size + edges.len();
-        if needed > state.hnsw.capacity() {
-            state
-                .hnsw
-                .reserve(needed.max(current_size * 2))
-                .map_err(|e| Error::Serialization(format!("usearch reserve: {e}")))?;
+        if needed > state.vectors.capacity() {
+            state.vectors.reserve(needed.max(current_size * 2))?;
         }
 
         // Insert new edge vectors.
@@ -486,15 +852,14 @@ impl Index {
             let key = state.next_key;
             state.next_key += 1;
 
-            state
-                .hnsw
-                .add(key, embedding)
-                .map_err(|e| Error::Serialization(format!("usearch add: {e}")))?;
+            state.vectors.add(key, embedding)?;
 
             state.id_to_key.insert(edge_id.clone(), key);
+            state.pending.edges.insert(edge_id.clone());
         }
 
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -513,7 +878,7 @@ impl Index {
 
         for chunk_id in &file.chunk_ids {
             if let Some(key) = state.id_to_key.remove(chunk_id) {
-                let _ = state.hnsw.remove(key);
+                let _ = state.vectors.remove(key);
             }
             state.metadata.chunks.remove(chunk_id);
         }
@@ -528,7 +893,7 @@ impl Index {
             .collect();
         for edge_id in &edge_ids_to_remove {
             if let Some(key) = state.id_to_key.remove(edge_id) {
-                let _ = state.hnsw.remove(key);
+                let _ = state.vectors.remove(key);
             }
         }
 
@@ -537,7 +902,10 @@ impl Index {
             mtimes.remove(relative_path);
         }
 
+        state.pending.chunks.insert(relative_path.to_string());
+        state.pending.edges.extend(edge_ids_to_remove);
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -580,7 +948,9 @@ impl Index {
                 })?;
         file.computed_fields = computed_fields;
         file.reconcile_materialized_proofs();
+        state.pending.files.insert(relative_path.to_string());
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -611,8 +981,10 @@ impl Index {
             .metadata
             .file_mtimes
             .get_or_insert_with(HashMap::new)
-            .insert(relative_path, file.modified_at);
+            .insert(relative_path.clone(), file.modified_at);
+        state.pending.files.insert(relative_path);
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -651,8 +1023,10 @@ impl Index {
             .metadata
             .file_mtimes
             .get_or_insert_with(HashMap::new)
-            .insert(relative_path, file.modified_at);
+            .insert(relative_path.clone(), file.modified_at);
+        state.pending.files.insert(relative_path);
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -661,7 +1035,8 @@ impl Index {
     /// A scope matches either an exact indexed path or all descendants of a
     /// folder prefix. Returns the number of removed field entries.
     pub fn clear_computed_fields_for_module(&self, module: &str, scope: Option<&str>) -> usize {
-        let mut state = self.state.write();
+        let mut guard = self.state.write();
+        let state = &mut *guard;
         let mut removed = 0usize;
 
         for (path, file) in &mut state.metadata.files {
@@ -671,11 +1046,15 @@ impl Index {
             let before = file.computed_fields.len();
             file.computed_fields
                 .retain(|_, entry| entry.module != module);
-            removed += before - file.computed_fields.len();
+            if file.computed_fields.len() < before {
+                removed += before - file.computed_fields.len();
+                state.pending.files.insert(path.clone());
+            }
         }
 
         if removed > 0 {
             state.dirty = true;
+            self.revision.fetch_add(1, Ordering::Relaxed);
         }
         removed
     }
@@ -686,6 +1065,12 @@ impl Index {
         state.metadata.files.clone()
     }
 
+    /// Visit every indexed file without cloning the file table.
+    pub fn for_each_file(&self, mut visit: impl FnMut(&StoredFile)) {
+        let state = self.state.read();
+        state.metadata.files.values().for_each(|file| visit(file));
+    }
+
     /// Get a map of all file paths to their content hashes.
     pub fn get_file_hashes(&self) -> HashMap<String, String> {
         let state = self.state.read();
@@ -710,7 +1095,7 @@ impl Index {
         IndexStatus {
             document_count: state.metadata.files.len(),
             chunk_count: state.metadata.chunks.len(),
-            vector_count: state.hnsw.size(),
+            vector_count: state.vectors.size(),
             edge_count,
             last_updated: state.metadata.last_updated,
             file_size,
@@ -721,6 +1106,122 @@ impl Index {
         }
     }
 
+    /// Quantization, sizes and rescoring of the stored vectors.
+    pub fn vector_storage(&self) -> VectorStorageInfo {
+        let state = self.state.read();
+        let quantization = state.vectors.quantization().clone();
+        let dims = state.metadata.embedding_config.dimensions;
+        let sidecar_file_size = quantization.keeps_full_precision().then(|| {
+            vectors::find_sidecar(&self.path, state.generation)
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map(|m| m.len())
+                .unwrap_or(0)
+        });
+        VectorStorageInfo {
+            pending_quantization: (self.write_options.quantization != quantization)
+                .then(|| self.write_options.quantization.clone()),
+            bytes_per_vector: quantization.bytes_per_vector(dims),
+            full_precision_bytes_per_vector: dims * 4,
+            sidecar_file_size,
+            full_precision_available: state.vectors.full_precision_available(),
+            rescore_factor: state.vectors.rescore_factor(),
+            recall: None,
+            quantization,
+        }
+    }
+
+    /// Measure search recall@`k` against an exhaustive exact search, using
+    /// up to `queries` stored chunk vectors, spread over the index, as
+    /// queries. A result counts as a hit when it is at least as close as the
+    /// exact k-th neighbor, so ties between duplicate chunks are not misses.
+    ///
+    /// The exact search reads every chunk vector once, so this is slow for
+    /// large indexes.
+    pub fn estimate_recall(&self, queries: usize, k: usize) -> Result<RecallEstimate> {
+        let state = self.state.read();
+        let mut keys: Vec<u64> = state
+            .id_to_key
+            .iter()
+            .filter(|(id, key)| !id.starts_with("edge:") && state.vectors.contains(**key))
+            .map(|(_, key)| *key)
+            .collect();
+        keys.sort_unstable();
+
+        let k = k.min(keys.len());
+        let step = (keys.len() / queries.max(1)).max(1);
+        let samples: Vec<Vec<f32>> = keys
+            .iter()
+            .step_by(step)
+            .take(queries)
+            .filter_map(|&key| state.vectors.vector(key))
+            .collect();
+        let rescore_factor = state.vectors.rescore_factor();
+        if k == 0 || samples.is_empty() {
+            return Ok(RecallEstimate {
+                queries: 0,
+                k,
+                quantized: 1.0,
+                rescored: (rescore_factor > 0).then_some(1.0),
+            });
+        }
+
+        // Distance of each query's exact k-th nearest neighbor, from one
+        // pass over the chunk vectors.
+        let nearest = keys
+            .par_chunks(1024)
+            .map(|block| {
+                let mut nearest = vec![Vec::with_capacity(k + 1); samples.len()];
+                for &key in block {
+                    let Some(vector) = state.vectors.vector(key) else {
+                        continue;
+                    };
+                    for (best, query) in nearest.iter_mut().zip(&samples) {
+                        insert_nearest(best, vectors::cosine_distance(query, &vector), k);
+                    }
+                }
+                nearest
+            })
+            .reduce(
+                || vec![Vec::new(); samples.len()],
+                |mut left, right| {
+                    for (best, other) in left.iter_mut().zip(right) {
+                        for distance in other {
+                            insert_nearest(best, distance, k);
+                        }
+                    }
+                    left
+                },
+            );
+
+        let chunk_keys: HashSet<u64> = keys.iter().copied().collect();
+        let is_chunk = |key: u64| chunk_keys.contains(&key);
+        let recall = |rescore_factor: usize| -> Result<f64> {
+            let mut hits = 0usize;
+            for (query, best) in samples.iter().zip(&nearest) {
+                let threshold = best.last().copied().unwrap_or(f32::INFINITY) + 1e-6;
+                let results =
+                    state
+                        .vectors
+                        .search_with(query, k, rescore_factor, Some(&is_chunk))?;
+                hits += results
+                    .iter()
+                    .filter_map(|(key, _)| state.vectors.vector(*key))
+                    .filter(|vector| vectors::cosine_distance(query, vector) <= threshold)
+                    .count();
+            }
+            Ok(hits as f64 / (samples.len() * k) as f64)
+        };
+
+        Ok(RecallEstimate {
+            queries: samples.len(),
+            k,
+            quantized: recall(0)?,
+            rescored: (rescore_factor > 0)
+                .then(|| recall(rescore_factor))
+                .transpose()?,
+        })
+    }
+
     /// Count files, chunks, and edge vectors within a path scope.
     ///
     /// `prefix` is a slash-terminated folder prefix (e.g. `"blog/"`); `None`
@@ -761,7 +1262,7 @@ impl Index {
     pub fn search_vectors(&self, query: &[f32], limit: usize) -> Result<Vec<(String, f64)>> {
         let state = self.state.read();
 
-        if state.hnsw.size() == 0 {
+        if state.vectors.size() == 0 {
             return Ok(Vec::new());
         }
 
@@ -771,11 +1272,11 @@ impl Index {
         // the final candidates during progressive scoped retrieval.
         let requested = limit.min(ids.len());
         let search_limit = if requested == ids.len() {
-            state.hnsw.size()
+            state.vectors.size()
         } else {
-            requested.saturating_mul(2).min(state.hnsw.size())
+            requested.saturating_mul(2).min(state.vectors.size())
         };
-        collect_vector_search_indices(&state.hnsw, &key_to_id_index, query, limit, search_limit)
+        collect_vector_search_indices(&state.vectors, &key_to_id_index, query, limit, search_limit)
             .map(|matches| {
                 matches
                     .into_iter()
@@ -784,6 +1285,102 @@ impl Index {
             })
     }
 
+    /// Collect the chunk IDs of every file accepted by `keep`.
+    ///
+    /// The search planner uses this to resolve path and metadata filters once
+    /// per file before vector retrieval, instead of once per candidate chunk.
+    pub fn chunk_ids_where(&self, mut keep: impl FnMut(&StoredFile) -> bool) -> HashSet<String> {
+        let state = self.state.read();
+        state
+            .metadata
+            .files
+            .values()
+            .filter(|file| keep(file))
+            .flat_map(|file| file.chunk_ids.iter().cloned())
+            .collect()
+    }
+
+    /// Collect the vector keys of the chunks of every file accepted by
+    /// `keep`, leaving out `exclude`.
+    ///
+    /// The search planner resolves path and metadata filters to keys once per
+    /// query, so filtered retrieval tests plain integers instead of cloned
+    /// chunk IDs.
+    pub fn chunk_keys_where(
+        &self,
+        mut keep: impl FnMut(&StoredFile) -> bool,
+        exclude: &HashSet<String>,
+    ) -> HashSet<u64> {
+        let state = self.state.read();
+        state
+            .metadata
+            .files
+            .values()
+            .filter(|file| keep(file))
+            .flat_map(|file| &file.chunk_ids)
+            .filter(|id| !exclude.contains(*id))
+            .filter_map(|id| state.id_to_key.get(id).copied())
+            .collect()
+    }
+
+    /// Search for nearest vectors among the `allowed` keys only (see
+    /// [`chunk_keys_where`](Self::chunk_keys_where)).
+    ///
+    /// The predicate is evaluated by usearch during graph traversal, so a
+    /// selective filter still yields up to `limit` matching candidates in a
+    /// single pass. Scores and ordering match [`search_vectors`](Self::search_vectors).
+    pub fn search_vectors_filtered(
+        &self,
+        query: &[f32],
+        limit: usize,
+        allowed: &HashSet<u64>,
+    ) -> Result<Vec<(String, f64)>> {
+        let state = self.state.read();
+
+        if limit == 0 || allowed.is_empty() || state.vectors.size() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let in_scope = |key: u64| allowed.contains(&key);
+        let results = state
+            .vectors
+            .filtered_search(query, limit.min(allowed.len()), in_scope)?;
+
+        let mut output: Vec<(u64, f64)> = results
+            .into_iter()
+            .filter(|(key, _)| allowed.contains(key))
+            .map(|(key, distance)| (key, 1.0 - distance as f64))
+            .collect();
+        output.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        output.truncate(limit);
+        Ok(resolve_chunk_keys(&state, output))
+    }
+
+    /// Exact cosine scan over the stored vectors of the `allowed` keys.
+    ///
+    /// Cheaper and exhaustive when the filtered set is small; returns the same
+    /// `(chunk_id, cosine_similarity)` shape as [`search_vectors`](Self::search_vectors).
+    pub fn search_vectors_exact(
+        &self,
+        query: &[f32],
+        limit: usize,
+        allowed: &HashSet<u64>,
+    ) -> Result<Vec<(String, f64)>> {
+        let state = self.state.read();
+
+        let mut output = Vec::with_capacity(allowed.len());
+        for &key in allowed {
+            let Some(buf) = state.vectors.vector(key) else {
+                continue;
+            };
+            let score = crate::clustering::cosine_similarity(query, &buf) as f64;
+            output.push((key, score));
+        }
+        output.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        output.truncate(limit);
+        Ok(resolve_chunk_keys(&state, output))
+    }
+
     /// Search many chunk vectors while holding one index snapshot.
     ///
     /// Chunk graph construction issues one nearest-neighbor query per node.
@@ -797,7 +1394,7 @@ impl Index {
     ) -> Result<VectorSearchBatch> {
         let state = self.state.read();
 
-        if state.hnsw.size() == 0 {
+        if state.vectors.size() == 0 {
             return Ok(VectorSearchBatch {
                 ids: Vec::new(),
                 matches: vec![Vec::new(); queries.len()],
@@ -813,7 +1410,7 @@ impl Index {
             // every node.
             .map(|query| {
                 collect_vector_search_indices(
-                    &state.hnsw,
+                    &state.vectors,
                     &key_to_id_index,
                     query,
                     limit,
@@ -858,29 +1455,26 @@ impl Index {
     pub fn search(&self, query: &[f32], limit: usize) -> Result<Vec<(String, f32)>> {
         let state = self.state.read();
 
-        if state.hnsw.size() == 0 {
+        if state.vectors.size() == 0 {
             return Ok(Vec::new());
         }
 
         // Over-fetch by 2x to compensate for edge vectors that will be filtered out.
         let over_fetch = limit * 2;
-        let results = state
-            .hnsw
-            .search(query, over_fetch)
-            .map_err(|e| Error::Serialization(format!("usearch search: {e}")))?;
+        let results = state.vectors.search(query, over_fetch)?;
 
         // Build reverse lookup: key → chunk_id.
         let key_to_id: HashMap<u64, &String> =
             state.id_to_key.iter().map(|(id, key)| (*key, id)).collect();
 
-        let mut output = Vec::with_capacity(results.keys.len());
-        for (key, distance) in results.keys.iter().zip(results.distances.iter()) {
-            if let Some(chunk_id) = key_to_id.get(key) {
+        let mut output = Vec::with_capacity(results.len());
+        for (key, distance) in results {
+            if let Some(chunk_id) = key_to_id.get(&key) {
                 // Post-filter out edge vectors.
                 if chunk_id.starts_with("edge:") {
                     continue;
                 }
-                output.push(((*chunk_id).clone(), *distance));
+                output.push(((*chunk_id).clone(), distance));
             }
         }
 
@@ -910,46 +1504,37 @@ impl Index {
         let mut result: HashMap<String, Vec<f32>> = HashMap::new();
 
         for (path, file) in &state.metadata.files {
-            let mut sum = vec![0.0f32; dims];
-            let mut count = 0usize;
-
-            for chunk_id in &file.chunk_ids {
-                if let Some(&key) = state.id_to_key.get(chunk_id) {
-                    let mut buf = vec![0.0f32; dims];
-                    if state.hnsw.get(key, &mut buf).is_ok() {
-                        for (s, v) in sum.iter_mut().zip(buf.iter()) {
-                            *s += v;
-                        }
-                        count += 1;
-                    }
-                }
-            }
-
-            if count > 0 {
-                let scale = 1.0 / count as f32;
-                for s in &mut sum {
-                    *s *= scale;
-                }
-                result.insert(path.clone(), sum);
+            if let Some(mean) = mean_chunk_vector(&state, &file.chunk_ids, dims) {
+                result.insert(path.clone(), mean);
             }
         }
 
         result
     }
 
+    /// Compute the document-level vector for a single file (the mean of its
+    /// chunk vectors).
+    ///
+    /// Returns `None` if the file is not indexed or none of its chunks has a
+    /// stored vector.
+    pub fn get_file_vector(&self, path: &str) -> Option<Vec<f32>> {
+        let state = self.state.read();
+        let dims = state.metadata.embedding_config.dimensions;
+        let file = state.metadata.files.get(path)?;
+        mean_chunk_vector(&state, &file.chunk_ids, dims)
+    }
+
     /// Get chunk-level vectors with metadata for graph visualization.
     ///
     /// Returns a vector of `ChunkVectorInfo` for every chunk that has a valid
     /// embedding in the HNSW index.
     pub fn get_chunk_vectors(&self) -> Vec<ChunkVectorInfo> {
         let state = self.state.read();
-        let dims = state.metadata.embedding_config.dimensions;
         let mut result = Vec::new();
 
         for (chunk_id, chunk) in &state.metadata.chunks {
             if let Some(&key) = state.id_to_key.get(chunk_id) {
-                let mut buf = vec![0.0f32; dims];
-                if state.hnsw.get(key, &mut buf).is_ok() {
+                if let Some(buf) = state.vectors.vector(key) {
                     result.push(ChunkVectorInfo {
                         chunk_id: chunk_id.clone(),
                         source_path: chunk.source_path.clone(),
@@ -965,6 +1550,25 @@ impl Index {
         result
     }
 
+    /// Get the stored vectors for the given chunk IDs.
+    ///
+    /// IDs without a vector in the HNSW index are omitted from the map. Used
+    /// by search-time re-ranking so no embedding call is needed.
+    pub fn get_vectors_for(&self, chunk_ids: &[&str]) -> HashMap<String, Vec<f32>> {
+        let state = self.state.read();
+        let mut result = HashMap::with_capacity(chunk_ids.len());
+
+        for chunk_id in chunk_ids {
+            if let Some(&key) = state.id_to_key.get(*chunk_id) {
+                if let Some(buf) = state.vectors.vector(key) {
+                    result.insert((*chunk_id).to_string(), buf);
+                }
+            }
+        }
+
+        result
+    }
+
     /// Get concatenated chunk content for each document (for keyword extraction).
     ///
     /// Returns a map from relative file path to the combined text of all its chunks.
@@ -990,11 +1594,46 @@ impl Index {
         result
     }
 
+    /// SHA-256 of every embedding input the index still references: chunk
+    /// texts and semantic-edge contexts. Used to prune the embedding cache.
+    pub(crate) fn embedding_input_hashes(&self) -> HashSet<TextHash> {
+        let state = self.state.read();
+        let chunks = state
+            .metadata
+            .chunks
+            .values()
+            .map(|chunk| chunk.content.as_str());
+        let edges = state
+            .metadata
+            .link_graph
+            .iter()
+            .flat_map(|graph| graph.semantic_edges.iter().flatten())
+            .map(|(_, edge)| edge.context_text.as_str());
+        chunks.chain(edges).map(text_hash).collect()
+    }
+
+    /// Number of embedding inputs (chunks and semantic edges) the index
+    /// references; a cheap size estimate for
+    /// [`embedding_input_hashes`](Self::embedding_input_hashes).
+    pub(crate) fn embedding_input_count(&self) -> usize {
+        let state = self.state.read();
+        let edges = state
+            .metadata
+            .link_graph
+            .iter()
+            .flat_map(|graph| graph.semantic_edges.iter())
+            .map(|edges| edges.len())
+            .sum::<usize>();
+        state.metadata.chunks.len() + edges
+    }
+
     /// Update (or clear) the cluster state.
     pub fn update_clusters(&self, cluster_state: Option<ClusterState>) {
         let mut state = self.state.write();
         state.metadata.cluster_state = cluster_state;
+        state.pending.clusters = true;
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Get the current custom cluster state, if any.
@@ -1007,7 +1646,9 @@ impl Index {
     pub fn update_custom_clusters(&self, custom_cluster_state: Option<CustomClusterState>) {
         let mut state = self.state.write();
         state.metadata.custom_cluster_state = custom_cluster_state;
+        state.pending.custom_clusters = true;
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Get the current link graph, if any.
@@ -1032,7 +1673,9 @@ impl Index {
     pub fn update_link_graph(&self, link_graph: Option<LinkGraph>) {
         let mut state = self.state.write();
         state.metadata.link_graph = link_graph;
+        state.pending.link_graph = true;
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Retrieve all edge vectors from the HNSW index.
@@ -1041,13 +1684,11 @@ impl Index {
     /// their vectors from the HNSW index.
     pub fn get_edge_vectors(&self) -> HashMap<String, Vec<f32>> {
         let state = self.state.read();
-        let dims = state.metadata.embedding_config.dimensions;
         let mut result = HashMap::new();
 
         for (id, &key) in &state.id_to_key {
             if id.starts_with("edge:") {
-                let mut buf = vec![0.0f32; dims];
-                if state.hnsw.get(key, &mut buf).is_ok() {
+                if let Some(buf) = state.vectors.vector(key) {
                     result.insert(id.clone(), buf);
                 }
             }
@@ -1063,7 +1704,7 @@ impl Index {
     pub fn search_edges(&self, query: &[f32], limit: usize) -> Result<Vec<(String, f64)>> {
         let state = self.state.read();
 
-        if state.hnsw.size() == 0 || limit == 0 {
+        if state.vectors.size() == 0 || limit == 0 {
             return Ok(Vec::new());
         }
 
@@ -1077,24 +1718,21 @@ impl Index {
         }
         let requested = limit.min(edge_count);
         let search_limit = if requested == edge_count {
-            state.hnsw.size()
+            state.vectors.size()
         } else {
-            requested.saturating_mul(2).min(state.hnsw.size())
+            requested.saturating_mul(2).min(state.vectors.size())
         };
-        let results = state
-            .hnsw
-            .search(query, search_limit)
-            .map_err(|e| Error::Serialization(format!("usearch search: {e}")))?;
+        let results = state.vectors.search(query, search_limit)?;
 
         // Build reverse lookup: key → id.
         let key_to_id: HashMap<u64, &String> =
             state.id_to_key.iter().map(|(id, key)| (*key, id)).collect();
 
         let mut output = Vec::new();
-        for (key, distance) in results.keys.iter().zip(results.distances.iter()) {
-            if let Some(id) = key_to_id.get(key) {
+        for (key, distance) in results {
+            if let Some(id) = key_to_id.get(&key) {
                 if id.starts_with("edge:") {
-                    let score = 1.0 - *distance as f64;
+                    let score = 1.0 - distance as f64;
                     output.push(((*id).clone(), score));
                 }
             }
@@ -1107,53 +1745,369 @@ impl Index {
         Ok(output)
     }
 
-    /// Get all indexed file paths as a HashSet.
-    pub fn get_indexed_file_paths(&self) -> std::collections::HashSet<String> {
-        let state = self.state.read();
-        state.metadata.files.keys().cloned().collect()
+    /// Get all indexed file paths as a HashSet.
+    pub fn get_indexed_file_paths(&self) -> std::collections::HashSet<String> {
+        let state = self.state.read();
+        state.metadata.files.keys().cloned().collect()
+    }
+
+    /// Set (or clear) the metadata schema.
+    pub fn set_schema(&self, schema: Option<Schema>) {
+        let mut state = self.state.write();
+        state.metadata.schema = schema;
+        state.pending.schema = true;
+        state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get all scoped schemas, if any.
+    pub fn get_scoped_schemas(&self) -> Option<Vec<crate::schema::ScopedSchema>> {
+        let state = self.state.read();
+        state.metadata.scoped_schemas.clone()
+    }
+
+    /// Get the scoped schema for a specific path prefix, if any.
+    pub fn get_scoped_schema(&self, prefix: &str) -> Option<crate::schema::ScopedSchema> {
+        let state = self.state.read();
+        state
+            .metadata
+            .scoped_schemas
+            .as_ref()
+            .and_then(|schemas| schemas.iter().find(|s| s.scope == prefix).cloned())
+    }
+
+    /// Set (or clear) the scoped schemas.
+    pub fn set_scoped_schemas(&self, scoped_schemas: Option<Vec<crate::schema::ScopedSchema>>) {
+        let mut state = self.state.write();
+        state.metadata.scoped_schemas = scoped_schemas;
+        state.pending.scoped_schemas = true;
+        state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Persist the index to disk.
+    ///
+    /// The changes since the last save are appended to the write-ahead log
+    /// (see [`wal`]) and fsynced. The base file is rewritten atomically
+    /// instead when the log is disabled or would pass its size or age limit
+    /// ([`WriteOptions`]), after an embedding-space reset, or when another
+    /// writer rewrote the base file or appended to the log since this handle
+    /// loaded it, so the last writer still wins.
+    ///
+    /// A rewrite compacts HNSW keys to sequential 0..N matching sorted chunk
+    /// ID order, ensuring that after any number of save/load cycles, keys
+    /// always match, and deletes the log.
+    ///
+    /// Takes an exclusive advisory cross-process lock on a sibling
+    /// `<index>.lock` file for the duration of the write critical section;
+    /// a concurrent writer (e.g. `mdvdb watch`) yields [`Error::IndexBusy`].
+    pub fn save(&self) -> Result<()> {
+        let mut state = self.state.write();
+        self.persist(&mut state, false)
+    }
+
+    /// Rewrite the base file with every change, deleting the write-ahead log.
+    /// Returns the size in bytes of the log that was folded in.
+    ///
+    /// A clean handle first reloads whatever other writers saved since it
+    /// was opened, so compacting never discards their changes.
+    pub fn compact(&self) -> Result<u64> {
+        let mut state = self.state.write();
+        if !state.dirty {
+            let _write_lock = acquire_write_lock(&self.path)?;
+            if self.changed_on_disk(&state) {
+                *state = Self::load_state(&self.path, &self.write_options)?;
+                self.revision.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let wal_len = state.wal_len;
+        self.persist(&mut state, true)?;
+        Ok(wal_len)
+    }
+
+    /// The whole index as write-ahead log ops, handed to `visit` one at a
+    /// time: every file with its chunks and chunk vectors, each edge vector,
+    /// and the derived state. `start` first turns their counts into the
+    /// visitor state, which is returned at the end.
+    /// [`replace_with`](Self::replace_with) rebuilds the index from the ops
+    /// as one record. The read lock is held throughout, so the ops form one
+    /// snapshot while only one file's vectors are in memory at a time.
+    pub(crate) fn visit_full_record<V>(
+        &self,
+        start: impl FnOnce(&RecordCounts) -> Result<V>,
+        mut visit: impl FnMut(&mut V, WalOp) -> Result<()>,
+    ) -> Result<V> {
+        let state = self.state.read();
+        let metadata = &state.metadata;
+
+        let mut paths: Vec<&String> = metadata.files.keys().collect();
+        paths.sort();
+        // Vectors without a chunk are edge vectors, as in `persist`.
+        let mut edge_ids: Vec<&String> = state
+            .id_to_key
+            .keys()
+            .filter(|id| !metadata.chunks.contains_key(*id))
+            .collect();
+        edge_ids.sort();
+
+        let mut counts = RecordCounts {
+            files: paths.len(),
+            edges: edge_ids.len(),
+            last_updated: metadata.last_updated,
+            ..Default::default()
+        };
+        for path in &paths {
+            for id in &metadata.files[*path].chunk_ids {
+                if metadata.chunks.contains_key(id) {
+                    counts.chunks += 1;
+                    counts.chunk_vectors += usize::from(state.id_to_key.contains_key(id));
+                }
+            }
+        }
+        let mut visitor = start(&counts)?;
+
+        for path in paths {
+            let file = &metadata.files[path];
+            let chunks = file
+                .chunk_ids
+                .iter()
+                .filter_map(|id| {
+                    let chunk = metadata.chunks.get(id)?;
+                    Some(WalChunk {
+                        id: id.clone(),
+                        chunk: chunk.clone(),
+                        vector: state.vector(id),
+                    })
+                })
+                .collect();
+            visit(
+                &mut visitor,
+                WalOp::UpsertFile {
+                    path: path.clone(),
+                    file: file.clone(),
+                    mtime: metadata
+                        .file_mtimes
+                        .as_ref()
+                        .and_then(|mtimes| mtimes.get(path))
+                        .copied(),
+                    chunks,
+                },
+            )?;
+        }
+
+        for id in edge_ids {
+            if let Some(vector) = state.vector(id) {
+                visit(
+                    &mut visitor,
+                    WalOp::Edges {
+                        upserted: vec![WalVector {
+                            id: id.clone(),
+                            vector,
+                        }],
+                        removed: Vec::new(),
+                    },
+                )?;
+            }
+        }
+
+        if metadata.schema.is_some() {
+            visit(&mut visitor, WalOp::Schema(metadata.schema.clone()))?;
+        }
+        if metadata.scoped_schemas.is_some() {
+            visit(
+                &mut visitor,
+                WalOp::ScopedSchemas(metadata.scoped_schemas.clone()),
+            )?;
+        }
+        if metadata.cluster_state.is_some() {
+            visit(
+                &mut visitor,
+                WalOp::Clusters(metadata.cluster_state.clone()),
+            )?;
+        }
+        if metadata.custom_cluster_state.is_some() {
+            visit(
+                &mut visitor,
+                WalOp::CustomClusters(metadata.custom_cluster_state.clone()),
+            )?;
+        }
+        if metadata.link_graph.is_some() {
+            visit(&mut visitor, WalOp::LinkGraph(metadata.link_graph.clone()))?;
+        }
+        Ok(visitor)
+    }
+
+    /// Replace the whole index with `record` applied to an empty generation
+    /// in the embedding space `config`. Like
+    /// [`reset_embedding_space`](Self::reset_embedding_space), nothing is
+    /// persisted until the next save, which rewrites the base file.
+    pub(crate) fn replace_with(&self, config: &EmbeddingConfig, record: WalRecord) -> Result<()> {
+        self.reset_embedding_space(config)?;
+        let mut state = self.state.write();
+        state.apply_wal_record(record)?;
+        self.revision.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Cross-check chunks, files, vectors and link graph sources. Link
+    /// targets are not checked: links to missing notes are broken links,
+    /// not corruption.
+    pub(crate) fn check_integrity(&self) -> IntegrityReport {
+        let state = self.state.read();
+        let metadata = &state.metadata;
+        let mut report = IntegrityReport::default();
+
+        let has_vector = |id: &str| {
+            state
+                .id_to_key
+                .get(id)
+                .is_some_and(|key| state.vectors.contains(*key))
+        };
+        let tracked_edges: HashSet<&str> = metadata
+            .link_graph
+            .as_ref()
+            .and_then(|graph| graph.semantic_edges.as_ref())
+            .map(|edges| edges.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+
+        for id in metadata.chunks.keys() {
+            if !has_vector(id) {
+                report.chunks_without_vectors.push(id.clone());
+            }
+        }
+        let mut mapped_vectors = 0;
+        for (id, key) in &state.id_to_key {
+            if !state.vectors.contains(*key) {
+                continue;
+            }
+            mapped_vectors += 1;
+            if !metadata.chunks.contains_key(id) && !tracked_edges.contains(id.as_str()) {
+                report.vectors_without_chunks.push(id.clone());
+            }
+        }
+        report.unmapped_vectors = state.vectors.size().saturating_sub(mapped_vectors);
+
+        let mut owned: HashSet<&str> = HashSet::new();
+        for (path, file) in &metadata.files {
+            for id in &file.chunk_ids {
+                match metadata.chunks.get(id) {
+                    Some(chunk) if chunk.source_path == *path => {
+                        owned.insert(id);
+                    }
+                    _ => report.missing_file_chunks.push((path.clone(), id.clone())),
+                }
+            }
+        }
+        report.unowned_chunks = metadata
+            .chunks
+            .keys()
+            .filter(|id| !owned.contains(id.as_str()))
+            .cloned()
+            .collect();
+
+        let mut reembed: HashSet<&String> = report
+            .missing_file_chunks
+            .iter()
+            .map(|(path, _)| path)
+            .collect();
+        for id in &report.chunks_without_vectors {
+            if owned.contains(id.as_str()) {
+                reembed.insert(&metadata.chunks[id].source_path);
+            }
+        }
+        report.files_to_reembed = reembed.into_iter().cloned().collect();
+
+        if let Some(graph) = &metadata.link_graph {
+            let edge_sources = graph
+                .semantic_edges
+                .iter()
+                .flat_map(|edges| edges.values().map(|edge| &edge.source));
+            let sources: HashSet<&String> = graph.forward.keys().chain(edge_sources).collect();
+            report.dangling_link_sources = sources
+                .into_iter()
+                .filter(|source| !metadata.files.contains_key(*source))
+                .cloned()
+                .collect();
+            report.dangling_edge_targets = graph
+                .semantic_edges
+                .iter()
+                .flatten()
+                .filter(|(_, edge)| !metadata.files.contains_key(&edge.target))
+                .map(|(id, _)| id.clone())
+                .collect();
+        }
+
+        report.chunks_without_vectors.sort();
+        report.vectors_without_chunks.sort();
+        report.missing_file_chunks.sort();
+        report.unowned_chunks.sort();
+        report.dangling_link_sources.sort();
+        report.dangling_edge_targets.sort();
+        report.files_to_reembed.sort();
+        report
     }
 
-    /// Set (or clear) the metadata schema.
-    pub fn set_schema(&self, schema: Option<Schema>) {
+    /// Fix what [`check_integrity`](Self::check_integrity) found: drop
+    /// dangling chunks, vectors and link graph entries, and clear the
+    /// embedding body hash of files with missing vectors so the next ingest
+    /// re-embeds them.
+    ///
+    /// Nothing is persisted until the next save, which rewrites the base
+    /// file and with it drops the unmapped vectors.
+    pub(crate) fn repair_integrity(&self, report: &IntegrityReport) {
         let mut state = self.state.write();
-        state.metadata.schema = schema;
-        state.dirty = true;
-    }
 
-    /// Get all scoped schemas, if any.
-    pub fn get_scoped_schemas(&self) -> Option<Vec<crate::schema::ScopedSchema>> {
-        let state = self.state.read();
-        state.metadata.scoped_schemas.clone()
-    }
+        for id in &report.vectors_without_chunks {
+            state.remove_vector(id);
+        }
+        for id in &report.unowned_chunks {
+            state.remove_vector(id);
+            state.metadata.chunks.remove(id);
+        }
+        for (path, id) in &report.missing_file_chunks {
+            if let Some(file) = state.metadata.files.get_mut(path) {
+                file.chunk_ids.retain(|chunk_id| chunk_id != id);
+            }
+        }
 
-    /// Get the scoped schema for a specific path prefix, if any.
-    pub fn get_scoped_schema(&self, prefix: &str) -> Option<crate::schema::ScopedSchema> {
-        let state = self.state.read();
-        state
-            .metadata
-            .scoped_schemas
-            .as_ref()
-            .and_then(|schemas| schemas.iter().find(|s| s.scope == prefix).cloned())
-    }
+        if !report.dangling_link_sources.is_empty() || !report.dangling_edge_targets.is_empty() {
+            let dangling: HashSet<&String> = report.dangling_link_sources.iter().collect();
+            let dangling_edges: HashSet<&String> = report.dangling_edge_targets.iter().collect();
+            let mut removed_edges = Vec::new();
+            if let Some(graph) = state.metadata.link_graph.as_mut() {
+                graph.forward.retain(|source, _| !dangling.contains(source));
+                if let Some(edges) = graph.semantic_edges.as_mut() {
+                    edges.retain(|id, edge| {
+                        let keep = !dangling.contains(&edge.source) && !dangling_edges.contains(id);
+                        if !keep {
+                            removed_edges.push(id.clone());
+                        }
+                        keep
+                    });
+                }
+            }
+            for id in &removed_edges {
+                state.remove_vector(id);
+            }
+        }
 
-    /// Set (or clear) the scoped schemas.
-    pub fn set_scoped_schemas(&self, scoped_schemas: Option<Vec<crate::schema::ScopedSchema>>) {
-        let mut state = self.state.write();
-        state.metadata.scoped_schemas = scoped_schemas;
+        for path in &report.files_to_reembed {
+            if let Some(file) = state.metadata.files.get_mut(path) {
+                file.embedding_body_hash.clear();
+            }
+        }
+
+        state.pending.rewrite = true;
         state.dirty = true;
+        self.revision.fetch_add(1, Ordering::Relaxed);
     }
 
-    /// Persist the index to disk atomically.
-    ///
-    /// Compacts HNSW keys to sequential 0..N matching sorted chunk ID order,
-    /// ensuring that after any number of save/load cycles, keys always match.
-    ///
-    /// Takes an exclusive advisory cross-process lock on a sibling
-    /// `<index>.lock` file for the duration of the write critical section;
-    /// a concurrent writer (e.g. `mdvdb watch`) yields [`Error::IndexBusy`].
-    pub fn save(&self) -> Result<()> {
-        let mut state = self.state.write();
+    /// Size in bytes of the write-ahead log this handle extends.
+    pub fn wal_len(&self) -> u64 {
+        self.state.read().wal_len
+    }
 
+    fn persist(&self, state: &mut IndexState, rewrite: bool) -> Result<()> {
         state.metadata.last_updated = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .map(|d| d.as_secs())
@@ -1191,13 +2145,21 @@ impl Index {
 
             for id in &orphaned {
                 if let Some(key) = state.id_to_key.remove(id) {
-                    let _ = state.hnsw.remove(key);
+                    let _ = state.vectors.remove(key);
                 }
             }
 
             if !orphaned.is_empty() {
                 debug!(count = orphaned.len(), "removed orphaned edge vectors");
             }
+            state.pending.edges.extend(orphaned);
+        }
+
+        if !rewrite && self.append_to_wal(state)? {
+            state.pending = PendingChanges::default();
+            state.dirty = false;
+            debug!(path = %self.path.display(), wal_len = state.wal_len, "index changes appended");
+            return Ok(());
         }
 
         let mut sorted_chunk_ids: Vec<&String> = state.metadata.chunks.keys().collect();
@@ -1212,64 +2174,131 @@ impl Index {
             .collect();
         sorted_edge_ids.sort();
 
+        // Keys are reassigned in order; the builder also streams the exact
+        // vectors of an `i8`/`b1` index into a new sidecar.
         let total = sorted_chunk_ids.len() + sorted_edge_ids.len();
-        let scalar_kind = storage::scalar_kind_for(&self.write_options.quantization);
-        let new_hnsw = storage::create_hnsw(dims, scalar_kind)?;
-        if total > 0 {
-            new_hnsw
-                .reserve(total.max(10))
-                .map_err(|e| Error::Serialization(format!("usearch reserve: {e}")))?;
-        }
-
+        let mut builder = StoreBuilder::new(
+            &self.path,
+            dims,
+            total,
+            &self.write_options,
+            state.vectors.full_precision_available(),
+        )?;
         let mut new_id_to_key = HashMap::new();
-        let mut buf = vec![0.0f32; dims];
-        let mut next = 0u64;
 
         for chunk_id in &sorted_chunk_ids {
-            if let Some(&old_key) = state.id_to_key.get(*chunk_id) {
-                if state.hnsw.get(old_key, &mut buf).is_ok() {
-                    new_hnsw
-                        .add(next, &buf)
-                        .map_err(|e| Error::Serialization(format!("usearch add: {e}")))?;
-                }
-            }
-            new_id_to_key.insert((*chunk_id).clone(), next);
-            next += 1;
+            let vector = state
+                .id_to_key
+                .get(*chunk_id)
+                .and_then(|&old_key| state.vectors.vector(old_key));
+            let key = builder.push(vector.as_deref())?;
+            new_id_to_key.insert((*chunk_id).clone(), key);
         }
 
         for edge_id in &sorted_edge_ids {
-            if let Some(&old_key) = state.id_to_key.get(edge_id) {
-                if state.hnsw.get(old_key, &mut buf).is_ok() {
-                    new_hnsw
-                        .add(next, &buf)
-                        .map_err(|e| Error::Serialization(format!("usearch add: {e}")))?;
-                }
-            }
-            new_id_to_key.insert(edge_id.clone(), next);
-            next += 1;
+            let vector = state
+                .id_to_key
+                .get(edge_id)
+                .and_then(|&old_key| state.vectors.vector(old_key));
+            let key = builder.push(vector.as_deref())?;
+            new_id_to_key.insert(edge_id.clone(), key);
         }
 
-        state.hnsw = new_hnsw;
+        let generation = storage::new_generation();
+        let (vectors, sidecar) = builder.finish(generation)?;
+        state.vectors = vectors;
+        state.next_key = new_id_to_key.len() as u64;
         state.id_to_key = new_id_to_key;
-        state.next_key = next;
 
         // Advisory cross-process lock held ONLY for the write critical section
         // (dropped at the end of this scope; File drop releases the OS lock).
         {
             let _write_lock = acquire_write_lock(&self.path)?;
-            storage::write_index(
+            sidecar.persist(&self.path)?;
+            storage::write_index_as(
                 &self.path,
                 &state.metadata,
-                &state.hnsw,
+                state.vectors.hnsw(),
                 &self.write_options,
+                generation,
             )?;
+            state.generation = generation;
+            // The new base file already holds the log's changes. Until the
+            // log is gone its generation no longer matches, so a crash here
+            // leaves it ignored.
+            wal::remove(&wal::wal_path(&self.path))?;
+            // The previous sidecar pairs with the base file just replaced;
+            // only now is it safe to drop.
+            vectors::remove_sidecars(&self.path, Some(generation))?;
         }
+        state.wal_len = 0;
+        state.pending = PendingChanges::default();
         state.dirty = false;
 
         debug!(path = %self.path.display(), "index saved");
         Ok(())
     }
 
+    /// Append the pending changes to the write-ahead log. Returns `false`
+    /// when the save has to rewrite the base file instead (see [`save`](Self::save)).
+    fn append_to_wal(&self, state: &mut IndexState) -> Result<bool> {
+        let options = &self.write_options;
+        if options.wal_max_bytes == 0
+            || state.pending.rewrite
+            || state.generation == 0
+            || *state.vectors.quantization() != options.quantization
+        {
+            return Ok(false);
+        }
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if state.wal_len > 0
+            && options.wal_max_age_secs > 0
+            && now.saturating_sub(state.wal_created_at) >= options.wal_max_age_secs
+        {
+            return Ok(false);
+        }
+
+        let frame = wal::encode_frame(&state.wal_record())?;
+        let header_len = if state.wal_len == 0 {
+            wal::WAL_HEADER_SIZE as u64
+        } else {
+            0
+        };
+        if state.wal_len + header_len + frame.len() as u64 > options.wal_max_bytes {
+            return Ok(false);
+        }
+
+        let _write_lock = acquire_write_lock(&self.path)?;
+        if self.changed_on_disk(state) {
+            debug!(path = %self.path.display(), "index changed on disk, rewriting");
+            return Ok(false);
+        }
+        let created_at = if state.wal_len == 0 {
+            now
+        } else {
+            state.wal_created_at
+        };
+        state.wal_len = wal::append(
+            &wal::wal_path(&self.path),
+            state.generation,
+            created_at,
+            state.wal_len,
+            &frame,
+        )?;
+        state.wal_created_at = created_at;
+        Ok(true)
+    }
+
+    /// Whether another writer rewrote the base file or appended to the log
+    /// since `state` was loaded or last saved.
+    fn changed_on_disk(&self, state: &IndexState) -> bool {
+        storage::read_generation(&self.path).ok() != Some(state.generation)
+            || wal::file_len(&wal::wal_path(&self.path)) != state.wal_len
+    }
+
     /// Check that the index's embedding configuration is compatible with the given config.
     ///
     /// Returns `Error::IndexCorrupted` if dimensions or model don't match.
@@ -1303,7 +2332,7 @@ impl Index {
 /// [`Error::IndexBusy`]. The lock is released when the returned `File` is
 /// dropped (the OS releases advisory locks on close), so callers hold it
 /// only for the write critical section by scoping the returned handle.
-fn acquire_write_lock(index_path: &Path) -> Result<std::fs::File> {
+pub(crate) fn acquire_write_lock(index_path: &Path) -> Result<std::fs::File> {
     const ATTEMPTS: usize = 10;
     const RETRY_DELAY_MS: u64 = 200;
 
@@ -1435,7 +2464,7 @@ mod tests {
 
         let state = index.state.read();
         // Edge vectors should be in HNSW and id_to_key but NOT in metadata.chunks.
-        assert_eq!(state.hnsw.size(), 2);
+        assert_eq!(state.vectors.size(), 2);
         assert!(state.id_to_key.contains_key("edge:a.md->b.md@0"));
         assert!(state.id_to_key.contains_key("edge:a.md->c.md@5"));
         assert!(state.metadata.chunks.is_empty());
@@ -1458,7 +2487,7 @@ mod tests {
 
         let state = index.state.read();
         // Should still have only 1 vector (old removed, new added).
-        assert_eq!(state.hnsw.size(), 1);
+        assert_eq!(state.vectors.size(), 1);
         assert!(state.id_to_key.contains_key("edge:a.md->b.md@0"));
     }
 
@@ -1862,7 +2891,7 @@ mod tests {
         index.upsert_edges(&edges).unwrap();
 
         let state = index.state.read();
-        assert_eq!(state.hnsw.size(), 2);
+        assert_eq!(state.vectors.size(), 2);
         assert_eq!(state.metadata.chunks.len(), 1);
         assert!(state.id_to_key.contains_key("test.md#0"));
         assert!(state.id_to_key.contains_key("edge:test.md->other.md@0"));
@@ -2053,6 +3082,58 @@ mod tests {
         assert_eq!(results[0].0, "test.md#0");
     }
 
+    #[test]
+    fn filtered_and_exact_search_only_return_allowed_chunks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.idx");
+        let index = Index::create(&path, &test_config()).unwrap();
+
+        for (i, name) in ["a.md", "b.md", "c.md"].into_iter().enumerate() {
+            let mut vector = vec![0.0f32; 128];
+            vector[0] = 1.0;
+            vector[1] = i as f32;
+            index
+                .upsert(&mk_file(name), &[mk_chunk(name, 0)], &[vector])
+                .unwrap();
+        }
+        let edges = vec![("edge:a.md->b.md@0".to_string(), vec![1.0f32; 128])];
+        index.upsert_edges(&edges).unwrap();
+
+        let allowed = index.chunk_keys_where(|file| file.relative_path != "a.md", &HashSet::new());
+        assert_eq!(allowed.len(), 2);
+        let excluded: HashSet<String> = ["c.md#0".to_string()].into();
+        assert_eq!(
+            index
+                .chunk_keys_where(|file| file.relative_path != "a.md", &excluded)
+                .len(),
+            1
+        );
+
+        let mut query = vec![0.0f32; 128];
+        query[0] = 1.0;
+        let exact = index.search_vectors_exact(&query, 10, &allowed).unwrap();
+        let filtered = index.search_vectors_filtered(&query, 10, &allowed).unwrap();
+        for results in [&exact, &filtered] {
+            let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+            assert_eq!(ids, vec!["b.md#0", "c.md#0"]);
+        }
+        for ((_, exact_score), (_, filtered_score)) in exact.iter().zip(&filtered) {
+            assert!((exact_score - filtered_score).abs() < 1e-3);
+        }
+
+        assert_eq!(
+            index
+                .search_vectors_exact(&query, 1, &allowed)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(index
+            .search_vectors_filtered(&query, 10, &HashSet::new())
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn batch_vector_search_matches_individual_queries() {
         let dir = TempDir::new().unwrap();
@@ -2214,7 +3295,7 @@ mod tests {
         assert!(!state.id_to_key.contains_key("edge:source.md->other.md@3"));
         // Edge from other file should remain.
         assert!(state.id_to_key.contains_key("edge:other.md->source.md@0"));
-        assert_eq!(state.hnsw.size(), 1);
+        assert_eq!(state.vectors.size(), 1);
     }
 
     #[test]
@@ -2456,4 +3537,387 @@ mod tests {
             .upsert(&file2, &[chunk2], &[vec![0.8f32; 128]])
             .expect("upsert should not fail with duplicate key error");
     }
+
+    fn upsert_one(index: &Index, path: &str, value: f32) {
+        index
+            .upsert(&mk_file(path), &[mk_chunk(path, 0)], &[vec![value; 128]])
+            .unwrap();
+    }
+
+    #[test]
+    fn save_appends_to_wal_and_open_replays_it() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let wal_path = dir.path().join("index.wal");
+        let index = Index::create(&path, &test_config()).unwrap();
+        let generation = storage::read_generation(&path).unwrap();
+        let base = std::fs::read(&path).unwrap();
+
+        upsert_one(&index, "a.md", 1.0);
+        upsert_one(&index, "b.md", 0.5);
+        index
+            .upsert_edges(&[("edge:a.md->b.md@0".to_string(), vec![0.25; 128])])
+            .unwrap();
+        // Saves drop edge vectors the link graph does not track.
+        {
+            use crate::links::{LinkGraph, SemanticEdge};
+            let edge = SemanticEdge {
+                edge_id: "edge:a.md->b.md@0".to_string(),
+                source: "a.md".to_string(),
+                target: "b.md".to_string(),
+                context_text: "link context".to_string(),
+                line_number: 1,
+                strength: None,
+                relationship_type: None,
+                cluster_id: None,
+            };
+            index.update_link_graph(Some(LinkGraph {
+                forward: HashMap::new(),
+                last_updated: 0,
+                semantic_edges: Some(HashMap::from([(edge.edge_id.clone(), edge)])),
+                edge_cluster_state: None,
+            }));
+        }
+        index.save().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), base, "base file untouched");
+        assert_eq!(index.wal_len(), wal::file_len(&wal_path));
+
+        let reopened = Index::open(&path).unwrap();
+        assert!(reopened.get_file("a.md").is_some());
+        assert_eq!(reopened.get_chunk("b.md#0").unwrap().content, "hello");
+        assert_eq!(reopened.get_file_vector("a.md").unwrap(), vec![1.0; 128]);
+        assert_eq!(reopened.get_edge_vectors().len(), 1);
+
+        // A second handle keeps appending after the replayed log.
+        reopened.remove_file("a.md").unwrap();
+        upsert_one(&reopened, "c.md", 0.75);
+        reopened.save().unwrap();
+        assert!(reopened.wal_len() > index.wal_len());
+        assert_eq!(storage::read_generation(&path).unwrap(), generation);
+
+        let state = Index::open(&path).unwrap();
+        let mut paths: Vec<String> = state.get_indexed_file_paths().into_iter().collect();
+        paths.sort();
+        assert_eq!(paths, vec!["b.md", "c.md"]);
+        assert!(state.get_chunk("a.md#0").is_none());
+        assert!(state.get_edge_vectors().is_empty());
+        assert_eq!(state.get_file_mtimes().len(), 2);
+        upsert_one(&state, "d.md", 0.1);
+    }
+
+    #[test]
+    fn compact_folds_the_wal_into_a_new_base_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let wal_path = dir.path().join("index.wal");
+        let index = Index::create(&path, &test_config()).unwrap();
+        let generation = storage::read_generation(&path).unwrap();
+
+        upsert_one(&index, "a.md", 1.0);
+        index.save().unwrap();
+        assert!(wal_path.exists());
+
+        let folded = index.compact().unwrap();
+        assert!(folded > 0);
+        assert!(!wal_path.exists());
+        assert_eq!(index.wal_len(), 0);
+        assert_ne!(storage::read_generation(&path).unwrap(), generation);
+        assert!(Index::open(&path).unwrap().get_file("a.md").is_some());
+
+        // Later saves start a new log on top of the new base file.
+        upsert_one(&index, "b.md", 0.5);
+        index.save().unwrap();
+        assert!(wal_path.exists());
+        assert!(Index::open(&path).unwrap().get_file("b.md").is_some());
+    }
+
+    #[test]
+    fn wal_size_limit_and_disabled_wal_rewrite_the_base_file() {
+        let dir = TempDir::new().unwrap();
+        for wal_max_bytes in [0, 64] {
+            let path = dir.path().join(format!("index-{wal_max_bytes}"));
+            let options = WriteOptions {
+                wal_max_bytes,
+                ..WriteOptions::default()
+            };
+            let index = Index::create_with_options(&path, &test_config(), options).unwrap();
+            let generation = storage::read_generation(&path).unwrap();
+
+            upsert_one(&index, "a.md", 1.0);
+            index.save().unwrap();
+            assert!(!wal::wal_path(&path).exists());
+            assert_ne!(storage::read_generation(&path).unwrap(), generation);
+            assert!(Index::open(&path).unwrap().get_file("a.md").is_some());
+        }
+    }
+
+    #[test]
+    fn stale_handle_rewrites_instead_of_appending() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let first = Index::create(&path, &test_config()).unwrap();
+        let second = Index::open(&path).unwrap();
+
+        upsert_one(&first, "first.md", 1.0);
+        first.save().unwrap();
+        upsert_one(&second, "second.md", 0.5);
+        second.save().unwrap();
+
+        // The last writer wins, exactly as with whole-file saves.
+        assert!(!wal::wal_path(&path).exists());
+        let reopened = Index::open(&path).unwrap();
+        assert!(reopened.get_file("first.md").is_none());
+        assert!(reopened.get_file("second.md").is_some());
+
+        // The first handle's log no longer applies either.
+        upsert_one(&first, "third.md", 0.25);
+        first.save().unwrap();
+        let reopened = Index::open(&path).unwrap();
+        assert!(reopened.get_file("second.md").is_none());
+        assert!(reopened.get_file("third.md").is_some());
+    }
+
+    #[test]
+    fn torn_wal_tail_is_dropped_and_next_save_rewrites() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let wal_path = dir.path().join("index.wal");
+        let index = Index::create(&path, &test_config()).unwrap();
+
+        upsert_one(&index, "kept.md", 1.0);
+        index.save().unwrap();
+        upsert_one(&index, "torn.md", 0.5);
+        index.save().unwrap();
+
+        // Simulate a crash partway through the second append.
+        let bytes = std::fs::read(&wal_path).unwrap();
+        std::fs::write(&wal_path, &bytes[..bytes.len() - 5]).unwrap();
+
+        let reopened = Index::open(&path).unwrap();
+        assert!(reopened.get_file("kept.md").is_some());
+        assert!(reopened.get_file("torn.md").is_none());
+
+        reopened.save().unwrap();
+        assert!(!wal_path.exists());
+        assert!(Index::open(&path).unwrap().get_file("kept.md").is_some());
+    }
+
+    #[test]
+    fn embedding_space_reset_rewrites_the_base_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let index = Index::create(&path, &test_config()).unwrap();
+        upsert_one(&index, "a.md", 1.0);
+        index.save().unwrap();
+
+        let config = EmbeddingConfig {
+            dimensions: 64,
+            ..test_config()
+        };
+        index.reset_embedding_space(&config).unwrap();
+        index.save().unwrap();
+
+        assert!(!wal::wal_path(&path).exists());
+        let reopened = Index::open(&path).unwrap();
+        assert_eq!(reopened.status().embedding_config.dimensions, 64);
+        assert!(reopened.get_file("a.md").is_none());
+    }
+
+    #[test]
+    fn integrity_check_finds_and_repair_drops_dangling_entries() {
+        use crate::links::{LinkEntry, LinkGraph, SemanticEdge};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let index = Index::create(&path, &test_config()).unwrap();
+        upsert_one(&index, "a.md", 1.0);
+        upsert_one(&index, "b.md", 0.5);
+        assert!(index.check_integrity().is_clean());
+
+        index.update_link_graph(Some(LinkGraph {
+            forward: HashMap::from([(
+                "gone.md".to_string(),
+                vec![LinkEntry {
+                    source: "gone.md".to_string(),
+                    target: "a.md".to_string(),
+                    text: "a".to_string(),
+                    line_number: 1,
+                    is_wikilink: false,
+                    field: None,
+                }],
+            )]),
+            last_updated: 0,
+            semantic_edges: Some(HashMap::from([(
+                "edge:a.md->gone.md@3".to_string(),
+                SemanticEdge {
+                    edge_id: "edge:a.md->gone.md@3".to_string(),
+                    source: "a.md".to_string(),
+                    target: "gone.md".to_string(),
+                    context_text: "see gone".to_string(),
+                    line_number: 3,
+                    strength: None,
+                    relationship_type: None,
+                    cluster_id: None,
+                },
+            )])),
+            edge_cluster_state: None,
+        }));
+
+        {
+            let mut state = index.state.write();
+            state
+                .add_vector("edge:a.md->gone.md@3".to_string(), &[0.5; 128])
+                .unwrap();
+            state.remove_vector("a.md#0");
+            let stray = state.metadata.chunks["b.md#0"].clone();
+            state.metadata.chunks.insert("b.md#7".to_string(), stray);
+            state
+                .add_vector("b.md#7".to_string(), &[0.25; 128])
+                .unwrap();
+            state.add_vector("stray".to_string(), &[0.75; 128]).unwrap();
+            let file = state.metadata.files.get_mut("b.md").unwrap();
+            file.chunk_ids.push("b.md#9".to_string());
+        }
+
+        let report = index.check_integrity();
+        assert_eq!(report.chunks_without_vectors, vec!["a.md#0"]);
+        assert_eq!(report.vectors_without_chunks, vec!["stray"]);
+        assert_eq!(report.unmapped_vectors, 0);
+        assert_eq!(
+            report.missing_file_chunks,
+            vec![("b.md".to_string(), "b.md#9".to_string())]
+        );
+        assert_eq!(report.unowned_chunks, vec!["b.md#7"]);
+        assert_eq!(report.dangling_link_sources, vec!["gone.md"]);
+        assert_eq!(report.dangling_edge_targets, vec!["edge:a.md->gone.md@3"]);
+        assert_eq!(report.files_to_reembed, vec!["a.md", "b.md"]);
+
+        index.repair_integrity(&report);
+        index.save().unwrap();
+        assert!(
+            !wal::wal_path(&path).exists(),
+            "repair rewrites the base file"
+        );
+
+        let reopened = Index::open(&path).unwrap();
+        let after = reopened.check_integrity();
+        assert_eq!(after.chunks_without_vectors, vec!["a.md#0"]);
+        assert!(after.vectors_without_chunks.is_empty());
+        assert!(after.missing_file_chunks.is_empty());
+        assert!(after.unowned_chunks.is_empty());
+        assert!(after.dangling_link_sources.is_empty());
+        assert!(after.dangling_edge_targets.is_empty());
+        assert_eq!(reopened.status().vector_count, 1);
+        for path in ["a.md", "b.md"] {
+            assert_eq!(reopened.get_file(path).unwrap().embedding_body_hash, "");
+        }
+        assert_eq!(reopened.get_file("b.md").unwrap().chunk_ids, vec!["b.md#0"]);
+    }
+
+    fn spread_vector(seed: usize) -> Vec<f32> {
+        (0..128)
+            .map(|i| ((seed * 37 + i * 11) % 101) as f32 / 50.0 - 1.0)
+            .collect()
+    }
+
+    fn upsert_spread(index: &Index, seed: usize) {
+        let path = format!("n{seed}.md");
+        index
+            .upsert(
+                &mk_file(&path),
+                &[mk_chunk(&path, 0)],
+                &[spread_vector(seed)],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn quantized_index_keeps_exact_vectors_across_rewrites_and_the_wal() {
+        use crate::config::VectorQuantization;
+
+        let dir = TempDir::new().unwrap();
+        for quantization in [VectorQuantization::I8, VectorQuantization::B1] {
+            let path = dir.path().join(format!("index-{quantization:?}"));
+            let options = WriteOptions {
+                quantization: quantization.clone(),
+                ..WriteOptions::default()
+            };
+            let index = Index::create_with_options(&path, &test_config(), options.clone()).unwrap();
+            for seed in 0..20 {
+                upsert_spread(&index, seed);
+            }
+            index.compact().unwrap();
+            upsert_spread(&index, 20);
+            index.save().unwrap();
+            assert!(
+                wal::wal_path(&path).exists(),
+                "the last vector is in the log"
+            );
+
+            let reopened = Index::open_with_options(&path, options).unwrap();
+            assert_eq!(reopened.get_file_vector("n3.md").unwrap(), spread_vector(3));
+            assert_eq!(
+                reopened.get_file_vector("n20.md").unwrap(),
+                spread_vector(20)
+            );
+            let results = reopened.search_vectors(&spread_vector(5), 3).unwrap();
+            assert_eq!(results[0].0, "n5.md#0");
+            assert!((results[0].1 - 1.0).abs() < 1e-5, "rescored: {results:?}");
+
+            let storage = reopened.vector_storage();
+            assert_eq!(storage.quantization, quantization);
+            assert!(storage.full_precision_available);
+            assert_eq!(storage.sidecar_file_size, Some(32 + 20 * 128 * 4));
+            assert_eq!(storage.rescore_factor, 4);
+            let recall = reopened.estimate_recall(8, 5).unwrap();
+            assert_eq!(recall.queries, 8);
+            assert_eq!(recall.rescored, Some(1.0));
+        }
+    }
+
+    #[test]
+    fn changing_quantization_rewrites_and_a_lost_sidecar_falls_back() {
+        use crate::config::VectorQuantization;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index");
+        let index = Index::create(&path, &test_config()).unwrap();
+        upsert_spread(&index, 0);
+        index.save().unwrap();
+        assert!(wal::wal_path(&path).exists());
+
+        let options = WriteOptions {
+            quantization: VectorQuantization::I8,
+            ..WriteOptions::default()
+        };
+        let index = Index::open_with_options(&path, options.clone()).unwrap();
+        assert_eq!(
+            index.vector_storage().pending_quantization,
+            Some(VectorQuantization::I8)
+        );
+        upsert_spread(&index, 1);
+        index.save().unwrap();
+        assert!(
+            !wal::wal_path(&path).exists(),
+            "quantization change rewrites"
+        );
+        let header = storage::read_header(&path).unwrap();
+        assert_eq!(header.quantization, VectorQuantization::I8);
+        let sidecar = vectors::sidecar_path(&path, header.generation);
+        assert!(sidecar.is_file());
+
+        std::fs::remove_file(&sidecar).unwrap();
+        let reopened = Index::open_with_options(&path, options).unwrap();
+        let storage = reopened.vector_storage();
+        assert!(!storage.full_precision_available);
+        assert_eq!(storage.sidecar_file_size, Some(0));
+        let approximate = reopened.get_file_vector("n1.md").unwrap();
+        assert!(vectors::cosine_distance(&approximate, &spread_vector(1)) < 0.01);
+
+        // Rewriting dequantized vectors must not claim full precision.
+        reopened.compact().unwrap();
+        let header = storage::read_header(&path).unwrap();
+        assert_eq!(vectors::find_sidecar(&path, header.generation), None);
+        assert!(!reopened.vector_storage().full_precision_available);
+    }
 }
@@ -24,17 +24,41 @@ const QUANT_F32: u8 = 0;
 /// Quantization byte value: F16.
 const QUANT_F16: u8 = 1;
 
+/// Quantization byte value: I8.
+const QUANT_I8: u8 = 2;
+
+/// Quantization byte value: B1 (one sign bit per dimension).
+const QUANT_B1: u8 = 3;
+
 /// Compression flag: zstd.
 const COMPRESS_ZSTD: u8 = 0x01;
 
 /// Default zstd compression level.
 const ZSTD_LEVEL: i32 = 3;
 
+/// Default size at which the write-ahead log is compacted (64 MiB).
+pub const DEFAULT_WAL_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Default age at which the write-ahead log is compacted (one hour).
+pub const DEFAULT_WAL_MAX_AGE_SECS: u64 = 3600;
+
+/// Default candidate multiplier for rescoring `i8`/`b1` searches.
+pub const DEFAULT_RESCORE_FACTOR: usize = 4;
+
 /// Options controlling how the index is written to disk.
 #[derive(Debug, Clone)]
 pub struct WriteOptions {
     pub quantization: VectorQuantization,
     pub compress_metadata: bool,
+    /// Saves append to the write-ahead log until it would grow past this
+    /// many bytes, then rewrite the base file. 0 disables the log.
+    pub wal_max_bytes: u64,
+    /// Saves rewrite the base file once the log is this many seconds old.
+    pub wal_max_age_secs: u64,
+    /// Searches of an `i8`/`b1` index fetch this many times the requested
+    /// candidates and re-rank them by exact cosine similarity. 0 ranks by
+    /// the quantized distances alone.
+    pub rescore_factor: usize,
 }
 
 impl Default for WriteOptions {
@@ -42,6 +66,9 @@ impl Default for WriteOptions {
         Self {
             quantization: VectorQuantization::F16,
             compress_metadata: true,
+            wal_max_bytes: DEFAULT_WAL_MAX_BYTES,
+            wal_max_age_secs: DEFAULT_WAL_MAX_AGE_SECS,
+            rescore_factor: DEFAULT_RESCORE_FACTOR,
         }
     }
 }
@@ -51,14 +78,24 @@ pub fn scalar_kind_for(q: &VectorQuantization) -> usearch::ScalarKind {
     match q {
         VectorQuantization::F16 => usearch::ScalarKind::F16,
         VectorQuantization::F32 => usearch::ScalarKind::F32,
+        VectorQuantization::I8 => usearch::ScalarKind::I8,
+        VectorQuantization::B1 => usearch::ScalarKind::B1,
     }
 }
 
 /// Create a new HNSW index with the given dimensionality and scalar kind.
+///
+/// Binary (`B1`) indexes compare sign bits by Hamming distance; usearch
+/// supports no cosine metric for them.
 pub fn create_hnsw(dimensions: usize, quantization: usearch::ScalarKind) -> Result<Index> {
+    let metric = if quantization == usearch::ScalarKind::B1 {
+        usearch::MetricKind::Hamming
+    } else {
+        usearch::MetricKind::Cos
+    };
     let opts = usearch::IndexOptions {
         dimensions,
-        metric: usearch::MetricKind::Cos,
+        metric,
         quantization,
         connectivity: 16,
         expansion_add: 128,
@@ -73,12 +110,33 @@ pub fn create_hnsw(dimensions: usize, quantization: usearch::ScalarKind) -> Resu
 /// [`rename_with_retry`]).
 ///
 /// Writes a V2 format header with quantization type and optional zstd compression
-/// of the rkyv metadata region.
+/// of the rkyv metadata region. Returns the random generation stamped into the
+/// header, which ties a write-ahead log to the base file it extends.
 pub fn write_index(
     path: &Path,
     metadata: &IndexMetadata,
     hnsw: &Index,
     options: &WriteOptions,
+) -> Result<u64> {
+    let generation = new_generation();
+    write_index_as(path, metadata, hnsw, options, generation)?;
+    Ok(generation)
+}
+
+/// A random generation for a new base file; never 0, which marks files
+/// written before the field existed.
+pub(crate) fn new_generation() -> u64 {
+    rand::random::<u64>().max(1)
+}
+
+/// Like [`write_index`], stamping a `generation` chosen by the caller so
+/// files written alongside the index (the vector sidecar) can carry it too.
+pub(crate) fn write_index_as(
+    path: &Path,
+    metadata: &IndexMetadata,
+    hnsw: &Index,
+    options: &WriteOptions,
+    generation: u64,
 ) -> Result<()> {
     // Serialize metadata via rkyv
     let meta_bytes_raw = rkyv::to_bytes::<rkyv::rancor::Error>(metadata)
@@ -118,6 +176,8 @@ pub fn write_index(
     header[42] = match options.quantization {
         VectorQuantization::F16 => QUANT_F16,
         VectorQuantization::F32 => QUANT_F32,
+        VectorQuantization::I8 => QUANT_I8,
+        VectorQuantization::B1 => QUANT_B1,
     };
     header[43] = if options.compress_metadata {
         COMPRESS_ZSTD
@@ -125,7 +185,9 @@ pub fn write_index(
         0
     };
     header[44..48].copy_from_slice(&uncompressed_meta_size.to_le_bytes());
-    // bytes 48..64 reserved
+    // Generation (bytes 48..56)
+    header[48..56].copy_from_slice(&generation.to_le_bytes());
+    // bytes 56..64 reserved
 
     // Write to a unique temp file in the destination directory, fsync, then
     // rename into place. A unique temp name (vs a fixed `.tmp` sibling) keeps
@@ -145,8 +207,20 @@ pub fn write_index(
     // reader's mmap causes transient sharing violations on rename.
     rename_with_retry(path, tmp, |t| {
         t.persist(path).map(|_| ()).map_err(|e| (e.error, e.file))
-    })?;
-    Ok(())
+    })
+}
+
+/// Read the generation stamped into an index file's header (0 for files
+/// written before generations existed).
+pub fn read_generation(path: &Path) -> Result<u64> {
+    use std::io::Read;
+
+    let mut header = [0u8; HEADER_SIZE];
+    fs::File::open(path)?.read_exact(&mut header)?;
+    if &header[..6] != MAGIC {
+        return Err(Error::IndexCorrupted("invalid magic bytes".into()));
+    }
+    Ok(u64::from_le_bytes(header[48..56].try_into().unwrap()))
 }
 
 /// Backoff schedule for [`rename_with_retry`] (exponential, milliseconds).
@@ -159,7 +233,7 @@ const RENAME_BACKOFF_MS: [u64; 5] = [50, 100, 200, 400, 800];
 /// [`tempfile::NamedTempFile::persist`], whose `PersistError` returns the
 /// temp file on failure. Exhausting all attempts yields [`Error::IndexBusy`]
 /// for `dest` (the usual cause is a concurrent reader/writer holding the file).
-fn rename_with_retry<T>(
+pub(crate) fn rename_with_retry<T>(
     dest: &Path,
     value: T,
     mut op: impl FnMut(T) -> std::result::Result<(), (std::io::Error, T)>,
@@ -188,28 +262,36 @@ fn rename_with_retry<T>(
     })
 }
 
-/// Load an index file via memory-mapping. Returns deserialized metadata and HNSW index.
-pub fn load_index(path: &Path) -> Result<(IndexMetadata, Index)> {
-    if !path.exists() {
-        return Err(Error::IndexNotFound {
-            path: path.to_path_buf(),
-        });
-    }
-
-    let file = fs::File::open(path)?;
-    let mmap = unsafe { Mmap::map(&file)? };
+/// Fields of a validated index file header.
+#[derive(Debug, Clone)]
+pub struct IndexHeader {
+    /// Format version.
+    pub version: u32,
+    /// Scalar type of the stored vectors.
+    pub quantization: VectorQuantization,
+    /// Whether the metadata region is zstd-compressed.
+    pub compressed: bool,
+    /// Random generation stamped by the writer (see [`read_generation`]).
+    pub generation: u64,
+    meta: std::ops::Range<usize>,
+    hnsw: std::ops::Range<usize>,
+    uncompressed_meta_size: usize,
+}
 
-    if mmap.len() < HEADER_SIZE {
+/// Validate the header at the start of `bytes`: magic, version, region
+/// bounds and quantization byte.
+fn parse_header(bytes: &[u8]) -> Result<IndexHeader> {
+    if bytes.len() < HEADER_SIZE {
         return Err(Error::IndexCorrupted("file too small for header".into()));
     }
 
     // Validate magic
-    if &mmap[..6] != MAGIC {
+    if &bytes[..6] != MAGIC {
         return Err(Error::IndexCorrupted("invalid magic bytes".into()));
     }
 
     // Validate version
-    let version = u32::from_le_bytes(mmap[6..10].try_into().unwrap());
+    let version = u32::from_le_bytes(bytes[6..10].try_into().unwrap());
     if version != VERSION {
         if version < VERSION {
             return Err(Error::IndexVersionMismatch { version });
@@ -220,25 +302,27 @@ pub fn load_index(path: &Path) -> Result<(IndexMetadata, Index)> {
     }
 
     // Read offsets
-    let meta_offset = u64::from_le_bytes(mmap[10..18].try_into().unwrap()) as usize;
-    let meta_size = u64::from_le_bytes(mmap[18..26].try_into().unwrap()) as usize;
-    let hnsw_offset = u64::from_le_bytes(mmap[26..34].try_into().unwrap()) as usize;
-    let hnsw_size = u64::from_le_bytes(mmap[34..42].try_into().unwrap()) as usize;
+    let meta_offset = u64::from_le_bytes(bytes[10..18].try_into().unwrap()) as usize;
+    let meta_size = u64::from_le_bytes(bytes[18..26].try_into().unwrap()) as usize;
+    let hnsw_offset = u64::from_le_bytes(bytes[26..34].try_into().unwrap()) as usize;
+    let hnsw_size = u64::from_le_bytes(bytes[34..42].try_into().unwrap()) as usize;
 
     // Validate regions fit in file
-    if meta_offset + meta_size > mmap.len() || hnsw_offset + hnsw_size > mmap.len() {
+    let fits = |offset: usize, size: usize| {
+        offset
+            .checked_add(size)
+            .is_some_and(|end| end <= bytes.len())
+    };
+    if !fits(meta_offset, meta_size) || !fits(hnsw_offset, hnsw_size) {
         return Err(Error::IndexCorrupted("truncated file".into()));
     }
 
-    // Read extension fields
-    let quantization_byte = mmap[42];
-    let compression_flags = mmap[43];
-    let uncompressed_meta_size = u32::from_le_bytes(mmap[44..48].try_into().unwrap()) as usize;
-
-    // Determine HNSW ScalarKind from header
-    let scalar_kind = match quantization_byte {
-        QUANT_F32 => usearch::ScalarKind::F32,
-        QUANT_F16 => usearch::ScalarKind::F16,
+    // Determine vector quantization from header
+    let quantization = match bytes[42] {
+        QUANT_F32 => VectorQuantization::F32,
+        QUANT_F16 => VectorQuantization::F16,
+        QUANT_I8 => VectorQuantization::I8,
+        QUANT_B1 => VectorQuantization::B1,
         other => {
             return Err(Error::IndexCorrupted(format!(
                 "unknown quantization type: {other}"
@@ -246,11 +330,58 @@ pub fn load_index(path: &Path) -> Result<(IndexMetadata, Index)> {
         }
     };
 
+    Ok(IndexHeader {
+        version,
+        quantization,
+        compressed: bytes[43] & COMPRESS_ZSTD != 0,
+        generation: u64::from_le_bytes(bytes[48..56].try_into().unwrap()),
+        meta: meta_offset..meta_offset + meta_size,
+        hnsw: hnsw_offset..hnsw_offset + hnsw_size,
+        uncompressed_meta_size: u32::from_le_bytes(bytes[44..48].try_into().unwrap()) as usize,
+    })
+}
+
+/// Read and validate the header of the index file at `path` without
+/// loading its metadata or vectors.
+pub fn read_header(path: &Path) -> Result<IndexHeader> {
+    if !path.exists() {
+        return Err(Error::IndexNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+    let file = fs::File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    parse_header(&mmap)
+}
+
+/// Load an index file via memory-mapping. Returns deserialized metadata and HNSW index.
+pub fn load_index(path: &Path) -> Result<(IndexMetadata, Index)> {
+    load_index_with_generation(path).map(|(metadata, hnsw, _)| (metadata, hnsw))
+}
+
+/// Like [`load_index`], also returning the file's generation (see
+/// [`read_generation`]).
+pub fn load_index_with_generation(path: &Path) -> Result<(IndexMetadata, Index, u64)> {
+    load_index_with_header(path).map(|(metadata, hnsw, header)| (metadata, hnsw, header.generation))
+}
+
+/// Like [`load_index`], also returning the file's validated header.
+pub fn load_index_with_header(path: &Path) -> Result<(IndexMetadata, Index, IndexHeader)> {
+    if !path.exists() {
+        return Err(Error::IndexNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let file = fs::File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    let header = parse_header(&mmap)?;
+
     // Decompress metadata if zstd flag is set
-    let raw_meta_bytes = &mmap[meta_offset..meta_offset + meta_size];
+    let raw_meta_bytes = &mmap[header.meta.clone()];
     let decompressed: Vec<u8>;
-    let meta_bytes: &[u8] = if compression_flags & COMPRESS_ZSTD != 0 {
-        decompressed = zstd::bulk::decompress(raw_meta_bytes, uncompressed_meta_size)
+    let meta_bytes: &[u8] = if header.compressed {
+        decompressed = zstd::bulk::decompress(raw_meta_bytes, header.uncompressed_meta_size)
             .map_err(|e| Error::Serialization(format!("zstd decompress: {e}")))?;
         &decompressed
     } else {
@@ -265,21 +396,15 @@ pub fn load_index(path: &Path) -> Result<(IndexMetadata, Index)> {
             ))?;
 
     // Load HNSW with the correct ScalarKind
-    let hnsw_bytes = &mmap[hnsw_offset..hnsw_offset + hnsw_size];
-    let hnsw = Index::new(&usearch::IndexOptions {
-        dimensions: metadata.embedding_config.dimensions,
-        metric: usearch::MetricKind::Cos,
-        quantization: scalar_kind,
-        connectivity: 16,
-        expansion_add: 128,
-        expansion_search: 64,
-        multi: false,
-    })
-    .map_err(|e| Error::Serialization(format!("usearch create: {e}")))?;
+    let hnsw_bytes = &mmap[header.hnsw.clone()];
+    let hnsw = create_hnsw(
+        metadata.embedding_config.dimensions,
+        scalar_kind_for(&header.quantization),
+    )?;
     hnsw.load_from_buffer(hnsw_bytes)
         .map_err(|e| Error::Serialization(format!("usearch load: {e}")))?;
 
-    Ok((metadata, hnsw))
+    Ok((metadata, hnsw, header))
 }
 
 #[cfg(test)]
@@ -470,6 +595,7 @@ mod tests {
         let options = WriteOptions {
             quantization: VectorQuantization::F16,
             compress_metadata: true,
+            ..WriteOptions::default()
         };
         write_index(&path, &meta, &hnsw, &options).unwrap();
 
@@ -489,6 +615,7 @@ mod tests {
         let options = WriteOptions {
             quantization: VectorQuantization::F32,
             compress_metadata: false,
+            ..WriteOptions::default()
         };
         write_index(&path, &meta, &hnsw, &options).unwrap();
 
@@ -496,6 +623,31 @@ mod tests {
         assert_eq!(loaded_meta.last_updated, 1234567890);
     }
 
+    #[test]
+    fn roundtrip_i8_and_binary_quantization() {
+        let dir = TempDir::new().unwrap();
+        for (quantization, byte) in [
+            (VectorQuantization::I8, QUANT_I8),
+            (VectorQuantization::B1, QUANT_B1),
+        ] {
+            let path = dir.path().join(format!("{quantization:?}.idx"));
+            let hnsw = create_hnsw(128, scalar_kind_for(&quantization)).unwrap();
+            hnsw.reserve(10).unwrap();
+            let options = WriteOptions {
+                quantization: quantization.clone(),
+                ..WriteOptions::default()
+            };
+            let generation = new_generation();
+            write_index_as(&path, &test_metadata(), &hnsw, &options, generation).unwrap();
+
+            assert_eq!(fs::read(&path).unwrap()[42], byte);
+            let (_, loaded, header) = load_index_with_header(&path).unwrap();
+            assert_eq!(header.quantization, quantization);
+            assert_eq!(header.generation, generation);
+            assert_eq!(loaded.dimensions(), 128);
+        }
+    }
+
     #[test]
     fn header_bytes_correct() {
         let dir = TempDir::new().unwrap();
@@ -507,6 +659,7 @@ mod tests {
         let options = WriteOptions {
             quantization: VectorQuantization::F16,
             compress_metadata: true,
+            ..WriteOptions::default()
         };
         write_index(&path, &meta, &hnsw, &options).unwrap();
 
@@ -519,6 +672,55 @@ mod tests {
         assert!(uncomp > 0);
     }
 
+    #[test]
+    fn read_header_validates_without_loading() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.idx");
+        let hnsw = create_hnsw(128, usearch::ScalarKind::F32).unwrap();
+        let options = WriteOptions {
+            quantization: VectorQuantization::F32,
+            compress_metadata: false,
+            ..WriteOptions::default()
+        };
+        let generation = write_index(&path, &test_metadata(), &hnsw, &options).unwrap();
+
+        let header = read_header(&path).unwrap();
+        assert_eq!(header.version, VERSION);
+        assert_eq!(header.quantization, VectorQuantization::F32);
+        assert!(!header.compressed);
+        assert_eq!(header.generation, generation);
+
+        let mut raw = fs::read(&path).unwrap();
+        raw[42] = 0xEE;
+        fs::write(&path, &raw).unwrap();
+        let err = read_header(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown quantization type: 238"));
+
+        raw.truncate(HEADER_SIZE + 4);
+        fs::write(&path, &raw).unwrap();
+        let err = read_header(&path).unwrap_err();
+        assert!(err.to_string().contains("truncated file"));
+    }
+
+    #[test]
+    fn every_write_stamps_a_new_generation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.idx");
+        let meta = test_metadata();
+        let hnsw = create_hnsw(128, usearch::ScalarKind::F16).unwrap();
+        hnsw.reserve(10).unwrap();
+
+        let first = write_index(&path, &meta, &hnsw, &WriteOptions::default()).unwrap();
+        assert_ne!(first, 0);
+        assert_eq!(read_generation(&path).unwrap(), first);
+        let (_, _, loaded) = load_index_with_generation(&path).unwrap();
+        assert_eq!(loaded, first);
+
+        let second = write_index(&path, &meta, &hnsw, &WriteOptions::default()).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(read_generation(&path).unwrap(), second);
+    }
+
     #[test]
     fn unknown_future_version_rejected() {
         let dir = TempDir::new().unwrap();
@@ -573,6 +775,7 @@ mod tests {
             WriteOptions {
                 quantization: VectorQuantization::F32,
                 compress_metadata: false,
+                ..WriteOptions::default()
             },
         )
         .unwrap();
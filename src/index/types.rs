@@ -3,6 +3,7 @@ use std::time::SystemTime;
 
 use crate::chunker::Chunk;
 use crate::clustering::{ClusterState, CustomClusterState};
+use crate::config::VectorQuantization;
 use crate::links::LinkGraph;
 use crate::parser::MarkdownFile;
 use crate::schema::{Schema, ScopedSchema};
@@ -221,7 +222,14 @@ pub struct StoredFile {
 
 /// Embedding configuration stored in the index and used for JSON output.
 #[derive(
-    Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    serde::Serialize,
+    serde::Deserialize,
 )]
 #[rkyv(derive(Debug))]
 pub struct EmbeddingConfig {
@@ -288,6 +296,46 @@ pub struct IndexStatus {
     pub embedding_compatibility_error: Option<String>,
 }
 
+/// How the index stores its vectors, returned by `Index::vector_storage()`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VectorStorageInfo {
+    /// Quantization of the vectors in the index file.
+    pub quantization: VectorQuantization,
+    /// Configured quantization the next save switches to, if different.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_quantization: Option<VectorQuantization>,
+    /// Bytes one vector takes in the HNSW index.
+    pub bytes_per_vector: usize,
+    /// Bytes one vector takes at full (`f32`) precision.
+    pub full_precision_bytes_per_vector: usize,
+    /// Size of the full-precision sidecar (`<index>.<generation>.vectors`) in bytes;
+    /// `None` for quantizations that keep no sidecar.
+    pub sidecar_file_size: Option<u64>,
+    /// Whether reads and rescoring use exact vectors. False when an `i8`/`b1`
+    /// index lost its sidecar.
+    pub full_precision_available: bool,
+    /// Candidate multiplier for rescoring searches by exact similarity; 0
+    /// when searches rank by the stored vectors directly.
+    pub rescore_factor: usize,
+    /// Measured search recall, when requested (`mdvdb info --recall`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recall: Option<RecallEstimate>,
+}
+
+/// Search recall measured against an exhaustive exact search, returned by
+/// `Index::estimate_recall()`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RecallEstimate {
+    /// Stored chunk vectors used as queries.
+    pub queries: usize,
+    /// Nearest neighbors compared per query.
+    pub k: usize,
+    /// Recall@k of HNSW search over the stored vectors alone.
+    pub quantized: f64,
+    /// Recall@k with rescoring; `None` when searches do not rescore.
+    pub rescored: Option<f64>,
+}
+
 /// Index-side counts for a path scope, returned by `Index::scoped_counts()`.
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
 pub struct ScopedCounts {
@@ -0,0 +1,665 @@
+//! The HNSW graph of an index and, for `i8` and `b1` quantization, the
+//! full-precision vectors behind it.
+//!
+//! usearch stores `i8` vectors as scaled integers and `b1` vectors as one
+//! sign bit per dimension, compared by Hamming distance, so neither can be
+//! read back exactly. [`VectorStore`] keeps the exact vectors of such
+//! indexes in a sidecar file next to the base file
+//! (`<index>.<generation>.vectors`), written on every base rewrite and
+//! memory-mapped on load. Each rewrite writes a new sidecar before renaming
+//! the base file and removes the old one only afterwards, so a crash in
+//! between leaves the old base with its own sidecar. Vectors added
+//! since the last rewrite are held in memory; the write-ahead log stores
+//! them in full. Reads return the exact vectors, and searches re-rank
+//! `rescore_factor` times the requested candidates by exact cosine
+//! similarity.
+//!
+//! Sidecar layout: a 32-byte header (magic, version, dimensions, base
+//! generation, row count) followed by one row of little-endian `f32`s per
+//! HNSW key; keys without a vector have a zero row. The generation must
+//! match the base file's. A missing or stale sidecar is ignored with a
+//! warning, and reads fall back to the quantized vectors until a reindex
+//! re-embeds them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+use tempfile::NamedTempFile;
+use tracing::warn;
+use usearch::{b1x8, Index as HnswIndex};
+
+use crate::config::VectorQuantization;
+use crate::error::{Error, Result};
+use crate::index::storage::{self, IndexHeader, WriteOptions};
+
+/// Magic bytes identifying an mdvdb vector sidecar.
+const SIDECAR_MAGIC: &[u8; 8] = b"MDVDBVEC";
+
+/// Current sidecar format version.
+const SIDECAR_VERSION: u32 = 1;
+
+/// Fixed sidecar header size in bytes.
+const SIDECAR_HEADER_SIZE: usize = 32;
+
+/// Path of the vector sidecar written with the base file of `generation`
+/// at `index_path`.
+pub(crate) fn sidecar_path(index_path: &Path, generation: u64) -> PathBuf {
+    index_path.with_extension(format!("{generation:016x}.vectors"))
+}
+
+/// Path of the sidecar of indexes written before sidecars were named by
+/// generation. Still read; its header must match the base generation.
+fn legacy_sidecar_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension("vectors")
+}
+
+/// The sidecar file the base file of `generation` at `index_path` would
+/// use, if one exists.
+pub(crate) fn find_sidecar(index_path: &Path, generation: u64) -> Option<PathBuf> {
+    [
+        sidecar_path(index_path, generation),
+        legacy_sidecar_path(index_path),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Remove every sidecar of the index at `index_path` except the one of
+/// generation `keep`.
+pub(crate) fn remove_sidecars(index_path: &Path, keep: Option<u64>) -> Result<()> {
+    let Some(stem) = index_path.file_stem().and_then(|stem| stem.to_str()) else {
+        return Ok(());
+    };
+    let dir = match index_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let keep = keep.map(|generation| format!("{stem}.{generation:016x}.vectors"));
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(rest) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(stem))
+            .and_then(|rest| rest.strip_prefix('.'))
+        else {
+            continue;
+        };
+        let is_sidecar = rest == "vectors"
+            || rest.strip_suffix(".vectors").is_some_and(|generation| {
+                generation.len() == 16 && generation.bytes().all(|b| b.is_ascii_hexdigit())
+            });
+        if !is_sidecar || keep.as_deref() == name.to_str() {
+            continue;
+        }
+        match fs::remove_file(entry.path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// HNSW graph plus the exact vectors of a quantized index.
+pub(crate) struct VectorStore {
+    hnsw: HnswIndex,
+    quantization: VectorQuantization,
+    dimensions: usize,
+    rescore_factor: usize,
+    /// Exact vectors of an `i8`/`b1` index; `None` for `f16`/`f32`.
+    exact: Option<ExactVectors>,
+}
+
+#[derive(Default)]
+struct ExactVectors {
+    /// Sidecar written by the last base rewrite, `None` if missing or stale.
+    sidecar: Option<Sidecar>,
+    /// Vectors added since the sidecar was written, by key.
+    added: HashMap<u64, Vec<f32>>,
+    /// The base file's vectors have no exact copy (the sidecar is missing
+    /// or stale), so reading them returns dequantized values.
+    lost: bool,
+}
+
+/// A validated, memory-mapped sidecar.
+struct Sidecar {
+    mmap: Mmap,
+    rows: u64,
+}
+
+impl Sidecar {
+    fn row(&self, key: u64, dimensions: usize) -> Option<Vec<f32>> {
+        if key >= self.rows {
+            return None;
+        }
+        let start = SIDECAR_HEADER_SIZE + key as usize * dimensions * 4;
+        let bytes = self.mmap.get(start..start + dimensions * 4)?;
+        Some(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+        )
+    }
+}
+
+impl VectorStore {
+    /// An empty store for `dimensions`-wide vectors at the configured
+    /// quantization.
+    pub(crate) fn create(dimensions: usize, options: &WriteOptions) -> Result<Self> {
+        let hnsw =
+            storage::create_hnsw(dimensions, storage::scalar_kind_for(&options.quantization))?;
+        Ok(Self::new(
+            hnsw,
+            options.quantization.clone(),
+            dimensions,
+            options.rescore_factor,
+        ))
+    }
+
+    fn new(
+        hnsw: HnswIndex,
+        quantization: VectorQuantization,
+        dimensions: usize,
+        rescore_factor: usize,
+    ) -> Self {
+        let exact = quantization
+            .keeps_full_precision()
+            .then(ExactVectors::default);
+        Self {
+            hnsw,
+            quantization,
+            dimensions,
+            rescore_factor,
+            exact,
+        }
+    }
+
+    /// Wrap an HNSW graph loaded from the base file described by `header`,
+    /// mapping its sidecar when the quantization keeps one.
+    pub(crate) fn load(
+        hnsw: HnswIndex,
+        header: &IndexHeader,
+        index_path: &Path,
+        dimensions: usize,
+        rescore_factor: usize,
+    ) -> Result<Self> {
+        let mut store = Self::new(
+            hnsw,
+            header.quantization.clone(),
+            dimensions,
+            rescore_factor,
+        );
+        if let Some(exact) = store.exact.as_mut() {
+            let path = find_sidecar(index_path, header.generation)
+                .unwrap_or_else(|| sidecar_path(index_path, header.generation));
+            exact.sidecar = open_sidecar(&path, dimensions, header.generation)?;
+            exact.lost = exact.sidecar.is_none() && store.hnsw.size() > 0;
+            if exact.lost {
+                warn!(
+                    path = %path.display(),
+                    "vector sidecar is missing or stale; reads and rescoring use quantized vectors until `mdvdb ingest --reindex`"
+                );
+            }
+        }
+        Ok(store)
+    }
+
+    /// The HNSW graph, for serialization into the base file.
+    pub(crate) fn hnsw(&self) -> &HnswIndex {
+        &self.hnsw
+    }
+
+    pub(crate) fn quantization(&self) -> &VectorQuantization {
+        &self.quantization
+    }
+
+    /// Whether exact vectors back every read and rescoring: always for
+    /// `f16`/`f32`, and for `i8`/`b1` when the sidecar is current.
+    pub(crate) fn full_precision_available(&self) -> bool {
+        self.exact.as_ref().is_none_or(|exact| !exact.lost)
+    }
+
+    /// The candidate multiplier searches apply; 0 when they do not rescore.
+    pub(crate) fn rescore_factor(&self) -> usize {
+        if self.exact.is_some() {
+            self.rescore_factor
+        } else {
+            0
+        }
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.hnsw.size()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.hnsw.capacity()
+    }
+
+    pub(crate) fn contains(&self, key: u64) -> bool {
+        self.hnsw.contains(key)
+    }
+
+    pub(crate) fn reserve(&self, capacity: usize) -> Result<()> {
+        self.hnsw
+            .reserve(capacity)
+            .map_err(|e| Error::Serialization(format!("usearch reserve: {e}")))
+    }
+
+    /// Add `vector` under `key`, keeping an exact copy for `i8`/`b1`.
+    pub(crate) fn add(&mut self, key: u64, vector: &[f32]) -> Result<()> {
+        self.add_quantized(key, vector)?;
+        if let Some(exact) = self.exact.as_mut() {
+            exact.added.insert(key, vector.to_vec());
+        }
+        Ok(())
+    }
+
+    fn add_quantized(&self, key: u64, vector: &[f32]) -> Result<()> {
+        let added = if self.quantization == VectorQuantization::B1 {
+            self.hnsw.add(key, b1x8::from_u8s(&pack_signs(vector)))
+        } else {
+            self.hnsw.add(key, vector)
+        };
+        added.map_err(|e| Error::Serialization(format!("usearch add: {e}")))
+    }
+
+    pub(crate) fn remove(&mut self, key: u64) -> Result<()> {
+        if let Some(exact) = self.exact.as_mut() {
+            exact.added.remove(&key);
+        }
+        self.hnsw
+            .remove(key)
+            .map(|_| ())
+            .map_err(|e| Error::Serialization(format!("usearch remove: {e}")))
+    }
+
+    /// The vector stored under `key`: exact when available, else as
+    /// reconstructed from the quantized graph.
+    pub(crate) fn vector(&self, key: u64) -> Option<Vec<f32>> {
+        if !self.hnsw.contains(key) {
+            return None;
+        }
+        if let Some(exact) = &self.exact {
+            if let Some(vector) = exact.added.get(&key) {
+                return Some(vector.clone());
+            }
+            if let Some(vector) = exact
+                .sidecar
+                .as_ref()
+                .and_then(|sidecar| sidecar.row(key, self.dimensions))
+            {
+                return Some(vector);
+            }
+        }
+        if self.quantization == VectorQuantization::B1 {
+            let mut bits = vec![0u8; self.dimensions.div_ceil(8)];
+            let found = self.hnsw.get(key, b1x8::from_mut_u8s(&mut bits)).ok()?;
+            return (found > 0).then(|| unpack_signs(&bits, self.dimensions));
+        }
+        let mut buf = vec![0.0f32; self.dimensions];
+        let found = self.hnsw.get(key, &mut buf).ok()?;
+        (found > 0).then_some(buf)
+    }
+
+    /// The `count` nearest keys to `query` as `(key, cosine distance)`,
+    /// closest first.
+    pub(crate) fn search(&self, query: &[f32], count: usize) -> Result<Vec<(u64, f32)>> {
+        self.search_with(query, count, self.rescore_factor(), None)
+    }
+
+    /// Like [`search`](Self::search), over the keys `filter` accepts.
+    pub(crate) fn filtered_search(
+        &self,
+        query: &[f32],
+        count: usize,
+        filter: impl Fn(u64) -> bool,
+    ) -> Result<Vec<(u64, f32)>> {
+        self.search_with(query, count, self.rescore_factor(), Some(&filter))
+    }
+
+    /// Search with an explicit rescore factor (0 ranks by the quantized
+    /// distances alone).
+    pub(crate) fn search_with(
+        &self,
+        query: &[f32],
+        count: usize,
+        rescore_factor: usize,
+        filter: Option<&dyn Fn(u64) -> bool>,
+    ) -> Result<Vec<(u64, f32)>> {
+        let candidates = count
+            .saturating_mul(rescore_factor.max(1))
+            .min(self.hnsw.size());
+        let matches = if self.quantization == VectorQuantization::B1 {
+            let bits = pack_signs(query);
+            let bits = b1x8::from_u8s(&bits);
+            match filter {
+                Some(filter) => self.hnsw.filtered_search(bits, candidates, filter),
+                None => self.hnsw.search(bits, candidates),
+            }
+        } else {
+            match filter {
+                Some(filter) => self.hnsw.filtered_search(query, candidates, filter),
+                None => self.hnsw.search(query, candidates),
+            }
+        }
+        .map_err(|e| Error::Serialization(format!("usearch search: {e}")))?;
+
+        let mut results: Vec<(u64, f32)> = if rescore_factor > 0 {
+            matches
+                .keys
+                .iter()
+                .zip(&matches.distances)
+                .map(|(&key, &distance)| {
+                    let distance = match self.vector(key) {
+                        Some(vector) => cosine_distance(query, &vector),
+                        None => self.cosine_distance_from(distance),
+                    };
+                    (key, distance)
+                })
+                .collect()
+        } else {
+            matches
+                .keys
+                .iter()
+                .zip(&matches.distances)
+                .map(|(&key, &distance)| (key, self.cosine_distance_from(distance)))
+                .collect()
+        };
+        results.sort_by(|a, b| a.1.total_cmp(&b.1));
+        results.truncate(count);
+        Ok(results)
+    }
+
+    /// Convert a distance reported by usearch to a cosine distance. Binary
+    /// indexes report Hamming distances; the fraction of differing sign
+    /// bits estimates the angle between the vectors as a fraction of π.
+    fn cosine_distance_from(&self, distance: f32) -> f32 {
+        if self.quantization == VectorQuantization::B1 && self.dimensions > 0 {
+            1.0 - (std::f32::consts::PI * distance / self.dimensions as f32).cos()
+        } else {
+            distance
+        }
+    }
+}
+
+/// Builds the store of a base rewrite, streaming exact vectors into a new
+/// sidecar instead of holding them in memory.
+///
+/// A rewrite from vectors that are no longer exact (an `i8`/`b1` index
+/// that lost its sidecar) writes no sidecar, so dequantized vectors are
+/// never passed off as full precision.
+pub(crate) struct StoreBuilder {
+    store: VectorStore,
+    sidecar: Option<(BufWriter<NamedTempFile>, u64)>,
+    next_key: u64,
+}
+
+impl StoreBuilder {
+    pub(crate) fn new(
+        index_path: &Path,
+        dimensions: usize,
+        capacity: usize,
+        options: &WriteOptions,
+        source_exact: bool,
+    ) -> Result<Self> {
+        let store = VectorStore::create(dimensions, options)?;
+        if capacity > 0 {
+            store.reserve(capacity.max(10))?;
+        }
+        let sidecar = if store.exact.is_some() && source_exact {
+            let parent = index_path.parent().filter(|p| !p.as_os_str().is_empty());
+            let tmp = tempfile::Builder::new()
+                .prefix(".vectors-")
+                .suffix(".tmp")
+                .tempfile_in(parent.unwrap_or_else(|| Path::new(".")))?;
+            let mut writer = BufWriter::new(tmp);
+            writer.write_all(&[0u8; SIDECAR_HEADER_SIZE])?;
+            Some((writer, 0))
+        } else {
+            None
+        };
+        Ok(Self {
+            store,
+            sidecar,
+            next_key: 0,
+        })
+    }
+
+    /// Add the next key (keys count up from 0), with its vector if it has one.
+    pub(crate) fn push(&mut self, vector: Option<&[f32]>) -> Result<u64> {
+        let key = self.next_key;
+        self.next_key += 1;
+        if let Some(vector) = vector {
+            self.store.add_quantized(key, vector)?;
+        }
+        if let Some((writer, rows)) = self.sidecar.as_mut() {
+            match vector {
+                Some(vector) if vector.len() == self.store.dimensions => {
+                    for value in vector {
+                        writer.write_all(&value.to_le_bytes())?;
+                    }
+                }
+                _ => writer.write_all(&vec![0u8; self.store.dimensions * 4])?,
+            }
+            *rows += 1;
+        }
+        Ok(key)
+    }
+
+    /// Finish the store for a base file of `generation`. The sidecar is
+    /// synced and mapped but stays a temp file until
+    /// [`PendingSidecar::persist`] moves it to its generation's path.
+    pub(crate) fn finish(mut self, generation: u64) -> Result<(VectorStore, PendingSidecar)> {
+        let Some((writer, rows)) = self.sidecar.take() else {
+            let size = self.store.hnsw.size();
+            if let Some(exact) = self.store.exact.as_mut() {
+                exact.lost = size > 0;
+            }
+            return Ok((self.store, PendingSidecar(None)));
+        };
+        let mut tmp = writer.into_inner().map_err(|e| Error::Io(e.into_error()))?;
+        let mut header = [0u8; SIDECAR_HEADER_SIZE];
+        header[..8].copy_from_slice(SIDECAR_MAGIC);
+        header[8..12].copy_from_slice(&SIDECAR_VERSION.to_le_bytes());
+        header[12..16].copy_from_slice(&(self.store.dimensions as u32).to_le_bytes());
+        header[16..24].copy_from_slice(&generation.to_le_bytes());
+        header[24..32].copy_from_slice(&rows.to_le_bytes());
+        tmp.seek(SeekFrom::Start(0))?;
+        tmp.write_all(&header)?;
+        tmp.as_file().sync_all()?;
+
+        let mmap = unsafe { Mmap::map(tmp.as_file())? };
+        if let Some(exact) = self.store.exact.as_mut() {
+            exact.sidecar = Some(Sidecar { mmap, rows });
+        }
+        Ok((self.store, PendingSidecar(Some((tmp, generation)))))
+    }
+}
+
+/// A finished sidecar not yet moved next to the index.
+pub(crate) struct PendingSidecar(Option<(NamedTempFile, u64)>);
+
+impl PendingSidecar {
+    /// Move the sidecar to its generation's path next to the index at
+    /// `index_path`. Sidecars of earlier generations stay until the new
+    /// base file is in place; see [`remove_sidecars`].
+    pub(crate) fn persist(self, index_path: &Path) -> Result<()> {
+        let Some((tmp, generation)) = self.0 else {
+            return Ok(());
+        };
+        let path = sidecar_path(index_path, generation);
+        storage::rename_with_retry(&path, tmp, |t| {
+            t.persist(&path).map(|_| ()).map_err(|e| (e.error, e.file))
+        })
+    }
+}
+
+/// Map and validate the sidecar at `path`; `None` when it is missing or
+/// belongs to another base file.
+fn open_sidecar(path: &Path, dimensions: usize, generation: u64) -> Result<Option<Sidecar>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mmap = unsafe { Mmap::map(&file)? };
+    if mmap.len() < SIDECAR_HEADER_SIZE || &mmap[..8] != SIDECAR_MAGIC {
+        return Ok(None);
+    }
+    let version = u32::from_le_bytes(mmap[8..12].try_into().unwrap());
+    let stored_dimensions = u32::from_le_bytes(mmap[12..16].try_into().unwrap()) as usize;
+    let stored_generation = u64::from_le_bytes(mmap[16..24].try_into().unwrap());
+    let rows = u64::from_le_bytes(mmap[24..32].try_into().unwrap());
+    let expected_len = (rows as usize)
+        .checked_mul(dimensions * 4)
+        .and_then(|len| len.checked_add(SIDECAR_HEADER_SIZE));
+    if version != SIDECAR_VERSION
+        || stored_dimensions != dimensions
+        || stored_generation != generation
+        || expected_len != Some(mmap.len())
+    {
+        return Ok(None);
+    }
+    Ok(Some(Sidecar { mmap, rows }))
+}
+
+/// Pack the signs of `vector` into bits, one per dimension (set = positive).
+fn pack_signs(vector: &[f32]) -> Vec<u8> {
+    let mut bits = vec![0u8; vector.len().div_ceil(8)];
+    for (i, value) in vector.iter().enumerate() {
+        if *value > 0.0 {
+            bits[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    bits
+}
+
+/// Inverse of [`pack_signs`]: a unit vector of ±1/√d per dimension.
+fn unpack_signs(bits: &[u8], dimensions: usize) -> Vec<f32> {
+    let magnitude = 1.0 / (dimensions.max(1) as f32).sqrt();
+    (0..dimensions)
+        .map(|i| {
+            if bits[i / 8] & (0x80 >> (i % 8)) != 0 {
+                magnitude
+            } else {
+                -magnitude
+            }
+        })
+        .collect()
+}
+
+/// `1 - cos(a, b)`, as usearch's cosine metric reports it.
+pub(crate) fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 1.0;
+    }
+    1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options(quantization: VectorQuantization) -> WriteOptions {
+        WriteOptions {
+            quantization,
+            ..WriteOptions::default()
+        }
+    }
+
+    fn vector(seed: u64, dims: usize) -> Vec<f32> {
+        (0..dims)
+            .map(|i| (((seed * 31 + i as u64 * 17) % 97) as f32 / 48.5) - 1.0)
+            .collect()
+    }
+
+    #[test]
+    fn sign_bits_round_trip_to_unit_vectors() {
+        let packed = pack_signs(&[0.5, -0.2, 0.0, 3.0, -1.0, 1.0, 1.0, -4.0, 2.0]);
+        assert_eq!(packed, vec![0b1001_0110, 0b1000_0000]);
+        let unpacked = unpack_signs(&packed, 9);
+        assert!(unpacked[0] > 0.0 && unpacked[1] < 0.0 && unpacked[8] > 0.0);
+        let norm: f32 = unpacked.iter().map(|v| v * v).sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn binary_store_reads_back_exact_vectors_and_rescores() {
+        let dims = 64;
+        let mut store = VectorStore::create(dims, &options(VectorQuantization::B1)).unwrap();
+        store.reserve(20).unwrap();
+        for key in 0..20 {
+            store.add(key, &vector(key, dims)).unwrap();
+        }
+
+        assert_eq!(store.vector(7), Some(vector(7, dims)));
+        let results = store.search(&vector(7, dims), 3).unwrap();
+        assert_eq!(results[0].0, 7);
+        assert!(results[0].1.abs() < 1e-5, "exact rescoring: {results:?}");
+
+        let unscored = store.search_with(&vector(7, dims), 3, 0, None).unwrap();
+        assert!(unscored.iter().any(|(key, _)| *key == 7));
+
+        store.remove(7).unwrap();
+        assert_eq!(store.vector(7), None);
+    }
+
+    #[test]
+    fn rebuilt_store_maps_its_sidecar_and_load_rejects_a_stale_one() {
+        let tmp = TempDir::new().unwrap();
+        let index_path = tmp.path().join("index");
+        let dims = 16;
+        let options = options(VectorQuantization::I8);
+
+        let mut builder = StoreBuilder::new(&index_path, dims, 3, &options, true).unwrap();
+        builder.push(Some(&vector(0, dims))).unwrap();
+        builder.push(None).unwrap();
+        builder.push(Some(&vector(2, dims))).unwrap();
+        let (store, pending) = builder.finish(42).unwrap();
+        pending.persist(&index_path).unwrap();
+
+        assert_eq!(store.vector(2), Some(vector(2, dims)));
+        assert_eq!(store.vector(1), None);
+        assert_eq!(
+            fs::metadata(sidecar_path(&index_path, 42)).unwrap().len(),
+            (SIDECAR_HEADER_SIZE + 3 * dims * 4) as u64
+        );
+
+        let path = sidecar_path(&index_path, 42);
+        let sidecar = open_sidecar(&path, dims, 42).unwrap();
+        assert_eq!(sidecar.unwrap().row(2, dims), Some(vector(2, dims)));
+        assert!(open_sidecar(&path, dims, 43).unwrap().is_none());
+        assert!(open_sidecar(&path, dims + 1, 42).unwrap().is_none());
+
+        let builder =
+            StoreBuilder::new(&index_path, dims, 0, &WriteOptions::default(), true).unwrap();
+        let (_, pending) = builder.finish(43).unwrap();
+        pending.persist(&index_path).unwrap();
+        assert!(path.exists(), "the old sidecar outlives the new persist");
+        assert_eq!(find_sidecar(&index_path, 43), None);
+
+        fs::write(legacy_sidecar_path(&index_path), b"").unwrap();
+        fs::write(tmp.path().join("index.notes"), b"").unwrap();
+        remove_sidecars(&index_path, Some(43)).unwrap();
+        assert!(!path.exists());
+        assert!(!legacy_sidecar_path(&index_path).exists());
+        assert!(tmp.path().join("index.notes").exists());
+    }
+}
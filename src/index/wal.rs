@@ -0,0 +1,347 @@
+//! Write-ahead log of index changes.
+//!
+//! Most saves touch a handful of files, so instead of rewriting the whole
+//! base file, [`Index::save`](crate::index::Index::save) appends one
+//! [`WalRecord`] describing the changes to a sibling `<index>.wal` file and
+//! fsyncs it. [`Index::open`](crate::index::Index::open) replays the records
+//! on top of the base file. Once the log grows past its size or age limit,
+//! or on `mdvdb compact`, the next save rewrites the base file and deletes
+//! the log.
+//!
+//! Layout: a 32-byte header (magic, version, base generation, creation time)
+//! followed by frames of `[u32 payload len][u32 uncompressed len][8-byte
+//! checksum][zstd rkyv payload]`. The header's generation must match the one
+//! stamped into the base file, so a log left behind by a crash during
+//! compaction is ignored. Replay stops at the first torn or damaged frame.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::clustering::{ClusterState, CustomClusterState};
+use crate::error::{Error, Result};
+use crate::index::types::{StoredChunk, StoredFile};
+use crate::links::LinkGraph;
+use crate::schema::{Schema, ScopedSchema};
+
+/// Magic bytes identifying an mdvdb write-ahead log.
+const WAL_MAGIC: &[u8; 8] = b"MDVDBWAL";
+
+/// Current write-ahead log format version.
+const WAL_VERSION: u32 = 1;
+
+/// Fixed log header size in bytes.
+pub(crate) const WAL_HEADER_SIZE: usize = 32;
+
+/// Size of the length and checksum prefix of every frame.
+const FRAME_HEADER_SIZE: usize = 16;
+
+/// zstd compression level for record payloads.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The changes made by one save.
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+pub(crate) struct WalRecord {
+    pub last_updated: u64,
+    pub ops: Vec<WalOp>,
+}
+
+/// One change in a [`WalRecord`]. Replay applies ops in order.
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+pub(crate) enum WalOp {
+    /// A file entry with its chunks and chunk vectors, replacing any
+    /// previous chunks of the file.
+    UpsertFile {
+        path: String,
+        file: StoredFile,
+        mtime: Option<u64>,
+        chunks: Vec<WalChunk>,
+    },
+    /// A file entry whose chunks and vectors are unchanged.
+    UpdateFile {
+        path: String,
+        file: StoredFile,
+        mtime: Option<u64>,
+    },
+    /// Remove a file entry, its chunks and vectors, and its mtime.
+    RemoveFile {
+        path: String,
+    },
+    /// Edge vectors added or replaced, and edge vectors removed.
+    Edges {
+        upserted: Vec<WalVector>,
+        removed: Vec<String>,
+    },
+    Schema(Option<Schema>),
+    ScopedSchemas(Option<Vec<ScopedSchema>>),
+    Clusters(Option<ClusterState>),
+    CustomClusters(Option<CustomClusterState>),
+    LinkGraph(Option<LinkGraph>),
+}
+
+/// A chunk of a [`WalOp::UpsertFile`] with its vector, if it has one.
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+pub(crate) struct WalChunk {
+    pub id: String,
+    pub chunk: StoredChunk,
+    pub vector: Option<Vec<f32>>,
+}
+
+/// An edge vector of a [`WalOp::Edges`].
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+pub(crate) struct WalVector {
+    pub id: String,
+    pub vector: Vec<f32>,
+}
+
+/// The valid prefix of a write-ahead log.
+pub(crate) struct WalContents {
+    /// Unix time the log was started.
+    pub created_at: u64,
+    pub records: Vec<WalRecord>,
+    /// Length in bytes of the header and every intact frame.
+    pub valid_len: u64,
+}
+
+/// Path of the write-ahead log belonging to the index at `index_path`.
+pub(crate) fn wal_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension("wal")
+}
+
+/// Length of the log file at `path`, 0 when it does not exist.
+pub(crate) fn file_len(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Read the log at `path` if it extends the base file of `generation`.
+///
+/// Returns `None` when there is no log, or when it belongs to another base
+/// file or format version. Frames after the first torn or damaged one are
+/// dropped; `valid_len` then stops short of the file length, which makes the
+/// next save rewrite the base file rather than append after the damage.
+pub(crate) fn read(path: &Path, generation: u64) -> Result<Option<WalContents>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if bytes.len() < WAL_HEADER_SIZE || &bytes[..8] != WAL_MAGIC {
+        return Ok(None);
+    }
+    let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    let log_generation = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+    if version != WAL_VERSION || log_generation != generation {
+        return Ok(None);
+    }
+    let created_at = u64::from_le_bytes(bytes[24..32].try_into().unwrap());
+
+    let mut records = Vec::new();
+    let mut offset = WAL_HEADER_SIZE;
+    while let Some((record, frame_len)) = decode_frame(&bytes[offset..]) {
+        records.push(record);
+        offset += frame_len;
+    }
+    if offset < bytes.len() {
+        warn!(
+            path = %path.display(),
+            valid_len = offset,
+            file_len = bytes.len(),
+            "ignoring torn or damaged write-ahead log tail"
+        );
+    }
+
+    Ok(Some(WalContents {
+        created_at,
+        records,
+        valid_len: offset as u64,
+    }))
+}
+
+/// Decode the frame at the start of `bytes`, returning the record and the
+/// frame length, or `None` when the frame is incomplete or damaged.
+fn decode_frame(bytes: &[u8]) -> Option<(WalRecord, usize)> {
+    if bytes.len() < FRAME_HEADER_SIZE {
+        return None;
+    }
+    let payload_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+    let raw_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    let payload = bytes.get(FRAME_HEADER_SIZE..FRAME_HEADER_SIZE + payload_len)?;
+    if bytes[8..16] != checksum(payload) {
+        return None;
+    }
+    let raw = zstd::bulk::decompress(payload, raw_len).ok()?;
+    // rkyv needs an aligned buffer.
+    let mut aligned = rkyv::util::AlignedVec::<16>::with_capacity(raw.len());
+    aligned.extend_from_slice(&raw);
+    let record = rkyv::from_bytes::<WalRecord, rkyv::rancor::Error>(&aligned).ok()?;
+    Some((record, FRAME_HEADER_SIZE + payload_len))
+}
+
+/// Serialize `record` into a frame ready for [`append`].
+pub(crate) fn encode_frame(record: &WalRecord) -> Result<Vec<u8>> {
+    let raw = rkyv::to_bytes::<rkyv::rancor::Error>(record)
+        .map_err(|e| Error::Serialization(e.to_string()))?;
+    let payload = zstd::bulk::compress(&raw, ZSTD_LEVEL)
+        .map_err(|e| Error::Serialization(format!("zstd compress: {e}")))?;
+    let too_large = |_| Error::Serialization("write-ahead log record too large".into());
+    let payload_len = u32::try_from(payload.len()).map_err(too_large)?;
+    let raw_len = u32::try_from(raw.len()).map_err(too_large)?;
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&payload_len.to_le_bytes());
+    frame.extend_from_slice(&raw_len.to_le_bytes());
+    frame.extend_from_slice(&checksum(&payload));
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Append `frame` to the log at `path` and fsync it. Returns the new log
+/// length.
+///
+/// `len` is the current length of the log; 0 starts a new log for the base
+/// file of `generation`, stamped with `created_at`. The caller must hold the
+/// index write lock and have checked that the file is exactly `len` bytes.
+pub(crate) fn append(
+    path: &Path,
+    generation: u64,
+    created_at: u64,
+    len: u64,
+    frame: &[u8],
+) -> Result<u64> {
+    if len == 0 {
+        let mut header = [0u8; WAL_HEADER_SIZE];
+        header[..8].copy_from_slice(WAL_MAGIC);
+        header[8..12].copy_from_slice(&WAL_VERSION.to_le_bytes());
+        // bytes 12..16 reserved
+        header[16..24].copy_from_slice(&generation.to_le_bytes());
+        header[24..32].copy_from_slice(&created_at.to_le_bytes());
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(&header)?;
+        file.write_all(frame)?;
+        file.sync_all()?;
+        return Ok((WAL_HEADER_SIZE + frame.len()) as u64);
+    }
+
+    let mut file = fs::OpenOptions::new().append(true).open(path)?;
+    file.write_all(frame)?;
+    file.sync_data()?;
+    Ok(len + frame.len() as u64)
+}
+
+/// Delete the log at `path`, if any.
+pub(crate) fn remove(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// First 8 bytes of the SHA-256 of `payload`.
+fn checksum(payload: &[u8]) -> [u8; 8] {
+    let digest = Sha256::digest(payload);
+    digest[..8].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(last_updated: u64) -> WalRecord {
+        WalRecord {
+            last_updated,
+            ops: vec![
+                WalOp::RemoveFile {
+                    path: "old.md".to_string(),
+                },
+                WalOp::Edges {
+                    upserted: vec![WalVector {
+                        id: "edge:a.md->b.md@0".to_string(),
+                        vector: vec![0.5; 4],
+                    }],
+                    removed: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn appended_records_read_back_in_order() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.wal");
+
+        let first = encode_frame(&record(1)).unwrap();
+        let len = append(&path, 7, 100, 0, &first).unwrap();
+        let second = encode_frame(&record(2)).unwrap();
+        let len = append(&path, 7, 100, len, &second).unwrap();
+        assert_eq!(len, file_len(&path));
+
+        let contents = read(&path, 7).unwrap().unwrap();
+        assert_eq!(contents.created_at, 100);
+        assert_eq!(contents.valid_len, len);
+        let stamps: Vec<u64> = contents.records.iter().map(|r| r.last_updated).collect();
+        assert_eq!(stamps, vec![1, 2]);
+        assert!(matches!(
+            &contents.records[0].ops[0],
+            WalOp::RemoveFile { path } if path == "old.md"
+        ));
+    }
+
+    #[test]
+    fn log_of_another_generation_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.wal");
+        append(&path, 7, 100, 0, &encode_frame(&record(1)).unwrap()).unwrap();
+
+        assert!(read(&path, 8).unwrap().is_none());
+        assert!(read(&dir.path().join("missing.wal"), 7).unwrap().is_none());
+    }
+
+    #[test]
+    fn torn_and_damaged_tails_are_dropped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.wal");
+        let len = append(&path, 7, 100, 0, &encode_frame(&record(1)).unwrap()).unwrap();
+        let frame = encode_frame(&record(2)).unwrap();
+
+        // A frame cut short by a crash.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.extend_from_slice(&frame[..frame.len() - 3]);
+        fs::write(&path, &bytes).unwrap();
+        let contents = read(&path, 7).unwrap().unwrap();
+        assert_eq!(contents.records.len(), 1);
+        assert_eq!(contents.valid_len, len);
+
+        // A complete frame with a flipped payload byte.
+        let mut damaged = frame.clone();
+        *damaged.last_mut().unwrap() ^= 0xff;
+        bytes.truncate(len as usize);
+        bytes.extend_from_slice(&damaged);
+        fs::write(&path, &bytes).unwrap();
+        let contents = read(&path, 7).unwrap().unwrap();
+        assert_eq!(contents.records.len(), 1);
+        assert_eq!(contents.valid_len, len);
+    }
+
+    #[test]
+    fn remove_tolerates_missing_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.wal");
+        remove(&path).unwrap();
+        append(&path, 7, 100, 0, &encode_frame(&record(1)).unwrap()).unwrap();
+        remove(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
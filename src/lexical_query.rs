@@ -0,0 +1,581 @@
+//! Lexical query syntax for the `lexical` and `hybrid` search modes.
+//!
+//! A query is a whitespace-separated list of clauses:
+//!
+//! ```text
+//! clause = ["+" | "-"] [field ":"] body
+//! body   = '"' phrase '"' ["~" slop]   exact phrase, optionally with slop
+//!        | word "~" [distance]         fuzzy word (edit distance 0-2, default 1)
+//!        | word "*"                    prefix match
+//!        | word                        stemmed word
+//! field  = "content" | "heading" | "frontmatter" | frontmatter-key
+//! ```
+//!
+//! A `key:` directly followed by a body scopes the clause to that top-level
+//! frontmatter key (`tags:postgres`) when the caller recognizes the key. With
+//! whitespace after the colon, or an unknown key, it stays ordinary text, so
+//! prose like `error: timeout` and `http://example.com` still works.
+//!
+//! Clauses without `+` or `-` are optional: a chunk must match at least one
+//! of them unless the query has `+required` clauses, and must match none of
+//! the `-excluded` ones. As in Tantivy's query parser, `a AND b` requires
+//! both clauses, `NOT a` excludes one, and `OR` is accepted and redundant.
+//! [`FtsIndex::search`](crate::fts::FtsIndex::search) compiles the parsed
+//! clauses into Tantivy queries.
+
+use tantivy::query::Occur;
+
+use crate::error::{Error, Result};
+
+/// Largest edit distance accepted by `word~N`.
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+/// Which indexed text a clause is matched against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexicalField {
+    /// Chunk content, heading hierarchy, and frontmatter values, each with
+    /// its configured boost.
+    Default,
+    /// Chunk content only (`content:`).
+    Content,
+    /// Heading hierarchy only (`heading:`).
+    Heading,
+    /// Values of one top-level frontmatter key (`tags:`).
+    Frontmatter(String),
+}
+
+/// The matched text of one clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexicalTerm {
+    /// A word, matched after the field's tokenizer and stemmer.
+    Word(String),
+    /// A quoted phrase; `slop` allows that many intervening positions.
+    Phrase { text: String, slop: u32 },
+    /// A word matched within `distance` edits.
+    Fuzzy { word: String, distance: u8 },
+    /// Words starting with the given prefix.
+    Prefix(String),
+}
+
+/// One parsed clause of a lexical query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicalClause {
+    /// Optional (`Should`), required (`Must`), or excluded (`MustNot`).
+    pub occur: Occur,
+    pub field: LexicalField,
+    pub term: LexicalTerm,
+    /// Byte offset of the clause in the query string.
+    pub position: usize,
+}
+
+/// Parse a lexical query into clauses.
+///
+/// `is_frontmatter_key` decides which `key:` prefixes name frontmatter keys.
+/// A blank query yields no clauses. Returns [`Error::LexicalQuery`] with the
+/// byte offset of the offending clause when the syntax is invalid.
+pub fn parse_lexical_query(
+    input: &str,
+    mut is_frontmatter_key: impl FnMut(&str) -> bool,
+) -> Result<Vec<LexicalClause>> {
+    parse(input, true, &mut is_frontmatter_key)
+}
+
+/// Parse a query that may be plain natural language, as hybrid mode does.
+///
+/// Never fails: a clause [`parse_lexical_query`] would reject becomes a
+/// plain word of its whitespace-separated token, so a stray `"`, `~/.config`
+/// or a trailing `-` still searches for the text, and a dangling operator is
+/// ignored.
+pub fn parse_lexical_query_lenient(
+    input: &str,
+    mut is_frontmatter_key: impl FnMut(&str) -> bool,
+) -> Vec<LexicalClause> {
+    parse(input, false, &mut is_frontmatter_key).unwrap_or_default()
+}
+
+fn parse(
+    input: &str,
+    strict: bool,
+    is_frontmatter_key: &mut dyn FnMut(&str) -> bool,
+) -> Result<Vec<LexicalClause>> {
+    let mut clauses: Vec<LexicalClause> = Vec::new();
+    // Operators waiting for the clause they apply to.
+    let mut pending = Pending::default();
+    let mut pos = 0;
+    while let Some(offset) = input[pos..].find(|c: char| !c.is_whitespace()) {
+        let start = pos + offset;
+        let (token, end) = match parse_clause(input, start, is_frontmatter_key) {
+            Ok(parsed) => parsed,
+            Err(error) if strict => return Err(error),
+            Err(_) => {
+                let clause = plain_word(input, start);
+                (Token::Clause(clause), start + word_at(input, start).len())
+            }
+        };
+        pos = end;
+        match token {
+            Token::Clause(mut clause) => {
+                if pending.and.is_some() {
+                    if let Some(before) = clauses.last_mut() {
+                        require(&mut before.occur);
+                    }
+                    require(&mut clause.occur);
+                }
+                if pending.not.is_some() {
+                    clause.occur = Occur::MustNot;
+                }
+                pending = Pending::default();
+                clauses.push(clause);
+            }
+            Token::Operator(Operator::Or) => {}
+            Token::Operator(operator) => {
+                // `AND` joins two clauses; `NOT` may follow `AND` but
+                // nothing may repeat or follow `NOT`.
+                let slot = match operator {
+                    Operator::And if !clauses.is_empty() && pending.is_empty() => {
+                        Some(&mut pending.and)
+                    }
+                    Operator::Not if pending.not.is_none() => Some(&mut pending.not),
+                    _ => None,
+                };
+                if let Some(slot) = slot {
+                    *slot = Some(start);
+                } else if strict {
+                    return Err(syntax_error(
+                        start,
+                        format!("expected a clause before `{}`", operator.as_str()),
+                    ));
+                } else {
+                    clauses.push(plain_word(input, start));
+                }
+            }
+        }
+    }
+    match (pending.and, pending.not) {
+        (_, Some(position)) | (Some(position), None) if strict => {
+            let operator = word_at(input, position);
+            Err(syntax_error(
+                position,
+                format!("expected a clause after `{operator}`"),
+            ))
+        }
+        _ => Ok(clauses),
+    }
+}
+
+/// One whitespace-separated unit of a query.
+enum Token {
+    Clause(LexicalClause),
+    Operator(Operator),
+}
+
+/// Boolean keywords, which are only operators when written in capitals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    And,
+    Or,
+    Not,
+}
+
+impl Operator {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Not => "NOT",
+        }
+    }
+}
+
+/// Byte offsets of an `AND` and a `NOT` seen since the last clause.
+#[derive(Default)]
+struct Pending {
+    and: Option<usize>,
+    not: Option<usize>,
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.and.is_none() && self.not.is_none()
+    }
+}
+
+/// `AND` makes both of its clauses required unless they carry `-`.
+fn require(occur: &mut Occur) {
+    if *occur == Occur::Should {
+        *occur = Occur::Must;
+    }
+}
+
+/// The token starting at `start` as an optional plain word, for the lenient
+/// parse.
+fn plain_word(input: &str, start: usize) -> LexicalClause {
+    LexicalClause {
+        occur: Occur::Should,
+        field: LexicalField::Default,
+        term: LexicalTerm::Word(word_at(input, start).to_string()),
+        position: start,
+    }
+}
+
+fn syntax_error(position: usize, message: impl Into<String>) -> Error {
+    Error::LexicalQuery {
+        position,
+        message: message.into(),
+    }
+}
+
+/// Parse the clause or operator starting at `start`. Returns it and the
+/// offset just past it.
+fn parse_clause(
+    input: &str,
+    start: usize,
+    is_frontmatter_key: &mut dyn FnMut(&str) -> bool,
+) -> Result<(Token, usize)> {
+    let mut pos = start;
+    let occur = match input[pos..].chars().next() {
+        Some('+') => Occur::Must,
+        Some('-') => Occur::MustNot,
+        _ => Occur::Should,
+    };
+    if occur != Occur::Should {
+        pos += 1;
+    }
+
+    // Built-in field names always scope a clause. Any other `name:` scopes a
+    // frontmatter key only when the key is known and the body follows without
+    // whitespace, so natural-language queries like `error: timeout` keep
+    // working.
+    let field = match field_prefix(&input[pos..]) {
+        Some(name @ "content") => {
+            pos += name.len() + 1;
+            LexicalField::Content
+        }
+        Some(name @ ("heading" | "heading_hierarchy")) => {
+            pos += name.len() + 1;
+            LexicalField::Heading
+        }
+        Some(name)
+            if !word_at(input, pos + name.len() + 1).is_empty()
+                && (name == "frontmatter" || is_frontmatter_key(name)) =>
+        {
+            pos += name.len() + 1;
+            LexicalField::Frontmatter(name.to_string())
+        }
+        _ => LexicalField::Default,
+    };
+
+    if input[pos..].starts_with('"') {
+        let (term, end) = parse_phrase(input, pos)?;
+        let clause = LexicalClause {
+            occur,
+            field,
+            term,
+            position: start,
+        };
+        return Ok((Token::Clause(clause), end));
+    }
+
+    let word = word_at(input, pos);
+    let end = pos + word.len();
+    if word.is_empty() {
+        return Err(syntax_error(pos, "expected a word or quoted phrase"));
+    }
+    if occur == Occur::Should && field == LexicalField::Default {
+        let operator = match word {
+            "AND" => Some(Operator::And),
+            "OR" => Some(Operator::Or),
+            "NOT" => Some(Operator::Not),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            return Ok((Token::Operator(operator), end));
+        }
+    }
+
+    let term = if let Some(prefix) = word.strip_suffix('*') {
+        LexicalTerm::Prefix(single_word(prefix, pos, "prefix")?.to_string())
+    } else if let Some((fuzzy, distance)) = word.rsplit_once('~') {
+        let distance = match distance {
+            "" => 1,
+            digits => digits
+                .parse::<u8>()
+                .ok()
+                .filter(|d| *d <= MAX_FUZZY_DISTANCE)
+                .ok_or_else(|| {
+                    syntax_error(
+                        pos + fuzzy.len() + 1,
+                        format!("fuzzy distance must be 0 to {MAX_FUZZY_DISTANCE}"),
+                    )
+                })?,
+        };
+        LexicalTerm::Fuzzy {
+            word: single_word(fuzzy, pos, "fuzzy")?.to_string(),
+            distance,
+        }
+    } else {
+        LexicalTerm::Word(word.to_string())
+    };
+    let clause = LexicalClause {
+        occur,
+        field,
+        term,
+        position: start,
+    };
+    Ok((Token::Clause(clause), end))
+}
+
+/// Parse `"phrase"` with an optional `~slop` starting at the opening quote.
+fn parse_phrase(input: &str, quote: usize) -> Result<(LexicalTerm, usize)> {
+    let body = quote + 1;
+    let Some(len) = input[body..].find('"') else {
+        return Err(syntax_error(
+            quote,
+            "unterminated phrase: missing closing `\"`",
+        ));
+    };
+    let text = &input[body..body + len];
+    if text.trim().is_empty() {
+        return Err(syntax_error(quote, "empty phrase"));
+    }
+    let mut end = body + len + 1;
+    let mut slop = 0;
+    if input[end..].starts_with('~') {
+        let digits = word_at(input, end + 1);
+        slop = digits
+            .parse::<u32>()
+            .map_err(|_| syntax_error(end, "expected a number after `~`"))?;
+        end += 1 + digits.len();
+    }
+    if !word_at(input, end).is_empty() {
+        return Err(syntax_error(end, "expected whitespace after closing `\"`"));
+    }
+    let term = LexicalTerm::Phrase {
+        text: text.to_string(),
+        slop,
+    };
+    Ok((term, end))
+}
+
+/// `name` when `rest` starts with `name:` and `name` looks like a field.
+fn field_prefix(rest: &str) -> Option<&str> {
+    let name = &rest[..rest.find(':')?];
+    let is_field = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    is_field.then_some(name)
+}
+
+/// The run of non-whitespace characters starting at `pos`.
+fn word_at(input: &str, pos: usize) -> &str {
+    let rest = &input[pos..];
+    &rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())]
+}
+
+/// Fuzzy and prefix matching apply to one indexed token, so the word must
+/// not contain separators the tokenizer would split on.
+fn single_word<'a>(word: &'a str, pos: usize, kind: &str) -> Result<&'a str> {
+    if word.is_empty() || !word.chars().all(char::is_alphanumeric) {
+        return Err(syntax_error(
+            pos,
+            format!("{kind} matching needs a single word of letters or digits"),
+        ));
+    }
+    Ok(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_key(key: &str) -> bool {
+        matches!(key, "tags" | "author")
+    }
+
+    fn strict(input: &str) -> Result<Vec<LexicalClause>> {
+        parse_lexical_query(input, is_key)
+    }
+
+    fn lenient(input: &str) -> Vec<LexicalClause> {
+        parse_lexical_query_lenient(input, is_key)
+    }
+
+    fn word(text: &str) -> LexicalTerm {
+        LexicalTerm::Word(text.to_string())
+    }
+
+    #[test]
+    fn parses_phrases_operators_fuzzy_prefix_and_fields() {
+        let clauses =
+            strict(r#""blue green"~1 -kubernetes +heading:deploy* rollbak~ ci~0"#).unwrap();
+        let summary: Vec<_> = clauses
+            .iter()
+            .map(|c| (c.occur, c.field.clone(), c.term.clone(), c.position))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Occur::Should,
+                    LexicalField::Default,
+                    LexicalTerm::Phrase {
+                        text: "blue green".into(),
+                        slop: 1
+                    },
+                    0
+                ),
+                (
+                    Occur::MustNot,
+                    LexicalField::Default,
+                    word("kubernetes"),
+                    15
+                ),
+                (
+                    Occur::Must,
+                    LexicalField::Heading,
+                    LexicalTerm::Prefix("deploy".into()),
+                    27
+                ),
+                (
+                    Occur::Should,
+                    LexicalField::Default,
+                    LexicalTerm::Fuzzy {
+                        word: "rollbak".into(),
+                        distance: 1
+                    },
+                    44
+                ),
+                (
+                    Occur::Should,
+                    LexicalField::Default,
+                    LexicalTerm::Fuzzy {
+                        word: "ci".into(),
+                        distance: 0
+                    },
+                    53
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_query_and_or_keyword_yield_no_clauses() {
+        assert!(strict("  ").unwrap().is_empty());
+        let clauses = strict("rust OR go").unwrap();
+        assert_eq!(clauses.len(), 2);
+        assert_eq!(clauses[1].term, word("go"));
+    }
+
+    #[test]
+    fn plain_punctuation_stays_a_word() {
+        let clauses = strict("c++ foo-bar 10:30").unwrap();
+        let terms: Vec<_> = clauses.into_iter().map(|c| c.term).collect();
+        assert_eq!(terms, vec![word("c++"), word("foo-bar"), word("10:30")]);
+
+        let clauses = strict("error: timeout").unwrap();
+        assert_eq!(clauses[0].field, LexicalField::Default);
+        assert_eq!(clauses[0].term, word("error:"));
+
+        // Unknown keys are not fields.
+        let clauses = strict("http://example.com status:open").unwrap();
+        assert!(clauses.iter().all(|c| c.field == LexicalField::Default));
+        let terms: Vec<_> = clauses.into_iter().map(|c| c.term).collect();
+        assert_eq!(terms, vec![word("http://example.com"), word("status:open")]);
+    }
+
+    #[test]
+    fn other_field_names_scope_frontmatter_keys() {
+        let clauses = strict(r#"tags:postgres -author:"jane doe""#).unwrap();
+        assert_eq!(clauses[0].field, LexicalField::Frontmatter("tags".into()));
+        assert_eq!(clauses[0].term, word("postgres"));
+        assert_eq!(clauses[1].occur, Occur::MustNot);
+        assert_eq!(clauses[1].field, LexicalField::Frontmatter("author".into()));
+        assert_eq!(
+            clauses[1].term,
+            LexicalTerm::Phrase {
+                text: "jane doe".into(),
+                slop: 0
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_syntax_reports_position() {
+        let cases = [
+            (r#"deploy "blue green"#, 7),
+            ("rollback~3", 9),
+            ("heading:", 8),
+            ("ok -", 4),
+            ("foo-ba*", 0),
+            (r#""""#, 0),
+            (r#""a b"c"#, 5),
+            ("rust AND", 5),
+            ("AND rust", 0),
+            ("rust NOT NOT go", 9),
+        ];
+        for (input, expected) in cases {
+            match strict(input) {
+                Err(Error::LexicalQuery { position, .. }) => {
+                    assert_eq!(position, expected, "{input}")
+                }
+                other => panic!("{input}: expected a syntax error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn and_requires_both_sides_and_not_excludes() {
+        let occurs = |input: &str| -> Vec<_> {
+            strict(input)
+                .unwrap()
+                .into_iter()
+                .map(|c| (c.term, c.occur))
+                .collect()
+        };
+        assert_eq!(
+            occurs("R AND D budget"),
+            vec![
+                (word("R"), Occur::Must),
+                (word("D"), Occur::Must),
+                (word("budget"), Occur::Should),
+            ]
+        );
+        assert_eq!(
+            occurs("deploy AND NOT staging"),
+            vec![
+                (word("deploy"), Occur::Must),
+                (word("staging"), Occur::MustNot),
+            ]
+        );
+        assert_eq!(
+            occurs("rust and go"),
+            vec![
+                (word("rust"), Occur::Should),
+                (word("and"), Occur::Should),
+                (word("go"), Occur::Should),
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_parse_keeps_invalid_clauses_as_words() {
+        let terms =
+            |input: &str| -> Vec<_> { lenient(input).into_iter().map(|c| c.term).collect() };
+        assert_eq!(
+            terms(r#"deploy "blue green"#),
+            vec![word("deploy"), word("\"blue"), word("green")]
+        );
+        assert_eq!(
+            terms("edit ~/.config"),
+            vec![word("edit"), word("~/.config")]
+        );
+        assert_eq!(terms("ok -"), vec![word("ok"), word("-")]);
+        assert_eq!(terms("rust AND"), vec![word("rust")]);
+        assert_eq!(
+            lenient("-staging +deploy*")
+                .into_iter()
+                .map(|c| c.occur)
+                .collect::<Vec<_>>(),
+            vec![Occur::MustNot, Occur::Must]
+        );
+    }
+}
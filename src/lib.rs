@@ -1,24 +1,33 @@
 pub mod chunker;
 pub mod clustering;
 pub mod config;
+pub mod context;
 pub mod discovery;
 pub mod embedding;
 pub mod error;
+pub mod export;
+pub mod filter;
 pub mod formula;
 pub mod frontmatter_write;
+pub mod fsck;
 pub mod fts;
 pub mod index;
 pub mod ingest;
+pub mod lexical_query;
 pub mod links;
 pub mod logging;
 pub mod modules;
 pub mod parser;
 pub mod path_util;
 pub mod relations;
+pub mod rerank;
 pub mod schema;
 pub mod search;
 pub mod shard_analysis;
 pub mod shards;
+pub mod snapshot;
+pub mod snippet;
+pub mod suggest;
 pub mod tree;
 pub mod watcher;
 
@@ -26,14 +35,21 @@ pub use error::Error;
 
 // Re-export key public types for convenience.
 pub use config::{Config, VectorQuantization};
-pub use index::types::{ComputedFieldDiagnostic, ComputedFieldEntry, IndexStatus};
+pub use context::{ContextPack, ContextSection};
+pub use export::ExportInfo;
+pub use fsck::{FsckCheck, FsckProblem, FsckReport};
+pub use index::types::{
+    ComputedFieldDiagnostic, ComputedFieldEntry, IndexStatus, RecallEstimate, VectorStorageInfo,
+};
 pub use modules::{ModuleDescriptor, ModuleDiagnostic, ModuleEvent, ModuleReport};
 pub use schema::{
     FieldType, FormulaResultType, RelationDirection, Schema, SchemaField, ScopedSchema,
 };
 pub use search::{
-    EdgeSearchResult, GraphContextItem, MetadataFilter, SearchMode, SearchQuery, SearchResponse,
-    SearchResult, SearchResultChunk, SearchResultFile, SearchTimings, SortOrder,
+    CompareOp, EdgeSearchResult, FacetCounts, FusionMethod, GraphContextItem, LinkBoostExplanation,
+    MetadataFilter, RegexMatch, RerankOptions, ScoreExplanation, SearchMode, SearchQuery,
+    SearchResponse, SearchResult, SearchResultChunk, SearchResultFile, SearchTimings, SortOrder,
+    VectorStrategy,
 };
 pub use shard_analysis::{
     ClusterAnalysisStatus, GraphAnalysisContext, GraphAnalysisInfo, TopicAnalysisStatus,
@@ -41,6 +57,9 @@ pub use shard_analysis::{
 pub use shards::{
     ShardDefinition, ShardInfo, ShardList, ShardMutation, ShardStore, ShardTopicMutation,
 };
+pub use snapshot::{SnapshotInfo, SnapshotReason};
+pub use snippet::{SearchSnippet, SnippetHighlight, SnippetSource};
+pub use suggest::{Suggestion, SuggestionKind};
 // Additional re-exports for library consumers.
 pub use clustering::{
     ClusterInfo, ClusterState, CustomClusterDef, CustomClusterInfo, CustomClusterState,
@@ -76,11 +95,20 @@ use serde::{Deserialize, Serialize};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
+use crate::embedding::chunk_cache::ChunkEmbeddingCache;
 use crate::embedding::provider::{create_provider, EmbeddingProvider};
+use crate::embedding::query_cache::{self, QueryEmbeddingCache, QueryVectors};
 use crate::fts::FtsIndex;
 use crate::index::state::Index;
 use crate::index::storage::WriteOptions;
 use crate::index::types::EmbeddingConfig;
+use crate::rerank::provider::{create_reranker, Reranker};
+
+/// Stored chunk vectors used as queries by [`MarkdownVdb::estimate_recall`].
+const RECALL_QUERIES: usize = 32;
+
+/// Neighbors compared per query by [`MarkdownVdb::estimate_recall`].
+const RECALL_K: usize = 10;
 
 fn resolve_dimensions_from_existing_index(root: &Path, config: &mut Config) -> Result<bool> {
     if config.embedding_dimensions != 0 {
@@ -93,6 +121,9 @@ fn resolve_dimensions_from_existing_index(root: &Path, config: &mut Config) -> R
     let write_options = WriteOptions {
         quantization: config.vector_quantization.clone(),
         compress_metadata: config.index_compression,
+        wal_max_bytes: config.index_wal_max_bytes,
+        wal_max_age_secs: config.index_wal_max_age_secs,
+        rescore_factor: config.index_rescore_factor,
     };
     let index = match Index::open_with_options(&index_path, write_options) {
         Ok(index) => index,
@@ -452,6 +483,8 @@ pub struct VaultInfo {
     pub reindex_estimated_api_calls: usize,
     /// Index file size on disk in bytes (whole index file — never scoped).
     pub index_file_size: u64,
+    /// Quantization, sizes and rescoring of the stored vectors (whole index).
+    pub vector_storage: VectorStorageInfo,
     /// Embedding provider/model/dimensions snapshot from the index.
     pub embedding: index::types::EmbeddingConfig,
     /// Sync-state breakdown of disk files vs the index.
@@ -516,6 +549,9 @@ pub struct IngestResult {
     pub api_calls: usize,
     /// Provider-independent local count of inputs successfully embedded.
     pub estimated_input_tokens: usize,
+    /// Number of chunk embeddings reused from the persistent embedding cache
+    /// instead of calling the provider.
+    pub embeddings_cached: usize,
     /// Number of files that failed to ingest.
     pub files_failed: usize,
     /// Errors encountered during ingestion.
@@ -529,6 +565,9 @@ pub struct IngestResult {
     pub timings: Option<IngestTimings>,
     /// Whether the ingestion was cancelled before completion.
     pub cancelled: bool,
+    /// ID of the snapshot taken before a full reindex replaced the index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
 }
 
 /// A single ingestion error for a specific file.
@@ -540,6 +579,39 @@ pub struct IngestError {
     pub message: String,
 }
 
+/// Embedding cache usage, returned by [`MarkdownVdb::cache_stats`].
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    /// Cached document embeddings, one entry per vector space.
+    pub chunk_spaces: Vec<embedding::chunk_cache::ChunkCacheSpace>,
+    /// Number of cached query embeddings.
+    pub query_entries: usize,
+    /// Size of the query cache file in bytes.
+    pub query_bytes: u64,
+    /// Combined size of both caches in bytes.
+    pub total_bytes: u64,
+}
+
+/// Outcome of [`MarkdownVdb::cache_prune`] and [`MarkdownVdb::cache_clear`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CachePruneReport {
+    /// Cached document embeddings removed.
+    pub chunk_entries_removed: usize,
+    /// Cached query embeddings removed.
+    pub query_entries_removed: usize,
+    /// Disk space released in bytes.
+    pub bytes_freed: u64,
+}
+
+/// Outcome of [`MarkdownVdb::compact`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompactReport {
+    /// Size of the write-ahead log folded into the index file, in bytes.
+    pub wal_bytes: u64,
+    /// Size of the rewritten index file, in bytes.
+    pub index_bytes: u64,
+}
+
 /// Information about an indexed document.
 #[derive(Debug, Clone, Serialize)]
 pub struct DocumentInfo {
@@ -594,6 +666,7 @@ pub struct CollectionQuery {
     /// Sort direction.
     pub order: SortOrder,
     /// Metadata filters (AND logic), reusing the search engine's `MetadataFilter`.
+    /// Boolean expressions parse into a single filter via `MetadataFilter::from_str`.
     pub filters: Vec<MetadataFilter>,
     /// Max rows to return after filtering+sorting. None = all rows.
     pub limit: Option<usize>,
@@ -601,6 +674,8 @@ pub struct CollectionQuery {
     pub offset: usize,
     /// Resolve frontmatter relations on the returned page rows (depth 1).
     pub populate: bool,
+    /// Frontmatter fields to count values for over the filtered rows.
+    pub facets: Vec<String>,
 }
 
 /// Top-level response for [`MarkdownVdb::collection`]: columns + the paginated
@@ -622,6 +697,10 @@ pub struct CollectionResponse {
     pub limit: Option<usize>,
     /// Echo of the applied offset.
     pub offset: usize,
+    /// Value → row count per requested facet field, over all filtered rows
+    /// (before limit/offset). Key omitted from JSON when no facets were requested.
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub facets: search::FacetCounts,
 }
 
 /// One table column. Derived from the scoped `Schema`'s `SchemaField`, plus `in_schema`.
@@ -1232,6 +1311,9 @@ pub struct MarkdownVdb {
     /// Commands like `tree` and `status` never need embeddings,
     /// so we defer creation to avoid requiring API keys for read-only ops.
     provider: Mutex<Option<Arc<dyn EmbeddingProvider>>>,
+    /// Reranker instance, lazily initialized on the first search when a
+    /// `rerank.provider` is configured.
+    reranker: Mutex<Option<Arc<dyn Reranker>>>,
     /// Vector index (Arc for sharing with watcher).
     index: Arc<Index>,
     /// Full-text search index (Arc for sharing with watcher).
@@ -1249,6 +1331,12 @@ pub struct MarkdownVdb {
     /// space. Lexical operations remain available; vector operations require
     /// a full reindex.
     embedding_compatible: AtomicBool,
+    /// Typeahead table for [`suggest`](Self::suggest), rebuilt on first use
+    /// after the index changes.
+    suggest_index: Mutex<Option<Arc<suggest::SuggestIndex>>>,
+    /// Persistent chunk embedding cache; one handle so its offset indexes
+    /// survive across ingests.
+    chunk_cache: ChunkEmbeddingCache,
 }
 
 impl MarkdownVdb {
@@ -1369,15 +1457,25 @@ impl MarkdownVdb {
         let write_options = WriteOptions {
             quantization: config.vector_quantization.clone(),
             compress_metadata: config.index_compression,
+            wal_max_bytes: config.index_wal_max_bytes,
+            wal_max_age_secs: config.index_wal_max_age_secs,
+            rescore_factor: config.index_rescore_factor,
         };
         let fts_path = index_dir.join("fts");
         // Take the long-lived FTS writer before the project transaction lock,
         // matching watcher event ordering. If Tantivy itself must be created,
-        // record the split before that companion-store mutation as well.
-        if !fts_path.join("meta.json").is_file() {
+        // or recreated because its schema is outdated, record the split before
+        // that companion-store mutation as well.
+        let lexical_settings = config.lexical_settings();
+        if !fts_path.join("meta.json").is_file()
+            || fts::index_outdated(&fts_path, &lexical_settings)?
+        {
             fts::begin_reconciliation(&root)?;
         }
-        let fts_index = Arc::new(FtsIndex::open_or_create(&fts_path)?);
+        let fts_index = Arc::new(
+            FtsIndex::open_or_create_with_settings(&fts_path, &lexical_settings)?
+                .with_field_boosts(config.search_lexical_boosts.clone()),
+        );
         let (index, rebuilt_index) = {
             // Serialize inspection and any replacement of the vector
             // generation. The hook is invoked before an incompatible archive
@@ -1455,6 +1553,9 @@ impl MarkdownVdb {
         let write_options = WriteOptions {
             quantization: config.vector_quantization.clone(),
             compress_metadata: config.index_compression,
+            wal_max_bytes: config.index_wal_max_bytes,
+            wal_max_age_secs: config.index_wal_max_age_secs,
+            rescore_factor: config.index_rescore_factor,
         };
         // Read-only commands must never delete/recreate an incompatible index.
         // A mutating open/ingest performs the automatic rebuild instead.
@@ -1465,8 +1566,15 @@ impl MarkdownVdb {
         // Read-only commands that never consult FTS (tree, get, links,
         // semantic search) must keep working while a writable process ingests
         // or after one was interrupted; FTS-dependent queries check the flag.
-        let fts_pending_reconcile = fts::reconciliation_required(&root)?;
-        let fts_index = Arc::new(FtsIndex::open_readonly(&fts_path)?);
+        // An FTS index with an outdated schema or lexical settings awaits
+        // the same rebuild.
+        let lexical_settings = config.lexical_settings();
+        let fts_pending_reconcile = fts::reconciliation_required(&root)?
+            || fts::index_outdated(&fts_path, &lexical_settings)?;
+        let fts_index = Arc::new(
+            FtsIndex::open_readonly_with_settings(&fts_path, &lexical_settings)?
+                .with_field_boosts(config.search_lexical_boosts.clone()),
+        );
 
         Self::finish_open(
             root,
@@ -1527,15 +1635,19 @@ impl MarkdownVdb {
             "opened markdown-vdb"
         );
 
+        let chunk_cache = ChunkEmbeddingCache::new(&root);
         Ok(Self {
             root,
             config,
             provider: Mutex::new(None),
+            reranker: Mutex::new(None),
             index,
             fts_index,
             fts_pending_reconcile,
             index_rebuilt: AtomicBool::new(index_rebuilt),
             embedding_compatible: AtomicBool::new(compatible),
+            suggest_index: Mutex::new(None),
+            chunk_cache,
         })
     }
 
@@ -1715,6 +1827,21 @@ impl MarkdownVdb {
         Ok(p)
     }
 
+    /// Return the configured reranker, or `None` when reranking is disabled.
+    /// Lazily creates the reranker on first call.
+    fn ensure_reranker(&self) -> Result<Option<Arc<dyn Reranker>>> {
+        let mut guard = self.reranker.lock();
+        if let Some(ref r) = *guard {
+            return Ok(Some(Arc::clone(r)));
+        }
+        let Some(r) = create_reranker(&self.config)? else {
+            return Ok(None);
+        };
+        let r: Arc<dyn Reranker> = Arc::from(r);
+        *guard = Some(Arc::clone(&r));
+        Ok(Some(r))
+    }
+
     /// Get a reference to the embedding provider.
     /// Lazily creates the provider on first call.
     pub fn provider(&self) -> Result<Arc<dyn EmbeddingProvider>> {
@@ -1807,12 +1934,14 @@ impl MarkdownVdb {
                 chunks_created: 0,
                 api_calls: 0,
                 estimated_input_tokens: 0,
+                embeddings_cached: 0,
                 files_failed: 0,
                 errors: Vec::new(),
                 module_reports: Vec::new(),
                 duration_secs: start_time.elapsed().as_secs_f64(),
                 timings: None,
                 cancelled: true,
+                snapshot: None,
             });
         }
 
@@ -1841,12 +1970,14 @@ impl MarkdownVdb {
             chunks_created: 0,
             api_calls: 0,
             estimated_input_tokens: 0,
+            embeddings_cached: 0,
             files_failed: 0,
             errors: Vec::new(),
             module_reports: Vec::new(),
             duration_secs: 0.0,
             timings: None,
             cancelled: false,
+            snapshot: None,
         };
 
         // Parse all files and collect chunks + hashes.
@@ -2002,8 +2133,7 @@ impl MarkdownVdb {
                             });
                         }
                         std::collections::hash_map::Entry::Occupied(canonical) => {
-                            edge_embedding_aliases
-                                .insert(edge_id.clone(), canonical.get().clone());
+                            edge_embedding_aliases.insert(edge_id.clone(), canonical.get().clone());
                         }
                     }
                     edge_metas.push(EdgeMeta {
@@ -2065,7 +2195,8 @@ impl MarkdownVdb {
                     api_calls: progress.api_calls,
                 });
             };
-            embedding::batch::embed_chunks(
+            let chunk_cache = self.chunk_cache();
+            embedding::batch::embed_chunks_cached(
                 if embedding_space_changed {
                     self.ensure_provider_unchecked()?
                 } else {
@@ -2076,6 +2207,7 @@ impl MarkdownVdb {
                 &embed_existing,
                 &embed_current,
                 self.config.embedding_batch_size,
+                chunk_cache.as_ref(),
                 Some(&on_batch),
             )
             .await?
@@ -2084,6 +2216,7 @@ impl MarkdownVdb {
 
         result.api_calls = embed_result.api_calls;
         result.estimated_input_tokens = embed_result.estimated_input_tokens;
+        result.embeddings_cached = embed_result.cache_hits;
 
         // Check cancellation after embedding.
         if is_cancelled() {
@@ -2094,6 +2227,18 @@ impl MarkdownVdb {
             return Ok(result);
         }
 
+        // Keep a way back before a full reindex replaces the index. The
+        // module-run lock held since the start keeps the on-disk generation
+        // unchanged, and nothing has been written yet.
+        if options.full
+            && self.config.index_snapshot_before_reindex
+            && self.index.status().document_count > 0
+        {
+            let info = snapshot::create(&self.root, SnapshotReason::PreReindex)?;
+            snapshot::prune(&self.root, self.config.index_snapshot_retention)?;
+            result.snapshot = Some(info.id);
+        }
+
         // Only replace the in-memory vector generation after every embedding
         // request succeeded and the final pre-mutation cancellation barrier
         // passed. The previous on-disk generation remains recoverable until
@@ -2160,7 +2305,8 @@ impl MarkdownVdb {
                 })
                 .collect();
             let path_str_fts = path_util::to_slash(path);
-            self.fts_index.upsert_chunks(&path_str_fts, &fts_chunks)?;
+            self.fts_index
+                .upsert_file(&path_str_fts, &fts_chunks, md.frontmatter.as_ref())?;
 
             result.files_indexed += 1;
             result.chunks_created += chunks.len();
@@ -2380,7 +2526,12 @@ impl MarkdownVdb {
                         })
                         .collect();
                     if !fts_chunks.is_empty() {
-                        self.fts_index.upsert_chunks(path_str, &fts_chunks)?;
+                        let frontmatter: Option<serde_json::Value> = file_entry
+                            .frontmatter
+                            .as_deref()
+                            .and_then(|json| serde_json::from_str(json).ok());
+                        self.fts_index
+                            .upsert_file(path_str, &fts_chunks, frontmatter.as_ref())?;
                     }
                 }
             }
@@ -2733,6 +2884,9 @@ impl MarkdownVdb {
         self.fts_index.commit()?;
         fts::finish_reconciliation(&self.root)?;
         write_embedding_space(&self.root, &self.config)?;
+        if let Some(cache) = self.chunk_cache() {
+            cache.compact_for_index(&self.index);
+        }
 
         let save_secs = save_start.elapsed().as_secs_f64();
 
@@ -2785,26 +2939,191 @@ impl MarkdownVdb {
         }
     }
 
-    /// Execute a search query against the index.
-    pub async fn search(&self, query: search::SearchQuery) -> Result<search::SearchResponse> {
+    /// Fill config-backed query defaults and check the FTS projection is
+    /// usable for the query's mode.
+    fn prepare_search_query(&self, query: &mut search::SearchQuery) -> Result<()> {
+        // Diversification and fusion defaults come from config; the engine
+        // treats `None` as disabled / RRF so direct `search::search` callers
+        // opt in explicitly.
+        if query.diversity.is_none() {
+            query.diversity = self.config.search_diversity;
+        }
+        if query.max_per_file.is_none() {
+            query.max_per_file = self.config.search_max_per_file;
+        }
+        if query.fusion.is_none() {
+            query.fusion = Some(self.config.search_fusion);
+        }
+        if query.semantic_weight.is_none() {
+            query.semantic_weight = Some(self.config.search_semantic_weight);
+        }
+        if query.rerank && query.reranker.is_none() {
+            query.reranker = self
+                .ensure_reranker()?
+                .map(|reranker| search::RerankOptions {
+                    reranker,
+                    top_n: self.config.rerank_top_n,
+                });
+        }
         // Lexical and hybrid retrieval read the FTS projection, which a
-        // pending reconciliation marks as possibly stale. Semantic queries
-        // never consult it and stay available on a read-only instance.
-        if self.fts_pending_reconcile && query.mode != SearchMode::Semantic {
+        // pending reconciliation marks as possibly stale. Semantic and regex
+        // queries never consult it and stay available on a read-only instance.
+        if self.fts_pending_reconcile
+            && !matches!(query.mode, SearchMode::Semantic | SearchMode::Regex)
+        {
             return Err(Error::Fts(
                 "FTS reconciliation is pending; open the project in writable mode to repair it"
                     .to_string(),
             ));
         }
-        // Pure lexical retrieval does not use vectors. Avoid constructing a
-        // cloud provider (and allow lexical access to an index whose embedding
-        // descriptor changed) unless graph expansion explicitly needs a query
-        // embedding.
-        let pure_lexical = query.mode == SearchMode::Lexical
+        // Reject malformed lexical syntax or patterns before any provider
+        // call. Hybrid queries are parsed leniently and never rejected.
+        match query.mode {
+            SearchMode::Regex => {
+                search::compile_regex(&query.query)?;
+            }
+            SearchMode::Lexical => {
+                self.fts_index.parse_query(&query.query, true)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pure lexical (or regex) retrieval does not use vectors unless graph
+    /// expansion explicitly needs a query embedding.
+    fn is_pure_lexical(&self, query: &search::SearchQuery) -> bool {
+        matches!(query.mode, SearchMode::Lexical | SearchMode::Regex)
             && query
                 .expand_graph
                 .unwrap_or(self.config.search_expand_graph)
-                == 0;
+                == 0
+    }
+
+    /// The project's persistent chunk embedding cache, or `None` when
+    /// `cache.chunk_embeddings` is off.
+    fn chunk_cache(&self) -> Option<ChunkEmbeddingCache> {
+        self.config
+            .chunk_embedding_cache
+            .then(|| self.chunk_cache.clone())
+    }
+
+    /// The project's on-disk query-embedding cache, or `None` when
+    /// `cache.query_max_entries` is 0.
+    fn query_cache(&self) -> Option<QueryEmbeddingCache> {
+        QueryEmbeddingCache::new(
+            &self.root,
+            self.config.query_cache_max_entries,
+            self.config.query_cache_ttl_secs,
+        )
+    }
+
+    /// The query cache file for maintenance, whether or not caching is on.
+    fn query_cache_file(&self) -> QueryEmbeddingCache {
+        QueryEmbeddingCache::open(
+            &self.root,
+            self.config.query_cache_max_entries,
+            self.config.query_cache_ttl_secs,
+        )
+    }
+
+    /// Run a prepared query. With `vector` the query embedding is already
+    /// known; otherwise `provider` embeds the query if the mode needs it.
+    async fn run_prepared_search(
+        &self,
+        query: &search::SearchQuery,
+        vector: Option<Vec<f32>>,
+        provider: &dyn EmbeddingProvider,
+    ) -> Result<search::SearchResponse> {
+        let query_vector = vector.clone().filter(|_| query.snippets);
+        let mut response = match vector {
+            Some(vector) => {
+                search::search_with_query_vector(
+                    query,
+                    &self.index,
+                    vector,
+                    Some(&self.fts_index),
+                    self.config.search_rrf_k,
+                    self.config.bm25_norm_k,
+                    self.config.search_decay_enabled,
+                    self.config.search_decay_half_life,
+                    &self.config.search_decay_exclude,
+                    &self.config.search_decay_include,
+                    self.config.search_boost_links,
+                    self.config.search_boost_hops,
+                    self.config.search_expand_graph,
+                    self.config.search_expand_limit,
+                    self.config.edge_boost_weight,
+                )
+                .await?
+            }
+            None => {
+                search::search(
+                    query,
+                    &self.index,
+                    provider,
+                    Some(&self.fts_index),
+                    self.config.search_rrf_k,
+                    self.config.bm25_norm_k,
+                    self.config.search_decay_enabled,
+                    self.config.search_decay_half_life,
+                    &self.config.search_decay_exclude,
+                    &self.config.search_decay_include,
+                    self.config.search_boost_links,
+                    self.config.search_boost_hops,
+                    self.config.search_expand_graph,
+                    self.config.search_expand_limit,
+                    self.config.edge_boost_weight,
+                )
+                .await?
+            }
+        };
+
+        if query.populate {
+            self.populate_result_relations(&mut response);
+        }
+        // A query that already finds a strong match needs no correction.
+        let weak = response
+            .results
+            .first()
+            .is_none_or(|top| top.score <= fts::SPELLING_SUGGESTION_MAX_SCORE);
+        if weak && matches!(query.mode, SearchMode::Hybrid | SearchMode::Lexical) {
+            let suggest_start = std::time::Instant::now();
+            // Suggestions are advisory; the results stand without them.
+            response.suggestions = self
+                .fts_index
+                .spelling_suggestions(&query.query, fts::SPELLING_SUGGESTIONS_PER_TERM)
+                .unwrap_or_else(|error| {
+                    warn!(%error, "failed to compute spelling suggestions");
+                    Vec::new()
+                });
+            response.timings.suggest_secs = suggest_start.elapsed().as_secs_f64();
+            response.timings.total_secs += response.timings.suggest_secs;
+        }
+        if query.snippets {
+            // The pure lexical provider is a mock; its embeddings rank nothing.
+            let provider = Some(provider).filter(|_| !self.is_pure_lexical(query));
+            snippet::attach_snippets(
+                &mut response,
+                query,
+                &self.fts_index,
+                provider,
+                query_vector,
+            )
+            .await?;
+        }
+        Ok(response)
+    }
+
+    /// Execute a search query against the index.
+    ///
+    /// Unless `query.cache` is false, the query embedding is read from (or
+    /// stored in) the on-disk query cache; `timings.cache_hit` reports which.
+    pub async fn search(&self, mut query: search::SearchQuery) -> Result<search::SearchResponse> {
+        self.prepare_search_query(&mut query)?;
+        let pure_lexical = self.is_pure_lexical(&query);
+        // Avoid constructing a cloud provider (and allow lexical access to an
+        // index whose embedding descriptor changed) for pure lexical queries.
         let provider: Arc<dyn EmbeddingProvider> = if pure_lexical {
             Arc::new(embedding::mock::MockProvider::new(
                 self.config.embedding_dimensions,
@@ -2812,13 +3131,239 @@ impl MarkdownVdb {
         } else {
             self.ensure_provider()?
         };
-        let mut response = search::search(
+        let cache = if query.cache && !pure_lexical && !query.query.trim().is_empty() {
+            self.query_cache()
+        } else {
+            None
+        };
+        let Some(cache) = cache else {
+            return self
+                .run_prepared_search(&query, None, provider.as_ref())
+                .await;
+        };
+
+        let texts = [query.query.clone()];
+        let embedded = query_cache::embed_queries(Some(&cache), provider.as_ref(), &texts).await?;
+        let vector = embedded.vectors.into_iter().next();
+        let mut response = self
+            .run_prepared_search(&query, vector, provider.as_ref())
+            .await?;
+        let timings = &mut response.timings;
+        timings.embed_secs = embedded.embed_secs;
+        timings.cache_lookup_secs = embedded.cache_lookup_secs;
+        timings.cache_hit = embedded.cache_hits[0];
+        timings.total_secs += embedded.embed_secs + embedded.cache_lookup_secs;
+        Ok(response)
+    }
+
+    /// Execute many search queries against this already-open index.
+    ///
+    /// Every query that needs a vector is looked up in the on-disk query
+    /// cache (unless its `cache` flag is false) and the misses are embedded
+    /// together in one
+    /// [`EmbeddingPurpose::Query`](embedding::provider::EmbeddingPurpose::Query)
+    /// provider call up front; the queries then run in order exactly as in
+    /// [`search`](Self::search), except that the shared `timings.embed_secs`
+    /// and `timings.cache_lookup_secs` are 0. Results are returned in input
+    /// order; a query that fails (e.g. a malformed `where` expression) yields
+    /// its own `Err` without affecting the others. The outer `Err` is reserved
+    /// for failures shared by the whole batch, such as the provider call.
+    pub async fn search_batch(
+        &self,
+        mut queries: Vec<search::SearchQuery>,
+    ) -> Result<Vec<Result<search::SearchResponse>>> {
+        let mut outcomes: Vec<Option<Result<search::SearchResponse>>> = queries
+            .iter_mut()
+            .map(|query| self.prepare_search_query(query).err().map(Err))
+            .collect();
+
+        let needs_vector: Vec<bool> = queries
+            .iter()
+            .zip(&outcomes)
+            .map(|(q, outcome)| {
+                outcome.is_none() && !self.is_pure_lexical(q) && !q.query.trim().is_empty()
+            })
+            .collect();
+        let texts = |cache: bool| -> Vec<String> {
+            queries
+                .iter()
+                .zip(&needs_vector)
+                .filter(|(q, needs)| **needs && q.cache == cache)
+                .map(|(q, _)| q.query.clone())
+                .collect()
+        };
+        let (cached_texts, uncached_texts) = (texts(true), texts(false));
+        let provider = if needs_vector.contains(&true) {
+            Some(self.ensure_provider()?)
+        } else {
+            None
+        };
+        let (cached, uncached) = if let Some(provider) = &provider {
+            let cache = self.query_cache();
+            (
+                query_cache::embed_queries(cache.as_ref(), provider.as_ref(), &cached_texts)
+                    .await?,
+                query_cache::embed_queries(None, provider.as_ref(), &uncached_texts).await?,
+            )
+        } else {
+            (QueryVectors::default(), QueryVectors::default())
+        };
+        let mut cached = cached.vectors.into_iter().zip(cached.cache_hits);
+        let mut uncached = uncached.vectors.into_iter().zip(uncached.cache_hits);
+
+        // Queries without a vector are pure lexical or empty; neither embeds.
+        let lexical_provider = embedding::mock::MockProvider::new(self.config.embedding_dimensions);
+
+        for ((query, needs_vector), outcome) in queries.iter().zip(needs_vector).zip(&mut outcomes)
+        {
+            if outcome.is_some() {
+                continue;
+            }
+            let embedded = match (needs_vector, query.cache) {
+                (false, _) => None,
+                (true, true) => cached.next(),
+                (true, false) => uncached.next(),
+            };
+            let (vector, cache_hit) = embedded.unzip();
+            // Only snippets use the provider once the vector is embedded.
+            let provider: &dyn EmbeddingProvider = match &provider {
+                Some(provider) if needs_vector => provider.as_ref(),
+                _ => &lexical_provider,
+            };
+            let response =
+                self.run_prepared_search(query, vector, provider)
+                    .await
+                    .map(|mut response| {
+                        response.timings.cache_hit = cache_hit.flatten();
+                        response
+                    });
+            *outcome = Some(response);
+        }
+        Ok(outcomes.into_iter().flatten().collect())
+    }
+
+    /// Complete `prefix` to note titles, frontmatter aliases, and headings
+    /// for "jump to note/section" typeahead.
+    ///
+    /// Matches the start of the text or of any later word, falling back to
+    /// fuzzy prefix matches (one edit for 3–5 characters, two beyond) when
+    /// fewer than `limit` match exactly. Served from an in-memory table that
+    /// is built on the first call and rebuilt only after the index changes,
+    /// so repeated lookups never touch the embedding provider or the FTS
+    /// index.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<Suggestion> {
+        let table = {
+            let mut cached = self.suggest_index.lock();
+            let revision = self.index.revision();
+            match cached.as_ref().filter(|table| table.revision() == revision) {
+                Some(table) => Arc::clone(table),
+                None => {
+                    let table = Arc::new(suggest::SuggestIndex::build(&self.index));
+                    *cached = Some(Arc::clone(&table));
+                    table
+                }
+            }
+        };
+        table.suggest(prefix, limit)
+    }
+
+    /// Populate relations on ranked results.
+    ///
+    /// Populate is post-processing in the search wrappers (the engine ignores
+    /// the flag) so overlay/index context never threads through its signature.
+    /// Only ranked results are populated — graph_context stays untouched.
+    fn populate_result_relations(&self, response: &mut search::SearchResponse) {
+        let ctx = self.relation_context();
+        let empty = serde_json::json!({});
+        for result in &mut response.results {
+            let frontmatter = result.file.frontmatter.as_ref().unwrap_or(&empty);
+            result.file.relations =
+                Some(self.compute_relations(&result.file.path, frontmatter, &ctx));
+        }
+    }
+
+    /// Retrieve context for `query` and pack it into `budget` tokens.
+    ///
+    /// Runs [`search`](Self::search) and greedily packs the ranked chunks —
+    /// then any graph context items, when `query.expand_graph` is set — into
+    /// a citation-headed markdown block. `query.limit` bounds the number of
+    /// candidates considered (see [`context::DEFAULT_CONTEXT_CANDIDATES`]).
+    pub async fn context(
+        &self,
+        query: search::SearchQuery,
+        budget: usize,
+    ) -> Result<context::ContextPack> {
+        let query_text = query.query.clone();
+        let response = self.search(query).await?;
+        Ok(context::pack_context(&query_text, &response, budget))
+    }
+
+    /// Find content similar to an indexed file or chunk ("more like this").
+    ///
+    /// `seed` is a vault-relative file path, or `path#N` for a single chunk.
+    /// A file seed uses the mean of its stored chunk vectors; a chunk seed
+    /// uses that chunk's stored vector. No embedding provider call is made.
+    ///
+    /// Filters, path prefix, decay, link boosting and diversification come
+    /// from `query` exactly as in [`search`](Self::search); `query.query` and
+    /// `query.mode` are ignored. The seed chunk is never returned, and the
+    /// rest of the seed file is excluded unless `include_seed_file` is set.
+    ///
+    /// Returns [`Error::FileNotInIndex`] when the seed file, chunk or its
+    /// stored vector is missing.
+    pub async fn similar(
+        &self,
+        seed: &str,
+        mut query: search::SearchQuery,
+        include_seed_file: bool,
+    ) -> Result<search::SearchResponse> {
+        if !self.embedding_compatible.load(Ordering::Acquire) {
+            return Err(Error::Config(
+                "embedding provider, model, or dimensions changed; run `mdvdb ingest --reindex` before using vector operations"
+                    .into(),
+            ));
+        }
+
+        let seed = path_util::normalize_path_input(seed);
+        let seed = seed.strip_prefix("./").unwrap_or(&seed);
+        let (path, chunk_id) = match seed.rsplit_once('#') {
+            Some((path, n)) if n.parse::<usize>().is_ok() => (path, Some(seed)),
+            _ => (seed, None),
+        };
+        let file = self
+            .index
+            .get_file_metadata(path)
+            .ok_or_else(|| Error::FileNotInIndex {
+                path: PathBuf::from(path),
+            })?;
+
+        let seed_vector = match chunk_id {
+            Some(chunk_id) => self.index.get_vectors_for(&[chunk_id]).remove(chunk_id),
+            None => self.index.get_file_vector(path),
+        }
+        .ok_or_else(|| Error::FileNotInIndex {
+            path: PathBuf::from(seed),
+        })?;
+
+        let mut exclude: HashSet<String> = HashSet::new();
+        if include_seed_file {
+            exclude.extend(chunk_id.map(str::to_string));
+        } else {
+            exclude.extend(file.chunk_ids);
+        }
+
+        query.query = seed.to_string();
+        if query.diversity.is_none() {
+            query.diversity = self.config.search_diversity;
+        }
+        if query.max_per_file.is_none() {
+            query.max_per_file = self.config.search_max_per_file;
+        }
+        let mut response = search::search_similar(
             &query,
             &self.index,
-            provider.as_ref(),
-            Some(&self.fts_index),
-            self.config.search_rrf_k,
-            self.config.bm25_norm_k,
+            seed_vector,
+            &exclude,
             self.config.search_decay_enabled,
             self.config.search_decay_half_life,
             &self.config.search_decay_exclude,
@@ -2831,17 +3376,8 @@ impl MarkdownVdb {
         )
         .await?;
 
-        // Populate is post-processing in this wrapper (the engine ignores the
-        // flag) so overlay/index context never threads through its signature.
-        // Only ranked results are populated — graph_context stays untouched.
         if query.populate {
-            let ctx = self.relation_context();
-            let empty = serde_json::json!({});
-            for result in &mut response.results {
-                let frontmatter = result.file.frontmatter.as_ref().unwrap_or(&empty);
-                result.file.relations =
-                    Some(self.compute_relations(&result.file.path, frontmatter, &ctx));
-            }
+            self.populate_result_relations(&mut response);
         }
 
         Ok(response)
@@ -2972,12 +3508,179 @@ impl MarkdownVdb {
             reindex_estimated_tokens,
             reindex_estimated_api_calls,
             index_file_size: status.file_size,
+            vector_storage: self.index.vector_storage(),
             embedding: status.embedding_config,
             sync,
             last_updated: status.last_updated,
         })
     }
 
+    /// Measure vector search recall@10 against an exhaustive exact search,
+    /// with and without rescoring (see [`Index::estimate_recall`]). Reads
+    /// every chunk vector, so it is opt-in: `mdvdb info --recall`.
+    pub fn estimate_recall(&self) -> Result<RecallEstimate> {
+        self.index.estimate_recall(RECALL_QUERIES, RECALL_K)
+    }
+
+    /// Report usage of the persistent chunk and query embedding caches.
+    pub fn cache_stats(&self) -> Result<CacheStats> {
+        let chunk_spaces = self.chunk_cache.stats()?;
+        let (query_entries, query_bytes) = self.query_cache_file().stats();
+        let total_bytes = query_bytes + chunk_spaces.iter().map(|space| space.bytes).sum::<u64>();
+        Ok(CacheStats {
+            chunk_spaces,
+            query_entries,
+            query_bytes,
+            total_bytes,
+        })
+    }
+
+    /// Drop cached document embeddings whose text no longer appears in the
+    /// index (in every vector space), and expired or over-cap query
+    /// embeddings.
+    pub fn cache_prune(&self) -> Result<CachePruneReport> {
+        let query_cache = self.query_cache_file();
+        let (_, query_before) = query_cache.stats();
+        let query_entries_removed = query_cache.prune()?;
+        let (_, query_after) = query_cache.stats();
+        let keep = self.index.embedding_input_hashes();
+        let (chunk_entries_removed, chunk_freed) = self.chunk_cache.prune(&keep)?;
+        Ok(CachePruneReport {
+            chunk_entries_removed,
+            query_entries_removed,
+            bytes_freed: chunk_freed + query_before.saturating_sub(query_after),
+        })
+    }
+
+    /// Delete every cached document and query embedding.
+    pub fn cache_clear(&self) -> Result<CachePruneReport> {
+        let query_cache = self.query_cache_file();
+        let (_, query_bytes) = query_cache.stats();
+        let query_entries_removed = query_cache.clear()?;
+        let (chunk_entries_removed, chunk_freed) = self.chunk_cache.clear()?;
+        Ok(CachePruneReport {
+            chunk_entries_removed,
+            query_entries_removed,
+            bytes_freed: chunk_freed + query_bytes,
+        })
+    }
+
+    /// Snapshot the index, FTS index and Shard analysis caches under
+    /// `.markdownvdb/snapshots/`, then prune snapshots beyond
+    /// `index.snapshot_retention`.
+    pub fn snapshot_create(&self) -> Result<SnapshotInfo> {
+        let _module_run_lock = modules::acquire_module_run_lock(&self.root)?;
+        let info = snapshot::create(&self.root, SnapshotReason::Manual)?;
+        snapshot::prune(&self.root, self.config.index_snapshot_retention)?;
+        Ok(info)
+    }
+
+    /// List snapshots, oldest first.
+    pub fn snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        snapshot::list(&self.root)
+    }
+
+    /// Delete all but the newest `keep` snapshots (default:
+    /// `index.snapshot_retention`; 0 keeps all). Returns the deleted ones.
+    pub fn snapshot_prune(&self, keep: Option<usize>) -> Result<Vec<SnapshotInfo>> {
+        let _module_run_lock = modules::acquire_module_run_lock(&self.root)?;
+        snapshot::prune(
+            &self.root,
+            keep.unwrap_or(self.config.index_snapshot_retention),
+        )
+    }
+
+    /// Replace the index of the project at `root` with snapshot `id`.
+    ///
+    /// This does not open the project: no other process may hold it open for
+    /// writing, and instances opened before the restore keep serving the old
+    /// index. The configured embedding model is not changed; if the snapshot
+    /// was taken with another one, vector operations require reverting the
+    /// configuration (or a reindex).
+    pub fn restore_snapshot(root: &Path, id: &str) -> Result<SnapshotInfo> {
+        let root = root.canonicalize().map_err(|e| {
+            Error::Config(format!(
+                "cannot canonicalize root '{}': {e}",
+                root.display()
+            ))
+        })?;
+        let _module_run_lock = modules::acquire_module_run_lock(&root)?;
+        snapshot::restore(&root, id)
+    }
+
+    /// Write the index to the empty or missing directory `dir` as versioned
+    /// JSONL plus a `vectors.npy` file, readable by other mdvdb versions and
+    /// other tools. See [`export`] for the format.
+    pub fn export(&self, dir: &Path) -> Result<ExportInfo> {
+        export::export(&self.index, &self.root, dir)
+    }
+
+    /// Replace the index of the project at `root` with the export in `dir`,
+    /// rebuilding the vector and full-text indexes from the exported vectors
+    /// without calling the embedding provider.
+    ///
+    /// Like [`restore_snapshot`](Self::restore_snapshot), this does not open
+    /// the project, fails while another process holds it open for writing,
+    /// and leaves the configured embedding model unchanged. `config` supplies
+    /// the index write options and lexical settings.
+    pub fn import(root: &Path, config: &Config, dir: &Path) -> Result<ExportInfo> {
+        let root = root.canonicalize().map_err(|e| {
+            Error::Config(format!(
+                "cannot canonicalize root '{}': {e}",
+                root.display()
+            ))
+        })?;
+        let write_options = WriteOptions {
+            quantization: config.vector_quantization.clone(),
+            compress_metadata: config.index_compression,
+            wal_max_bytes: config.index_wal_max_bytes,
+            wal_max_age_secs: config.index_wal_max_age_secs,
+            rescore_factor: config.index_rescore_factor,
+        };
+        let _module_run_lock = modules::acquire_module_run_lock(&root)?;
+        export::import(&root, dir, write_options, &config.lexical_settings())
+    }
+
+    /// Check the integrity of the index of the project at `root`; with
+    /// `repair`, fix what can be fixed without re-embedding. See [`fsck`]
+    /// for the checks.
+    ///
+    /// Like [`restore_snapshot`](Self::restore_snapshot), this does not open
+    /// the project, so it works on an index that no longer loads. Repairing
+    /// fails while another process holds the project open for writing.
+    pub fn fsck(root: &Path, config: &Config, repair: bool) -> Result<FsckReport> {
+        let root = root.canonicalize().map_err(|e| {
+            Error::Config(format!(
+                "cannot canonicalize root '{}': {e}",
+                root.display()
+            ))
+        })?;
+        let write_options = WriteOptions {
+            quantization: config.vector_quantization.clone(),
+            compress_metadata: config.index_compression,
+            wal_max_bytes: config.index_wal_max_bytes,
+            wal_max_age_secs: config.index_wal_max_age_secs,
+            rescore_factor: config.index_rescore_factor,
+        };
+        let _module_run_lock = repair
+            .then(|| modules::acquire_module_run_lock(&root))
+            .transpose()?;
+        fsck::check(&root, write_options, &config.lexical_settings(), repair)
+    }
+
+    /// Fold the index write-ahead log into a freshly written index file.
+    ///
+    /// Saves normally append their changes to the log and compact it once it
+    /// reaches `index.wal_max_bytes` or `index.wal_max_age_secs`; this
+    /// compacts right away.
+    pub fn compact(&self) -> Result<CompactReport> {
+        let wal_bytes = self.index.compact()?;
+        Ok(CompactReport {
+            wal_bytes,
+            index_bytes: self.index.status().file_size,
+        })
+    }
+
     /// Return a status snapshot of the index.
     pub fn status(&self) -> index::types::IndexStatus {
         let mut status = self.index.status();
@@ -3106,6 +3809,8 @@ search:
   min_score: 0.0
   mode: hybrid
   rrf_k: 60.0
+  # fusion: rrf            # rrf | linear
+  # semantic_weight: 0.7   # linear fusion only
   # decay:
   #   enabled: false
   #   half_life: 30
@@ -4475,8 +5180,14 @@ sources:
             search::evaluate_filters(&opts.filters, Some(&effective))
         });
 
-        // 3. Total count after filtering, before pagination.
+        // 3. Total count and facets after filtering, before pagination.
         let total_rows = rows.len();
+        let mut facets = search::empty_facets(&opts.facets);
+        let list_fields = search::facet_list_fields(self.index.get_schema().as_ref(), &opts.facets);
+        for row in &rows {
+            let effective = effective_collection_frontmatter(row);
+            search::add_facet_values(&mut facets, &opts.facets, &list_fields, Some(&effective));
+        }
 
         // 4. Sort (type-aware, nulls-last; path-asc when no sort field).
         sort_collection_rows(&mut rows, opts.sort_by.as_deref(), opts.order);
@@ -4522,6 +5233,7 @@ sources:
             total_rows,
             limit: opts.limit,
             offset: opts.offset,
+            facets,
         })
     }
 
@@ -8,7 +8,9 @@ use tracing::debug;
 use crate::parser::MarkdownFile;
 
 /// A single link extracted from a markdown file.
-#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize)]
+#[derive(
+    Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize, serde::Deserialize,
+)]
 #[rkyv(derive(Debug))]
 pub struct LinkEntry {
     /// Source file (relative path).
@@ -84,7 +86,9 @@ pub struct EdgeClusterState {
 }
 
 /// The complete link graph stored in the index.
-#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize)]
+#[derive(
+    Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize, serde::Deserialize,
+)]
 #[rkyv(derive(Debug))]
 pub struct LinkGraph {
     /// Forward links: source path → list of link entries.
@@ -363,23 +367,40 @@ pub fn bfs_neighbors(
     seeds: &[String],
     max_depth: usize,
 ) -> HashMap<String, usize> {
+    bfs_neighbors_with_origin(graph, backlinks, seeds, max_depth)
+        .into_iter()
+        .map(|(path, (distance, _))| (path, distance))
+        .collect()
+}
+
+/// Like [`bfs_neighbors`], but also reports the seed each file was first
+/// reached from: `path → (hop distance, seed path)`.
+///
+/// Seeds are expanded in the given order, so when two seeds reach a file at
+/// the same distance the earlier seed wins.
+pub fn bfs_neighbors_with_origin(
+    graph: &LinkGraph,
+    backlinks: &HashMap<String, Vec<LinkEntry>>,
+    seeds: &[String],
+    max_depth: usize,
+) -> HashMap<String, (usize, String)> {
     let max_depth = max_depth.min(3);
     if max_depth == 0 || seeds.is_empty() {
         return HashMap::new();
     }
 
     let mut visited: HashSet<String> = HashSet::new();
-    let mut result: HashMap<String, usize> = HashMap::new();
-    let mut queue: VecDeque<(String, usize)> = VecDeque::new();
+    let mut result: HashMap<String, (usize, String)> = HashMap::new();
+    let mut queue: VecDeque<(String, usize, &str)> = VecDeque::new();
 
     // Initialize with seeds
     for seed in seeds {
         if visited.insert(seed.clone()) {
-            queue.push_back((seed.clone(), 0));
+            queue.push_back((seed.clone(), 0, seed.as_str()));
         }
     }
 
-    while let Some((current, depth)) = queue.pop_front() {
+    while let Some((current, depth, origin)) = queue.pop_front() {
         if depth >= max_depth {
             continue;
         }
@@ -390,8 +411,8 @@ pub fn bfs_neighbors(
         if let Some(entries) = graph.forward.get(&current) {
             for entry in entries {
                 if visited.insert(entry.target.clone()) {
-                    result.insert(entry.target.clone(), next_depth);
-                    queue.push_back((entry.target.clone(), next_depth));
+                    result.insert(entry.target.clone(), (next_depth, origin.to_string()));
+                    queue.push_back((entry.target.clone(), next_depth, origin));
                 }
             }
         }
@@ -400,8 +421,8 @@ pub fn bfs_neighbors(
         if let Some(entries) = backlinks.get(&current) {
             for entry in entries {
                 if visited.insert(entry.source.clone()) {
-                    result.insert(entry.source.clone(), next_depth);
-                    queue.push_back((entry.source.clone(), next_depth));
+                    result.insert(entry.source.clone(), (next_depth, origin.to_string()));
+                    queue.push_back((entry.source.clone(), next_depth, origin));
                 }
             }
         }
@@ -1420,6 +1441,25 @@ mod tests {
         assert!(neighbors.is_empty());
     }
 
+    #[test]
+    fn bfs_with_origin_reports_seed() {
+        // a -> b -> c, d -> e
+        let files = vec![
+            make_file("a.md", vec![make_link("b", "B", 1, false)]),
+            make_file("b.md", vec![make_link("c", "C", 1, false)]),
+            make_file("d.md", vec![make_link("e", "E", 1, false)]),
+        ];
+        let graph = build_link_graph(&files, &empty_ctx());
+        let backlinks = compute_backlinks(&graph);
+
+        let seeds = ["a.md".to_string(), "d.md".to_string()];
+        let neighbors = bfs_neighbors_with_origin(&graph, &backlinks, &seeds, 2);
+
+        assert_eq!(neighbors["b.md"], (1, "a.md".to_string()));
+        assert_eq!(neighbors["c.md"], (2, "a.md".to_string()));
+        assert_eq!(neighbors["e.md"], (1, "d.md".to_string()));
+    }
+
     // --- neighborhood tests ---
 
     #[test]
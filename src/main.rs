@@ -12,10 +12,11 @@ use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use serde_json::Value;
 
+use mdvdb::fts::SpellingSuggestion;
 use mdvdb::links::{LinkQueryResult, OrphanFile, ResolvedLink, SemanticEdge};
 use mdvdb::search::{
-    EdgeSearchResult, GraphContextItem, MetadataFilter, SearchMode, SearchQuery, SearchResult,
-    SearchTimings, SortOrder,
+    EdgeSearchResult, FacetCounts, FusionMethod, GraphContextItem, MetadataFilter, SearchMode,
+    SearchQuery, SearchResult, SearchTimings, SortOrder,
 };
 use mdvdb::{CollectionQuery, GraphLevel, IngestTimings, MarkdownVdb};
 
@@ -31,6 +32,7 @@ const SUPPORTED_SECRET_NAMES: &[&str] = &[
     "AWS_SECRET_ACCESS_KEY",
     "AWS_SESSION_TOKEN",
     "OLLAMA_HOST",
+    "MDVDB_RERANK_API_KEY",
 ];
 
 /// Wrapped search output for JSON mode.
@@ -46,6 +48,10 @@ struct SearchOutput {
     graph_context: Vec<GraphContextItem>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     edge_results: Vec<EdgeSearchResult>,
+    #[serde(skip_serializing_if = "FacetCounts::is_empty")]
+    facets: FacetCounts,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<SpellingSuggestion>,
 }
 
 /// Wrapped ingest output for JSON mode (verbosity-gated timings).
@@ -57,6 +63,7 @@ struct IngestOutput {
     chunks_created: usize,
     api_calls: usize,
     estimated_input_tokens: usize,
+    embeddings_cached: usize,
     files_failed: usize,
     errors: Vec<mdvdb::IngestError>,
     module_reports: Vec<mdvdb::modules::ModuleReport>,
@@ -117,6 +124,14 @@ struct BacklinksOutput {
     total_backlinks: usize,
 }
 
+/// Wrapped suggest output for JSON mode.
+#[derive(serde::Serialize)]
+struct SuggestOutput {
+    prefix: String,
+    suggestions: Vec<mdvdb::Suggestion>,
+    total_suggestions: usize,
+}
+
 /// Wrapped orphans output for JSON mode.
 #[derive(serde::Serialize)]
 struct OrphansOutput {
@@ -168,6 +183,15 @@ enum Commands {
     /// Semantic search across indexed markdown files
     Search(SearchArgs),
 
+    /// Find files or chunks similar to an indexed file (uses stored vectors)
+    Similar(SimilarArgs),
+
+    /// Pack search results into a token budget as cited context for prompts
+    Context(ContextArgs),
+
+    /// Complete a prefix to note titles, aliases, and headings (typeahead)
+    Suggest(SuggestArgs),
+
     /// Ingest markdown files into the index
     Ingest(IngestArgs),
 
@@ -211,9 +235,27 @@ enum Commands {
     /// Discover and probe embedding provider models
     Embedding(EmbeddingArgs),
 
+    /// Inspect, prune, or clear the persistent embedding caches
+    Cache(CacheArgs),
+
+    /// Fold the index write-ahead log into a freshly written index file
+    Compact(CompactArgs),
+
+    /// Create, list, restore, or prune index snapshots
+    Snapshot(SnapshotArgs),
+
+    /// Export the index as portable JSONL plus a raw vector file
+    Export(ExportArgs),
+
+    /// Replace the index with an export, without re-embedding
+    Import(ImportArgs),
+
     /// Run diagnostic checks on config, provider, and index
     Doctor(DoctorArgs),
 
+    /// Check index integrity and optionally repair it
+    Fsck(FsckArgs),
+
     /// Show links originating from a file
     Links(LinksArgs),
 
@@ -241,7 +283,13 @@ enum Commands {
 #[derive(Parser)]
 struct SearchArgs {
     /// Search query string
-    query: String,
+    #[arg(required_unless_present = "batch")]
+    query: Option<String>,
+
+    /// Read one JSON query object per line from stdin and write one JSON
+    /// response per line; other flags apply to every query
+    #[arg(long, conflicts_with = "query")]
+    batch: bool,
 
     /// Maximum number of results to return
     #[arg(short, long)]
@@ -255,6 +303,10 @@ struct SearchArgs {
     #[arg(short, long)]
     filter: Vec<String>,
 
+    /// Boolean filter expression, e.g. 'status != draft and (year >= 2023 or tags contains rust)'
+    #[arg(long = "where", value_name = "EXPR")]
+    where_expr: Option<String>,
+
     /// Enable link boosting (favor results linked to/from top matches)
     #[arg(long, conflicts_with = "no_boost_links")]
     boost_links: bool,
@@ -263,22 +315,26 @@ struct SearchArgs {
     #[arg(long, conflicts_with = "boost_links")]
     no_boost_links: bool,
 
-    /// Search mode: hybrid, semantic, or lexical
+    /// Search mode: hybrid, semantic, lexical, edge, or regex
     #[arg(long, value_name = "MODE")]
     mode: Option<SearchMode>,
 
     /// Shorthand for --mode=semantic
-    #[arg(long, conflicts_with_all = ["lexical", "mode", "edge_search"])]
+    #[arg(long, conflicts_with_all = ["lexical", "mode", "edge_search", "regex"])]
     semantic: bool,
 
     /// Shorthand for --mode=lexical
-    #[arg(long, conflicts_with_all = ["semantic", "mode", "edge_search"])]
+    #[arg(long, conflicts_with_all = ["semantic", "mode", "edge_search", "regex"])]
     lexical: bool,
 
     /// Shorthand for --mode=edge (search edge embeddings)
-    #[arg(long, conflicts_with_all = ["semantic", "lexical", "mode"])]
+    #[arg(long, conflicts_with_all = ["semantic", "lexical", "mode", "regex"])]
     edge_search: bool,
 
+    /// Shorthand for --mode=regex (treat the query as a regular expression)
+    #[arg(long, conflicts_with_all = ["semantic", "lexical", "mode", "edge_search"])]
+    regex: bool,
+
     /// Restrict search to files under this path prefix
     #[arg(long, conflicts_with = "shard")]
     path: Option<String>,
@@ -315,11 +371,177 @@ struct SearchArgs {
     #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=3))]
     expand: Option<u8>,
 
+    /// Hybrid fusion method: rrf or linear
+    #[arg(long, value_name = "METHOD")]
+    fusion: Option<FusionMethod>,
+
+    /// Semantic weight for linear fusion (0.0-1.0; lexical weight is 1 - weight)
+    #[arg(long, value_name = "WEIGHT")]
+    semantic_weight: Option<f64>,
+
+    /// MMR diversification lambda (0.0-1.0, 1.0 = pure relevance)
+    #[arg(long, value_name = "LAMBDA", conflicts_with = "no_diversity")]
+    diversity: Option<f64>,
+
+    /// Maximum number of results from any single file (0 = unlimited)
+    #[arg(long, value_name = "N", conflicts_with = "no_diversity")]
+    max_per_file: Option<usize>,
+
+    /// Turn off configured MMR diversification and per-file cap for this query
+    #[arg(long)]
+    no_diversity: bool,
+
+    /// Skip the configured reranker for this query
+    #[arg(long)]
+    no_rerank: bool,
+
+    /// Bypass the on-disk query-embedding cache (neither read nor write it)
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Show a per-signal score breakdown for each result
+    #[arg(long)]
+    explain: bool,
+
+    /// Attach N neighboring chunks before and after each result
+    #[arg(long, value_name = "N", default_value = "0", value_parser = clap::value_parser!(u16).range(0..=mdvdb::search::MAX_WINDOW as i64))]
+    window: u16,
+
+    /// Return the full enclosing heading section instead of a sub-split fragment
+    #[arg(long)]
+    parent: bool,
+
+    /// Show the best-matching fragment of each result with query terms highlighted
+    #[arg(long)]
+    snippets: bool,
+
+    /// Count values of these frontmatter fields over the filtered candidates (comma-separated)
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    facets: Vec<String>,
+
     /// Resolve frontmatter relations (.md paths, wiki links, or Markdown links) inline: path, existence, title, target frontmatter
     #[arg(long)]
     populate: bool,
 }
 
+#[derive(Parser)]
+struct SimilarArgs {
+    /// Seed file path, or PATH#N for a single chunk
+    seed: String,
+
+    /// Maximum number of results to return
+    #[arg(short, long)]
+    limit: Option<usize>,
+
+    /// Minimum similarity score (0.0 to 1.0)
+    #[arg(long)]
+    min_score: Option<f32>,
+
+    /// Metadata filter expression (KEY=VALUE)
+    #[arg(short, long)]
+    filter: Vec<String>,
+
+    /// Boolean filter expression, e.g. 'status != draft and (year >= 2023 or tags contains rust)'
+    #[arg(long = "where", value_name = "EXPR")]
+    where_expr: Option<String>,
+
+    /// Restrict results to files under this path prefix
+    #[arg(long, conflicts_with = "shard")]
+    path: Option<String>,
+
+    /// Restrict results to a configured Shard
+    #[arg(long, conflicts_with = "path")]
+    shard: Option<String>,
+
+    /// Enable time decay (favor recently modified files)
+    #[arg(long, conflicts_with = "no_decay")]
+    decay: bool,
+
+    /// Disable time decay (even if enabled in config)
+    #[arg(long, conflicts_with = "decay")]
+    no_decay: bool,
+
+    /// Half-life in days for time decay (how many days until score is halved)
+    #[arg(long, value_name = "DAYS")]
+    decay_half_life: Option<f64>,
+
+    /// Comma-separated path prefixes excluded from time decay
+    #[arg(long, value_name = "PATTERNS")]
+    decay_exclude: Option<String>,
+
+    /// Comma-separated path prefixes where time decay applies (whitelist)
+    #[arg(long, value_name = "PATTERNS")]
+    decay_include: Option<String>,
+
+    /// MMR diversification lambda (0.0-1.0, 1.0 = pure relevance)
+    #[arg(long, value_name = "LAMBDA", conflicts_with = "no_diversity")]
+    diversity: Option<f64>,
+
+    /// Maximum number of results from any single file (0 = unlimited)
+    #[arg(long, value_name = "N", conflicts_with = "no_diversity")]
+    max_per_file: Option<usize>,
+
+    /// Turn off configured MMR diversification and per-file cap for this query
+    #[arg(long)]
+    no_diversity: bool,
+
+    /// Also return other chunks from the seed file
+    #[arg(long)]
+    include_seed: bool,
+
+    /// Resolve frontmatter relations inline (see `search --populate`)
+    #[arg(long)]
+    populate: bool,
+}
+
+#[derive(Parser)]
+struct ContextArgs {
+    /// Search query string
+    query: String,
+
+    /// Token budget for the packed context (cl100k_base tokens)
+    #[arg(long, value_name = "TOKENS", default_value_t = mdvdb::context::DEFAULT_CONTEXT_BUDGET)]
+    budget: usize,
+
+    /// Number of search results considered for packing
+    #[arg(short, long, default_value_t = mdvdb::context::DEFAULT_CONTEXT_CANDIDATES)]
+    limit: usize,
+
+    /// Metadata filter expression (KEY=VALUE)
+    #[arg(short, long)]
+    filter: Vec<String>,
+
+    /// Boolean filter expression, e.g. 'status != draft and (year >= 2023 or tags contains rust)'
+    #[arg(long = "where", value_name = "EXPR")]
+    where_expr: Option<String>,
+
+    /// Search mode: hybrid, semantic, lexical, or regex
+    #[arg(long, value_name = "MODE")]
+    mode: Option<SearchMode>,
+
+    /// Restrict search to files under this path prefix
+    #[arg(long, conflicts_with = "shard")]
+    path: Option<String>,
+
+    /// Restrict search to a configured Shard
+    #[arg(long, conflicts_with = "path")]
+    shard: Option<String>,
+
+    /// Pack chunks from linked files after the ranked results (0-3, 0 disables)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=3))]
+    expand: Option<u8>,
+}
+
+#[derive(Parser)]
+struct SuggestArgs {
+    /// Prefix typed so far
+    prefix: String,
+
+    /// Maximum number of suggestions to return
+    #[arg(short, long, default_value_t = mdvdb::suggest::DEFAULT_SUGGEST_LIMIT)]
+    limit: usize,
+}
+
 #[derive(Parser)]
 struct IngestArgs {
     /// Force re-embedding of all files
@@ -355,6 +577,10 @@ struct InfoArgs {
     /// Restrict stats to a configured Shard
     #[arg(long, conflicts_with = "path")]
     shard: Option<String>,
+
+    /// Measure vector search recall against an exact search (reads every vector)
+    #[arg(long)]
+    recall: bool,
 }
 
 #[derive(Parser)]
@@ -533,6 +759,10 @@ struct CollectionArgs {
     #[arg(short, long)]
     filter: Vec<String>,
 
+    /// Boolean filter expression, e.g. 'status != draft and (year >= 2023 or tags contains rust)'
+    #[arg(long = "where", value_name = "EXPR")]
+    where_expr: Option<String>,
+
     /// Maximum number of rows to return
     #[arg(long)]
     limit: Option<usize>,
@@ -541,6 +771,10 @@ struct CollectionArgs {
     #[arg(long, default_value = "0")]
     offset: usize,
 
+    /// Count values of these frontmatter fields over all filtered rows (comma-separated)
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    facets: Vec<String>,
+
     /// Resolve frontmatter relations (.md paths, wiki links, or Markdown links) inline: path, existence, title, target frontmatter
     #[arg(long)]
     populate: bool,
@@ -696,6 +930,22 @@ enum EmbeddingAction {
     Probe,
 }
 
+#[derive(Parser)]
+struct CacheArgs {
+    #[command(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show entry counts and disk usage per cached vector space
+    Stats,
+    /// Drop cached vectors no longer referenced by the index and expired queries
+    Prune,
+    /// Remove every cached document and query embedding
+    Clear,
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Set a config value in .markdownvdb/config.yaml using a dotted key path
@@ -732,9 +982,58 @@ enum SecretAction {
     Unset { name: String },
 }
 
+#[derive(Parser)]
+struct CompactArgs {}
+
+#[derive(Parser)]
+struct SnapshotArgs {
+    #[command(subcommand)]
+    action: SnapshotAction,
+}
+
+#[derive(Subcommand)]
+enum SnapshotAction {
+    /// Snapshot the index, FTS index and Shard analysis caches
+    Create,
+    /// List snapshots, newest first
+    List,
+    /// Replace the index with a snapshot (stop `mdvdb watch` first)
+    Restore {
+        /// Snapshot ID, as shown by `mdvdb snapshot list`
+        id: String,
+    },
+    /// Delete all but the newest snapshots
+    Prune {
+        /// Number of snapshots to keep (default: index.snapshot_retention)
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+}
+
+#[derive(Parser)]
+struct ExportArgs {
+    /// Directory to write the export to; must be empty or missing
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Parser)]
+struct ImportArgs {
+    /// Directory written by `mdvdb export` (stop `mdvdb watch` first)
+    dir: PathBuf,
+}
+
 #[derive(Parser)]
 struct DoctorArgs {}
 
+#[derive(Parser)]
+struct FsckArgs {
+    /// Drop dangling entries, rebuild the full-text index, and mark files
+    /// with missing vectors for re-embedding (stop `mdvdb watch` first)
+    #[arg(long)]
+    repair: bool,
+}
+
 #[derive(Parser)]
 struct ChunksArgs {
     /// Directory containing markdown files to chunk
@@ -763,6 +1062,92 @@ struct CompletionsArgs {
     shell: ShellType,
 }
 
+/// One line of `search --batch` input. Omitted fields keep the values given
+/// by command-line flags; `filters` and `where` are ANDed with them.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchSearchLine {
+    query: String,
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    filters: Vec<String>,
+    #[serde(default, rename = "where")]
+    where_expr: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+/// Run `search --batch`: parse JSONL queries from stdin on top of `base`,
+/// execute them in one warm process, and write one compact `SearchResponse`
+/// per line in input order. A line that cannot be parsed or whose query
+/// fails is answered with an `{"line": N, "error": "..."}` object instead,
+/// and the rest of the batch still runs.
+async fn run_search_batch(vdb: &MarkdownVdb, base: SearchQuery) -> anyhow::Result<()> {
+    let mut queries = Vec::new();
+    // (line number, parse error) per non-blank line; `None` means the line's
+    // query is next in `queries`.
+    let mut lines: Vec<(usize, Option<String>)> = Vec::new();
+    for (i, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_batch_line(&base, &line) {
+            Ok(query) => {
+                queries.push(query);
+                lines.push((i + 1, None));
+            }
+            Err(e) => lines.push((i + 1, Some(e.to_string()))),
+        }
+    }
+
+    let mut responses = vdb.search_batch(queries).await?.into_iter();
+    let mut stdout = std::io::stdout().lock();
+    for (line, parse_error) in lines {
+        let error = match parse_error {
+            Some(message) => message,
+            None => match responses.next().expect("one response per parsed line") {
+                Ok(response) => {
+                    serde_json::to_writer(&mut stdout, &response)?;
+                    writeln!(stdout)?;
+                    continue;
+                }
+                Err(e) => e.to_string(),
+            },
+        };
+        let error = serde_json::json!({ "line": line, "error": error });
+        serde_json::to_writer(&mut stdout, &error)?;
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+/// Parse one `search --batch` JSONL line into a query layered on `base`.
+fn parse_batch_line(base: &SearchQuery, line: &str) -> anyhow::Result<SearchQuery> {
+    let parsed: BatchSearchLine = serde_json::from_str(line)?;
+    let mut query = base.clone();
+    query.query = parsed.query;
+    if let Some(mode) = parsed.mode {
+        query = query.with_mode(mode.parse::<SearchMode>()?);
+    }
+    for f in &parsed.filters {
+        query = query.with_filter(parse_filter(f)?);
+    }
+    if let Some(ref expr) = parsed.where_expr {
+        query = query.with_filter(expr.parse::<MetadataFilter>()?);
+    }
+    if let Some(limit) = parsed.limit {
+        query = query.with_limit(limit);
+    }
+    if let Some(ref path) = parsed.path {
+        query = query.with_path_prefix(mdvdb::path_util::normalize_path_input(path));
+    }
+    Ok(query)
+}
+
 /// Parse a KEY=VALUE filter string into a MetadataFilter::Equals.
 fn parse_filter(s: &str) -> anyhow::Result<MetadataFilter> {
     let (key, val) = s
@@ -1130,6 +1515,8 @@ async fn run() -> anyhow::Result<()> {
                 SearchMode::Lexical
             } else if args.edge_search {
                 SearchMode::Edge
+            } else if args.regex {
+                SearchMode::Regex
             } else {
                 config.search_default_mode
             };
@@ -1138,7 +1525,7 @@ async fn run() -> anyhow::Result<()> {
                 resolve_shard_or_path(&cwd, args.path.as_deref(), args.shard.as_deref())?;
             let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
 
-            let mut query = SearchQuery::new(&args.query);
+            let mut query = SearchQuery::new(args.query.as_deref().unwrap_or_default());
             if let Some(limit) = args.limit {
                 query = query.with_limit(limit);
             }
@@ -1148,6 +1535,9 @@ async fn run() -> anyhow::Result<()> {
             for f in &args.filter {
                 query = query.with_filter(parse_filter(f)?);
             }
+            if let Some(ref expr) = args.where_expr {
+                query = query.with_filter(expr.parse::<MetadataFilter>()?);
+            }
             if args.boost_links {
                 query = query.with_boost_links(true);
             } else if args.no_boost_links {
@@ -1179,17 +1569,65 @@ async fn run() -> anyhow::Result<()> {
             if let Some(expand) = args.expand {
                 query = query.with_expand_graph(expand as usize);
             }
+            if let Some(fusion) = args.fusion {
+                query = query.with_fusion(fusion);
+            }
+            if let Some(weight) = args.semantic_weight {
+                if !(0.0..=1.0).contains(&weight) {
+                    anyhow::bail!(
+                        "--semantic-weight must be between 0.0 and 1.0, got {}",
+                        weight
+                    );
+                }
+                query = query.with_semantic_weight(weight);
+            }
+            if let Some(lambda) = args.diversity {
+                if !(0.0..=1.0).contains(&lambda) {
+                    anyhow::bail!("--diversity must be between 0.0 and 1.0, got {}", lambda);
+                }
+                query = query.with_diversity(lambda);
+            }
+            if let Some(max) = args.max_per_file {
+                query = query.with_max_per_file(max);
+            }
+            if args.no_diversity {
+                query = query.with_diversity(1.0).with_max_per_file(0);
+            }
+            if args.no_rerank {
+                query = query.with_rerank(false);
+            }
+            if args.no_cache {
+                query = query.with_cache(false);
+            }
+            if args.explain {
+                query = query.with_explain(true);
+            }
+            if args.parent {
+                query = query.with_parent(true);
+            }
+            if args.snippets {
+                query = query.with_snippets(true);
+            }
+            query = query.with_window(usize::from(args.window));
+            if !args.facets.is_empty() {
+                query = query.with_facets(args.facets.clone());
+            }
             if args.populate {
                 query = query.with_populate(true);
             }
 
+            if args.batch {
+                return run_search_batch(&vdb, query).await;
+            }
+
+            let query_text = query.query.clone();
             let effective_mode = query.mode;
             let response = vdb.search(query).await?;
 
             if json {
                 let output = SearchOutput {
                     total_results: response.results.len(),
-                    query: args.query.clone(),
+                    query: query_text,
                     results: response.results,
                     mode: effective_mode,
                     timings: if cli.verbose > 0 {
@@ -1199,27 +1637,203 @@ async fn run() -> anyhow::Result<()> {
                     },
                     graph_context: response.graph_context,
                     edge_results: response.edge_results,
+                    facets: response.facets,
+                    suggestions: response.suggestions,
                 };
                 serde_json::to_writer_pretty(std::io::stdout(), &output)?;
                 writeln!(std::io::stdout())?;
             } else {
-                format::print_search_results(&response.results, &args.query);
+                format::print_search_results(&response.results, &query_text);
+                format::print_spelling_suggestions(&response.suggestions);
+                format::print_facets(&response.facets);
                 if !response.graph_context.is_empty() {
                     format::print_graph_context(&response.graph_context);
                 }
                 if cli.verbose > 0 {
                     eprintln!(
-                        "  [timing] embed={:.0}ms hnsw={:.0}ms bm25={:.0}ms fusion={:.0}ms assemble={:.0}ms total={:.0}ms",
+                        "  [timing] embed={:.0}ms hnsw={:.0}ms bm25={:.0}ms fusion={:.0}ms rerank={:.0}ms assemble={:.0}ms suggest={:.0}ms total={:.0}ms",
                         response.timings.embed_secs * 1000.0,
                         response.timings.vector_search_secs * 1000.0,
                         response.timings.lexical_search_secs * 1000.0,
                         response.timings.fusion_secs * 1000.0,
+                        response.timings.rerank_secs * 1000.0,
                         response.timings.assemble_secs * 1000.0,
+                        response.timings.suggest_secs * 1000.0,
                         response.timings.total_secs * 1000.0,
                     );
+                    if let Some(hit) = response.timings.cache_hit {
+                        eprintln!(
+                            "  [cache] query embedding {} lookup={:.0}ms",
+                            if hit { "hit" } else { "miss" },
+                            response.timings.cache_lookup_secs * 1000.0,
+                        );
+                    }
+                    if let Some(strategy) = response.timings.vector_strategy {
+                        match response.timings.filter_selectivity {
+                            Some(selectivity) => eprintln!(
+                                "  [plan] vector={strategy} selectivity={:.1}%",
+                                selectivity * 100.0
+                            ),
+                            None => eprintln!("  [plan] vector={strategy}"),
+                        }
+                    }
                 }
             }
         }
+        Some(Commands::Similar(args)) => {
+            let scope_path =
+                resolve_shard_or_path(&cwd, args.path.as_deref(), args.shard.as_deref())?;
+            let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+
+            let mut query = SearchQuery::new(&args.seed).with_mode(SearchMode::Semantic);
+            if let Some(limit) = args.limit {
+                query = query.with_limit(limit);
+            }
+            if let Some(min_score) = args.min_score {
+                query = query.with_min_score(min_score as f64);
+            }
+            for f in &args.filter {
+                query = query.with_filter(parse_filter(f)?);
+            }
+            if let Some(ref expr) = args.where_expr {
+                query = query.with_filter(expr.parse::<MetadataFilter>()?);
+            }
+            if let Some(ref path) = scope_path {
+                query = query.with_path_prefix(path);
+            }
+            if args.decay {
+                query = query.with_decay(true);
+            } else if args.no_decay {
+                query = query.with_decay(false);
+            }
+            if let Some(half_life) = args.decay_half_life {
+                query = query.with_decay_half_life(half_life);
+            }
+            if let Some(ref patterns) = args.decay_exclude {
+                let list: Vec<String> = patterns.split(',').map(|s| s.trim().to_string()).collect();
+                query = query.with_decay_exclude(list);
+            }
+            if let Some(ref patterns) = args.decay_include {
+                let list: Vec<String> = patterns.split(',').map(|s| s.trim().to_string()).collect();
+                query = query.with_decay_include(list);
+            }
+            if let Some(lambda) = args.diversity {
+                if !(0.0..=1.0).contains(&lambda) {
+                    anyhow::bail!("--diversity must be between 0.0 and 1.0, got {}", lambda);
+                }
+                query = query.with_diversity(lambda);
+            }
+            if let Some(max) = args.max_per_file {
+                query = query.with_max_per_file(max);
+            }
+            if args.no_diversity {
+                query = query.with_diversity(1.0).with_max_per_file(0);
+            }
+            if args.populate {
+                query = query.with_populate(true);
+            }
+
+            let response = vdb.similar(&args.seed, query, args.include_seed).await?;
+
+            if json {
+                let output = SearchOutput {
+                    total_results: response.results.len(),
+                    query: args.seed.clone(),
+                    results: response.results,
+                    mode: SearchMode::Semantic,
+                    timings: if cli.verbose > 0 {
+                        Some(response.timings)
+                    } else {
+                        None
+                    },
+                    graph_context: response.graph_context,
+                    edge_results: response.edge_results,
+                    facets: response.facets,
+                    suggestions: response.suggestions,
+                };
+                serde_json::to_writer_pretty(std::io::stdout(), &output)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                format::print_search_results(&response.results, &args.seed);
+                if cli.verbose > 0 {
+                    eprintln!(
+                        "  [timing] hnsw={:.0}ms assemble={:.0}ms total={:.0}ms",
+                        response.timings.vector_search_secs * 1000.0,
+                        response.timings.assemble_secs * 1000.0,
+                        response.timings.total_secs * 1000.0,
+                    );
+                }
+            }
+        }
+        Some(Commands::Context(args)) => {
+            if args.budget == 0 {
+                anyhow::bail!("--budget must be at least 1");
+            }
+            let scope_path =
+                resolve_shard_or_path(&cwd, args.path.as_deref(), args.shard.as_deref())?;
+            let mode = args.mode.unwrap_or(config.search_default_mode);
+            let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+
+            let mut query = SearchQuery::new(&args.query)
+                .with_limit(args.limit)
+                .with_mode(mode);
+            for f in &args.filter {
+                query = query.with_filter(parse_filter(f)?);
+            }
+            if let Some(ref expr) = args.where_expr {
+                query = query.with_filter(expr.parse::<MetadataFilter>()?);
+            }
+            if let Some(ref path) = scope_path {
+                query = query.with_path_prefix(path);
+            }
+            if let Some(expand) = args.expand {
+                query = query.with_expand_graph(expand as usize);
+            }
+
+            let pack = vdb.context(query, args.budget).await?;
+
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &pack)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                if pack.sections.is_empty() {
+                    eprintln!("  No context found for \"{}\"", args.query);
+                }
+                // Plain text so the block can be piped or pasted as-is.
+                write!(std::io::stdout(), "{}", pack.text)?;
+                if cli.verbose > 0 {
+                    eprintln!(
+                        "  [context] {} section(s), {}/{} tokens, {} chunk(s) omitted",
+                        pack.sections.len(),
+                        pack.tokens_used,
+                        pack.budget,
+                        pack.chunks_omitted,
+                    );
+                }
+            }
+        }
+        Some(Commands::Suggest(args)) => {
+            let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+            let start = std::time::Instant::now();
+            let suggestions = vdb.suggest(&args.prefix, args.limit);
+            let elapsed = start.elapsed();
+
+            if json {
+                let output = SuggestOutput {
+                    prefix: args.prefix,
+                    total_suggestions: suggestions.len(),
+                    suggestions,
+                };
+                serde_json::to_writer_pretty(std::io::stdout(), &output)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                format::print_suggestions(&suggestions, &args.prefix);
+            }
+            if cli.verbose > 0 {
+                // The first call also builds the in-memory table.
+                eprintln!("  [timing] suggest={:.1}ms", elapsed.as_secs_f64() * 1000.0);
+            }
+        }
         Some(Commands::Ingest(args)) => {
             let full_reindex = args.reindex || args.full;
             if args.preview {
@@ -1427,6 +2041,7 @@ async fn run() -> anyhow::Result<()> {
                 chunks_created: result.chunks_created,
                 api_calls: result.api_calls,
                 estimated_input_tokens: result.estimated_input_tokens,
+                embeddings_cached: result.embeddings_cached,
                 files_failed: result.files_failed,
                 errors: result.errors.clone(),
                 module_reports: result.module_reports.clone(),
@@ -1505,7 +2120,10 @@ async fn run() -> anyhow::Result<()> {
                 resolve_shard_or_path(&cwd, args.path.as_deref(), args.shard.as_deref())?
                     .unwrap_or_else(|| ".".to_string());
             let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
-            let info = vdb.info(Some(&scope_path))?;
+            let mut info = vdb.info(Some(&scope_path))?;
+            if args.recall {
+                info.vector_storage.recall = Some(vdb.estimate_recall()?);
+            }
 
             if json {
                 serde_json::to_writer_pretty(std::io::stdout(), &info)?;
@@ -1928,6 +2546,9 @@ async fn run() -> anyhow::Result<()> {
             for f in &args.filter {
                 filters.push(parse_filter(f)?);
             }
+            if let Some(ref expr) = args.where_expr {
+                filters.push(expr.parse::<MetadataFilter>()?);
+            }
 
             let opts = CollectionQuery {
                 path: mdvdb::path_util::normalize_path_input(&scope_path),
@@ -1938,6 +2559,7 @@ async fn run() -> anyhow::Result<()> {
                 limit: args.limit,
                 offset: args.offset,
                 populate: args.populate,
+                facets: args.facets,
             };
             let resp = vdb.collection(opts)?;
 
@@ -1992,6 +2614,104 @@ async fn run() -> anyhow::Result<()> {
                 format::print_backlinks(&path_str, &result);
             }
         }
+        Some(Commands::Cache(args)) => {
+            let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+            match args.action {
+                CacheAction::Stats => {
+                    let stats = vdb.cache_stats()?;
+                    if json {
+                        serde_json::to_writer_pretty(std::io::stdout(), &stats)?;
+                        writeln!(std::io::stdout())?;
+                    } else {
+                        format::print_cache_stats(&stats);
+                    }
+                }
+                CacheAction::Prune | CacheAction::Clear => {
+                    let cleared = matches!(args.action, CacheAction::Clear);
+                    let report = if cleared {
+                        vdb.cache_clear()?
+                    } else {
+                        vdb.cache_prune()?
+                    };
+                    if json {
+                        serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                        writeln!(std::io::stdout())?;
+                    } else {
+                        format::print_cache_prune(&report, cleared);
+                    }
+                }
+            }
+        }
+        Some(Commands::Compact(_args)) => {
+            let vdb = MarkdownVdb::open_with_config(cwd, config)?;
+            let report = vdb.compact()?;
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                format::print_compact(&report);
+            }
+        }
+        Some(Commands::Snapshot(args)) => match args.action {
+            SnapshotAction::Create => {
+                let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+                let info = vdb.snapshot_create()?;
+                if json {
+                    serde_json::to_writer_pretty(std::io::stdout(), &info)?;
+                    writeln!(std::io::stdout())?;
+                } else {
+                    format::print_snapshot_created(&info);
+                }
+            }
+            SnapshotAction::List => {
+                let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+                let snapshots = vdb.snapshots()?;
+                if json {
+                    serde_json::to_writer_pretty(std::io::stdout(), &snapshots)?;
+                    writeln!(std::io::stdout())?;
+                } else {
+                    format::print_snapshots(&snapshots);
+                }
+            }
+            SnapshotAction::Restore { id } => {
+                let info = MarkdownVdb::restore_snapshot(&cwd, &id)?;
+                if json {
+                    serde_json::to_writer_pretty(std::io::stdout(), &info)?;
+                    writeln!(std::io::stdout())?;
+                } else {
+                    format::print_snapshot_restored(&info);
+                }
+            }
+            SnapshotAction::Prune { keep } => {
+                let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+                let removed = vdb.snapshot_prune(keep)?;
+                if json {
+                    serde_json::to_writer_pretty(std::io::stdout(), &removed)?;
+                    writeln!(std::io::stdout())?;
+                } else {
+                    format::print_snapshot_prune(&removed);
+                }
+            }
+        },
+        Some(Commands::Export(args)) => {
+            let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
+            let info = vdb.export(&args.out)?;
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &info)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                format::print_export(&info);
+            }
+        }
+        Some(Commands::Import(args)) => {
+            let info = MarkdownVdb::import(&cwd, &config, &args.dir)?;
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &info)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                format::print_import(&info);
+            }
+        }
         Some(Commands::Orphans(_args)) => {
             let vdb = MarkdownVdb::open_readonly_with_config(cwd, config)?;
             let result = vdb.orphans()?;
@@ -2397,6 +3117,24 @@ async fn run() -> anyhow::Result<()> {
                 format::print_doctor(&result);
             }
         }
+        Some(Commands::Fsck(args)) => {
+            let report = MarkdownVdb::fsck(&cwd, &config, args.repair)?;
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                writeln!(std::io::stdout())?;
+            } else {
+                format::print_fsck(&report);
+            }
+            let unresolved = report.unresolved();
+            if unresolved > 0 {
+                if report.problems.iter().all(|problem| problem.repairable) {
+                    anyhow::bail!(
+                        "{unresolved} index problem(s) found; run `mdvdb fsck --repair` to fix them"
+                    );
+                }
+                anyhow::bail!("{unresolved} index problem(s) cannot be repaired");
+            }
+        }
         Some(Commands::Chunks(args)) => {
             use mdvdb::chunker::chunk_document;
             use mdvdb::parser::parse_markdown_file;
@@ -0,0 +1,299 @@
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::provider::Reranker;
+use crate::embedding::provider::{describe_request_error, embedding_http_client_with};
+use crate::error::Error;
+
+const MAX_RETRIES: u32 = 3;
+
+/// Wire format spoken by a `/rerank` endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RerankApiStyle {
+    /// Cohere/Jina/Voyage: `{model, query, documents, top_n}` in,
+    /// `{results: [{index, relevance_score}]}` out.
+    Cohere,
+    /// Hugging Face text-embeddings-inference: `{query, texts}` in,
+    /// `[{index, score}]` out.
+    Tei,
+}
+
+/// Reranker backed by an HTTP `/rerank` endpoint.
+pub struct HttpReranker {
+    client: reqwest::Client,
+    style: RerankApiStyle,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CohereRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: &'a [String],
+    top_n: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct TeiRequest<'a> {
+    query: &'a str,
+    texts: &'a [String],
+    truncate: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CohereResponse {
+    results: Vec<CohereResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CohereResult {
+    index: usize,
+    relevance_score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeiResult {
+    index: usize,
+    score: f64,
+}
+
+impl HttpReranker {
+    /// Create a new HTTP reranker. `model` is only sent in Cohere-style
+    /// requests; a TEI server hosts a single model.
+    pub fn new(
+        style: RerankApiStyle,
+        endpoint: String,
+        model: String,
+        api_key: Option<String>,
+    ) -> Self {
+        Self {
+            client: embedding_http_client_with(
+                std::time::Duration::from_secs(10),
+                std::time::Duration::from_secs(60),
+            ),
+            style,
+            endpoint,
+            model,
+            api_key,
+        }
+    }
+
+    fn request_body(&self, query: &str, documents: &[String]) -> serde_json::Value {
+        let body = match self.style {
+            RerankApiStyle::Cohere => serde_json::to_value(CohereRequest {
+                model: &self.model,
+                query,
+                documents,
+                top_n: documents.len(),
+            }),
+            RerankApiStyle::Tei => serde_json::to_value(TeiRequest {
+                query,
+                texts: documents,
+                truncate: true,
+            }),
+        };
+        body.expect("rerank request serializes to JSON")
+    }
+
+    /// Map the style-specific response body to one score per document, in
+    /// input order.
+    fn parse_scores(&self, body: &str, document_count: usize) -> crate::Result<Vec<f64>> {
+        let pairs: Vec<(usize, f64)> = match self.style {
+            RerankApiStyle::Cohere => serde_json::from_str::<CohereResponse>(body).map(|r| {
+                r.results
+                    .into_iter()
+                    .map(|item| (item.index, item.relevance_score))
+                    .collect()
+            }),
+            RerankApiStyle::Tei => serde_json::from_str::<Vec<TeiResult>>(body)
+                .map(|r| r.into_iter().map(|item| (item.index, item.score)).collect()),
+        }
+        .map_err(|e| Error::Reranker(format!("failed to parse response: {e}")))?;
+
+        let mut scores: Vec<Option<f64>> = vec![None; document_count];
+        for (index, score) in pairs {
+            let slot = scores.get_mut(index).ok_or_else(|| {
+                Error::Reranker(format!(
+                    "response index {index} out of range for {document_count} documents"
+                ))
+            })?;
+            *slot = Some(score);
+        }
+        scores
+            .into_iter()
+            .enumerate()
+            .map(|(index, score)| {
+                score.ok_or_else(|| {
+                    Error::Reranker(format!("response is missing a score for document {index}"))
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Reranker for HttpReranker {
+    async fn rerank(&self, query: &str, documents: &[String]) -> crate::Result<Vec<f64>> {
+        if documents.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let request_body = self.request_body(query, documents);
+        let mut last_error = None;
+
+        for attempt in 0..=MAX_RETRIES {
+            if attempt > 0 {
+                let delay = std::time::Duration::from_secs(1 << (attempt - 1));
+                debug!(
+                    attempt,
+                    delay_secs = delay.as_secs(),
+                    "retrying rerank request"
+                );
+                tokio::time::sleep(delay).await;
+            }
+
+            let mut request = self.client.post(&self.endpoint).json(&request_body);
+            if let Some(token) = &self.api_key {
+                request = request.header("Authorization", format!("Bearer {token}"));
+            }
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(error) if error.is_timeout() || error.is_connect() => {
+                    last_error = Some(Error::Reranker(format!(
+                        "transient request failure: {}",
+                        describe_request_error(&error)
+                    )));
+                    continue;
+                }
+                Err(error) => {
+                    return Err(Error::Reranker(format!(
+                        "request failed: {}",
+                        describe_request_error(&error)
+                    )))
+                }
+            };
+
+            let status = response.status();
+
+            if status == StatusCode::UNAUTHORIZED {
+                return Err(Error::Reranker(
+                    "authentication failed (401): check MDVDB_RERANK_API_KEY".into(),
+                ));
+            }
+
+            if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                let msg = if status == StatusCode::TOO_MANY_REQUESTS {
+                    "rate limited (429)".to_string()
+                } else {
+                    format!("server error ({})", status.as_u16())
+                };
+                warn!("{msg}, attempt {}/{}", attempt + 1, MAX_RETRIES + 1);
+                last_error = Some(Error::Reranker(msg));
+                continue;
+            }
+
+            let body = response.text().await.map_err(|error| {
+                Error::Reranker(format!(
+                    "failed to read response: {}",
+                    describe_request_error(&error)
+                ))
+            })?;
+
+            if !status.is_success() {
+                return Err(Error::Reranker(format!(
+                    "unexpected status {}: {body}",
+                    status.as_u16()
+                )));
+            }
+
+            return self.parse_scores(&body, documents.len());
+        }
+
+        Err(last_error.unwrap_or_else(|| Error::Reranker("max retries exceeded".into())))
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn name(&self) -> &str {
+        match self.style {
+            RerankApiStyle::Cohere => "cohere",
+            RerankApiStyle::Tei => "tei",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reranker(style: RerankApiStyle) -> HttpReranker {
+        HttpReranker::new(
+            style,
+            "http://localhost:8080/rerank".into(),
+            "rerank-model".into(),
+            None,
+        )
+    }
+
+    #[test]
+    fn cohere_request_serialization() {
+        let docs = vec!["a".to_string(), "b".to_string()];
+        let json = reranker(RerankApiStyle::Cohere).request_body("q", &docs);
+        assert_eq!(json["model"], "rerank-model");
+        assert_eq!(json["query"], "q");
+        assert_eq!(json["documents"], serde_json::json!(["a", "b"]));
+        assert_eq!(json["top_n"], 2);
+    }
+
+    #[test]
+    fn tei_request_serialization() {
+        let docs = vec!["a".to_string()];
+        let json = reranker(RerankApiStyle::Tei).request_body("q", &docs);
+        assert_eq!(json["query"], "q");
+        assert_eq!(json["texts"], serde_json::json!(["a"]));
+        assert!(json.get("documents").is_none());
+    }
+
+    #[test]
+    fn cohere_response_is_reordered_by_index() {
+        let body = r#"{"results": [
+            {"index": 1, "relevance_score": 0.9},
+            {"index": 0, "relevance_score": 0.2}
+        ]}"#;
+        let scores = reranker(RerankApiStyle::Cohere)
+            .parse_scores(body, 2)
+            .unwrap();
+        assert_eq!(scores, vec![0.2, 0.9]);
+    }
+
+    #[test]
+    fn tei_response_is_reordered_by_index() {
+        let body = r#"[{"index": 1, "score": 0.7}, {"index": 0, "score": 0.1}]"#;
+        let scores = reranker(RerankApiStyle::Tei).parse_scores(body, 2).unwrap();
+        assert_eq!(scores, vec![0.1, 0.7]);
+    }
+
+    #[test]
+    fn response_missing_document_is_an_error() {
+        let body = r#"[{"index": 0, "score": 0.1}]"#;
+        let err = reranker(RerankApiStyle::Tei)
+            .parse_scores(body, 2)
+            .unwrap_err();
+        assert!(err.to_string().contains("missing a score for document 1"));
+    }
+
+    #[test]
+    fn response_index_out_of_range_is_an_error() {
+        let body = r#"[{"index": 5, "score": 0.1}]"#;
+        let err = reranker(RerankApiStyle::Tei)
+            .parse_scores(body, 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+    }
+}
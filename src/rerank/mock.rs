@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::provider::Reranker;
+
+/// A mock reranker for deterministic testing.
+///
+/// Scores each document by the fraction of distinct query terms it contains
+/// (case-insensitive, alphanumeric terms). Tracks how many times `rerank`
+/// has been called.
+pub struct MockReranker {
+    call_count: Arc<AtomicUsize>,
+}
+
+impl MockReranker {
+    /// Create a new mock reranker.
+    pub fn new() -> Self {
+        Self {
+            call_count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the number of times `rerank` has been called.
+    pub fn call_count(&self) -> usize {
+        self.call_count.load(Ordering::SeqCst)
+    }
+
+    fn terms(text: &str) -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+}
+
+impl Default for MockReranker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Reranker for MockReranker {
+    async fn rerank(&self, query: &str, documents: &[String]) -> crate::Result<Vec<f64>> {
+        self.call_count.fetch_add(1, Ordering::SeqCst);
+        let query_terms = Self::terms(query);
+        if query_terms.is_empty() {
+            return Ok(vec![0.0; documents.len()]);
+        }
+        let scores = documents
+            .iter()
+            .map(|doc| {
+                let doc_terms = Self::terms(doc);
+                let hits = query_terms.intersection(&doc_terms).count();
+                hits as f64 / query_terms.len() as f64
+            })
+            .collect();
+        Ok(scores)
+    }
+
+    fn model(&self) -> &str {
+        "mock-reranker"
+    }
+
+    fn name(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_reranker_scores_term_overlap() {
+        let reranker = MockReranker::new();
+        let docs = vec![
+            "Nothing relevant here".to_string(),
+            "How to rotate API keys".to_string(),
+            "Rotate the keys".to_string(),
+        ];
+        let scores = reranker.rerank("rotate API keys", &docs).await.unwrap();
+        assert_eq!(scores.len(), 3);
+        assert_eq!(scores[0], 0.0);
+        assert_eq!(scores[1], 1.0);
+        assert!((scores[2] - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_mock_reranker_call_count() {
+        let reranker = MockReranker::new();
+        assert_eq!(reranker.call_count(), 0);
+        reranker.rerank("q", &["q".to_string()]).await.unwrap();
+        reranker.rerank("q", &[]).await.unwrap();
+        assert_eq!(reranker.call_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_reranker_empty_query() {
+        let reranker = MockReranker::new();
+        let scores = reranker.rerank("  ", &["doc".to_string()]).await.unwrap();
+        assert_eq!(scores, vec![0.0]);
+    }
+}
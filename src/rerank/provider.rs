@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+
+use crate::config::{Config, RerankProviderType};
+use crate::error::Error;
+
+use super::http::{HttpReranker, RerankApiStyle};
+use super::mock::MockReranker;
+
+/// Trait for rescoring candidate documents against a query, typically with a
+/// cross-encoder model.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Score each document's relevance to `query`, returning one score per
+    /// input in input order. Higher is more relevant.
+    async fn rerank(&self, query: &str, documents: &[String]) -> crate::Result<Vec<f64>>;
+
+    fn model(&self) -> &str;
+
+    /// Human-readable name for this reranker.
+    fn name(&self) -> &str;
+}
+
+/// Validate reranker output: exactly one finite score per document.
+pub fn validate_scores(scores: &[f64], expected_count: usize) -> crate::Result<()> {
+    if scores.len() != expected_count {
+        return Err(Error::Reranker(format!(
+            "expected {expected_count} scores, got {}",
+            scores.len()
+        )));
+    }
+    if scores.iter().any(|score| !score.is_finite()) {
+        return Err(Error::Reranker(
+            "reranker returned a non-finite score".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Create the configured reranker, or `None` when reranking is disabled.
+pub fn create_reranker(config: &Config) -> crate::Result<Option<Box<dyn Reranker>>> {
+    let api_key = std::env::var("MDVDB_RERANK_API_KEY")
+        .ok()
+        .filter(|value| !value.is_empty());
+    match config.rerank_provider {
+        RerankProviderType::None => Ok(None),
+        RerankProviderType::Cohere => {
+            let model = config.rerank_model.clone().ok_or_else(|| {
+                Error::Config("the cohere reranker requires rerank.model to be set".into())
+            })?;
+            Ok(Some(Box::new(HttpReranker::new(
+                RerankApiStyle::Cohere,
+                config
+                    .rerank_endpoint
+                    .clone()
+                    .unwrap_or_else(|| "https://api.cohere.com/v2/rerank".to_string()),
+                model,
+                api_key,
+            ))))
+        }
+        RerankProviderType::Tei => Ok(Some(Box::new(HttpReranker::new(
+            RerankApiStyle::Tei,
+            config
+                .rerank_endpoint
+                .clone()
+                .unwrap_or_else(|| "http://localhost:8080/rerank".to_string()),
+            config.rerank_model.clone().unwrap_or_default(),
+            api_key,
+        )))),
+        RerankProviderType::Mock => Ok(Some(Box::new(MockReranker::new()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_scores_accepts_matching_count() {
+        assert!(validate_scores(&[0.1, 0.9], 2).is_ok());
+        assert!(validate_scores(&[], 0).is_ok());
+    }
+
+    #[test]
+    fn validate_scores_rejects_count_mismatch() {
+        let err = validate_scores(&[0.5], 2).unwrap_err();
+        assert!(err.to_string().contains("expected 2 scores, got 1"));
+    }
+
+    #[test]
+    fn validate_scores_rejects_nan() {
+        assert!(validate_scores(&[f64::NAN], 1).is_err());
+    }
+}
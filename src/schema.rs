@@ -8,7 +8,16 @@ use tracing::debug;
 use crate::parser::MarkdownFile;
 
 /// The type of a frontmatter field, inferred from values across files.
-#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    Serialize,
+    serde::Deserialize,
+)]
 #[rkyv(derive(Debug))]
 pub enum FieldType {
     String,
@@ -179,7 +188,9 @@ pub struct OverlaySchema {
 }
 
 /// A merged schema field combining inferred data with overlay annotations.
-#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize)]
+#[derive(
+    Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize, serde::Deserialize,
+)]
 #[rkyv(derive(Debug))]
 pub struct SchemaField {
     /// Field name.
@@ -216,7 +227,9 @@ pub struct SchemaField {
 }
 
 /// A schema tagged with its path scope, persisted in the index.
-#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize)]
+#[derive(
+    Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize, serde::Deserialize,
+)]
 #[rkyv(derive(Debug))]
 pub struct ScopedSchema {
     /// Path prefix for this scope (e.g. "blog/").
@@ -226,7 +239,9 @@ pub struct ScopedSchema {
 }
 
 /// The complete metadata schema, persisted in the index.
-#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize)]
+#[derive(
+    Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize, serde::Deserialize,
+)]
 #[rkyv(derive(Debug))]
 pub struct Schema {
     /// Schema fields sorted alphabetically by name.
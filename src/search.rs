@@ -1,23 +1,30 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Instant;
 
+use regex::{Regex, RegexBuilder};
 use serde::Serialize;
 use serde_json::Value;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+use crate::chunker::{chunk_index_from_id, join_chunk_content};
 use crate::embedding::provider::EmbeddingProvider;
 use crate::error::{Error, Result};
-use crate::fts::FtsIndex;
+use crate::fts::{FtsIndex, SpellingSuggestion};
 use crate::index::state::Index;
-use crate::index::types::ComputedFieldDiagnostic;
+use crate::index::types::{ComputedFieldDiagnostic, StoredChunk, StoredFile};
 use crate::links;
+use crate::rerank::provider::{validate_scores, Reranker};
+use crate::schema::{infer_field_type, FieldType, FormulaResultType, Schema};
+use crate::snippet::SearchSnippet;
 
 /// Search mode controlling which retrieval signals are used.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SearchMode {
-    /// Both semantic (HNSW) and lexical (BM25) search, fused via RRF.
+    /// Both semantic (HNSW) and lexical (BM25) search, fused via RRF or
+    /// linear interpolation (see [`FusionMethod`]).
     #[default]
     Hybrid,
     /// Semantic search only (embedding + HNSW).
@@ -26,6 +33,9 @@ pub enum SearchMode {
     Lexical,
     /// Edge-based search: retrieve by semantic edges between documents.
     Edge,
+    /// Regular-expression scan of stored chunk content. No embedding API
+    /// call or FTS index needed; see [`SearchResultChunk::matches`].
+    Regex,
 }
 
 impl FromStr for SearchMode {
@@ -37,8 +47,9 @@ impl FromStr for SearchMode {
             "semantic" => Ok(Self::Semantic),
             "lexical" => Ok(Self::Lexical),
             "edge" => Ok(Self::Edge),
+            "regex" => Ok(Self::Regex),
             other => Err(Error::Config(format!(
-                "unknown search mode '{other}': expected hybrid, semantic, lexical, or edge"
+                "unknown search mode '{other}': expected hybrid, semantic, lexical, edge, or regex"
             ))),
         }
     }
@@ -51,10 +62,70 @@ impl std::fmt::Display for SearchMode {
             Self::Semantic => write!(f, "semantic"),
             Self::Lexical => write!(f, "lexical"),
             Self::Edge => write!(f, "edge"),
+            Self::Regex => write!(f, "regex"),
         }
     }
 }
 
+/// How hybrid search combines its semantic and lexical candidate lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FusionMethod {
+    /// Reciprocal Rank Fusion: rank-based, ignores score magnitudes.
+    #[default]
+    Rrf,
+    /// Weighted linear interpolation of cosine and normalized BM25 scores.
+    Linear,
+}
+
+impl FromStr for FusionMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rrf" => Ok(Self::Rrf),
+            "linear" => Ok(Self::Linear),
+            other => Err(Error::Config(format!(
+                "unknown fusion method '{other}': expected rrf or linear"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for FusionMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rrf => write!(f, "rrf"),
+            Self::Linear => write!(f, "linear"),
+        }
+    }
+}
+
+/// How semantic candidates were retrieved, as chosen by the search planner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorStrategy {
+    /// Unfiltered approximate HNSW search; filters apply after retrieval.
+    Hnsw,
+    /// HNSW search with the filters evaluated as a predicate over chunk keys.
+    Filtered,
+    /// Exact cosine scan over the chunks that pass the filters.
+    Exact,
+}
+
+impl std::fmt::Display for VectorStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Hnsw => write!(f, "hnsw"),
+            Self::Filtered => write!(f, "filtered"),
+            Self::Exact => write!(f, "exact"),
+        }
+    }
+}
+
+/// Default semantic weight (α) for linear fusion when none is configured.
+pub const DEFAULT_SEMANTIC_WEIGHT: f64 = 0.7;
+
 /// Sort direction for collection rows (and any future ordered listing).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -98,7 +169,8 @@ pub struct SearchQuery {
     pub limit: usize,
     /// Minimum cosine similarity score (0.0–1.0).
     pub min_score: f64,
-    /// Metadata filters applied with AND logic.
+    /// Metadata filters applied with AND logic. Use [`MetadataFilter::Or`] /
+    /// [`MetadataFilter::Not`] (or parse an expression) for other combinations.
     pub filters: Vec<MetadataFilter>,
     /// Per-query override for link boosting (None = use config default).
     pub boost_links: Option<bool>,
@@ -121,6 +193,46 @@ pub struct SearchQuery {
     /// Resolve frontmatter relations on each result file (depth 1). Applied by
     /// the `MarkdownVdb::search` wrapper as post-processing; the engine ignores it.
     pub populate: bool,
+    /// Maximal Marginal Relevance lambda in `[0, 1]` (1 = pure relevance,
+    /// 0 = pure diversity). `None` disables MMR; `MarkdownVdb::search` fills
+    /// it from `search.diversity` when unset, so `Some(1.0)` turns a
+    /// configured default off.
+    pub diversity: Option<f64>,
+    /// Maximum number of results from the same file. `None` = unlimited;
+    /// `MarkdownVdb::search` fills it from `search.max_per_file` when unset,
+    /// so `Some(0)` lifts a configured cap.
+    pub max_per_file: Option<usize>,
+    /// Whether the reranker runs for this query (default `true`).
+    pub rerank: bool,
+    /// Reranking stage applied when `rerank` is set. `MarkdownVdb::search`
+    /// fills it from the configured reranker when unset.
+    pub reranker: Option<RerankOptions>,
+    /// Hybrid fusion method. `None` = RRF; `MarkdownVdb::search` fills it
+    /// from `search.fusion` when unset.
+    pub fusion: Option<FusionMethod>,
+    /// Semantic weight α in `[0, 1]` for linear fusion (lexical weight is
+    /// `1 - α`). `None` = [`DEFAULT_SEMANTIC_WEIGHT`]; `MarkdownVdb::search`
+    /// fills it from `search.semantic_weight` when unset.
+    pub semantic_weight: Option<f64>,
+    /// Attach a per-signal [`ScoreExplanation`] to each result.
+    pub explain: bool,
+    /// Number of neighboring chunks (by chunk index, same file) attached
+    /// before and after each result chunk. 0 disables; values above
+    /// [`MAX_WINDOW`] are treated as `MAX_WINDOW`.
+    pub window: usize,
+    /// Return the full enclosing heading section instead of a sub-split
+    /// fragment; results from the same section collapse into the best one.
+    pub parent: bool,
+    /// Frontmatter fields to count values for over the files passing the
+    /// query's scope and filters (see [`SearchResponse::facets`]).
+    pub facets: Vec<String>,
+    /// Whether the query embedding may be read from and written to the
+    /// on-disk query cache (default `true`). Applied by the
+    /// `MarkdownVdb::search` wrapper.
+    pub cache: bool,
+    /// Attach a query-focused [`SearchSnippet`] with highlighted matches to
+    /// each result chunk. Applied by the `MarkdownVdb::search` wrapper.
+    pub snippets: bool,
 }
 
 impl SearchQuery {
@@ -141,6 +253,18 @@ impl SearchQuery {
             boost_hops: None,
             expand_graph: None,
             populate: false,
+            diversity: None,
+            max_per_file: None,
+            rerank: true,
+            reranker: None,
+            fusion: None,
+            semantic_weight: None,
+            explain: false,
+            window: 0,
+            parent: false,
+            facets: Vec::new(),
+            cache: true,
+            snippets: false,
         }
     }
 
@@ -221,9 +345,119 @@ impl SearchQuery {
         self.populate = populate;
         self
     }
+
+    /// Enable MMR diversification with the given lambda (0.0–1.0).
+    pub fn with_diversity(mut self, lambda: f64) -> Self {
+        self.diversity = Some(lambda);
+        self
+    }
+
+    /// Cap the number of results returned from any single file.
+    pub fn with_max_per_file(mut self, max: usize) -> Self {
+        self.max_per_file = Some(max);
+        self
+    }
+
+    /// Enable or disable the configured reranker for this query.
+    pub fn with_rerank(mut self, rerank: bool) -> Self {
+        self.rerank = rerank;
+        self
+    }
+
+    /// Rerank the top candidates of this query with `reranker`.
+    pub fn with_reranker(mut self, reranker: RerankOptions) -> Self {
+        self.reranker = Some(reranker);
+        self
+    }
+
+    /// Set the hybrid fusion method for this query.
+    pub fn with_fusion(mut self, fusion: FusionMethod) -> Self {
+        self.fusion = Some(fusion);
+        self
+    }
+
+    /// Set the semantic weight α (0.0–1.0) used by linear fusion.
+    pub fn with_semantic_weight(mut self, weight: f64) -> Self {
+        self.semantic_weight = Some(weight);
+        self
+    }
+
+    /// Enable or disable per-result score explanations.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Attach `n` neighboring chunks before and after each result chunk,
+    /// at most [`MAX_WINDOW`].
+    pub fn with_window(mut self, n: usize) -> Self {
+        self.window = n.min(MAX_WINDOW);
+        self
+    }
+
+    /// Return full parent heading sections instead of sub-split fragments.
+    pub fn with_parent(mut self, parent: bool) -> Self {
+        self.parent = parent;
+        self
+    }
+
+    /// Count values of these frontmatter fields over the filtered candidates.
+    pub fn with_facets(mut self, fields: Vec<String>) -> Self {
+        self.facets = fields;
+        self
+    }
+
+    /// Enable or disable the on-disk query-embedding cache for this query.
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Attach a highlighted best-matching snippet to each result chunk.
+    pub fn with_snippets(mut self, snippets: bool) -> Self {
+        self.snippets = snippets;
+        self
+    }
+}
+
+/// Ordering operator used by [`MetadataFilter::Compare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// Strictly greater than (`>`).
+    Gt,
+    /// Greater than or equal (`>=`).
+    Ge,
+    /// Strictly less than (`<`).
+    Lt,
+    /// Less than or equal (`<=`).
+    Le,
+}
+
+impl std::fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gt => write!(f, ">"),
+            Self::Ge => write!(f, ">="),
+            Self::Lt => write!(f, "<"),
+            Self::Le => write!(f, "<="),
+        }
+    }
 }
 
 /// Metadata filter for narrowing search results by frontmatter fields.
+///
+/// Leaf variants test a single top-level field; [`And`](Self::And),
+/// [`Or`](Self::Or) and [`Not`](Self::Not) combine them. Boolean trees are
+/// usually built by parsing a filter expression (see [`crate::filter`]):
+///
+/// ```
+/// use mdvdb::search::MetadataFilter;
+///
+/// let filter: MetadataFilter = "status != draft and (year >= 2023 or tags contains rust)"
+///     .parse()
+///     .unwrap();
+/// assert!(matches!(filter, MetadataFilter::And(_)));
+/// ```
 #[derive(Debug, Clone)]
 pub enum MetadataFilter {
     /// Exact value equality. If the field is an array, checks if the array contains the value.
@@ -238,6 +472,30 @@ pub enum MetadataFilter {
     },
     /// Field exists in frontmatter and is not null.
     Exists { field: String },
+    /// Ordering comparison with a strict or inclusive operator. If the field is
+    /// an array, any element may satisfy the comparison.
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: Value,
+    },
+    /// Array field contains the value, or string field contains it as a substring.
+    Contains { field: String, value: Value },
+    /// All inner filters pass.
+    And(Vec<MetadataFilter>),
+    /// At least one inner filter passes.
+    Or(Vec<MetadataFilter>),
+    /// The inner filter does not pass. A missing field negates to `true`.
+    Not(Box<MetadataFilter>),
+}
+
+impl FromStr for MetadataFilter {
+    type Err = Error;
+
+    /// Parse a boolean filter expression (see [`crate::filter`] for the grammar).
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        crate::filter::parse_filter_expression(s)
+    }
 }
 
 /// A single search result with relevance score, chunk content, and file context.
@@ -247,12 +505,76 @@ pub struct SearchResult {
     ///
     /// - **Semantic**: cosine similarity (absolute).
     /// - **Lexical**: BM25 score normalized via saturation `score/(score+k)`.
-    /// - **Hybrid**: RRF score normalized by theoretical maximum.
+    /// - **Hybrid** (RRF): RRF score normalized by theoretical maximum.
+    /// - **Hybrid** (linear): `α × semantic + (1 − α) × normalized BM25`.
     pub score: f64,
     /// The matched chunk.
     pub chunk: SearchResultChunk,
     /// File-level metadata for the chunk's source file.
     pub file: SearchResultFile,
+    /// Per-signal score breakdown (only when [`SearchQuery::explain`] is set).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ScoreExplanation>,
+}
+
+/// How a result's final score was produced, signal by signal.
+///
+/// Stages apply in field order: retrieval (vector and/or BM25, fused),
+/// time decay, link boosting, reranking. Fields for stages that did not
+/// touch the result are omitted.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScoreExplanation {
+    /// Raw cosine similarity from vector search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_score: Option<f64>,
+    /// 1-based rank in the vector search candidate list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_rank: Option<usize>,
+    /// Raw (unnormalized) BM25 score.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bm25_score: Option<f64>,
+    /// 1-based rank in the BM25 candidate list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bm25_rank: Option<usize>,
+    /// RRF term `1 / (rrf_k + semantic_rank)` (hybrid RRF only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rrf_semantic: Option<f64>,
+    /// RRF term `1 / (rrf_k + bm25_rank)` (hybrid RRF only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rrf_lexical: Option<f64>,
+    /// Score after fusion and normalization, before decay and boosting.
+    pub retrieval_score: f64,
+    /// Time-decay multiplier in `(0, 1]`. Absent when decay did not apply.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decay_multiplier: Option<f64>,
+    /// Link-graph boost applied to this result, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_boost: Option<LinkBoostExplanation>,
+    /// Score assigned by the reranker (replaces the score up to this point).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f64>,
+    /// Final score, equal to [`SearchResult::score`].
+    pub final_score: f64,
+}
+
+/// Link-graph boost details within a [`ScoreExplanation`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkBoostExplanation {
+    /// Hop distance from the boosting source file (1 = direct link).
+    pub hops: usize,
+    /// Top-ranked result file whose link neighborhood reached this file.
+    pub source: String,
+    /// Best cosine similarity between the query and a semantic edge touching
+    /// this file. Absent when the flat boost was used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_similarity: Option<f64>,
+    /// Edge boost before hop attenuation: `edge_boost_weight × edge_similarity`,
+    /// or `edge_boost_weight` when no edge similarity is available.
+    pub edge_boost: f64,
+    /// Score multiplier `1 + edge_boost / 2^(hops - 1)`.
+    pub multiplier: f64,
+    /// Score added by the boost.
+    pub delta: f64,
 }
 
 /// Chunk-level data within a search result.
@@ -268,6 +590,54 @@ pub struct SearchResultChunk {
     pub start_line: usize,
     /// 1-based end line in the source file (inclusive).
     pub end_line: usize,
+    /// Preceding chunks from the same file, in file order (`SearchQuery::window`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<SearchResultChunk>,
+    /// Following chunks from the same file, in file order (`SearchQuery::window`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<SearchResultChunk>,
+    /// IDs of the sub-split chunks joined into `content`, when the chunk was
+    /// widened to its parent heading section (`SearchQuery::parent`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub section_chunk_ids: Vec<String>,
+    /// Best-matching fragment of `content` with highlighted query terms
+    /// (`SearchQuery::snippets`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<SearchSnippet>,
+    /// Every match of the pattern in `content`, in text order
+    /// ([`SearchMode::Regex`] only).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<RegexMatch>,
+}
+
+/// One pattern match within a [`SearchResultChunk`] (regex mode).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegexMatch {
+    /// The matched text.
+    pub text: String,
+    /// Byte offset of the match in the chunk content.
+    pub start: usize,
+    /// End byte offset of the match in the chunk content (exclusive).
+    pub end: usize,
+    /// 1-based source file line the match starts on.
+    pub line: usize,
+}
+
+impl SearchResultChunk {
+    pub(crate) fn from_stored(chunk_id: String, chunk: &StoredChunk) -> Self {
+        Self {
+            chunk_id,
+            heading_hierarchy: chunk.heading_hierarchy.clone(),
+            content: chunk.content.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            before: Vec::new(),
+            after: Vec::new(),
+            section_chunk_ids: Vec::new(),
+            snippet: None,
+            matches: Vec::new(),
+        }
+    }
 }
 
 /// File-level metadata within a search result.
@@ -348,14 +718,34 @@ pub struct SearchTimings {
     pub embed_secs: f64,
     /// Time spent in HNSW vector search (usearch). 0 if lexical-only.
     pub vector_search_secs: f64,
-    /// Time spent in BM25 lexical search (tantivy). 0 if semantic-only.
+    /// Time spent in BM25 lexical search (tantivy), or scanning chunks in
+    /// regex mode. 0 if semantic-only.
     pub lexical_search_secs: f64,
-    /// Time spent in RRF fusion + score normalization. 0 if not hybrid.
+    /// Time spent in score fusion + normalization. 0 if not hybrid.
     pub fusion_secs: f64,
+    /// Time spent in the reranker. 0 if no reranker is configured.
+    pub rerank_secs: f64,
     /// Time spent assembling results (filtering, decay, link boosting).
     pub assemble_secs: f64,
+    /// Time spent computing spelling suggestions. 0 if none were computed.
+    pub suggest_secs: f64,
     /// Total wall-clock time.
     pub total_secs: f64,
+    /// Vector retrieval strategy chosen by the planner. `None` if no vector
+    /// search ran (lexical-only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_strategy: Option<VectorStrategy>,
+    /// Fraction of chunks passing the path and metadata filters, when the
+    /// planner evaluated them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_selectivity: Option<f64>,
+    /// Time spent reading the on-disk query-embedding cache. 0 if the cache
+    /// was not consulted.
+    pub cache_lookup_secs: f64,
+    /// Whether the query embedding was served from the on-disk cache
+    /// (`embed_secs` is then 0). `None` if the cache was not consulted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
 }
 
 /// Wrapper response for search operations, containing ranked results,
@@ -372,6 +762,98 @@ pub struct SearchResponse {
     /// Edge-based search results (populated when mode is Edge).
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub edge_results: Vec<EdgeSearchResult>,
+    /// Value counts per requested facet field (`SearchQuery::facets`),
+    /// counted once per file over every indexed file that passes the query's
+    /// path scope and metadata filters — the same set `collection` facets
+    /// count, independent of the query text, `min_score`, and the limit.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: FacetCounts,
+    /// Corrections for query words that occur nowhere in the lexical index
+    /// (lexical and hybrid queries; see [`FtsIndex::spelling_suggestions`]).
+    /// Filled by the `MarkdownVdb::search` wrapper.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<SpellingSuggestion>,
+}
+
+/// Largest neighbor window ([`SearchQuery::window`]) attached to a result.
+pub const MAX_WINDOW: usize = 50;
+
+/// Value → document count for each requested frontmatter field.
+pub type FacetCounts = BTreeMap<String, BTreeMap<String, usize>>;
+
+/// Facet counts with an empty entry for each of `fields`.
+pub(crate) fn empty_facets(fields: &[String]) -> FacetCounts {
+    fields
+        .iter()
+        .map(|field| (field.clone(), BTreeMap::new()))
+        .collect()
+}
+
+/// Whether each of `fields` holds lists whose elements are counted as
+/// separate facet values, according to `schema`. Fields the schema does not
+/// know are left out and judged by their values (see [`add_facet_values`]).
+pub(crate) fn facet_list_fields(
+    schema: Option<&Schema>,
+    fields: &[String],
+) -> HashMap<String, bool> {
+    let Some(schema) = schema else {
+        return HashMap::new();
+    };
+    schema
+        .fields
+        .iter()
+        .filter(|field| fields.contains(&field.name))
+        .map(|field| {
+            let is_list = match field.field_type {
+                FieldType::List | FieldType::Relation | FieldType::File | FieldType::Mixed => true,
+                FieldType::Formula | FieldType::Lookup | FieldType::Rollup => {
+                    field.result_type == Some(FormulaResultType::List)
+                }
+                _ => false,
+            };
+            (field.name.clone(), is_list)
+        })
+        .collect()
+}
+
+/// Count one document's frontmatter values into `facets`.
+///
+/// Values of list fields (see [`facet_list_fields`]; for fields the schema
+/// does not know, arrays of scalars) count once per distinct element. Other
+/// arrays, such as `Json` fields, count as one value. Missing and null
+/// values are skipped. Non-string values are counted under their JSON
+/// rendering.
+pub(crate) fn add_facet_values(
+    facets: &mut FacetCounts,
+    fields: &[String],
+    list_fields: &HashMap<String, bool>,
+    frontmatter: Option<&Value>,
+) {
+    for field in fields {
+        let Some(value) = frontmatter.and_then(|fm| fm.get(field)) else {
+            continue;
+        };
+        let is_list = list_fields
+            .get(field)
+            .copied()
+            .unwrap_or_else(|| infer_field_type(value) != FieldType::Json);
+        let items = match value {
+            Value::Array(items) if is_list => items.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        let keys: BTreeSet<String> = items
+            .iter()
+            .filter_map(|item| match item {
+                Value::Null => None,
+                Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            })
+            .collect();
+        let counts = facets.entry(field.clone()).or_default();
+        for key in keys {
+            *counts.entry(key).or_insert(0) += 1;
+        }
+    }
 }
 
 /// Apply exponential time decay to a score based on file age.
@@ -408,6 +890,26 @@ fn should_apply_decay(path: &str, exclude: &[String], include: &[String]) -> boo
     true
 }
 
+/// Reranking stage of a [`SearchQuery`].
+#[derive(Clone)]
+pub struct RerankOptions {
+    /// Reranker used to rescore the top candidates.
+    pub reranker: Arc<dyn Reranker>,
+    /// Number of top assembled candidates sent to the reranker. Raised to the
+    /// query limit when smaller, and to the whole diversification pool when
+    /// the query diversifies.
+    pub top_n: usize,
+}
+
+impl std::fmt::Debug for RerankOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RerankOptions")
+            .field("reranker", &self.reranker.name())
+            .field("top_n", &self.top_n)
+            .finish()
+    }
+}
+
 /// Execute a search query against the index, supporting hybrid, semantic, and lexical modes.
 ///
 /// Pipeline varies by mode:
@@ -417,6 +919,12 @@ fn should_apply_decay(path: &str, exclude: &[String], include: &[String]) -> boo
 ///
 /// When `fts_index` is `None` and mode is Hybrid or Lexical, falls back to semantic-only.
 ///
+/// With `query.reranker` set (and `query.rerank` on), the top `top_n`
+/// assembled results (any non-edge mode) are rescored by the reranker and
+/// re-sorted before truncation to the limit; the reported scores are the
+/// reranker's relevance scores. A failing reranker is logged and the fused
+/// ranking is kept.
+///
 /// Returns a `SearchResponse` containing ranked results, optional graph context items
 /// (from linked files), and timing information.
 #[allow(clippy::too_many_arguments)]
@@ -436,6 +944,147 @@ pub async fn search(
     expand_graph_default: usize,
     expand_limit: usize,
     edge_boost_weight: f64,
+) -> Result<SearchResponse> {
+    execute_search(
+        query,
+        index,
+        QueryInput::Embed(provider),
+        fts_index,
+        rrf_k,
+        bm25_norm_k,
+        decay_enabled,
+        decay_half_life,
+        decay_exclude,
+        decay_include,
+        boost_links_default,
+        boost_hops_default,
+        expand_graph_default,
+        expand_limit,
+        edge_boost_weight,
+        &HashSet::new(),
+    )
+    .await
+}
+
+/// Like [`search`], but with the query text already embedded.
+///
+/// Batch callers embed many query strings in one provider call and pass each
+/// vector here; the pipeline is otherwise identical and `embed_secs` is 0.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_with_query_vector(
+    query: &SearchQuery,
+    index: &Index,
+    query_embedding: Vec<f32>,
+    fts_index: Option<&FtsIndex>,
+    rrf_k: f64,
+    bm25_norm_k: f64,
+    decay_enabled: bool,
+    decay_half_life: f64,
+    decay_exclude: &[String],
+    decay_include: &[String],
+    boost_links_default: bool,
+    boost_hops_default: usize,
+    expand_graph_default: usize,
+    expand_limit: usize,
+    edge_boost_weight: f64,
+) -> Result<SearchResponse> {
+    execute_search(
+        query,
+        index,
+        QueryInput::Vector(query_embedding),
+        fts_index,
+        rrf_k,
+        bm25_norm_k,
+        decay_enabled,
+        decay_half_life,
+        decay_exclude,
+        decay_include,
+        boost_links_default,
+        boost_hops_default,
+        expand_graph_default,
+        expand_limit,
+        edge_boost_weight,
+        &HashSet::new(),
+    )
+    .await
+}
+
+/// Find chunks similar to a stored seed vector ("more like this").
+///
+/// Runs the semantic pipeline with `seed` as the query vector, so no
+/// embedding provider call is made. `query.mode` is ignored (always
+/// semantic); `query.query` is only used for logging. Filters, path prefix,
+/// decay, link boosting, diversification and graph expansion behave as in
+/// [`search`]. Chunks in `exclude_chunk_ids` (typically the seed file's own
+/// chunks) never appear in the results.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_similar(
+    query: &SearchQuery,
+    index: &Index,
+    seed: Vec<f32>,
+    exclude_chunk_ids: &HashSet<String>,
+    decay_enabled: bool,
+    decay_half_life: f64,
+    decay_exclude: &[String],
+    decay_include: &[String],
+    boost_links_default: bool,
+    boost_hops_default: usize,
+    expand_graph_default: usize,
+    expand_limit: usize,
+    edge_boost_weight: f64,
+) -> Result<SearchResponse> {
+    let mut query = query.clone();
+    query.mode = SearchMode::Semantic;
+    // There is no query text for a reranker to score against.
+    query.reranker = None;
+    execute_search(
+        &query,
+        index,
+        QueryInput::Vector(seed),
+        None,
+        // Fusion and BM25 constants are unused in semantic mode.
+        60.0,
+        1.5,
+        decay_enabled,
+        decay_half_life,
+        decay_exclude,
+        decay_include,
+        boost_links_default,
+        boost_hops_default,
+        expand_graph_default,
+        expand_limit,
+        edge_boost_weight,
+        exclude_chunk_ids,
+    )
+    .await
+}
+
+/// Where the query vector comes from.
+enum QueryInput<'a> {
+    /// Embed `SearchQuery::query` with the provider (only when needed).
+    Embed(&'a dyn EmbeddingProvider),
+    /// Use a precomputed vector, e.g. a stored document or chunk vector.
+    Vector(Vec<f32>),
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn execute_search(
+    query: &SearchQuery,
+    index: &Index,
+    input: QueryInput<'_>,
+    fts_index: Option<&FtsIndex>,
+    rrf_k: f64,
+    bm25_norm_k: f64,
+    decay_enabled: bool,
+    decay_half_life: f64,
+    decay_exclude: &[String],
+    decay_include: &[String],
+    boost_links_default: bool,
+    boost_hops_default: usize,
+    expand_graph_default: usize,
+    expand_limit: usize,
+    edge_boost_weight: f64,
+    exclude_chunk_ids: &HashSet<String>,
 ) -> Result<SearchResponse> {
     let total_start = Instant::now();
 
@@ -451,9 +1100,17 @@ pub async fn search(
                 vector_search_secs: 0.0,
                 lexical_search_secs: 0.0,
                 fusion_secs: 0.0,
+                rerank_secs: 0.0,
                 assemble_secs: 0.0,
+                suggest_secs: 0.0,
                 total_secs: 0.0,
+                vector_strategy: None,
+                filter_selectivity: None,
+                cache_lookup_secs: 0.0,
+                cache_hit: None,
             },
+            facets: empty_facets(&query.facets),
+            suggestions: Vec::new(),
         });
     }
 
@@ -475,10 +1132,30 @@ pub async fn search(
     // Over-fetch to account for filtering. 5x for hybrid (RRF needs more
     // candidates), 3x otherwise. Scoped searches widen this progressively
     // below when the first global candidate window does not fill the limit.
-    let over_fetch = match effective_mode {
-        SearchMode::Hybrid => query.limit.saturating_mul(5),
-        _ => query.limit.saturating_mul(3),
-    };
+    //
+    // Diversification and reranking re-rank a wider pool of assembled
+    // results, and parent mode collapses sibling fragments, so the candidate
+    // window scales with the pool rather than the final limit.
+    let diversity = query.diversity.filter(|&lambda| lambda < 1.0);
+    let max_per_file = query.max_per_file.filter(|&max| max > 0);
+    let diversify = diversity.is_some() || max_per_file.is_some();
+    let rerank = query.reranker.as_ref().filter(|_| query.rerank);
+    let rerank_limit = rerank.map(|r| r.top_n.max(query.limit));
+    let pool_limit = if diversify || query.parent {
+        query.limit.saturating_mul(MMR_POOL_FACTOR)
+    } else {
+        query.limit
+    }
+    .max(rerank_limit.unwrap_or(0));
+    let fusion = query.fusion.unwrap_or_default();
+    let semantic_weight = query.semantic_weight.unwrap_or(DEFAULT_SEMANTIC_WEIGHT);
+    let over_fetch = match (effective_mode, fusion) {
+        // Linear fusion keeps score magnitudes, so it needs no extra headroom.
+        (SearchMode::Hybrid, FusionMethod::Rrf) => pool_limit.saturating_mul(5),
+        _ => pool_limit.saturating_mul(3),
+    }
+    // Excluded chunks are dropped before assembly; fetch enough to replace them.
+    .saturating_add(exclude_chunk_ids.len());
 
     // Track per-phase timing.
     let mut embed_secs = 0.0_f64;
@@ -486,22 +1163,31 @@ pub async fn search(
     let mut lexical_search_secs = 0.0_f64;
     let mut fusion_secs = 0.0_f64;
 
+    // Compile the pattern before any provider call.
+    let regex = match effective_mode {
+        SearchMode::Regex => Some(compile_regex(&query.query)?),
+        _ => None,
+    };
+
     // Embed at most once. Progressive scoped retrieval reuses this vector.
-    let needs_embedding = effective_mode != SearchMode::Lexical || effective_expand_graph > 0;
-    let query_embedding: Option<Vec<f32>> = if needs_embedding {
-        let t0 = Instant::now();
-        let embeddings = provider
-            .embed_batch_for(
-                std::slice::from_ref(&query.query),
-                crate::embedding::provider::EmbeddingPurpose::Query,
-            )
-            .await?;
-        embed_secs = t0.elapsed().as_secs_f64();
-        Some(embeddings.into_iter().next().ok_or_else(|| {
-            Error::EmbeddingProvider("provider returned no query embedding".to_string())
-        })?)
-    } else {
-        None
+    let needs_embedding = !matches!(effective_mode, SearchMode::Lexical | SearchMode::Regex)
+        || effective_expand_graph > 0;
+    let query_embedding: Option<Vec<f32>> = match input {
+        QueryInput::Vector(vector) => Some(vector),
+        QueryInput::Embed(_) if !needs_embedding => None,
+        QueryInput::Embed(provider) => {
+            let t0 = Instant::now();
+            let embeddings = provider
+                .embed_batch_for(
+                    std::slice::from_ref(&query.query),
+                    crate::embedding::provider::EmbeddingPurpose::Query,
+                )
+                .await?;
+            embed_secs = t0.elapsed().as_secs_f64();
+            Some(embeddings.into_iter().next().ok_or_else(|| {
+                Error::EmbeddingProvider("provider returned no query embedding".to_string())
+            })?)
+        }
     };
 
     let status = index.status();
@@ -511,7 +1197,7 @@ pub async fn search(
     };
     let candidate_ceiling = match effective_mode {
         SearchMode::Edge => status.edge_count,
-        SearchMode::Semantic => status.chunk_count,
+        SearchMode::Semantic | SearchMode::Regex => status.chunk_count,
         SearchMode::Lexical => fts_document_count,
         SearchMode::Hybrid => status.chunk_count.max(fts_document_count),
     };
@@ -570,9 +1256,17 @@ pub async fn search(
                 vector_search_secs,
                 lexical_search_secs,
                 fusion_secs,
+                rerank_secs: 0.0,
                 assemble_secs,
+                suggest_secs: 0.0,
                 total_secs,
+                vector_strategy: Some(VectorStrategy::Hnsw),
+                filter_selectivity: None,
+                cache_lookup_secs: 0.0,
+                cache_hit: None,
             },
+            facets: FacetCounts::new(),
+            suggestions: Vec::new(),
         });
     }
 
@@ -592,23 +1286,42 @@ pub async fn search(
     let link_graph = index.get_link_graph();
     let backlinks = link_graph.as_ref().map(links::compute_backlinks);
 
+    // Resolve path and metadata filters before vector retrieval so selective
+    // filters do not starve the semantic candidate window.
+    let uses_vectors = matches!(effective_mode, SearchMode::Semantic | SearchMode::Hybrid);
+    let vector_plan = if uses_vectors {
+        let plan_start = Instant::now();
+        let plan = plan_vector_search(query, index, status.chunk_count, exclude_chunk_ids);
+        vector_search_secs += plan_start.elapsed().as_secs_f64();
+        plan
+    } else {
+        VectorPlan::unfiltered()
+    };
+
     // Retrieve, filter, and assemble results. If a scoped query is starved by
-    // globally higher-ranked candidates, double the window until the requested
-    // limit is filled or every candidate has been considered.
+    // globally higher-ranked candidates that were filtered after retrieval,
+    // double the window until the requested limit is filled or every
+    // candidate has been considered.
     let mut assemble_secs = 0.0_f64;
     let results = loop {
+        // Raw per-retriever scores and ranks, recorded only for explain.
+        let mut signals: HashMap<String, RetrievalSignals> = HashMap::new();
         let mut ranked_candidates = match effective_mode {
             SearchMode::Semantic => {
                 let search_start = Instant::now();
                 let candidates = if candidate_limit == 0 {
                     Vec::new()
                 } else {
-                    index.search_vectors(
+                    vector_plan.search(
+                        index,
                         require_query_embedding(query_embedding.as_deref())?,
                         candidate_limit,
                     )?
                 };
                 vector_search_secs += search_start.elapsed().as_secs_f64();
+                if query.explain {
+                    record_signals(&mut signals, &candidates, |s| &mut s.semantic);
+                }
                 candidates
             }
             SearchMode::Lexical => {
@@ -618,6 +1331,9 @@ pub async fn search(
                 } else {
                     let (candidates, elapsed) = lexical_search(query, fts, candidate_limit)?;
                     lexical_search_secs += elapsed;
+                    if query.explain {
+                        record_signals(&mut signals, &candidates, |s| &mut s.lexical);
+                    }
                     candidates
                 }
             }
@@ -628,7 +1344,8 @@ pub async fn search(
                 let semantic = if candidate_limit == 0 {
                     Vec::new()
                 } else {
-                    index.search_vectors(
+                    vector_plan.search(
+                        index,
                         require_query_embedding(query_embedding.as_deref())?,
                         candidate_limit,
                     )?
@@ -643,25 +1360,52 @@ pub async fn search(
                     candidates
                 };
 
+                if query.explain {
+                    record_signals(&mut signals, &semantic, |s| &mut s.semantic);
+                    record_signals(&mut signals, &lexical, |s| &mut s.lexical);
+                }
+
                 debug!(
                     semantic_count = semantic.len(),
                     lexical_count = lexical.len(),
-                    rrf_k = rrf_k,
-                    "fusing semantic and lexical results via RRF"
+                    %fusion,
+                    rrf_k,
+                    semantic_weight,
+                    "fusing semantic and lexical results"
                 );
                 let fusion_start = Instant::now();
-                let fused = reciprocal_rank_fusion(&semantic, &lexical, rrf_k);
+                let fused = match fusion {
+                    FusionMethod::Rrf => reciprocal_rank_fusion(&semantic, &lexical, rrf_k),
+                    FusionMethod::Linear => {
+                        let mut lexical = lexical;
+                        normalize_bm25_scores(&mut lexical, bm25_norm_k);
+                        linear_score_fusion(&semantic, &lexical, semantic_weight)
+                    }
+                };
                 fusion_secs += fusion_start.elapsed().as_secs_f64();
                 fused
             }
+            SearchMode::Regex => {
+                let regex = regex.as_ref().unwrap(); // safe: compiled above
+                let (candidates, elapsed) = regex_search(query, index, regex);
+                lexical_search_secs += elapsed;
+                candidates
+            }
             SearchMode::Edge => unreachable!("edge mode returns above"),
         };
 
-        // Normalize scores to [0, 1] for non-semantic modes.
-        match effective_mode {
-            SearchMode::Lexical => normalize_bm25_scores(&mut ranked_candidates, bm25_norm_k),
-            SearchMode::Hybrid => normalize_rrf_scores(&mut ranked_candidates, rrf_k, 2),
-            SearchMode::Edge | SearchMode::Semantic => {}
+        // Normalize scores to [0, 1] for non-semantic modes. Linear fusion
+        // already combines normalized inputs.
+        match (effective_mode, fusion) {
+            (SearchMode::Lexical, _) => normalize_bm25_scores(&mut ranked_candidates, bm25_norm_k),
+            (SearchMode::Hybrid, FusionMethod::Rrf) => {
+                normalize_rrf_scores(&mut ranked_candidates, rrf_k, 2)
+            }
+            (SearchMode::Hybrid, FusionMethod::Linear)
+            | (SearchMode::Edge | SearchMode::Semantic | SearchMode::Regex, _) => {}
+        }
+        if !exclude_chunk_ids.is_empty() {
+            ranked_candidates.retain(|(id, _)| !exclude_chunk_ids.contains(id));
         }
 
         debug!(
@@ -673,10 +1417,11 @@ pub async fn search(
         );
 
         let result_assembly_start = Instant::now();
-        let results = assemble_results(&AssembleParams {
+        let params = AssembleParams {
             query,
             index,
             candidates: &ranked_candidates,
+            limit: pool_limit,
             decay_enabled: should_decay,
             decay_half_life: effective_half_life,
             decay_exclude: effective_decay_exclude,
@@ -688,10 +1433,22 @@ pub async fn search(
             now,
             query_embedding: query_embedding.as_deref(),
             edge_boost_weight,
-        })?;
+            signals: query.explain.then_some(&signals),
+            rrf_k: (effective_mode == SearchMode::Hybrid && fusion == FusionMethod::Rrf)
+                .then_some(rrf_k),
+        };
+        let results = assemble_results(&params)?;
         assemble_secs += result_assembly_start.elapsed().as_secs_f64();
 
-        if query.path_prefix.is_none() || results.len() >= query.limit {
+        // Pre-filtered vector retrieval and the regex scan only return
+        // in-scope candidates, so only lexical-only or post-filtered
+        // retrieval needs a wider window.
+        let post_filtered = match effective_mode {
+            SearchMode::Regex => false,
+            SearchMode::Lexical => true,
+            _ => vector_plan.allowed.is_none(),
+        };
+        if query.path_prefix.is_none() || !post_filtered || results.len() >= pool_limit {
             break results;
         }
         let Some(next_limit) = widened_candidate_limit(candidate_limit, candidate_ceiling) else {
@@ -700,6 +1457,62 @@ pub async fn search(
         candidate_limit = next_limit;
     };
 
+    // Widen sub-split fragments to their heading section before reranking so
+    // the reranker scores the text that is returned.
+    let mut results = if query.parent {
+        expand_to_parent_sections(index, results)
+    } else {
+        results
+    };
+
+    // Rescore the top of the pool with the reranker before truncation.
+    let mut rerank_secs = 0.0_f64;
+    if let (Some(rerank), Some(rerank_limit)) = (rerank, rerank_limit) {
+        let rerank_start = Instant::now();
+        // Diversification picks from the whole pool, so all of it is reranked.
+        let head = if diversify { pool_limit } else { rerank_limit }.min(results.len());
+        match rerank_results(rerank.reranker.as_ref(), &query.query, &mut results[..head]).await {
+            Ok(()) => results.truncate(head),
+            Err(e) => warn!(
+                reranker = rerank.reranker.name(),
+                error = %e,
+                "reranking failed; keeping the fused ranking"
+            ),
+        }
+        rerank_secs = rerank_start.elapsed().as_secs_f64();
+    }
+
+    // Diversify the pool down to the requested limit (MMR and/or per-file cap).
+    let mut results = if diversify {
+        let diversify_start = Instant::now();
+        let ids: Vec<&str> = results.iter().map(|r| r.chunk.chunk_id.as_str()).collect();
+        let vectors = if diversity.is_some() {
+            index.get_vectors_for(&ids)
+        } else {
+            HashMap::new()
+        };
+        let diversified =
+            diversify_results(results, &vectors, diversity, max_per_file, query.limit);
+        assemble_secs += diversify_start.elapsed().as_secs_f64();
+        diversified
+    } else {
+        results.truncate(query.limit);
+        results
+    };
+
+    if query.window > 0 {
+        attach_window(index, &mut results, query.window.min(MAX_WINDOW));
+    }
+    if let Some(regex) = &regex {
+        attach_regex_matches(regex, &mut results);
+    }
+
+    for result in &mut results {
+        if let Some(explain) = result.explain.as_mut() {
+            explain.final_score = result.score;
+        }
+    }
+
     // Graph context expansion: find relevant chunks from linked files.
     let graph_assembly_start = Instant::now();
     let graph_context = if effective_expand_graph > 0 {
@@ -721,6 +1534,14 @@ pub async fn search(
     };
     assemble_secs += graph_assembly_start.elapsed().as_secs_f64();
 
+    let facets_start = Instant::now();
+    let facets = if query.facets.is_empty() {
+        FacetCounts::new()
+    } else {
+        filtered_facets(query, index, exclude_chunk_ids)
+    };
+    assemble_secs += facets_start.elapsed().as_secs_f64();
+
     let total_secs = total_start.elapsed().as_secs_f64();
 
     info!(
@@ -736,8 +1557,14 @@ pub async fn search(
         vector_search_secs,
         lexical_search_secs,
         fusion_secs,
+        rerank_secs,
         assemble_secs,
+        suggest_secs: 0.0,
         total_secs,
+        vector_strategy: uses_vectors.then_some(vector_plan.strategy),
+        filter_selectivity: vector_plan.selectivity,
+        cache_lookup_secs: 0.0,
+        cache_hit: None,
     };
 
     Ok(SearchResponse {
@@ -745,6 +1572,8 @@ pub async fn search(
         graph_context,
         edge_results: Vec::new(),
         timings,
+        facets,
+        suggestions: Vec::new(),
     })
 }
 
@@ -763,6 +1592,9 @@ fn require_query_embedding(query_embedding: Option<&[f32]>) -> Result<&[f32]> {
 
 /// Run lexical (BM25) search and return ranked (chunk_id, score) pairs plus timing.
 ///
+/// Only [`SearchMode::Lexical`] rejects invalid syntax; other modes treat the
+/// offending tokens as plain words.
+///
 /// Returns `(candidates, lexical_search_secs)`.
 fn lexical_search(
     query: &SearchQuery,
@@ -770,7 +1602,8 @@ fn lexical_search(
     limit: usize,
 ) -> Result<(Vec<(String, f64)>, f64)> {
     let t0 = Instant::now();
-    let fts_results = fts_index.search(&query.query, limit)?;
+    let clauses = fts_index.parse_query(&query.query, query.mode == SearchMode::Lexical)?;
+    let fts_results = fts_index.search_clauses(&clauses, limit)?;
     let lexical_search_secs = t0.elapsed().as_secs_f64();
     Ok((
         fts_results
@@ -781,11 +1614,172 @@ fn lexical_search(
     ))
 }
 
+/// Compiled size limit for [`SearchMode::Regex`] patterns, so a pattern like
+/// `\w{1000}{1000}` is rejected instead of exhausting memory.
+const REGEX_COMPILED_MAX_BYTES: usize = 1024 * 1024;
+
+/// Compile a [`SearchMode::Regex`] pattern. `^` and `$` match at line
+/// boundaries, as in grep; prefix the pattern with `(?i)` to ignore case.
+pub(crate) fn compile_regex(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .multi_line(true)
+        .size_limit(REGEX_COMPILED_MAX_BYTES)
+        .dfa_size_limit(REGEX_COMPILED_MAX_BYTES)
+        .build()
+        .map_err(|e| Error::RegexPattern {
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })
+}
+
+/// Scan the stored content of every chunk passing the query's path and
+/// metadata filters for `regex`.
+///
+/// Every matching chunk scores 1.0, so results keep path and line order.
+/// Returns `(candidates, scan_secs)`.
+fn regex_search(query: &SearchQuery, index: &Index, regex: &Regex) -> (Vec<(String, f64)>, f64) {
+    let t0 = Instant::now();
+    let in_scope = index.chunk_ids_where(|file| {
+        query
+            .path_prefix
+            .as_deref()
+            .is_none_or(|scope| crate::path_util::path_is_in_scope(&file.relative_path, scope))
+            && evaluate_filters(&query.filters, file.effective_frontmatter().as_ref())
+    });
+    let mut matched: Vec<(String, StoredChunk)> = in_scope
+        .into_iter()
+        .filter_map(|id| index.get_chunk(&id).map(|chunk| (id, chunk)))
+        .filter(|(_, chunk)| regex.is_match(&chunk.content))
+        .collect();
+    matched.sort_by(|(_, a), (_, b)| {
+        a.source_path
+            .cmp(&b.source_path)
+            .then(a.start_line.cmp(&b.start_line))
+    });
+    let candidates = matched.into_iter().map(|(id, _)| (id, 1.0)).collect();
+    (candidates, t0.elapsed().as_secs_f64())
+}
+
+/// Record every match of `regex` in each result's (final) chunk content.
+fn attach_regex_matches(regex: &Regex, results: &mut [SearchResult]) {
+    for result in results {
+        let chunk = &mut result.chunk;
+        let mut line = chunk.start_line;
+        let mut scanned = 0;
+        chunk.matches = regex
+            .find_iter(&chunk.content)
+            .map(|m| {
+                line += chunk.content[scanned..m.start()].matches('\n').count();
+                scanned = m.start();
+                RegexMatch {
+                    text: m.as_str().to_string(),
+                    start: m.start(),
+                    end: m.end(),
+                    line,
+                }
+            })
+            .collect();
+    }
+}
+
+/// Largest filtered chunk set the planner scans exactly rather than through
+/// the HNSW graph.
+const EXACT_SCAN_MAX_CHUNKS: usize = 2_048;
+
+/// Below this fraction of matching chunks, filtered HNSW traversal visits
+/// mostly rejected nodes and an exact scan of the matches is cheaper.
+const EXACT_SCAN_MAX_SELECTIVITY: f64 = 0.02;
+
+/// Planner output for semantic candidate retrieval.
+struct VectorPlan {
+    strategy: VectorStrategy,
+    /// Vector keys of the chunks passing the query's path and metadata
+    /// filters. `None` when the query is unfiltered or the filters admit
+    /// every chunk.
+    allowed: Option<HashSet<u64>>,
+    /// Fraction of indexed chunks in `allowed`, when filters were evaluated.
+    selectivity: Option<f64>,
+}
+
+impl VectorPlan {
+    fn unfiltered() -> Self {
+        Self {
+            strategy: VectorStrategy::Hnsw,
+            allowed: None,
+            selectivity: None,
+        }
+    }
+
+    fn search(&self, index: &Index, query: &[f32], limit: usize) -> Result<Vec<(String, f64)>> {
+        match (self.strategy, &self.allowed) {
+            (VectorStrategy::Exact, Some(allowed)) => {
+                index.search_vectors_exact(query, limit, allowed)
+            }
+            (VectorStrategy::Filtered, Some(allowed)) => {
+                index.search_vectors_filtered(query, limit, allowed)
+            }
+            _ => index.search_vectors(query, limit),
+        }
+    }
+}
+
+/// Choose how to retrieve semantic candidates for a filtered query.
+///
+/// Filters are evaluated once per file against stored metadata, which gives
+/// the exact selectivity. Small or very selective matching sets are scanned
+/// exactly; larger ones use HNSW with a key predicate; filters that admit
+/// every chunk fall back to plain HNSW.
+fn plan_vector_search(
+    query: &SearchQuery,
+    index: &Index,
+    chunk_count: usize,
+    exclude_chunk_ids: &HashSet<String>,
+) -> VectorPlan {
+    if query.path_prefix.is_none() && query.filters.is_empty() {
+        return VectorPlan::unfiltered();
+    }
+
+    let in_scope = |file: &StoredFile| {
+        query
+            .path_prefix
+            .as_deref()
+            .is_none_or(|scope| crate::path_util::path_is_in_scope(&file.relative_path, scope))
+            && evaluate_filters(&query.filters, file.effective_frontmatter().as_ref())
+    };
+    let allowed = index.chunk_keys_where(in_scope, exclude_chunk_ids);
+
+    let selectivity = if chunk_count == 0 {
+        0.0
+    } else {
+        allowed.len() as f64 / chunk_count as f64
+    };
+    let strategy = if allowed.len() >= chunk_count {
+        VectorStrategy::Hnsw
+    } else if allowed.len() <= EXACT_SCAN_MAX_CHUNKS || selectivity < EXACT_SCAN_MAX_SELECTIVITY {
+        VectorStrategy::Exact
+    } else {
+        VectorStrategy::Filtered
+    };
+    debug!(
+        %strategy,
+        matching = allowed.len(),
+        chunk_count,
+        selectivity,
+        "planned vector search"
+    );
+
+    VectorPlan {
+        strategy,
+        allowed: (strategy != VectorStrategy::Hnsw).then_some(allowed),
+        selectivity: Some(selectivity),
+    }
+}
+
 /// Assemble SearchResult objects from ranked candidates, applying decay, filters, min_score,
 /// and multi-hop link-graph boosting.
 #[allow(clippy::too_many_arguments)]
 /// Compute cosine similarity between two vectors. Returns 0.0 if either is zero-length.
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
     if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
@@ -807,10 +1801,29 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
     }
 }
 
+/// Raw score and 1-based rank of a candidate in each retriever's list.
+#[derive(Default)]
+struct RetrievalSignals {
+    semantic: Option<(f64, usize)>,
+    lexical: Option<(f64, usize)>,
+}
+
+fn record_signals(
+    signals: &mut HashMap<String, RetrievalSignals>,
+    candidates: &[(String, f64)],
+    slot: fn(&mut RetrievalSignals) -> &mut Option<(f64, usize)>,
+) {
+    for (rank, (id, score)) in candidates.iter().enumerate() {
+        *slot(signals.entry(id.clone()).or_default()) = Some((*score, rank + 1));
+    }
+}
+
 struct AssembleParams<'a> {
     query: &'a SearchQuery,
     index: &'a Index,
     candidates: &'a [(String, f64)],
+    /// Number of results to assemble (the query limit, or the wider MMR pool).
+    limit: usize,
     decay_enabled: bool,
     decay_half_life: f64,
     decay_exclude: &'a [String],
@@ -822,6 +1835,62 @@ struct AssembleParams<'a> {
     now: u64,
     query_embedding: Option<&'a [f32]>,
     edge_boost_weight: f64,
+    /// Retrieval signals per chunk; `Some` attaches a [`ScoreExplanation`].
+    signals: Option<&'a HashMap<String, RetrievalSignals>>,
+    /// RRF constant when the candidates were RRF-fused (for explain).
+    rrf_k: Option<f64>,
+}
+
+impl AssembleParams<'_> {
+    /// Time-decay multiplier for a file, or `None` when decay does not apply.
+    fn decay_multiplier(
+        &self,
+        path: &str,
+        file: &StoredFile,
+        file_mtimes: &HashMap<String, u64>,
+    ) -> Option<f64> {
+        (self.decay_enabled && should_apply_decay(path, self.decay_exclude, self.decay_include))
+            .then(|| {
+                let modified = file_mtimes.get(path).copied().unwrap_or(file.indexed_at);
+                apply_time_decay(1.0, modified, self.decay_half_life, self.now)
+            })
+    }
+}
+
+/// Facet counts over every indexed file passing the query's path scope and
+/// metadata filters, counting each file once. Files whose chunks are all in
+/// `exclude_chunk_ids` (a similar-search seed) are left out.
+fn filtered_facets(
+    query: &SearchQuery,
+    index: &Index,
+    exclude_chunk_ids: &HashSet<String>,
+) -> FacetCounts {
+    let list_fields = facet_list_fields(index.get_schema().as_ref(), &query.facets);
+    let mut facets = empty_facets(&query.facets);
+    index.for_each_file(|file| {
+        let in_scope = query
+            .path_prefix
+            .as_deref()
+            .is_none_or(|scope| crate::path_util::path_is_in_scope(&file.relative_path, scope));
+        let excluded = !exclude_chunk_ids.is_empty()
+            && file
+                .chunk_ids
+                .iter()
+                .all(|id| exclude_chunk_ids.contains(id));
+        if !in_scope || excluded {
+            return;
+        }
+        let frontmatter = file.effective_frontmatter();
+        if evaluate_filters(&query.filters, frontmatter.as_ref()) {
+            add_facet_values(
+                &mut facets,
+                &query.facets,
+                &list_fields,
+                frontmatter.as_ref(),
+            );
+        }
+    });
+    facets
 }
 
 fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
@@ -847,17 +1916,8 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
         };
 
         // Apply time decay if enabled and path is not excluded.
-        let effective_score = if p.decay_enabled
-            && should_apply_decay(&chunk.source_path, p.decay_exclude, p.decay_include)
-        {
-            let modified = file_mtimes
-                .get(&chunk.source_path)
-                .copied()
-                .unwrap_or(file.indexed_at);
-            apply_time_decay(*score, modified, p.decay_half_life, p.now)
-        } else {
-            *score
-        };
+        let decay_multiplier = p.decay_multiplier(&chunk.source_path, &file, &file_mtimes);
+        let effective_score = score * decay_multiplier.unwrap_or(1.0);
 
         // Apply min_score threshold (on potentially decayed score).
         if effective_score < p.query.min_score {
@@ -877,16 +1937,28 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
         }
 
         let modified_at = file_mtimes.get(&chunk.source_path).copied();
+        let explain = p.signals.map(|signals| {
+            let signals = signals.get(chunk_id);
+            let semantic = signals.and_then(|s| s.semantic);
+            let lexical = signals.and_then(|s| s.lexical);
+            let rrf_term = |rank: usize| p.rrf_k.map(|k| 1.0 / (k + rank as f64));
+            ScoreExplanation {
+                semantic_score: semantic.map(|(score, _)| score),
+                semantic_rank: semantic.map(|(_, rank)| rank),
+                bm25_score: lexical.map(|(score, _)| score),
+                bm25_rank: lexical.map(|(_, rank)| rank),
+                rrf_semantic: semantic.and_then(|(_, rank)| rrf_term(rank)),
+                rrf_lexical: lexical.and_then(|(_, rank)| rrf_term(rank)),
+                retrieval_score: *score,
+                decay_multiplier,
+                final_score: effective_score,
+                ..Default::default()
+            }
+        });
 
         results.push(SearchResult {
             score: effective_score,
-            chunk: SearchResultChunk {
-                chunk_id: chunk_id.clone(),
-                heading_hierarchy: chunk.heading_hierarchy.clone(),
-                content: chunk.content.clone(),
-                start_line: chunk.start_line,
-                end_line: chunk.end_line,
-            },
+            chunk: SearchResultChunk::from_stored(chunk_id.clone(), &chunk),
             file: SearchResultFile {
                 path: chunk.source_path.clone(),
                 frontmatter,
@@ -897,10 +1969,11 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
                 modified_at,
                 relations: None,
             },
+            explain,
         });
 
         // Stop early if no decay (order preserved) and we have enough results.
-        if !p.decay_enabled && results.len() >= p.query.limit {
+        if !p.decay_enabled && results.len() >= p.limit {
             break;
         }
     }
@@ -912,7 +1985,7 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
                 .partial_cmp(&a.score)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
-        results.truncate(p.query.limit);
+        results.truncate(p.limit);
     }
 
     // Apply multi-hop link-graph boosting if requested.
@@ -926,7 +1999,7 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
                 .collect();
 
             // BFS to find neighbors at configurable depth (1–3 hops).
-            let neighbors = links::bfs_neighbors(graph, bl, &top_paths, p.boost_hops);
+            let neighbors = links::bfs_neighbors_with_origin(graph, bl, &top_paths, p.boost_hops);
 
             if !neighbors.is_empty() {
                 // Pre-fetch edge vectors for semantic edge boosting.
@@ -964,17 +2037,30 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
                     };
 
                 for result in &mut results {
-                    if let Some(&distance) = neighbors.get(&result.file.path) {
+                    if let Some((distance, source)) = neighbors.get(&result.file.path) {
+                        let distance = *distance;
                         // Use edge-weighted boost when edge scores available,
                         // otherwise fall back to flat boost.
-                        let boost = if let Some(&edge_sim) = edge_scores.get(&result.file.path) {
+                        let edge_sim = edge_scores.get(&result.file.path).copied();
+                        let boost = if let Some(edge_sim) = edge_sim {
                             p.edge_boost_weight * edge_sim
                         } else {
                             // Flat fallback: use edge_boost_weight as the base boost.
                             p.edge_boost_weight
                         };
                         let multiplier = 1.0 + boost / 2.0_f64.powi(distance as i32 - 1);
-                        result.score *= multiplier;
+                        let boosted = result.score * multiplier;
+                        if let Some(explain) = result.explain.as_mut() {
+                            explain.link_boost = Some(LinkBoostExplanation {
+                                hops: distance,
+                                source: source.clone(),
+                                edge_similarity: edge_sim,
+                                edge_boost: boost,
+                                multiplier,
+                                delta: boosted - result.score,
+                            });
+                        }
+                        result.score = boosted;
                         debug!(
                             path = %result.file.path,
                             distance = distance,
@@ -996,6 +2082,232 @@ fn assemble_results(p: &AssembleParams<'_>) -> Result<Vec<SearchResult>> {
     Ok(results)
 }
 
+/// Replace each sub-split result with its full heading section.
+///
+/// Results are visited in score order, so each section keeps the score of its
+/// best fragment; later fragments of an already-widened section are dropped.
+fn expand_to_parent_sections(index: &Index, results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut covered: HashSet<String> = HashSet::new();
+    let mut expanded = Vec::with_capacity(results.len());
+    for mut result in results {
+        if !covered.insert(result.chunk.chunk_id.clone()) {
+            continue;
+        }
+        let Some(hit) = index.get_chunk(&result.chunk.chunk_id) else {
+            expanded.push(result);
+            continue;
+        };
+        if !hit.is_sub_split {
+            expanded.push(result);
+            continue;
+        }
+
+        let section = parent_section_chunks(index, &result.file.path, hit);
+        let mut content = String::new();
+        for (_, chunk) in &section {
+            content = if content.is_empty() {
+                chunk.content.clone()
+            } else {
+                join_chunk_content(&content, &chunk.content)
+            };
+        }
+        if let (Some((_, first)), Some((_, last))) = (section.first(), section.last()) {
+            result.chunk.start_line = first.start_line;
+            result.chunk.end_line = last.end_line.max(result.chunk.end_line);
+        }
+        result.chunk.content = content;
+        result.chunk.section_chunk_ids = section.into_iter().map(|(id, _)| id).collect();
+        covered.extend(result.chunk.section_chunk_ids.iter().cloned());
+        expanded.push(result);
+    }
+    expanded
+}
+
+/// Collect the sub-split chunks making up the heading section of `hit`, in
+/// file order.
+///
+/// Sub-splits of one section are consecutive chunks sharing a heading
+/// hierarchy whose line ranges overlap or touch.
+fn parent_section_chunks(
+    index: &Index,
+    path: &str,
+    hit: StoredChunk,
+) -> Vec<(String, StoredChunk)> {
+    let same_section = |earlier: &StoredChunk, later: &StoredChunk| {
+        earlier.is_sub_split
+            && later.is_sub_split
+            && earlier.heading_hierarchy == later.heading_hierarchy
+            && later.start_line <= earlier.end_line + 1
+    };
+
+    let mut preceding = Vec::new();
+    let mut edge = hit.clone();
+    while let Some(i) = edge.chunk_index.checked_sub(1) {
+        let id = format!("{path}#{i}");
+        match index.get_chunk(&id) {
+            Some(prev) if same_section(&prev, &edge) => {
+                edge = prev.clone();
+                preceding.push((id, prev));
+            }
+            _ => break,
+        }
+    }
+    preceding.reverse();
+
+    let mut section = preceding;
+    let mut edge = hit.clone();
+    section.push((format!("{path}#{}", hit.chunk_index), hit));
+    loop {
+        let id = format!("{path}#{}", edge.chunk_index + 1);
+        match index.get_chunk(&id) {
+            Some(next) if same_section(&edge, &next) => {
+                edge = next.clone();
+                section.push((id, next));
+            }
+            _ => break,
+        }
+    }
+    section
+}
+
+/// Attach up to `window` neighboring chunks from the same file before and
+/// after each result. For widened parent sections, neighbors are taken
+/// around the whole section rather than the matching fragment.
+fn attach_window(index: &Index, results: &mut [SearchResult], window: usize) {
+    for result in results {
+        let ids = if result.chunk.section_chunk_ids.is_empty() {
+            std::slice::from_ref(&result.chunk.chunk_id)
+        } else {
+            &result.chunk.section_chunk_ids[..]
+        };
+        let indices: Vec<usize> = ids
+            .iter()
+            .filter_map(|id| chunk_index_from_id(id))
+            .collect();
+        let (Some(&first), Some(&last)) = (indices.iter().min(), indices.iter().max()) else {
+            continue;
+        };
+        let path = result.file.path.clone();
+        let neighbor = |i: usize| {
+            let id = format!("{path}#{i}");
+            index
+                .get_chunk(&id)
+                .map(|chunk| SearchResultChunk::from_stored(id, &chunk))
+        };
+        // Chunk indices are contiguous per file, so the first missing
+        // neighbor marks the file boundary; stop there however large
+        // `window` is.
+        let mut before: Vec<SearchResultChunk> = (first.saturating_sub(window)..first)
+            .rev()
+            .map_while(&neighbor)
+            .collect();
+        before.reverse();
+        result.chunk.before = before;
+        result.chunk.after = (last + 1..=last.saturating_add(window))
+            .map_while(&neighbor)
+            .collect();
+    }
+}
+
+/// Replace each result's score with the reranker's relevance score for its
+/// chunk content and re-sort. Ties keep their fused order.
+async fn rerank_results(
+    reranker: &dyn Reranker,
+    query: &str,
+    results: &mut [SearchResult],
+) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let documents: Vec<String> = results.iter().map(|r| r.chunk.content.clone()).collect();
+    let scores = reranker.rerank(query, &documents).await?;
+    validate_scores(&scores, documents.len())?;
+    debug!(
+        reranker = reranker.name(),
+        candidates = documents.len(),
+        "reranked search candidates"
+    );
+    for (result, score) in results.iter_mut().zip(scores) {
+        result.score = score;
+        if let Some(explain) = result.explain.as_mut() {
+            explain.rerank_score = Some(score);
+        }
+    }
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(())
+}
+
+/// Pool size multiplier for diversification: MMR picks `limit` results out of
+/// `limit * MMR_POOL_FACTOR` assembled candidates.
+const MMR_POOL_FACTOR: usize = 4;
+
+/// Re-rank results with Maximal Marginal Relevance and/or a per-file cap.
+///
+/// Greedily selects the result maximizing
+/// `λ · score − (1 − λ) · max cosine(result, already selected)`, using the
+/// stored chunk vectors in `vectors` (results without a vector count as fully
+/// novel). With `lambda = None` the original order is kept and only the
+/// per-file cap applies. Scores are left unchanged; only order and membership
+/// differ from the input.
+pub(crate) fn diversify_results(
+    results: Vec<SearchResult>,
+    vectors: &HashMap<String, Vec<f32>>,
+    lambda: Option<f64>,
+    max_per_file: Option<usize>,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let max_per_file = max_per_file.unwrap_or(usize::MAX);
+    let mut per_file: HashMap<String, usize> = HashMap::new();
+    let mut remaining: Vec<Option<SearchResult>> = results.into_iter().map(Some).collect();
+    let mut selected: Vec<SearchResult> = Vec::with_capacity(limit.min(remaining.len()));
+
+    while selected.len() < limit {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, candidate) in remaining.iter().enumerate() {
+            let Some(candidate) = candidate else {
+                continue;
+            };
+            if per_file.get(&candidate.file.path).copied().unwrap_or(0) >= max_per_file {
+                continue;
+            }
+            let value = match lambda {
+                Some(lambda) => {
+                    let redundancy = vectors
+                        .get(&candidate.chunk.chunk_id)
+                        .map(|cv| {
+                            selected
+                                .iter()
+                                .filter_map(|s| vectors.get(&s.chunk.chunk_id))
+                                .map(|sv| cosine_similarity(cv, sv))
+                                .fold(0.0_f64, f64::max)
+                        })
+                        .unwrap_or(0.0);
+                    lambda * candidate.score - (1.0 - lambda) * redundancy
+                }
+                // Without MMR the first admissible candidate wins (input order).
+                None => -(i as f64),
+            };
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((i, value));
+            }
+        }
+        let Some((i, _)) = best else {
+            break;
+        };
+        let Some(chosen) = remaining[i].take() else {
+            break;
+        };
+        *per_file.entry(chosen.file.path.clone()).or_default() += 1;
+        selected.push(chosen);
+    }
+
+    selected
+}
+
 /// Assemble EdgeSearchResult entries from edge search candidates and the link graph.
 ///
 /// For each edge candidate (edge_id, score), looks up the SemanticEdge metadata from
@@ -1187,13 +2499,7 @@ fn expand_graph_context(
                 find_edge_context(link_graph, &linked_from, &path);
 
             items.push(GraphContextItem {
-                chunk: SearchResultChunk {
-                    chunk_id: chunk_id.clone(),
-                    heading_hierarchy: chunk.heading_hierarchy.clone(),
-                    content: chunk.content.clone(),
-                    start_line: chunk.start_line,
-                    end_line: chunk.end_line,
-                },
+                chunk: SearchResultChunk::from_stored(chunk_id.clone(), &chunk),
                 file: SearchResultFile {
                     path: path.clone(),
                     frontmatter,
@@ -1303,6 +2609,30 @@ pub fn reciprocal_rank_fusion(
     results
 }
 
+/// Linear score fusion: `α × semantic + (1 − α) × lexical` per chunk.
+///
+/// Both inputs must already be in `[0, 1]` (cosine similarity and
+/// saturation-normalized BM25), so the fused score is too. A chunk missing
+/// from one list contributes 0 for that signal.
+/// Returns a list of `(chunk_id, fused_score)` sorted by score descending.
+pub fn linear_score_fusion(
+    semantic: &[(String, f64)],
+    lexical: &[(String, f64)],
+    alpha: f64,
+) -> Vec<(String, f64)> {
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for (id, score) in semantic {
+        *scores.entry(id.clone()).or_default() += alpha * score;
+    }
+    for (id, score) in lexical {
+        *scores.entry(id.clone()).or_default() += (1.0 - alpha) * score;
+    }
+
+    let mut results: Vec<(String, f64)> = scores.into_iter().collect();
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    results
+}
+
 /// Normalize BM25 scores to `[0, 1]` using saturation: `score / (score + k)`.
 ///
 /// This maps the unbounded BM25 range `(0, ∞)` into `(0, 1)` with diminishing returns.
@@ -1334,14 +2664,14 @@ fn normalize_rrf_scores(results: &mut [(String, f64)], rrf_k: f64, num_lists: us
 /// Evaluate all metadata filters against parsed frontmatter. Returns `true` if all pass.
 ///
 /// - Empty filters → always `true`
-/// - `None` frontmatter with any filter → `false`
+/// - `None` frontmatter is treated as an empty object, so every leaf filter
+///   fails and only negations (e.g. `status != draft`) can pass
 pub(crate) fn evaluate_filters(filters: &[MetadataFilter], frontmatter: Option<&Value>) -> bool {
     if filters.is_empty() {
         return true;
     }
-    let Some(fm) = frontmatter else {
-        return false;
-    };
+    let empty = Value::Object(serde_json::Map::new());
+    let fm = frontmatter.unwrap_or(&empty);
 
     filters
         .iter()
@@ -1414,6 +2744,40 @@ fn evaluate_single_filter(filter: &MetadataFilter, frontmatter: &Value) -> bool
             true
         }
         MetadataFilter::Exists { field } => frontmatter.get(field).is_some_and(|v| !v.is_null()),
+        MetadataFilter::Compare { field, op, value } => {
+            let Some(field_value) = frontmatter.get(field) else {
+                return false;
+            };
+            let satisfies = |v: &Value| {
+                if v.is_null() {
+                    return false;
+                }
+                let ord = compare_json_for_sort(v, value);
+                match op {
+                    CompareOp::Gt => ord == std::cmp::Ordering::Greater,
+                    CompareOp::Ge => ord != std::cmp::Ordering::Less,
+                    CompareOp::Lt => ord == std::cmp::Ordering::Less,
+                    CompareOp::Le => ord != std::cmp::Ordering::Greater,
+                }
+            };
+            if let Some(arr) = field_value.as_array() {
+                arr.iter().any(satisfies)
+            } else {
+                satisfies(field_value)
+            }
+        }
+        MetadataFilter::Contains { field, value } => match frontmatter.get(field) {
+            Some(Value::Array(arr)) => arr.iter().any(|v| filter_values_equal(v, value)),
+            Some(Value::String(s)) => s.contains(value_as_string(value).as_str()),
+            _ => false,
+        },
+        MetadataFilter::And(filters) => filters
+            .iter()
+            .all(|filter| evaluate_single_filter(filter, frontmatter)),
+        MetadataFilter::Or(filters) => filters
+            .iter()
+            .any(|filter| evaluate_single_filter(filter, frontmatter)),
+        MetadataFilter::Not(filter) => !evaluate_single_filter(filter, frontmatter),
     }
 }
 
@@ -1583,12 +2947,21 @@ mod tests {
                 vector_search_secs: 0.0,
                 lexical_search_secs: 0.0,
                 fusion_secs: 0.0,
+                rerank_secs: 0.0,
                 assemble_secs: 0.0,
+                suggest_secs: 0.0,
                 total_secs: 0.0,
+                vector_strategy: None,
+                filter_selectivity: None,
+                cache_lookup_secs: 0.0,
+                cache_hit: None,
             },
+            facets: FacetCounts::new(),
+            suggestions: Vec::new(),
         };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(!json.contains("edge_results"));
+        assert!(!json.contains("facets"));
     }
 
     #[test]
@@ -1600,6 +2973,11 @@ mod tests {
                 content: "test".into(),
                 start_line: 1,
                 end_line: 5,
+                before: vec![],
+                after: vec![],
+                section_chunk_ids: vec![],
+                snippet: None,
+                matches: vec![],
             },
             file: SearchResultFile {
                 path: "a.md".into(),
@@ -1630,6 +3008,11 @@ mod tests {
                 content: "test".into(),
                 start_line: 1,
                 end_line: 5,
+                before: vec![],
+                after: vec![],
+                section_chunk_ids: vec![],
+                snippet: None,
+                matches: vec![],
             },
             file: SearchResultFile {
                 path: "a.md".into(),
@@ -1651,6 +3034,29 @@ mod tests {
         assert!(!json.contains("edge_relationship"));
     }
 
+    #[test]
+    fn test_search_mode_regex_round_trip() {
+        assert_eq!("regex".parse::<SearchMode>().unwrap(), SearchMode::Regex);
+        assert_eq!(SearchMode::Regex.to_string(), "regex");
+        assert_eq!(
+            serde_json::to_string(&SearchMode::Regex).unwrap(),
+            "\"regex\""
+        );
+    }
+
+    #[test]
+    fn test_compile_regex_is_multi_line_and_rejects_invalid() {
+        let regex = compile_regex("^ERR-\\d+").unwrap();
+        assert_eq!(regex.find_iter("ok\nERR-12 x ERR-3").count(), 1);
+        let err = compile_regex("ERR-(").unwrap_err();
+        assert!(
+            matches!(&err, Error::RegexPattern { pattern, .. } if pattern == "ERR-("),
+            "{err}"
+        );
+        let err = compile_regex(r"\w{1000}{1000}").unwrap_err();
+        assert!(matches!(err, Error::RegexPattern { .. }), "{err}");
+    }
+
     #[test]
     fn test_search_mode_from_str_invalid() {
         let err = "invalid".parse::<SearchMode>().unwrap_err();
@@ -1843,6 +3249,12 @@ mod tests {
         assert_eq!(q.filters.len(), 1);
     }
 
+    #[test]
+    fn test_search_query_with_window_is_bounded() {
+        assert_eq!(SearchQuery::new("q").with_window(3).window, 3);
+        assert_eq!(SearchQuery::new("q").with_window(10_000).window, MAX_WINDOW);
+    }
+
     #[test]
     fn test_search_query_with_mode() {
         let q = SearchQuery::new("test").with_mode(SearchMode::Lexical);
@@ -1866,6 +3278,73 @@ mod tests {
         assert!(!evaluate_filters(&filters, None));
     }
 
+    #[test]
+    fn test_negated_filter_passes_without_frontmatter() {
+        let filters = vec![MetadataFilter::Not(Box::new(MetadataFilter::Equals {
+            field: "status".into(),
+            value: json!("draft"),
+        }))];
+        assert!(evaluate_filters(&filters, None));
+    }
+
+    #[test]
+    fn test_compare_filter_strict_bounds() {
+        let fm = json!({"year": 2024, "scores": [1, 9]});
+        let compare = |field: &str, op, value| MetadataFilter::Compare {
+            field: field.into(),
+            op,
+            value,
+        };
+        assert!(evaluate_filters(
+            &[compare("year", CompareOp::Gt, json!(2023))],
+            Some(&fm)
+        ));
+        assert!(!evaluate_filters(
+            &[compare("year", CompareOp::Gt, json!(2024))],
+            Some(&fm)
+        ));
+        assert!(evaluate_filters(
+            &[compare("year", CompareOp::Le, json!(2024))],
+            Some(&fm)
+        ));
+        // Arrays match when any element satisfies the comparison.
+        assert!(evaluate_filters(
+            &[compare("scores", CompareOp::Gt, json!(5))],
+            Some(&fm)
+        ));
+        assert!(!evaluate_filters(
+            &[compare("missing", CompareOp::Lt, json!(5))],
+            Some(&fm)
+        ));
+    }
+
+    #[test]
+    fn test_or_filter_any_branch() {
+        let fm = json!({"status": "draft"});
+        let filters = vec![MetadataFilter::Or(vec![
+            MetadataFilter::Equals {
+                field: "status".into(),
+                value: json!("published"),
+            },
+            MetadataFilter::Equals {
+                field: "status".into(),
+                value: json!("draft"),
+            },
+        ])];
+        assert!(evaluate_filters(&filters, Some(&fm)));
+        assert!(!evaluate_filters(
+            &[MetadataFilter::Or(Vec::new())],
+            Some(&fm)
+        ));
+    }
+
+    #[test]
+    fn test_metadata_filter_from_str() {
+        let filter: MetadataFilter = "tags contains rust".parse().unwrap();
+        assert!(matches!(filter, MetadataFilter::Contains { .. }));
+        assert!("tags contains".parse::<MetadataFilter>().is_err());
+    }
+
     #[test]
     fn test_equals_filter_matches() {
         let fm = json!({"status": "draft"});
@@ -2251,6 +3730,72 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    // --- Linear fusion tests ---
+
+    #[test]
+    fn test_linear_fusion_overlapping_lists() {
+        let semantic = vec![("a".to_string(), 0.9), ("b".to_string(), 0.5)];
+        let lexical = vec![("b".to_string(), 0.4), ("c".to_string(), 0.8)];
+        let results = linear_score_fusion(&semantic, &lexical, 0.7);
+
+        let b_score = results.iter().find(|(id, _)| id == "b").unwrap().1;
+        assert!((b_score - (0.7 * 0.5 + 0.3 * 0.4)).abs() < 1e-10);
+        let a_score = results.iter().find(|(id, _)| id == "a").unwrap().1;
+        assert!((a_score - 0.7 * 0.9).abs() < 1e-10);
+        let c_score = results.iter().find(|(id, _)| id == "c").unwrap().1;
+        assert!((c_score - 0.3 * 0.8).abs() < 1e-10);
+
+        // Strong semantic-only hit beats a mediocre hit found by both.
+        assert_eq!(results[0].0, "a");
+        for w in results.windows(2) {
+            assert!(w[0].1 >= w[1].1);
+        }
+    }
+
+    #[test]
+    fn test_linear_fusion_alpha_extremes() {
+        let semantic = vec![("a".to_string(), 0.9)];
+        let lexical = vec![("b".to_string(), 0.6)];
+
+        let pure_semantic = linear_score_fusion(&semantic, &lexical, 1.0);
+        assert_eq!(pure_semantic[0].0, "a");
+        assert!((pure_semantic[0].1 - 0.9).abs() < 1e-10);
+        assert_eq!(pure_semantic[1].1, 0.0);
+
+        let pure_lexical = linear_score_fusion(&semantic, &lexical, 0.0);
+        assert_eq!(pure_lexical[0].0, "b");
+        assert!((pure_lexical[0].1 - 0.6).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_linear_fusion_scores_in_unit_range() {
+        let semantic = vec![("a".to_string(), 1.0), ("b".to_string(), 0.2)];
+        let lexical = vec![("a".to_string(), 1.0), ("b".to_string(), 0.99)];
+        for alpha in [0.0, 0.3, 0.7, 1.0] {
+            for (_, score) in linear_score_fusion(&semantic, &lexical, alpha) {
+                assert!((0.0..=1.0).contains(&score), "score {score} out of range");
+            }
+        }
+    }
+
+    #[test]
+    fn test_linear_fusion_empty_inputs() {
+        let empty: Vec<(String, f64)> = vec![];
+        assert!(linear_score_fusion(&empty, &empty, 0.7).is_empty());
+    }
+
+    #[test]
+    fn test_fusion_method_from_str_and_display() {
+        assert_eq!("rrf".parse::<FusionMethod>().unwrap(), FusionMethod::Rrf);
+        assert_eq!(
+            "LINEAR".parse::<FusionMethod>().unwrap(),
+            FusionMethod::Linear
+        );
+        assert!("borda".parse::<FusionMethod>().is_err());
+        assert_eq!(FusionMethod::Linear.to_string(), "linear");
+        assert_eq!(FusionMethod::default(), FusionMethod::Rrf);
+    }
+
     // --- BM25 normalization tests ---
 
     #[test]
@@ -2834,4 +4379,144 @@ mod tests {
         assert!(m2 > m3);
         assert!(m3 > 1.0);
     }
+
+    #[test]
+    fn test_add_facet_values_counts_list_elements_once_per_document() {
+        let fields = vec!["tags".to_string(), "status".to_string(), "year".to_string()];
+        let mut facets = empty_facets(&fields);
+        let list_fields = HashMap::new();
+        add_facet_values(
+            &mut facets,
+            &fields,
+            &list_fields,
+            Some(&json!({"tags": ["rust", "cli", "rust"], "status": "draft", "year": 2024})),
+        );
+        add_facet_values(
+            &mut facets,
+            &fields,
+            &list_fields,
+            Some(&json!({"tags": ["rust"], "status": null})),
+        );
+        add_facet_values(&mut facets, &fields, &list_fields, None);
+
+        assert_eq!(facets["tags"]["rust"], 2);
+        assert_eq!(facets["tags"]["cli"], 1);
+        assert_eq!(facets["status"].len(), 1);
+        assert_eq!(facets["status"]["draft"], 1);
+        assert_eq!(facets["year"]["2024"], 1);
+    }
+
+    #[test]
+    fn test_add_facet_values_splits_only_list_fields() {
+        let fields = vec!["tags".to_string(), "points".to_string()];
+        let frontmatter = json!({"tags": ["a", "b"], "points": [{"x": 1}, {"x": 2}]});
+
+        let mut facets = empty_facets(&fields);
+        add_facet_values(&mut facets, &fields, &HashMap::new(), Some(&frontmatter));
+        assert_eq!(facets["tags"].len(), 2);
+        assert_eq!(
+            facets["points"].len(),
+            1,
+            "arrays of objects are Json values"
+        );
+
+        // The schema decides over the value's shape.
+        let list_fields = HashMap::from([("tags".to_string(), false)]);
+        let mut facets = empty_facets(&fields);
+        add_facet_values(&mut facets, &fields, &list_fields, Some(&frontmatter));
+        assert_eq!(facets["tags"].len(), 1);
+        assert_eq!(facets["tags"]["[\"a\",\"b\"]"], 1);
+    }
+
+    // --- Diversification tests ---
+
+    fn diversity_result(chunk_id: &str, path: &str, score: f64) -> SearchResult {
+        SearchResult {
+            score,
+            chunk: SearchResultChunk {
+                chunk_id: chunk_id.into(),
+                heading_hierarchy: vec![],
+                content: String::new(),
+                start_line: 1,
+                end_line: 1,
+                before: vec![],
+                after: vec![],
+                section_chunk_ids: vec![],
+                snippet: None,
+                matches: vec![],
+            },
+            file: SearchResultFile {
+                path: path.into(),
+                frontmatter: None,
+                computed_fields: serde_json::Map::new(),
+                computed_field_errors: std::collections::BTreeMap::new(),
+                file_size: 0,
+                path_components: vec![path.into()],
+                modified_at: None,
+                relations: None,
+            },
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn test_diversify_without_options_keeps_order_and_limit() {
+        let results = vec![
+            diversity_result("a.md#0", "a.md", 0.9),
+            diversity_result("a.md#1", "a.md", 0.8),
+            diversity_result("b.md#0", "b.md", 0.7),
+        ];
+        let out = diversify_results(results, &HashMap::new(), None, None, 2);
+        let ids: Vec<&str> = out.iter().map(|r| r.chunk.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a.md#0", "a.md#1"]);
+    }
+
+    #[test]
+    fn test_diversify_max_per_file_caps_results() {
+        let results = vec![
+            diversity_result("a.md#0", "a.md", 0.9),
+            diversity_result("a.md#1", "a.md", 0.8),
+            diversity_result("a.md#2", "a.md", 0.75),
+            diversity_result("b.md#0", "b.md", 0.5),
+        ];
+        let out = diversify_results(results, &HashMap::new(), None, Some(1), 3);
+        let ids: Vec<&str> = out.iter().map(|r| r.chunk.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a.md#0", "b.md#0"]);
+    }
+
+    #[test]
+    fn test_diversify_mmr_prefers_dissimilar_chunk() {
+        let results = vec![
+            diversity_result("a.md#0", "a.md", 0.90),
+            diversity_result("a.md#1", "a.md", 0.88),
+            diversity_result("b.md#0", "b.md", 0.80),
+        ];
+        let mut vectors = HashMap::new();
+        vectors.insert("a.md#0".to_string(), vec![1.0, 0.0]);
+        vectors.insert("a.md#1".to_string(), vec![0.99, 0.01]);
+        vectors.insert("b.md#0".to_string(), vec![0.0, 1.0]);
+
+        let out = diversify_results(results, &vectors, Some(0.5), None, 2);
+        let ids: Vec<&str> = out.iter().map(|r| r.chunk.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a.md#0", "b.md#0"]);
+        // Scores are reported unchanged.
+        assert_eq!(out[1].score, 0.80);
+    }
+
+    #[test]
+    fn test_diversify_lambda_one_is_pure_relevance() {
+        let results = vec![
+            diversity_result("a.md#0", "a.md", 0.90),
+            diversity_result("a.md#1", "a.md", 0.88),
+            diversity_result("b.md#0", "b.md", 0.80),
+        ];
+        let mut vectors = HashMap::new();
+        vectors.insert("a.md#0".to_string(), vec![1.0, 0.0]);
+        vectors.insert("a.md#1".to_string(), vec![1.0, 0.0]);
+        vectors.insert("b.md#0".to_string(), vec![0.0, 1.0]);
+
+        let out = diversify_results(results, &vectors, Some(1.0), None, 2);
+        let ids: Vec<&str> = out.iter().map(|r| r.chunk.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a.md#0", "a.md#1"]);
+    }
 }
@@ -0,0 +1,578 @@
+//! Point-in-time copies of the index under `.markdownvdb/snapshots/`.
+//!
+//! A snapshot holds everything needed to go back to an earlier index after a
+//! bad model or chunking change: the vector index file, its write-ahead log
+//! and full-precision vector sidecar, the Tantivy FTS directory, the
+//! embedding-space descriptor, and the shard analysis caches. Each lives in
+//! `.markdownvdb/snapshots/<ID>/`, where the ID is the UTC creation time
+//! (`20261016T142530Z`), next to a `snapshot.json` manifest.
+//!
+//! Files that mdvdb only ever replaces by rename (the index file and sidecar,
+//! Tantivy segments, caches) are hardlinked, so a snapshot costs almost no
+//! disk space until the live copies are rewritten. The write-ahead log is
+//! appended in place and is therefore always copied, as a reflink where the
+//! filesystem supports it.
+//!
+//! Callers hold the project's module-run lock (see
+//! [`acquire_module_run_lock`](crate::modules::acquire_module_run_lock)),
+//! which keeps ingest and watch from committing between the vector index and
+//! the FTS copy.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::Error;
+use crate::fts;
+use crate::index::state::{acquire_write_lock, Index};
+use crate::index::{storage, vectors, wal};
+use crate::Result;
+
+const SNAPSHOT_FORMAT: &str = "mdvdb.snapshot";
+const SNAPSHOT_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "snapshot.json";
+const INDEX_FILE: &str = "index";
+const WAL_FILE: &str = "index.wal";
+const SIDECAR_FILE: &str = "index.vectors";
+const FTS_DIR: &str = "fts";
+const EMBEDDING_SPACE_FILE: &str = "embedding-space.json";
+const SHARD_CACHE_DIR: [&str; 2] = ["cache", "shards"];
+/// Attempts at copying an FTS directory whose commit stays unchanged.
+const FTS_COPY_ATTEMPTS: usize = 3;
+
+/// Why a snapshot was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    /// `mdvdb snapshot create`.
+    Manual,
+    /// Taken automatically before `mdvdb ingest --reindex` replaced the index.
+    PreReindex,
+}
+
+/// One snapshot, as recorded in its `snapshot.json` manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Directory name under `.markdownvdb/snapshots/`.
+    pub id: String,
+    /// Unix time the snapshot was taken.
+    pub created_at: u64,
+    pub reason: SnapshotReason,
+    /// Embedding provider the captured vectors were produced by.
+    pub embedding_provider: String,
+    /// Embedding model the captured vectors were produced by.
+    pub embedding_model: String,
+    /// Dimensionality of the captured vectors.
+    pub embedding_dimensions: usize,
+    /// Number of indexed documents.
+    pub document_count: usize,
+    /// Number of indexed chunks.
+    pub chunk_count: usize,
+    /// Total size of the captured files in bytes. Hardlinked files share
+    /// their blocks with the live index until it is rewritten.
+    pub bytes: u64,
+    /// True when the captured FTS index was missing or mid-reconciliation;
+    /// restoring it schedules a rebuild from the vector index.
+    #[serde(default)]
+    pub fts_rebuild_required: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotManifest {
+    format: String,
+    version: u32,
+    #[serde(flatten)]
+    info: SnapshotInfo,
+}
+
+/// Directory holding every snapshot of the project at `root`.
+pub(crate) fn snapshots_dir(root: &Path) -> PathBuf {
+    root.join(".markdownvdb").join("snapshots")
+}
+
+/// Capture the current index as a new snapshot.
+pub(crate) fn create(root: &Path, reason: SnapshotReason) -> Result<SnapshotInfo> {
+    let state_dir = root.join(".markdownvdb");
+    let index_path = state_dir.join(INDEX_FILE);
+    if !index_path.is_file() {
+        return Err(Error::IndexNotFound { path: index_path });
+    }
+    let snapshots = snapshots_dir(root);
+    fs::create_dir_all(&snapshots)?;
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let id = unused_id(&snapshots, created_at);
+    // Populate a hidden directory and rename it into place, so listing and
+    // restoring never see a partial snapshot.
+    let staging = snapshots.join(format!(".{id}.tmp"));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir(&staging)?;
+    let result = populate(&state_dir, &staging).and_then(|fts_captured| {
+        let status = Index::open(&staging.join(INDEX_FILE))?.status();
+        let info = SnapshotInfo {
+            id: id.clone(),
+            created_at,
+            reason,
+            embedding_provider: status.embedding_config.provider,
+            embedding_model: status.embedding_config.model,
+            embedding_dimensions: status.embedding_config.dimensions,
+            document_count: status.document_count,
+            chunk_count: status.chunk_count,
+            bytes: dir_size(&staging)?,
+            fts_rebuild_required: !fts_captured || fts::reconciliation_required(root)?,
+        };
+        write_manifest(&staging, &info)?;
+        sync_directory(&staging)?;
+        fs::rename(&staging, snapshots.join(&id))?;
+        sync_directory(&snapshots)?;
+        Ok(info)
+    });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    let info = result?;
+    info!(id = %info.id, reason = ?info.reason, "created index snapshot");
+    Ok(info)
+}
+
+/// Copy the live index files into `target`. Returns whether an FTS index was
+/// captured.
+fn populate(state_dir: &Path, target: &Path) -> Result<bool> {
+    let index_path = state_dir.join(INDEX_FILE);
+    {
+        // Appends and rewrites both hold the index lock, so the base file
+        // and its log are captured at the same generation.
+        let _lock = acquire_write_lock(&index_path)?;
+        link_or_copy(&index_path, &target.join(INDEX_FILE))?;
+        let generation = storage::read_generation(&index_path)?;
+        if let Some(sidecar_path) = vectors::find_sidecar(&index_path, generation) {
+            link_or_copy(&sidecar_path, &target.join(SIDECAR_FILE))?;
+        }
+        let wal_path = wal::wal_path(&index_path);
+        if wal_path.is_file() {
+            clone_or_copy(&wal_path, &target.join(WAL_FILE))?;
+        }
+    }
+    let fts_captured = copy_fts(&state_dir.join(FTS_DIR), &target.join(FTS_DIR))?;
+    let embedding_space = state_dir.join(EMBEDDING_SPACE_FILE);
+    if embedding_space.is_file() {
+        link_or_copy(&embedding_space, &target.join(EMBEDDING_SPACE_FILE))?;
+    }
+    let shard_caches = shard_cache_dir(state_dir);
+    if shard_caches.is_dir() {
+        let target_caches = shard_cache_dir(target);
+        fs::create_dir_all(&target_caches)?;
+        for path in shard_cache_files(&shard_caches)? {
+            if let Some(name) = path.file_name() {
+                link_or_copy(&path, &target_caches.join(name))?;
+            }
+        }
+    }
+    Ok(fts_captured)
+}
+
+/// Copy a Tantivy directory. Segment files are immutable, and `meta.json`
+/// names the ones the last commit uses; a background merge may delete
+/// others, so the copy is retried until `meta.json` is unchanged across it.
+fn copy_fts(source: &Path, target: &Path) -> Result<bool> {
+    let meta_path = source.join("meta.json");
+    for _ in 0..FTS_COPY_ATTEMPTS {
+        let meta = match fs::read(&meta_path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        if target.exists() {
+            fs::remove_dir_all(target)?;
+        }
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+            if name_str == "meta.json" || name_str.ends_with(".lock") || !entry.path().is_file() {
+                continue;
+            }
+            match link_or_copy(&entry.path(), &target.join(&name)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if fs::read(&meta_path)? == meta {
+            fs::write(target.join("meta.json"), &meta)?;
+            return Ok(true);
+        }
+    }
+    Err(Error::Snapshot(format!(
+        "the full-text index at '{}' kept changing while it was copied; retry",
+        source.display()
+    )))
+}
+
+/// Every readable snapshot, oldest first.
+pub(crate) fn list(root: &Path) -> Result<Vec<SnapshotInfo>> {
+    let snapshots = snapshots_dir(root);
+    let entries = match fs::read_dir(&snapshots) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut found = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || !entry.path().is_dir() {
+            continue;
+        }
+        match read_manifest(&entry.path()) {
+            Ok(info) => found.push(info),
+            Err(error) => {
+                warn!(snapshot = %name, %error, "ignoring unreadable snapshot");
+            }
+        }
+    }
+    found.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+    Ok(found)
+}
+
+/// Delete all but the newest `keep` snapshots, plus directories left behind
+/// by interrupted [`create`] calls. `keep == 0` keeps every snapshot.
+/// Returns the deleted snapshots, oldest first.
+pub(crate) fn prune(root: &Path, keep: usize) -> Result<Vec<SnapshotInfo>> {
+    let snapshots = snapshots_dir(root);
+    if let Ok(entries) = fs::read_dir(&snapshots) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                let _ = fs::remove_dir_all(entry.path());
+            }
+        }
+    }
+    if keep == 0 {
+        return Ok(Vec::new());
+    }
+    let mut removed = list(root)?;
+    let excess = removed.len().saturating_sub(keep);
+    removed.truncate(excess);
+    for snapshot in &removed {
+        fs::remove_dir_all(snapshots.join(&snapshot.id))?;
+        info!(id = %snapshot.id, "pruned index snapshot");
+    }
+    Ok(removed)
+}
+
+/// Replace the live index with the snapshot `id`.
+///
+/// Fails with [`Error::IndexBusy`] while another process (e.g. `mdvdb
+/// watch`) holds the index open for writing. The vector index is replaced
+/// first under an FTS reconciliation marker, so a crash part-way leaves a
+/// project whose next writable open rebuilds FTS from the restored vectors.
+pub(crate) fn restore(root: &Path, id: &str) -> Result<SnapshotInfo> {
+    let source = snapshot_dir(root, id)?;
+    let info = read_manifest(&source)?;
+    // Refuse a damaged snapshot before touching the live index.
+    Index::open(&source.join(INDEX_FILE))?;
+
+    let state_dir = root.join(".markdownvdb");
+    let index_path = state_dir.join(INDEX_FILE);
+    let fts_path = state_dir.join(FTS_DIR);
+    fts::ensure_no_writer(&fts_path)?;
+
+    let staged_fts = state_dir.join("fts.restoring");
+    if staged_fts.exists() {
+        fs::remove_dir_all(&staged_fts)?;
+    }
+    let snapshot_fts = source.join(FTS_DIR);
+    let fts_restored = snapshot_fts.join("meta.json").is_file();
+    if fts_restored {
+        fs::create_dir_all(&staged_fts)?;
+        for entry in fs::read_dir(&snapshot_fts)? {
+            let entry = entry?;
+            link_or_copy(&entry.path(), &staged_fts.join(entry.file_name()))?;
+        }
+    }
+
+    fts::begin_reconciliation(root)?;
+    {
+        let _lock = acquire_write_lock(&index_path)?;
+        // The sidecar goes in under the snapshot's generation before the base
+        // file that points at it; the live one is dropped afterwards.
+        let generation = storage::read_generation(&source.join(INDEX_FILE))?;
+        let snapshot_sidecar = source.join(SIDECAR_FILE);
+        if snapshot_sidecar.is_file() {
+            replace_file(
+                &snapshot_sidecar,
+                &vectors::sidecar_path(&index_path, generation),
+                false,
+            )?;
+        }
+        replace_file(&source.join(INDEX_FILE), &index_path, false)?;
+        vectors::remove_sidecars(&index_path, Some(generation))?;
+        // A log left from the replaced base file no longer matches its
+        // generation and would be ignored, but is removed all the same.
+        let wal_path = wal::wal_path(&index_path);
+        let snapshot_wal = source.join(WAL_FILE);
+        if snapshot_wal.is_file() {
+            replace_file(&snapshot_wal, &wal_path, true)?;
+        } else {
+            wal::remove(&wal_path)?;
+        }
+    }
+
+    let replaced_fts = state_dir.join("fts.replaced");
+    if replaced_fts.exists() {
+        fs::remove_dir_all(&replaced_fts)?;
+    }
+    if fts_path.exists() {
+        fs::rename(&fts_path, &replaced_fts)?;
+    }
+    if fts_restored {
+        fs::rename(&staged_fts, &fts_path)?;
+    }
+    if replaced_fts.exists() {
+        fs::remove_dir_all(&replaced_fts)?;
+    }
+
+    let embedding_space = state_dir.join(EMBEDDING_SPACE_FILE);
+    let snapshot_space = source.join(EMBEDDING_SPACE_FILE);
+    if snapshot_space.is_file() {
+        replace_file(&snapshot_space, &embedding_space, false)?;
+    } else if embedding_space.is_file() {
+        fs::remove_file(&embedding_space)?;
+    }
+
+    let shard_caches = shard_cache_dir(&state_dir);
+    if shard_caches.is_dir() {
+        for path in shard_cache_files(&shard_caches)? {
+            fs::remove_file(path)?;
+        }
+    }
+    let snapshot_caches = shard_cache_dir(&source);
+    if snapshot_caches.is_dir() {
+        fs::create_dir_all(&shard_caches)?;
+        for path in shard_cache_files(&snapshot_caches)? {
+            if let Some(name) = path.file_name() {
+                link_or_copy(&path, &shard_caches.join(name))?;
+            }
+        }
+    }
+
+    if fts_restored && !info.fts_rebuild_required {
+        fts::finish_reconciliation(root)?;
+    }
+    info!(id = %info.id, "restored index snapshot");
+    Ok(info)
+}
+
+/// Directory of snapshot `id`, rejecting IDs that are not a snapshot name.
+fn snapshot_dir(root: &Path, id: &str) -> Result<PathBuf> {
+    let valid = !id.is_empty()
+        && !id.starts_with('.')
+        && !id.contains(['/', '\\'])
+        && Path::new(id).file_name().is_some_and(|name| name == id);
+    let dir = snapshots_dir(root).join(id);
+    if !valid || !dir.join(MANIFEST_FILE).is_file() {
+        return Err(Error::Snapshot(format!(
+            "snapshot '{id}' not found; run `mdvdb snapshot list`"
+        )));
+    }
+    Ok(dir)
+}
+
+fn read_manifest(dir: &Path) -> Result<SnapshotInfo> {
+    let path = dir.join(MANIFEST_FILE);
+    let bytes = fs::read(&path)?;
+    let manifest: SnapshotManifest = serde_json::from_slice(&bytes).map_err(|error| {
+        Error::Serialization(format!(
+            "failed to parse snapshot manifest '{}': {error}",
+            path.display()
+        ))
+    })?;
+    if manifest.format != SNAPSHOT_FORMAT || manifest.version != SNAPSHOT_VERSION {
+        return Err(Error::Snapshot(format!(
+            "unsupported snapshot format {} v{} in '{}'",
+            manifest.format,
+            manifest.version,
+            path.display()
+        )));
+    }
+    Ok(manifest.info)
+}
+
+fn write_manifest(dir: &Path, info: &SnapshotInfo) -> Result<()> {
+    let manifest = SnapshotManifest {
+        format: SNAPSHOT_FORMAT.to_string(),
+        version: SNAPSHOT_VERSION,
+        info: info.clone(),
+    };
+    let mut bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|error| Error::Serialization(error.to_string()))?;
+    bytes.push(b'\n');
+    let mut file = File::create(dir.join(MANIFEST_FILE))?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Atomically replace `target` with a hardlink to (or, with `copy`, a copy
+/// of) `source`.
+fn replace_file(source: &Path, target: &Path, copy: bool) -> Result<()> {
+    let mut staged = target.as_os_str().to_owned();
+    staged.push(".restoring");
+    let staged = PathBuf::from(staged);
+    if staged.exists() {
+        fs::remove_file(&staged)?;
+    }
+    if copy {
+        clone_or_copy(source, &staged)?;
+    } else {
+        link_or_copy(source, &staged)?;
+    }
+    fs::rename(&staged, target)?;
+    Ok(())
+}
+
+/// Hardlink `source` to `target`, falling back to a copy across filesystems
+/// or where hardlinks are unsupported.
+fn link_or_copy(source: &Path, target: &Path) -> io::Result<()> {
+    match fs::hard_link(source, target) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(e),
+        Err(_) => clone_or_copy(source, target),
+    }
+}
+
+/// Copy `source` to `target` as a reflink where the filesystem supports it.
+fn clone_or_copy(source: &Path, target: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let from = File::open(source)?;
+        let to = File::create(target)?;
+        if rustix::fs::ioctl_ficlone(&to, &from).is_ok() {
+            return to.sync_all();
+        }
+    }
+    fs::copy(source, target)?;
+    File::open(target)?.sync_all()
+}
+
+fn shard_cache_dir(state_dir: &Path) -> PathBuf {
+    SHARD_CACHE_DIR
+        .iter()
+        .fold(state_dir.to_path_buf(), |path, part| path.join(part))
+}
+
+/// Shard analysis cache files in `dir` (their lock files are skipped).
+fn shard_cache_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn dir_size(dir: &Path) -> Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(total)
+}
+
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> Result<()> {
+    File::open(path)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// First free snapshot ID for `created_at`, suffixed `-2`, `-3`, ... when
+/// several snapshots are taken within one second.
+fn unused_id(snapshots: &Path, created_at: u64) -> String {
+    let base = format_id(created_at);
+    let mut id = base.clone();
+    let mut n = 1;
+    while snapshots.join(&id).exists() {
+        n += 1;
+        id = format!("{base}-{n}");
+    }
+    id
+}
+
+/// Format Unix seconds as a compact UTC timestamp, e.g. `20261016T142530Z`.
+fn format_id(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_prime = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_prime + 2) / 5 + 1;
+    let month = if month_prime < 10 {
+        month_prime + 3
+    } else {
+        month_prime - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_sortable_utc_timestamps() {
+        assert_eq!(format_id(0), "19700101T000000Z");
+        assert_eq!(format_id(1_792_160_730), "20261016T142530Z");
+        assert_eq!(format_id(951_782_400), "20000229T000000Z");
+    }
+
+    #[test]
+    fn ids_taken_within_one_second_get_a_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("19700101T000000Z")).unwrap();
+        fs::create_dir(dir.path().join("19700101T000000Z-2")).unwrap();
+        assert_eq!(unused_id(dir.path(), 0), "19700101T000000Z-3");
+        assert_eq!(unused_id(dir.path(), 1), "19700101T000001Z");
+    }
+
+    #[test]
+    fn snapshot_ids_cannot_escape_the_snapshot_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        for id in ["", "..", ".hidden", "../index", "a/b"] {
+            let error = snapshot_dir(dir.path(), id).unwrap_err();
+            assert!(error.to_string().contains("not found"), "{id}");
+        }
+    }
+}
@@ -0,0 +1,452 @@
+use std::ops::Range;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::embedding::provider::{EmbeddingProvider, EmbeddingPurpose};
+use crate::embedding::query_cache;
+use crate::error::Result;
+use crate::fts::{FtsIndex, FtsSnippet};
+use crate::search::{cosine_similarity, SearchMode, SearchQuery, SearchResponse};
+
+/// Maximum length in bytes of a lexical snippet fragment.
+pub const LEXICAL_SNIPPET_MAX_CHARS: usize = 200;
+
+/// Number of top results that get a semantic snippet. Each one costs an
+/// embedding of every sentence window of its chunk.
+pub const SEMANTIC_SNIPPET_RESULTS: usize = 5;
+
+/// Number of consecutive sentences in a semantic snippet window.
+const SENTENCES_PER_WINDOW: usize = 2;
+
+/// The most relevant fragment of a result chunk (`SearchQuery::snippets`).
+///
+/// Offsets are byte offsets into
+/// [`SearchResultChunk::content`](crate::search::SearchResultChunk::content);
+/// lines are 1-based source file lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchSnippet {
+    /// The fragment text, `content[start..end]`.
+    pub text: String,
+    /// Start of the fragment in the chunk content.
+    pub start: usize,
+    /// End of the fragment in the chunk content (exclusive).
+    pub end: usize,
+    /// Source file line the fragment starts on.
+    pub start_line: usize,
+    /// Source file line the fragment ends on (inclusive).
+    pub end_line: usize,
+    /// How the fragment was chosen.
+    pub source: SnippetSource,
+    /// Matched query terms within the fragment, in text order. Empty for
+    /// semantic snippets.
+    pub highlights: Vec<SnippetHighlight>,
+}
+
+/// How a [`SearchSnippet`] was chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnippetSource {
+    /// The fragment with the most matched lexical query terms.
+    Lexical,
+    /// The sentence window most similar to the query embedding.
+    Semantic,
+}
+
+/// One matched query term in a [`SearchSnippet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SnippetHighlight {
+    /// Start of the term in the chunk content.
+    pub start: usize,
+    /// End of the term in the chunk content (exclusive).
+    pub end: usize,
+    /// Source file line of the term.
+    pub line: usize,
+}
+
+/// Attach snippets to the results of `response`.
+///
+/// Lexical and hybrid queries highlight the query terms in each chunk. In
+/// semantic and hybrid mode, the top [`SEMANTIC_SNIPPET_RESULTS`] results
+/// without lexical matches get the sentence window closest to the query
+/// vector (`query_vector`, or a fresh query embedding when `None`). Pass no
+/// `provider` when no real provider is available. If embedding fails, the
+/// error is logged and those results keep no snippet.
+pub(crate) async fn attach_snippets(
+    response: &mut SearchResponse,
+    query: &SearchQuery,
+    fts: &FtsIndex,
+    provider: Option<&dyn EmbeddingProvider>,
+    query_vector: Option<Vec<f32>>,
+) -> Result<()> {
+    let start = Instant::now();
+
+    if matches!(query.mode, SearchMode::Hybrid | SearchMode::Lexical) {
+        let highlighter = fts.highlighter(&query.query, LEXICAL_SNIPPET_MAX_CHARS)?;
+        for result in &mut response.results {
+            let chunk = &mut result.chunk;
+            chunk.snippet = highlighter
+                .snippet(
+                    &result.file.path,
+                    result.file.frontmatter.as_ref(),
+                    &chunk.content,
+                )
+                .map(|fts| lexical_snippet(&chunk.content, chunk.start_line, fts));
+        }
+    }
+
+    let provider =
+        provider.filter(|_| matches!(query.mode, SearchMode::Semantic | SearchMode::Hybrid));
+    if let Some(provider) = provider.filter(|_| !query.query.trim().is_empty()) {
+        let mut pending = Vec::new();
+        let mut texts = Vec::new();
+        for (i, result) in response
+            .results
+            .iter_mut()
+            .enumerate()
+            .take(SEMANTIC_SNIPPET_RESULTS)
+        {
+            let chunk = &mut result.chunk;
+            if chunk.snippet.is_some() {
+                continue;
+            }
+            let windows = sentence_windows(&chunk.content);
+            if let [only] = windows.as_slice() {
+                chunk.snippet = Some(semantic_snippet(
+                    &chunk.content,
+                    chunk.start_line,
+                    only.clone(),
+                ));
+                continue;
+            }
+            texts.extend(windows.iter().map(|w| chunk.content[w.clone()].to_string()));
+            pending.extend(windows.into_iter().map(|w| (i, w)));
+        }
+
+        if !texts.is_empty() {
+            let embed_start = Instant::now();
+            let embedded = embed_windows(provider, query, query_vector, &texts).await;
+            response.timings.embed_secs += embed_start.elapsed().as_secs_f64();
+            // Snippets are an extra; the results stand without them.
+            let (query_vector, vectors) = match embedded {
+                Ok(embedded) => embedded,
+                Err(error) => {
+                    tracing::warn!(%error, "failed to embed semantic snippet windows");
+                    response.timings.total_secs += start.elapsed().as_secs_f64();
+                    return Ok(());
+                }
+            };
+
+            let mut best: Vec<Option<(f64, Range<usize>)>> = vec![None; response.results.len()];
+            for ((i, window), vector) in pending.into_iter().zip(&vectors) {
+                let similarity = cosine_similarity(&query_vector, vector);
+                if best[i]
+                    .as_ref()
+                    .is_none_or(|(score, _)| similarity > *score)
+                {
+                    best[i] = Some((similarity, window));
+                }
+            }
+            for (result, best) in response.results.iter_mut().zip(best) {
+                if let Some((_, window)) = best {
+                    let chunk = &mut result.chunk;
+                    chunk.snippet =
+                        Some(semantic_snippet(&chunk.content, chunk.start_line, window));
+                }
+            }
+        }
+    }
+
+    response.timings.total_secs += start.elapsed().as_secs_f64();
+    Ok(())
+}
+
+/// The query vector (`query_vector`, or a fresh query embedding) and the
+/// embeddings of the sentence windows `texts`.
+async fn embed_windows(
+    provider: &dyn EmbeddingProvider,
+    query: &SearchQuery,
+    query_vector: Option<Vec<f32>>,
+    texts: &[String],
+) -> Result<(Vec<f32>, Vec<Vec<f32>>)> {
+    let query_vector = match query_vector {
+        Some(vector) => vector,
+        None => {
+            let embedded =
+                query_cache::embed_queries(None, provider, std::slice::from_ref(&query.query))
+                    .await?;
+            embedded.vectors.into_iter().next().unwrap_or_default()
+        }
+    };
+    let vectors = provider
+        .embed_batch_for(texts, EmbeddingPurpose::Document)
+        .await?;
+    Ok((query_vector, vectors))
+}
+
+fn lexical_snippet(content: &str, start_line: usize, fts: FtsSnippet) -> SearchSnippet {
+    let highlights = fts
+        .highlights
+        .iter()
+        .map(|range| SnippetHighlight {
+            start: range.start,
+            end: range.end,
+            line: line_at(content, start_line, range.start),
+        })
+        .collect();
+    SearchSnippet {
+        highlights,
+        ..fragment(
+            content,
+            start_line,
+            fts.start..fts.end,
+            SnippetSource::Lexical,
+        )
+    }
+}
+
+fn semantic_snippet(content: &str, start_line: usize, range: Range<usize>) -> SearchSnippet {
+    fragment(content, start_line, range, SnippetSource::Semantic)
+}
+
+fn fragment(
+    content: &str,
+    start_line: usize,
+    range: Range<usize>,
+    source: SnippetSource,
+) -> SearchSnippet {
+    SearchSnippet {
+        text: content[range.clone()].to_string(),
+        start: range.start,
+        end: range.end,
+        start_line: line_at(content, start_line, range.start),
+        end_line: line_at(
+            content,
+            start_line,
+            range.end.saturating_sub(1).max(range.start),
+        ),
+        source,
+        highlights: Vec::new(),
+    }
+}
+
+/// Source file line of byte `offset` in chunk content starting on `start_line`.
+fn line_at(content: &str, start_line: usize, offset: usize) -> usize {
+    start_line
+        + content.as_bytes()[..offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+}
+
+/// Byte ranges of the sentences in `content`, trimmed of whitespace.
+///
+/// Sentences end at `.`, `!` or `?` followed by whitespace, at their CJK
+/// full-width forms, and at line breaks (so list items and headings stand
+/// alone). Fragments without alphanumeric characters, such as code fences,
+/// are dropped.
+pub(crate) fn sentences(content: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = content.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let ends = match c {
+            '\n' | '。' | '！' | '？' => true,
+            '.' | '!' | '?' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if ends {
+            let end = i + c.len_utf8();
+            push_trimmed(content, start..end, &mut sentences);
+            start = end;
+        }
+    }
+    push_trimmed(content, start..content.len(), &mut sentences);
+    sentences
+}
+
+fn push_trimmed(content: &str, range: Range<usize>, sentences: &mut Vec<Range<usize>>) {
+    let text = &content[range.clone()];
+    let start = range.start + (text.len() - text.trim_start().len());
+    let end = range.start + text.trim_end().len();
+    if start < end && content[start..end].chars().any(char::is_alphanumeric) {
+        sentences.push(start..end);
+    }
+}
+
+/// Candidate semantic snippets: every run of [`SENTENCES_PER_WINDOW`]
+/// consecutive sentences, or the lone sentence of a one-sentence chunk.
+#[allow(clippy::single_range_in_vec_init)]
+pub(crate) fn sentence_windows(content: &str) -> Vec<Range<usize>> {
+    let sentences = sentences(content);
+    if sentences.len() <= SENTENCES_PER_WINDOW {
+        return match (sentences.first(), sentences.last()) {
+            (Some(first), Some(last)) => vec![first.start..last.end],
+            _ => Vec::new(),
+        };
+    }
+    sentences
+        .windows(SENTENCES_PER_WINDOW)
+        .map(|window| window[0].start..window[SENTENCES_PER_WINDOW - 1].end)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{SearchResult, SearchResultChunk, SearchResultFile, SearchTimings};
+
+    fn texts(content: &str, ranges: &[Range<usize>]) -> Vec<String> {
+        ranges
+            .iter()
+            .map(|r| content[r.clone()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn sentences_split_on_terminators_and_lines() {
+        let content = "First one. Second v1.2 here!\n- list item\n```\nWhy? 東京です。次";
+        assert_eq!(
+            texts(content, &sentences(content)),
+            vec![
+                "First one.",
+                "Second v1.2 here!",
+                "- list item",
+                "Why?",
+                "東京です。",
+                "次"
+            ]
+        );
+    }
+
+    #[test]
+    fn windows_pair_consecutive_sentences() {
+        let content = "A one. B two. C three.";
+        assert_eq!(
+            texts(content, &sentence_windows(content)),
+            vec!["A one. B two.", "B two. C three."]
+        );
+
+        let short = "  Just this. And that.  ";
+        assert_eq!(
+            texts(short, &sentence_windows(short)),
+            vec!["Just this. And that."]
+        );
+        assert!(sentence_windows("```\n---\n").is_empty());
+    }
+
+    struct FailingProvider;
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for FailingProvider {
+        async fn embed_batch(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Err(crate::Error::EmbeddingProvider("503 unavailable".into()))
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+
+        fn model(&self) -> &str {
+            "failing-model"
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    fn response_with(contents: &[&str]) -> SearchResponse {
+        let results = contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| SearchResult {
+                score: 1.0,
+                chunk: SearchResultChunk {
+                    chunk_id: format!("note.md#{i}"),
+                    heading_hierarchy: vec![],
+                    content: content.to_string(),
+                    start_line: 1,
+                    end_line: 1,
+                    before: vec![],
+                    after: vec![],
+                    section_chunk_ids: vec![],
+                    snippet: None,
+                    matches: vec![],
+                },
+                file: SearchResultFile {
+                    path: "note.md".into(),
+                    frontmatter: None,
+                    computed_fields: serde_json::Map::new(),
+                    computed_field_errors: std::collections::BTreeMap::new(),
+                    file_size: 0,
+                    path_components: vec!["note.md".into()],
+                    modified_at: None,
+                    relations: None,
+                },
+                explain: None,
+            })
+            .collect();
+        SearchResponse {
+            results,
+            graph_context: Vec::new(),
+            timings: SearchTimings {
+                embed_secs: 0.0,
+                vector_search_secs: 0.0,
+                lexical_search_secs: 0.0,
+                fusion_secs: 0.0,
+                rerank_secs: 0.0,
+                assemble_secs: 0.0,
+                suggest_secs: 0.0,
+                total_secs: 0.0,
+                vector_strategy: None,
+                filter_selectivity: None,
+                cache_lookup_secs: 0.0,
+                cache_hit: None,
+            },
+            edge_results: Vec::new(),
+            facets: Default::default(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn embedding_failure_leaves_results_without_semantic_snippets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let fts = FtsIndex::open_or_create(&dir.path().join("fts")).unwrap();
+        let query = SearchQuery::new("tides")
+            .with_mode(SearchMode::Semantic)
+            .with_snippets(true);
+
+        // Without a query vector the query embedding fails; with one, the
+        // window embeddings do.
+        for query_vector in [None, Some(vec![1.0, 0.0])] {
+            let mut response =
+                response_with(&["Just one sentence.", "Waves roll. Tides turn. Storms pass."]);
+            attach_snippets(
+                &mut response,
+                &query,
+                &fts,
+                Some(&FailingProvider as &dyn EmbeddingProvider),
+                query_vector,
+            )
+            .await
+            .unwrap();
+            // A single window needs no embedding to be chosen.
+            let single = response.results[0].chunk.snippet.as_ref().unwrap();
+            assert_eq!(single.text, "Just one sentence.");
+            assert!(response.results[1].chunk.snippet.is_none());
+        }
+    }
+
+    #[test]
+    fn fragment_lines_follow_chunk_start_line() {
+        let content = "intro\n\nsecond para. more\nthird";
+        let start = content.find("second").unwrap();
+        let end = content.len();
+        let snippet = semantic_snippet(content, 10, start..end);
+        assert_eq!(snippet.start_line, 12);
+        assert_eq!(snippet.end_line, 13);
+        assert_eq!(snippet.text, "second para. more\nthird");
+    }
+}
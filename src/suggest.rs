@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tantivy_fst::{Automaton, IntoStreamer, Map, Streamer};
+
+use crate::fts::LevenshteinDfa;
+use crate::index::state::Index;
+
+/// Default number of suggestions returned by `mdvdb suggest`.
+pub const DEFAULT_SUGGEST_LIMIT: usize = 10;
+
+/// What a [`Suggestion`] completes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    /// A note title: frontmatter `title`, else the note's first H1.
+    Title,
+    /// A frontmatter `aliases` (or `alias`) entry.
+    Alias,
+    /// A section heading.
+    Heading,
+}
+
+/// One typeahead completion for a note or section.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    /// The completed title, alias, or heading as written.
+    pub text: String,
+    pub kind: SuggestionKind,
+    /// Relative path of the note.
+    pub path: String,
+    /// Chunk to jump to: the heading's first chunk, or for titles and
+    /// aliases the H1 chunk or the note's first chunk.
+    pub chunk_id: String,
+    /// 1-based source line of `chunk_id`.
+    pub line: usize,
+    /// Heading hierarchy down to this heading (headings only).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub heading_hierarchy: Vec<String>,
+    /// Match quality: 1.0 when the whole text starts with the prefix, 0.5 /
+    /// 0.25 when it does within one / two edits. Matches at a later word
+    /// score 0.75 times as much.
+    pub score: f64,
+}
+
+/// Score of a suggestion whose full text starts with the prefix.
+const FULL_PREFIX_SCORE: f64 = 1.0;
+/// Score of a suggestion with a later word starting with the prefix.
+const WORD_PREFIX_SCORE: f64 = 0.75;
+
+/// In-memory completion table over titles, aliases, and headings.
+///
+/// Every entry is keyed by its normalized text (lowercased, whitespace
+/// collapsed) and by each suffix starting at a later word, so `retry`
+/// completes "Connection retry policy". Keys are kept sorted, so a prefix
+/// lookup is a binary search plus a scan of the matching run. Fuzzy
+/// matching walks an FST of the distinct keys with a Levenshtein automaton
+/// and only runs when prefix matches leave room.
+pub(crate) struct SuggestIndex {
+    /// [`Index::revision`] the table was built from.
+    revision: u64,
+    entries: Vec<Suggestion>,
+    /// (normalized key, entry index, key starts at the first word).
+    keys: Vec<(String, usize, bool)>,
+    /// Each distinct key mapped to the position of its first row in `keys`.
+    key_set: Map<Vec<u8>>,
+}
+
+impl SuggestIndex {
+    /// Build the table from every indexed file.
+    pub(crate) fn build(index: &Index) -> Self {
+        let revision = index.revision();
+        let mut files: Vec<_> = index.get_all_files().into_values().collect();
+        files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+        let mut entries = Vec::new();
+        for file in files {
+            let path = file.relative_path.clone();
+            let frontmatter = file.effective_frontmatter();
+            let chunks: Vec<(String, crate::index::types::StoredChunk)> = file
+                .chunk_ids
+                .iter()
+                .filter_map(|id| index.get_chunk(id).map(|chunk| (id.clone(), chunk)))
+                .collect();
+            let Some((first_id, first_chunk)) = chunks.first() else {
+                continue;
+            };
+
+            // The first H1 opens the first chunk whose content starts with `# `.
+            let h1 = chunks.iter().find_map(|(id, chunk)| {
+                let line = chunk.content.lines().next()?.trim_start();
+                match chunk.heading_hierarchy.as_slice() {
+                    [text] if line.starts_with("# ") => Some((id, chunk, text.clone())),
+                    _ => None,
+                }
+            });
+            let (anchor_id, anchor_line) = match &h1 {
+                Some((id, chunk, _)) => ((*id).clone(), chunk.start_line),
+                None => (first_id.clone(), first_chunk.start_line),
+            };
+            let title = frontmatter
+                .as_ref()
+                .and_then(|fm| fm.get("title"))
+                .and_then(serde_json::Value::as_str)
+                .map(str::trim)
+                .filter(|title| !title.is_empty())
+                .map(str::to_string)
+                .or_else(|| h1.as_ref().map(|(_, _, text)| text.clone()));
+
+            let note_entry = |text: String, kind: SuggestionKind| Suggestion {
+                text,
+                kind,
+                path: path.clone(),
+                chunk_id: anchor_id.clone(),
+                line: anchor_line,
+                heading_hierarchy: Vec::new(),
+                score: 0.0,
+            };
+            if let Some(title) = &title {
+                entries.push(note_entry(title.clone(), SuggestionKind::Title));
+            }
+            for alias in aliases(frontmatter.as_ref()) {
+                entries.push(note_entry(alias, SuggestionKind::Alias));
+            }
+
+            // One entry per heading section; sub-split chunks share it.
+            let mut seen: Vec<&[String]> = Vec::new();
+            for (id, chunk) in &chunks {
+                let hierarchy = chunk.heading_hierarchy.as_slice();
+                let Some(heading) = hierarchy.last() else {
+                    continue;
+                };
+                if seen.contains(&hierarchy) {
+                    continue;
+                }
+                seen.push(hierarchy);
+                // A title taken from the H1 already points at this section.
+                if h1.as_ref().is_some_and(|(h1_id, _, _)| *h1_id == id)
+                    && title.as_deref() == Some(heading.as_str())
+                {
+                    continue;
+                }
+                entries.push(Suggestion {
+                    text: heading.clone(),
+                    kind: SuggestionKind::Heading,
+                    path: path.clone(),
+                    chunk_id: id.clone(),
+                    line: chunk.start_line,
+                    heading_hierarchy: hierarchy.to_vec(),
+                    score: 0.0,
+                });
+            }
+        }
+
+        let mut keys = Vec::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let normalized = normalize(&entry.text);
+            for (start, _) in normalized.match_indices(' ') {
+                keys.push((normalized[start + 1..].to_string(), i, false));
+            }
+            keys.push((normalized, i, true));
+        }
+        keys.sort();
+        let mut distinct: Vec<(&str, u64)> = Vec::new();
+        for (row, (key, _, _)) in keys.iter().enumerate() {
+            if distinct
+                .last()
+                .is_none_or(|(last, _)| *last != key.as_str())
+            {
+                distinct.push((key.as_str(), row as u64));
+            }
+        }
+        let key_set = Map::from_iter(distinct).expect("keys are sorted and distinct");
+
+        Self {
+            revision,
+            entries,
+            keys,
+            key_set,
+        }
+    }
+
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Up to `limit` entries matching `prefix`, best first. Ties go to
+    /// titles, then aliases, then headings, then shorter text.
+    pub(crate) fn suggest(&self, prefix: &str, limit: usize) -> Vec<Suggestion> {
+        let prefix = normalize(prefix);
+        if prefix.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        let start = self
+            .keys
+            .partition_point(|(key, _, _)| key.as_str() < prefix.as_str());
+        for (key, entry, full) in &self.keys[start..] {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let score = if *full {
+                FULL_PREFIX_SCORE
+            } else {
+                WORD_PREFIX_SCORE
+            };
+            let best = scores.entry(*entry).or_insert(score);
+            *best = best.max(score);
+        }
+
+        let query: Vec<char> = prefix.chars().collect();
+        let max_distance = match query.len() {
+            0..=2 => 0,
+            3..=5 => 1,
+            _ => 2,
+        };
+        if scores.len() < limit && max_distance > 0 {
+            // Keys with a prefix within `max_distance` edits of the query.
+            let dfa = LevenshteinDfa::new(&prefix, max_distance as u8);
+            let mut stream = self.key_set.search(dfa.starts_with()).into_stream();
+            while let Some((key, first_row)) = stream.next() {
+                let Some(distance) = std::str::from_utf8(key)
+                    .ok()
+                    .and_then(|key| prefix_edit_distance(&query, key, max_distance))
+                else {
+                    continue;
+                };
+                if distance == 0 {
+                    continue;
+                }
+                let rows = self.keys[first_row as usize..]
+                    .iter()
+                    .take_while(|(other, _, _)| other.as_bytes() == key);
+                for (_, entry, full) in rows {
+                    let score = 0.5 / distance as f64 * if *full { 1.0 } else { WORD_PREFIX_SCORE };
+                    let best = scores.entry(*entry).or_insert(score);
+                    *best = best.max(score);
+                }
+            }
+        }
+
+        let mut matches: Vec<Suggestion> = scores
+            .into_iter()
+            .map(|(entry, score)| Suggestion {
+                score,
+                ..self.entries[entry].clone()
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.kind.cmp(&b.kind))
+                .then(a.text.chars().count().cmp(&b.text.chars().count()))
+                .then(a.path.cmp(&b.path))
+                .then(a.line.cmp(&b.line))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// String entries of the frontmatter `aliases` (or `alias`) key, which may
+/// be a single string or a list.
+fn aliases(frontmatter: Option<&serde_json::Value>) -> Vec<String> {
+    let Some(value) = frontmatter.and_then(|fm| fm.get("aliases").or_else(|| fm.get("alias")))
+    else {
+        return Vec::new();
+    };
+    let values = match value {
+        serde_json::Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    values
+        .into_iter()
+        .filter_map(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Lowercase and collapse runs of whitespace to single spaces.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Smallest edit distance between `query` and any prefix of `key`, or `None`
+/// when it exceeds `max`.
+fn prefix_edit_distance(query: &[char], key: &str, max: usize) -> Option<usize> {
+    let key: Vec<char> = key.chars().take(query.len() + max).collect();
+    if key.len() + max < query.len() {
+        return None;
+    }
+    let mut previous: Vec<usize> = (0..=key.len()).collect();
+    for (i, q) in query.iter().enumerate() {
+        let mut current = vec![i + 1; key.len() + 1];
+        for (j, k) in key.iter().enumerate() {
+            let substitution = previous[j] + usize::from(q != k);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().all(|&d| d > max) {
+            return None;
+        }
+        previous = current;
+    }
+    previous.into_iter().min().filter(|&d| d <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_edit_distance_matches_key_prefixes() {
+        let distance = |q: &str, key: &str, max: usize| {
+            let q: Vec<char> = q.chars().collect();
+            prefix_edit_distance(&q, key, max)
+        };
+        assert_eq!(distance("conf", "configuration", 1), Some(0));
+        assert_eq!(distance("cnof", "configuration", 2), Some(2));
+        assert_eq!(distance("confg", "configuration", 1), Some(1));
+        assert_eq!(distance("deplyo", "deployment", 2), Some(1));
+        assert_eq!(distance("xyz", "configuration", 1), None);
+        assert_eq!(distance("configure", "con", 1), None);
+    }
+
+    #[test]
+    fn aliases_accept_strings_and_lists() {
+        let fm = serde_json::json!({"aliases": ["K8s", " ", 3, "kube"]});
+        assert_eq!(aliases(Some(&fm)), vec!["K8s", "kube"]);
+        let fm = serde_json::json!({"alias": "Runbook"});
+        assert_eq!(aliases(Some(&fm)), vec!["Runbook"]);
+        assert!(aliases(None).is_empty());
+    }
+
+    #[test]
+    fn normalize_lowercases_and_collapses_whitespace() {
+        assert_eq!(
+            normalize("  Getting\tStarted  Guide "),
+            "getting started guide"
+        );
+    }
+}
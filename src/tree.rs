@@ -388,6 +388,8 @@ mod tests {
             search_min_score: 0.0,
             search_default_mode: crate::search::SearchMode::Hybrid,
             search_rrf_k: 60.0,
+            search_fusion: crate::search::FusionMethod::Rrf,
+            search_semantic_weight: 0.7,
             bm25_norm_k: 1.5,
             search_decay_enabled: false,
             search_decay_half_life: 90.0,
@@ -397,8 +399,26 @@ mod tests {
             search_boost_hops: 1,
             search_expand_graph: 0,
             search_expand_limit: 3,
+            search_diversity: None,
+            search_max_per_file: None,
+            search_lexical_boosts: Default::default(),
+            search_lexical_language: Default::default(),
+            search_lexical_paths: Default::default(),
+            search_lexical_stopwords: false,
+            rerank_provider: crate::config::RerankProviderType::None,
+            rerank_model: None,
+            rerank_endpoint: None,
+            rerank_top_n: 50,
+            query_cache_max_entries: 1000,
+            query_cache_ttl_secs: 604800,
+            chunk_embedding_cache: true,
             vector_quantization: crate::config::VectorQuantization::F16,
             index_compression: true,
+            index_wal_max_bytes: 64 * 1024 * 1024,
+            index_wal_max_age_secs: 3600,
+            index_rescore_factor: 4,
+            index_snapshot_retention: 5,
+            index_snapshot_before_reindex: true,
             edge_embeddings: true,
             edge_boost_weight: 0.15,
             edge_cluster_rebalance: 50,
@@ -11,6 +11,8 @@ use tracing::{debug, error, info, warn};
 
 use crate::config::Config;
 use crate::discovery::FileDiscovery;
+use crate::embedding::batch::embed_inputs_cached;
+use crate::embedding::chunk_cache::ChunkEmbeddingCache;
 use crate::embedding::provider::EmbeddingProvider;
 use crate::error::{Error, Result};
 use crate::fts::{FtsChunkData, FtsIndex};
@@ -93,6 +95,9 @@ pub struct Watcher {
     #[allow(dead_code)]
     discovery: FileDiscovery,
     event_callback: Option<WatchEventCallback>,
+    /// Kept for the watcher's lifetime so cache lookups reuse its offset
+    /// indexes; `None` when `cache.chunk_embeddings` is off.
+    chunk_cache: Option<ChunkEmbeddingCache>,
 }
 
 impl Watcher {
@@ -106,6 +111,9 @@ impl Watcher {
         event_callback: Option<WatchEventCallback>,
     ) -> Self {
         let discovery = FileDiscovery::new(project_root, &config);
+        let chunk_cache = config
+            .chunk_embedding_cache
+            .then(|| ChunkEmbeddingCache::new(project_root));
         Self {
             config,
             project_root: project_root.to_path_buf(),
@@ -114,6 +122,7 @@ impl Watcher {
             provider,
             discovery,
             event_callback,
+            chunk_cache,
         }
     }
 
@@ -276,6 +285,10 @@ impl Watcher {
             Err(error) => Err(error),
         };
 
+        if let (Ok(_), Some(cache)) = (&result, &self.chunk_cache) {
+            cache.compact_for_index(&self.index);
+        }
+
         let duration_ms = start.elapsed().as_millis() as u64;
         let (success, error, chunks_processed, estimated_input_tokens, api_calls, module_reports) =
             match &result {
@@ -539,7 +552,10 @@ impl Watcher {
             (Vec::new(), 0, 0)
         } else {
             let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
-            crate::embedding::batch::embed_inputs_adaptively(self.provider.as_ref(), texts).await?
+            let (vectors, api_calls, tokens, _cache_hits) =
+                embed_inputs_cached(self.provider.as_ref(), texts, self.chunk_cache.as_ref())
+                    .await?;
+            (vectors, api_calls, tokens)
         };
 
         // Upsert vector index and FTS index. Embedding — the dominant failure
@@ -563,7 +579,8 @@ impl Watcher {
             })
             .collect();
         let path_str_fts = crate::path_util::to_slash(relative_path);
-        self.fts_index.upsert_chunks(&path_str_fts, &fts_chunks)?;
+        self.fts_index
+            .upsert_file(&path_str_fts, &fts_chunks, file.frontmatter.as_ref())?;
 
         // Rebuild raw global/scoped schema metadata without embedding. This
         // keeps occurrence counts correct and ensures a first file in a new
@@ -842,40 +859,38 @@ fn classify_event(
                 }
             }
         }
-        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
-            if paths.len() >= 2 {
-                let from_schema = schema_relative(&paths[0]);
-                let to_schema = schema_relative(&paths[1]);
-                if let Some(schema_path) = to_schema.or(from_schema) {
-                    result.push(FileEvent::SchemaChanged(schema_path));
-                    return result;
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() >= 2 => {
+            let from_schema = schema_relative(&paths[0]);
+            let to_schema = schema_relative(&paths[1]);
+            if let Some(schema_path) = to_schema.or(from_schema) {
+                result.push(FileEvent::SchemaChanged(schema_path));
+                return result;
+            }
+            let from_rel = paths[0]
+                .strip_prefix(project_root)
+                .ok()
+                .map(Path::to_path_buf);
+            let to_rel = to_relative(&paths[1]);
+            match (from_rel, to_rel) {
+                (Some(from), Some(to)) => {
+                    result.push(FileEvent::Renamed {
+                        from: from.to_path_buf(),
+                        to,
+                    });
                 }
-                let from_rel = paths[0]
-                    .strip_prefix(project_root)
-                    .ok()
-                    .map(Path::to_path_buf);
-                let to_rel = to_relative(&paths[1]);
-                match (from_rel, to_rel) {
-                    (Some(from), Some(to)) => {
-                        result.push(FileEvent::Renamed {
-                            from: from.to_path_buf(),
-                            to,
-                        });
-                    }
-                    (Some(from), None) => {
-                        // Renamed to non-indexable path = delete
-                        if from.extension().and_then(|e| e.to_str()) == Some("md") {
-                            result.push(FileEvent::Deleted(from.to_path_buf()));
-                        }
-                    }
-                    (None, Some(to)) => {
-                        // Renamed from non-indexable path = create
-                        result.push(FileEvent::Created(to));
-                    }
-                    _ => {}
+                // Renamed to non-indexable path = delete
+                (Some(from), None) if from.extension().and_then(|e| e.to_str()) == Some("md") => {
+                    result.push(FileEvent::Deleted(from.to_path_buf()));
+                }
+                (None, Some(to)) => {
+                    // Renamed from non-indexable path = create
+                    result.push(FileEvent::Created(to));
                 }
+                _ => {}
             }
         }
+        // A paired rename missing one of its paths is ignored, as before.
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {}
         EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
             for path in paths {
                 if let Some(rel) = schema_relative(path) {
@@ -957,6 +972,8 @@ mod tests {
             search_min_score: 0.0,
             search_default_mode: crate::search::SearchMode::Hybrid,
             search_rrf_k: 60.0,
+            search_fusion: crate::search::FusionMethod::Rrf,
+            search_semantic_weight: 0.7,
             bm25_norm_k: 1.5,
             search_decay_enabled: false,
             search_decay_half_life: 90.0,
@@ -966,8 +983,26 @@ mod tests {
             search_boost_hops: 1,
             search_expand_graph: 0,
             search_expand_limit: 3,
+            search_diversity: None,
+            search_max_per_file: None,
+            search_lexical_boosts: Default::default(),
+            search_lexical_language: Default::default(),
+            search_lexical_paths: Default::default(),
+            search_lexical_stopwords: false,
+            rerank_provider: crate::config::RerankProviderType::None,
+            rerank_model: None,
+            rerank_endpoint: None,
+            rerank_top_n: 50,
+            query_cache_max_entries: 1000,
+            query_cache_ttl_secs: 604800,
+            chunk_embedding_cache: true,
             vector_quantization: crate::config::VectorQuantization::F16,
             index_compression: true,
+            index_wal_max_bytes: 64 * 1024 * 1024,
+            index_wal_max_age_secs: 3600,
+            index_rescore_factor: 4,
+            index_snapshot_retention: 5,
+            index_snapshot_before_reindex: true,
             edge_embeddings: true,
             edge_boost_weight: 0.15,
             edge_cluster_rebalance: 50,
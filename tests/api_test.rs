@@ -3,8 +3,12 @@ use std::path::PathBuf;
 
 use mdvdb::config::{Config, EmbeddingProviderType};
 use mdvdb::error::Error;
+use mdvdb::fts::LexicalLanguage;
 use mdvdb::search::SearchQuery;
-use mdvdb::{CheckStatus, IngestOptions, MarkdownVdb, SearchMode, SearchResponse};
+use mdvdb::{
+    CheckStatus, FsckCheck, IngestOptions, MarkdownVdb, SearchMode, SearchResponse, SnippetSource,
+    SuggestionKind,
+};
 use tempfile::TempDir;
 
 // ---------------------------------------------------------------------------
@@ -41,6 +45,8 @@ fn mock_config() -> Config {
         search_min_score: 0.0,
         search_default_mode: mdvdb::SearchMode::Hybrid,
         search_rrf_k: 60.0,
+        search_fusion: mdvdb::search::FusionMethod::Rrf,
+        search_semantic_weight: 0.7,
         bm25_norm_k: 1.5,
         search_decay_enabled: false,
         search_decay_half_life: 90.0,
@@ -50,8 +56,26 @@ fn mock_config() -> Config {
         search_boost_hops: 1,
         search_expand_graph: 0,
         search_expand_limit: 3,
+        search_diversity: None,
+        search_max_per_file: None,
+        search_lexical_boosts: Default::default(),
+        search_lexical_language: Default::default(),
+        search_lexical_paths: Default::default(),
+        search_lexical_stopwords: false,
+        rerank_provider: mdvdb::config::RerankProviderType::None,
+        rerank_model: None,
+        rerank_endpoint: None,
+        rerank_top_n: 50,
+        query_cache_max_entries: 1000,
+        query_cache_ttl_secs: 604800,
+        chunk_embedding_cache: true,
         vector_quantization: mdvdb::VectorQuantization::F16,
         index_compression: true,
+        index_wal_max_bytes: 64 * 1024 * 1024,
+        index_wal_max_age_secs: 3600,
+        index_rescore_factor: 4,
+        index_snapshot_retention: 5,
+        index_snapshot_before_reindex: true,
         edge_embeddings: true,
         edge_boost_weight: 0.15,
         edge_cluster_rebalance: 50,
@@ -319,6 +343,22 @@ async fn test_get_document_missing_returns_error() {
     }
 }
 
+#[tokio::test]
+async fn test_similar_missing_chunk_returns_file_not_in_index() {
+    let (_dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let result = vdb
+        .similar("hello.md#99", SearchQuery::new("ignored"), false)
+        .await;
+    match result.unwrap_err() {
+        Error::FileNotInIndex { path } => {
+            assert_eq!(path, PathBuf::from("hello.md#99"));
+        }
+        other => panic!("expected FileNotInIndex error, got: {other}"),
+    }
+}
+
 #[tokio::test]
 async fn test_schema_returns_fields_after_ingest() {
     let (_dir, vdb) = setup_project();
@@ -556,6 +596,282 @@ async fn test_search_lexical_mode_via_api() {
     );
 }
 
+#[tokio::test]
+async fn zero_max_per_file_lifts_the_configured_cap() {
+    let (dir, _) = setup_project();
+    let mut config = mock_config();
+    config.search_max_per_file = Some(1);
+    let vdb = MarkdownVdb::open_with_config(dir.path().to_path_buf(), config).unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let rust_results = |response: SearchResponse| {
+        response
+            .results
+            .iter()
+            .filter(|r| r.file.path == "rust.md")
+            .count()
+    };
+    let query = SearchQuery::new("rust").with_mode(SearchMode::Lexical);
+    let capped = vdb.search(query.clone()).await.unwrap();
+    assert_eq!(rust_results(capped), 1);
+
+    let uncapped = vdb.search(query.with_max_per_file(0)).await.unwrap();
+    assert!(rust_results(uncapped) > 1, "both rust.md sections match");
+}
+
+#[tokio::test]
+async fn test_search_rejects_invalid_lexical_syntax() {
+    let (_dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let query = SearchQuery::new("\"systems programming -python").with_mode(SearchMode::Lexical);
+    let err = vdb.search(query).await.unwrap_err();
+    assert!(matches!(err, Error::LexicalQuery { position: 0, .. }));
+
+    // Hybrid queries fall back to treating the offending tokens as words.
+    let query = SearchQuery::new("\"systems programming -").with_mode(SearchMode::Hybrid);
+    assert!(!vdb.search(query).await.unwrap().results.is_empty());
+
+    // Semantic queries are embedded verbatim and never parsed.
+    let query = SearchQuery::new("\"systems programming").with_mode(SearchMode::Semantic);
+    assert!(vdb.search(query).await.is_ok());
+}
+
+#[tokio::test]
+async fn lexical_language_change_rebuilds_fts_on_open() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::write(
+        root.join("haus.md"),
+        "# Wohnen\n\nDie alten Häuser stehen am Fluss.\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("tokyo.md"),
+        "---\nlang: ja\n---\n\n# 天気\n\n東京都の天気は晴れです。\n",
+    )
+    .unwrap();
+    let lexical_paths = |response: SearchResponse| -> Vec<String> {
+        response.results.into_iter().map(|r| r.file.path).collect()
+    };
+
+    let vdb = MarkdownVdb::open_with_config(root.clone(), mock_config()).unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let haus = SearchQuery::new("Haus").with_mode(SearchMode::Lexical);
+    assert!(lexical_paths(vdb.search(haus.clone()).await.unwrap()).is_empty());
+    // The frontmatter `lang` override selects CJK n-grams under any default.
+    let tokyo = SearchQuery::new("東京").with_mode(SearchMode::Lexical);
+    assert_eq!(
+        lexical_paths(vdb.search(tokyo.clone()).await.unwrap()),
+        vec!["tokyo.md"]
+    );
+    drop(vdb);
+
+    // Opening with a different language rebuilds the FTS index.
+    let mut german = mock_config();
+    german.search_lexical_language = LexicalLanguage::German;
+    let reopened = MarkdownVdb::open_with_config(root, german).unwrap();
+    assert_eq!(
+        lexical_paths(reopened.search(haus).await.unwrap()),
+        vec!["haus.md"]
+    );
+    assert_eq!(
+        lexical_paths(reopened.search(tokyo).await.unwrap()),
+        vec!["tokyo.md"]
+    );
+}
+
+#[tokio::test]
+async fn snippets_highlight_lexical_terms_and_pick_semantic_windows() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::write(
+        root.join("sea.md"),
+        "# Sea\n\nWaves roll onto the shore. Tides follow the moon.\n\
+         Sailors track them closely. Storms arrive in winter.\n",
+    )
+    .unwrap();
+    let vdb = MarkdownVdb::open_with_config(root, mock_config()).unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let lexical = SearchQuery::new("tides").with_mode(SearchMode::Lexical);
+    let response = vdb.search(lexical.clone()).await.unwrap();
+    assert!(response.results[0].chunk.snippet.is_none());
+
+    let response = vdb.search(lexical.with_snippets(true)).await.unwrap();
+    let chunk = &response.results[0].chunk;
+    let snippet = chunk.snippet.as_ref().expect("lexical snippet");
+    assert_eq!(snippet.source, SnippetSource::Lexical);
+    assert_eq!(chunk.content[snippet.start..snippet.end], snippet.text);
+    let [highlight] = snippet.highlights.as_slice() else {
+        panic!("expected one highlight, got {:?}", snippet.highlights);
+    };
+    assert_eq!(&chunk.content[highlight.start..highlight.end], "Tides");
+    assert_eq!(highlight.line, 3);
+
+    // The mock provider embeds identical text identically, so the window
+    // equal to the query is the most similar one.
+    let semantic = SearchQuery::new("Sailors track them closely. Storms arrive in winter.")
+        .with_mode(SearchMode::Semantic)
+        .with_snippets(true);
+    let response = vdb.search(semantic).await.unwrap();
+    let snippet = response.results[0].chunk.snippet.as_ref().unwrap();
+    assert_eq!(snippet.source, SnippetSource::Semantic);
+    assert_eq!(
+        snippet.text,
+        "Sailors track them closely. Storms arrive in winter."
+    );
+    assert_eq!((snippet.start_line, snippet.end_line), (4, 4));
+    assert!(snippet.highlights.is_empty());
+}
+
+#[tokio::test]
+async fn misspelled_lexical_query_returns_suggestions() {
+    let (_dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let query = SearchQuery::new("greetngs").with_mode(SearchMode::Lexical);
+    let response = vdb.search(query).await.unwrap();
+    assert!(response.results.is_empty());
+    let [suggestion] = response.suggestions.as_slice() else {
+        panic!("expected one suggestion, got {:?}", response.suggestions);
+    };
+    assert_eq!(suggestion.term, "greetngs");
+    assert_eq!(suggestion.suggestion, "greetings");
+    assert_eq!(suggestion.doc_freq, 1);
+    assert!(response.timings.suggest_secs > 0.0);
+
+    let semantic = SearchQuery::new("greetngs").with_mode(SearchMode::Semantic);
+    assert!(vdb.search(semantic).await.unwrap().suggestions.is_empty());
+    let correct = SearchQuery::new("greetings").with_mode(SearchMode::Lexical);
+    assert!(vdb.search(correct).await.unwrap().suggestions.is_empty());
+}
+
+#[tokio::test]
+async fn regex_search_scans_chunks_within_scope() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("ops")).unwrap();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(
+        root.join("ops/incidents.md"),
+        "# Incidents\n\nNothing yet.\n\n## Outage\n\nSaw ERR-1042 at boot.\n\
+         Retried; ERR-1042 again and ERR-7.\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("ops/runbook.md"),
+        "---\nstatus: done\n---\n# Runbook\n\nERR-9 means restart.\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("notes/todo.md"),
+        "---\nstatus: draft\n---\n# Todo\n\nFix ERR-55.\n",
+    )
+    .unwrap();
+    let vdb = MarkdownVdb::open_with_config(root, mock_config()).unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let query = SearchQuery::new(r"ERR-\d+")
+        .with_mode(SearchMode::Regex)
+        .with_path_prefix("ops");
+    let response = vdb.search(query).await.unwrap();
+    let paths: Vec<&str> = response
+        .results
+        .iter()
+        .map(|r| r.file.path.as_str())
+        .collect();
+    assert_eq!(paths, vec!["ops/incidents.md", "ops/runbook.md"]);
+    let outage = &response.results[0].chunk;
+    assert_eq!(outage.heading_hierarchy, vec!["Incidents", "Outage"]);
+    let matches: Vec<(&str, usize)> = outage
+        .matches
+        .iter()
+        .map(|m| (m.text.as_str(), m.line))
+        .collect();
+    assert_eq!(
+        matches,
+        vec![("ERR-1042", 7), ("ERR-1042", 8), ("ERR-7", 8)]
+    );
+    let first = &outage.matches[0];
+    assert_eq!(&outage.content[first.start..first.end], "ERR-1042");
+
+    // Metadata filters apply; `^` anchors at line starts.
+    let filtered = SearchQuery::new(r"ERR-\d+")
+        .with_mode(SearchMode::Regex)
+        .with_filter("status = done".parse().unwrap());
+    let response = vdb.search(filtered).await.unwrap();
+    assert_eq!(response.results.len(), 1);
+    assert_eq!(response.results[0].file.path, "ops/runbook.md");
+    let anchored = SearchQuery::new("(?i)^fix").with_mode(SearchMode::Regex);
+    let response = vdb.search(anchored).await.unwrap();
+    assert_eq!(response.results.len(), 1);
+    assert_eq!(response.results[0].file.path, "notes/todo.md");
+
+    let invalid = SearchQuery::new("ERR-(").with_mode(SearchMode::Regex);
+    let err = vdb.search(invalid).await.unwrap_err();
+    assert!(matches!(err, Error::RegexPattern { .. }), "{err}");
+}
+
+#[tokio::test]
+async fn suggest_completes_titles_aliases_and_headings() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::write(
+        root.join("guide.md"),
+        "---\ntitle: Getting Started Guide\naliases: [Onboarding]\n---\n\n# Welcome\n\nIntro.\n\n\
+         ## Install Steps\n\nRun it.\n\n## Connection Retry Policy\n\nBack off.\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("k8s.md"),
+        "# Kubernetes Deployments\n\nPods.\n\n## Rollbacks\n\nUndo.\n",
+    )
+    .unwrap();
+    let vdb = MarkdownVdb::open_with_config(root.clone(), mock_config()).unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let texts = |prefix: &str| -> Vec<String> {
+        vdb.suggest(prefix, 10)
+            .into_iter()
+            .map(|s| s.text)
+            .collect()
+    };
+
+    let title = &vdb.suggest("getting  ST", 10)[0];
+    assert_eq!(title.text, "Getting Started Guide");
+    assert_eq!(title.kind, SuggestionKind::Title);
+    assert_eq!(title.score, 1.0);
+
+    let alias = &vdb.suggest("onboard", 10)[0];
+    assert_eq!(alias.kind, SuggestionKind::Alias);
+    assert_eq!(
+        (alias.path.as_str(), alias.chunk_id.as_str()),
+        ("guide.md", "guide.md#0")
+    );
+
+    // Later words match too, below whole-text matches.
+    let heading = &vdb.suggest("retry", 10)[0];
+    assert_eq!(heading.kind, SuggestionKind::Heading);
+    assert_eq!(
+        heading.heading_hierarchy,
+        vec!["Welcome", "Connection Retry Policy"]
+    );
+    assert_eq!(heading.score, 0.75);
+
+    // The H1 title is not repeated as a heading; typos still complete.
+    assert_eq!(texts("kubernetes"), vec!["Kubernetes Deployments"]);
+    let fuzzy = &vdb.suggest("kuberentes", 10)[0];
+    assert_eq!(fuzzy.text, "Kubernetes Deployments");
+    assert!(fuzzy.score < 1.0);
+    assert!(texts("zzz").is_empty());
+
+    // The table is rebuilt after the index changes.
+    assert!(texts("release").is_empty());
+    fs::write(root.join("notes.md"), "# Release Notes\n\nShipped.\n").unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    assert_eq!(texts("release"), vec!["Release Notes"]);
+}
+
 #[tokio::test]
 async fn test_fts_index_populated_after_ingest() {
     let (_dir, vdb) = setup_project();
@@ -1207,6 +1523,30 @@ fn test_info_before_ingest() {
     assert!(info.reindex_estimated_tokens > 0);
 }
 
+#[tokio::test]
+async fn test_info_reports_binary_quantization_and_recall() {
+    let (dir, vdb) = setup_project();
+    drop(vdb);
+    let mut config = mock_config();
+    config.vector_quantization = mdvdb::VectorQuantization::B1;
+    let vdb = MarkdownVdb::open_with_config(dir.path().to_path_buf(), config).unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+
+    let storage = vdb.info(None).unwrap().vector_storage;
+    assert_eq!(storage.quantization, mdvdb::VectorQuantization::B1);
+    assert_eq!(storage.bytes_per_vector, 1);
+    assert_eq!(storage.full_precision_bytes_per_vector, DIMS * 4);
+    assert!(storage.sidecar_file_size.unwrap() > 0);
+    assert!(storage.full_precision_available);
+    assert_eq!(storage.rescore_factor, 4);
+    assert!(storage.recall.is_none());
+
+    let recall = vdb.estimate_recall().unwrap();
+    assert!(recall.queries > 0);
+    assert_eq!(recall.rescored, Some(1.0));
+    assert!(recall.quantized <= 1.0);
+}
+
 #[tokio::test]
 async fn test_info_scoped_folder() {
     let (dir, vdb) = setup_project();
@@ -2060,3 +2400,293 @@ async fn readonly_open_tolerates_a_pending_fts_reconciliation() {
     drop(writable);
     assert!(!marker.exists());
 }
+
+#[tokio::test]
+async fn snapshot_restore_returns_to_the_captured_index() {
+    let (dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let snapshot = vdb.snapshot_create().unwrap();
+    assert_eq!(snapshot.document_count, 2);
+    assert_eq!(snapshot.embedding_dimensions, DIMS);
+    assert!(!snapshot.fts_rebuild_required);
+
+    fs::write(
+        dir.path().join("zebra.md"),
+        "# Zebra\n\nStriped animals graze.\n",
+    )
+    .unwrap();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    assert_eq!(vdb.status().document_count, 3);
+
+    // The open instance holds the FTS writer, so restoring must wait.
+    let err = MarkdownVdb::restore_snapshot(dir.path(), &snapshot.id).unwrap_err();
+    assert!(matches!(err, Error::IndexBusy { .. }), "{err}");
+    drop(vdb);
+
+    let err = MarkdownVdb::restore_snapshot(dir.path(), "19700101T000000Z").unwrap_err();
+    assert!(err.to_string().contains("not found"), "{err}");
+
+    let restored = MarkdownVdb::restore_snapshot(dir.path(), &snapshot.id).unwrap();
+    assert_eq!(restored.id, snapshot.id);
+    assert!(!dir
+        .path()
+        .join(".markdownvdb/fts-reconcile-required")
+        .exists());
+
+    let vdb = MarkdownVdb::open_with_config(dir.path().to_path_buf(), mock_config()).unwrap();
+    assert_eq!(vdb.status().document_count, 2);
+    let resp = vdb
+        .search(SearchQuery::new("striped").with_mode(SearchMode::Lexical))
+        .await
+        .unwrap();
+    assert!(
+        resp.results.is_empty(),
+        "restored FTS must not know zebra.md"
+    );
+    assert_eq!(vdb.snapshots().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn reindex_snapshots_first_and_retention_prunes_old_snapshots() {
+    let (dir, vdb) = setup_project();
+    drop(vdb);
+    let mut config = mock_config();
+    config.index_snapshot_retention = 2;
+    let vdb = MarkdownVdb::open_with_config(dir.path().to_path_buf(), config).unwrap();
+
+    let first = vdb.ingest(IngestOptions::default()).await.unwrap();
+    assert_eq!(first.snapshot, None, "only a full reindex snapshots");
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let result = vdb
+            .ingest(IngestOptions {
+                full: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        ids.push(result.snapshot.expect("reindex takes a snapshot"));
+    }
+    let kept: Vec<_> = vdb.snapshots().unwrap().into_iter().map(|s| s.id).collect();
+    assert_eq!(kept, ids[1..].to_vec(), "oldest snapshot pruned");
+    assert!(vdb
+        .snapshots()
+        .unwrap()
+        .iter()
+        .all(|s| s.reason == mdvdb::SnapshotReason::PreReindex));
+
+    let removed = vdb.snapshot_prune(Some(1)).unwrap();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].id, ids[1]);
+    assert!(
+        vdb.snapshot_prune(Some(0)).unwrap().is_empty(),
+        "0 keeps all"
+    );
+    assert_eq!(vdb.snapshots().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn export_import_rebuilds_an_equivalent_index_without_embedding() {
+    let (source_dir, source) = setup_project_with_links();
+    source.ingest(IngestOptions::default()).await.unwrap();
+    let exports = TempDir::new().unwrap();
+    let out = exports.path().join("export");
+    let exported = source.export(&out).unwrap();
+    assert_eq!(exported.document_count, 4);
+    assert_eq!(exported.embedding_dimensions, DIMS);
+    assert!(out.join("index.jsonl").is_file());
+    let npy = fs::read(out.join("vectors.npy")).unwrap();
+    assert_eq!(&npy[..6], b"\x93NUMPY");
+
+    let err = source.export(&out).unwrap_err();
+    assert!(err.to_string().contains("not empty"), "{err}");
+
+    // The target project has the same notes but has never been ingested.
+    let target_dir = TempDir::new().unwrap();
+    fs::create_dir_all(target_dir.path().join(".markdownvdb")).unwrap();
+    for name in ["alpha.md", "beta.md", "gamma.md", "orphan.md"] {
+        fs::copy(source_dir.path().join(name), target_dir.path().join(name)).unwrap();
+    }
+    let imported = MarkdownVdb::import(target_dir.path(), &mock_config(), &out).unwrap();
+    assert_eq!(imported.chunk_count, exported.chunk_count);
+    assert_eq!(imported.edge_count, exported.edge_count);
+    assert!(!target_dir
+        .path()
+        .join(".markdownvdb/fts-reconcile-required")
+        .exists());
+
+    let target =
+        MarkdownVdb::open_with_config(target_dir.path().to_path_buf(), mock_config()).unwrap();
+    let (before, after) = (source.status(), target.status());
+    assert_eq!(after.document_count, before.document_count);
+    assert_eq!(after.chunk_count, before.chunk_count);
+    assert_eq!(after.vector_count, before.vector_count);
+    assert!(!after.reindex_required);
+    assert_eq!(
+        target.links("alpha.md").unwrap().outgoing.len(),
+        source.links("alpha.md").unwrap().outgoing.len()
+    );
+
+    let query = SearchQuery::new("links back").with_mode(SearchMode::Semantic);
+    let expected = source.search(query.clone()).await.unwrap();
+    let actual = target.search(query).await.unwrap();
+    let ids = |resp: &SearchResponse| -> Vec<(String, f64)> {
+        resp.results
+            .iter()
+            .map(|r| (r.chunk.chunk_id.clone(), r.score))
+            .collect()
+    };
+    assert_eq!(ids(&actual), ids(&expected));
+
+    let lexical = target
+        .search(SearchQuery::new("outgoing").with_mode(SearchMode::Lexical))
+        .await
+        .unwrap();
+    assert_eq!(lexical.results[0].file.path, "gamma.md");
+
+    // Nothing changed on disk, so an incremental ingest embeds nothing.
+    let result = target.ingest(IngestOptions::default()).await.unwrap();
+    assert_eq!(result.files_indexed, 0);
+}
+
+#[tokio::test]
+async fn import_rejects_newer_export_formats_before_touching_the_index() {
+    let (dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let exports = TempDir::new().unwrap();
+    let out = exports.path().join("export");
+    vdb.export(&out).unwrap();
+    drop(vdb);
+
+    let lines_path = out.join("index.jsonl");
+    let lines = fs::read_to_string(&lines_path).unwrap();
+    let (header, rest) = lines.split_once('\n').unwrap();
+    let mut header: serde_json::Value = serde_json::from_str(header).unwrap();
+    header["version"] = serde_json::json!(99);
+    fs::write(&lines_path, format!("{header}\n{rest}")).unwrap();
+
+    let err = MarkdownVdb::import(dir.path(), &mock_config(), &out).unwrap_err();
+    assert!(matches!(err, Error::Export(_)), "{err}");
+    assert!(err.to_string().contains("v99"), "{err}");
+
+    let vdb = MarkdownVdb::open_with_config(dir.path().to_path_buf(), mock_config()).unwrap();
+    assert_eq!(vdb.status().document_count, 2);
+}
+
+#[tokio::test]
+async fn import_rejects_a_vector_file_outside_the_export() {
+    let (dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let exports = TempDir::new().unwrap();
+    let out = exports.path().join("export");
+    vdb.export(&out).unwrap();
+    drop(vdb);
+    fs::copy(out.join("vectors.npy"), exports.path().join("outside.npy")).unwrap();
+
+    let lines_path = out.join("index.jsonl");
+    let lines = fs::read_to_string(&lines_path).unwrap();
+    let (header, rest) = lines.split_once('\n').unwrap();
+    let header: serde_json::Value = serde_json::from_str(header).unwrap();
+    for vector_file in ["../outside.npy", "/etc/passwd", ""] {
+        let mut header = header.clone();
+        header["vector_file"] = serde_json::json!(vector_file);
+        fs::write(&lines_path, format!("{header}\n{rest}")).unwrap();
+
+        let err = MarkdownVdb::import(dir.path(), &mock_config(), &out).unwrap_err();
+        assert!(matches!(err, Error::Export(_)), "{err}");
+        assert!(err.to_string().contains("expected a file name"), "{err}");
+    }
+}
+
+#[tokio::test]
+async fn fsck_passes_a_fresh_index_and_repairs_a_lost_full_text_index() {
+    let (dir, vdb) = setup_project_with_links();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let status = vdb.status();
+    drop(vdb);
+
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), false).unwrap();
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+    assert_eq!(report.format_version, Some(1));
+    assert_eq!(report.chunk_count, status.chunk_count);
+    assert_eq!(report.vector_count, status.vector_count);
+    assert_eq!(report.fts_document_count, Some(status.chunk_count as u64));
+
+    fs::remove_dir_all(dir.path().join(".markdownvdb/fts")).unwrap();
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), false).unwrap();
+    assert_eq!(report.problems.len(), 1);
+    assert_eq!(report.problems[0].check, FsckCheck::FullText);
+    assert_eq!(report.unresolved(), 1);
+
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), true).unwrap();
+    assert!(report.repaired);
+    assert_eq!(report.unresolved(), 0);
+    assert!(report.needs_reembedding.is_empty());
+
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), false).unwrap();
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+    let vdb = MarkdownVdb::open_with_config(dir.path().to_path_buf(), mock_config()).unwrap();
+    let lexical = vdb
+        .search(SearchQuery::new("outgoing").with_mode(SearchMode::Lexical))
+        .await
+        .unwrap();
+    assert_eq!(lexical.results[0].file.path, "gamma.md");
+}
+
+#[tokio::test]
+async fn fsck_recreates_a_corrupt_full_text_index_only_while_unlocked() {
+    use tantivy::directory::{Directory, MmapDirectory, INDEX_WRITER_LOCK};
+
+    let (dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    let status = vdb.status();
+    drop(vdb);
+    let fts_path = dir.path().join(".markdownvdb/fts");
+    fs::write(fts_path.join("meta.json"), "not json").unwrap();
+
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), false).unwrap();
+    assert_eq!(report.problems[0].check, FsckCheck::FullText);
+    assert_eq!(report.fts_document_count, None);
+
+    // Another writer's lock keeps the files in place.
+    {
+        let directory = MmapDirectory::open(&fts_path).unwrap();
+        let _lock = directory.acquire_lock(&INDEX_WRITER_LOCK).unwrap();
+        let err = MarkdownVdb::fsck(dir.path(), &mock_config(), true).unwrap_err();
+        assert!(matches!(err, Error::IndexBusy { .. }), "{err}");
+        assert_eq!(
+            fs::read_to_string(fts_path.join("meta.json")).unwrap(),
+            "not json"
+        );
+    }
+
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), true).unwrap();
+    assert!(report.repaired);
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), false).unwrap();
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+    assert_eq!(report.fts_document_count, Some(status.chunk_count as u64));
+}
+
+#[tokio::test]
+async fn fsck_reports_a_damaged_header_without_touching_the_index() {
+    let (dir, vdb) = setup_project();
+    vdb.ingest(IngestOptions::default()).await.unwrap();
+    drop(vdb);
+
+    let index_path = dir.path().join(".markdownvdb/index");
+    let mut bytes = fs::read(&index_path).unwrap();
+    bytes[42] = 0xEE;
+    fs::write(&index_path, &bytes).unwrap();
+
+    let report = MarkdownVdb::fsck(dir.path(), &mock_config(), true).unwrap();
+    assert!(!report.repaired);
+    assert_eq!(report.format_version, None);
+    assert_eq!(report.problems.len(), 1);
+    assert_eq!(report.problems[0].check, FsckCheck::Header);
+    assert!(!report.problems[0].repairable);
+    assert!(report.problems[0]
+        .detail
+        .contains("unknown quantization type"));
+    assert_eq!(fs::read(&index_path).unwrap(), bytes);
+}
@@ -321,6 +321,204 @@ fn test_search_json_output_format() {
     );
 }
 
+#[test]
+fn test_search_reuses_cached_query_embedding() {
+    let dir = setup_and_ingest();
+    let search_timings = |extra: &[&str]| {
+        let output = mdvdb_bin()
+            .args(["search", "rust programming", "--json", "-v"])
+            .args(extra)
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mdvdb");
+        assert!(
+            output.status.success(),
+            "search should succeed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["timings"].clone()
+    };
+
+    assert_eq!(search_timings(&[])["cache_hit"], false);
+    let query_cache = dir.path().join(".markdownvdb/cache/queries");
+    assert_eq!(query_cache.read_dir().unwrap().count(), 1);
+    let cached = search_timings(&[]);
+    assert_eq!(cached["cache_hit"], true);
+    assert_eq!(cached["embed_secs"], 0.0);
+
+    let bypassed = search_timings(&["--no-cache"]);
+    assert!(bypassed.get("cache_hit").is_none());
+}
+
+#[test]
+fn test_reindex_reuses_cached_chunk_embeddings() {
+    let dir = setup_and_ingest();
+    let run_json = |args: &[&str]| {
+        let output = mdvdb_bin()
+            .args(args)
+            .arg("--json")
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mdvdb");
+        assert!(
+            output.status.success(),
+            "{args:?} should succeed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let stats = run_json(&["cache", "stats"]);
+    let spaces = stats["chunk_spaces"].as_array().unwrap();
+    assert_eq!(spaces.len(), 1);
+    assert!(spaces[0]["entries"].as_u64().unwrap() > 0);
+
+    let reindex = run_json(&["ingest", "--reindex"]);
+    assert_eq!(reindex["api_calls"], 0);
+    assert!(reindex["embeddings_cached"].as_u64().unwrap() > 0);
+
+    let pruned = run_json(&["cache", "prune"]);
+    assert_eq!(pruned["chunk_entries_removed"], 0);
+
+    let cleared = run_json(&["cache", "clear"]);
+    assert!(cleared["chunk_entries_removed"].as_u64().unwrap() > 0);
+    let stats = run_json(&["cache", "stats"]);
+    assert!(stats["chunk_spaces"].as_array().unwrap().is_empty());
+    assert_eq!(stats["total_bytes"], 0);
+}
+
+#[test]
+fn test_similar_json_excludes_seed_file() {
+    let dir = setup_and_ingest();
+
+    let output = mdvdb_bin()
+        .args(["similar", "hello.md", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+
+    assert!(
+        output.status.success(),
+        "similar --json should succeed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["query"], "hello.md");
+    let results = json["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r["file"]["path"] != "hello.md"));
+
+    let output = mdvdb_bin()
+        .args(["similar", "missing.md", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+    assert!(!output.status.success(), "unknown seed should fail");
+}
+
+#[test]
+fn test_search_batch_writes_one_response_per_line() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let dir = setup_and_ingest();
+
+    let mut child = mdvdb_bin()
+        .args(["search", "--batch"])
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run mdvdb");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            concat!(
+                "{\"query\": \"rust programming\", \"mode\": \"lexical\", \"limit\": 1}\n",
+                "\n",
+                "{\"query\": \"greetings\", \"filters\": [\"status=published\"]}\n",
+                "{\"query\": \"systems\", \"path\": \"missing/\"}\n",
+                "not json\n",
+                "{\"query\": \"(\", \"mode\": \"regex\"}\n",
+                "{\"query\": \"Rust\", \"mode\": \"regex\", \"limit\": 1}\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "search --batch should succeed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let responses: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 6, "blank lines are skipped");
+
+    let lexical = responses[0]["results"].as_array().unwrap();
+    assert_eq!(lexical.len(), 1);
+    assert_eq!(lexical[0]["file"]["path"], "rust.md");
+    let filtered = responses[1]["results"].as_array().unwrap();
+    assert!(!filtered.is_empty());
+    assert!(filtered.iter().all(|r| r["file"]["path"] == "hello.md"));
+    assert!(responses[2]["results"].as_array().unwrap().is_empty());
+
+    // Failing lines are answered in place and do not abort the batch.
+    assert_eq!(responses[3]["line"], 5);
+    assert!(responses[3]["error"].is_string());
+    assert_eq!(responses[4]["line"], 6);
+    assert!(responses[4]["error"].is_string());
+    assert!(responses[5]["error"].is_null());
+    assert_eq!(responses[5]["results"].as_array().unwrap().len(), 1);
+
+    let output = mdvdb_bin()
+        .args(["search", "rust", "--batch"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+    assert!(!output.status.success(), "--batch conflicts with a query");
+}
+
+#[test]
+fn test_context_packs_cited_chunks_within_budget() {
+    let dir = setup_and_ingest();
+
+    let output = mdvdb_bin()
+        .args(["context", "rust programming", "--budget", "200", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+
+    assert!(
+        output.status.success(),
+        "context --json should succeed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["query"], "rust programming");
+    assert_eq!(json["budget"], 200);
+    assert!(json["tokens_used"].as_u64().unwrap() <= 200);
+    let sections = json["sections"].as_array().unwrap();
+    assert!(!sections.is_empty());
+    assert_eq!(sections[0]["citation"], 1);
+    assert!(json["text"].as_str().unwrap().starts_with("### [1] "));
+
+    let output = mdvdb_bin()
+        .args(["context", "rust programming", "--budget", "200"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(".md:"));
+}
+
 #[test]
 fn test_status_json_output_after_ingest() {
     let dir = setup_and_ingest();
@@ -960,6 +1158,32 @@ fn test_search_lexical_shorthand_flag() {
     );
 }
 
+#[test]
+fn test_search_regex_shorthand_flag() {
+    let dir = setup_and_ingest();
+
+    let output = mdvdb_bin()
+        .args(["search", r"system\w* program", "--regex", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+
+    assert!(
+        output.status.success(),
+        "search --regex should succeed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("should be valid JSON");
+    assert_eq!(json["mode"], "regex");
+    assert_eq!(json["total_results"], 1);
+    assert_eq!(json["results"][0]["file"]["path"], "rust.md");
+    assert_eq!(
+        json["results"][0]["chunk"]["matches"][0]["text"],
+        "systems program"
+    );
+}
+
 #[test]
 fn test_search_invalid_mode_rejected() {
     let dir = setup_and_ingest();
@@ -1200,6 +1424,44 @@ fn test_doctor_human_output() {
     assert!(stdout.contains("checks passed"), "should show pass count");
 }
 
+#[test]
+fn test_fsck_exits_nonzero_until_repaired() {
+    let dir = setup_and_ingest();
+    let fsck = |args: &[&str]| {
+        mdvdb_bin()
+            .args(["--no-color", "fsck"])
+            .args(args)
+            .env("MDVDB_NO_USER_CONFIG", "1")
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mdvdb")
+    };
+
+    let output = fsck(&[]);
+    assert!(
+        output.status.success(),
+        "fsck should pass a fresh index, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No problems found"));
+
+    std::fs::remove_dir_all(dir.path().join(".markdownvdb").join("fts")).unwrap();
+    let output = fsck(&["--json"]);
+    assert!(!output.status.success(), "problems should fail fsck");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+    assert_eq!(json["problems"][0]["check"], "full_text");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("mdvdb fsck --repair"));
+
+    let output = fsck(&["--repair"]);
+    assert!(
+        output.status.success(),
+        "repair should succeed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Repaired 1 problem(s)"));
+    assert!(fsck(&[]).status.success());
+}
+
 #[test]
 fn test_help_shows_new_subcommands() {
     let output = mdvdb_bin()
@@ -2858,6 +3120,70 @@ fn test_collection_filter_cli_repeatable() {
     );
 }
 
+#[test]
+fn test_collection_where_expression_cli() {
+    let dir = setup_collection_cli();
+
+    let run = |expr: &str| -> serde_json::Value {
+        let output = mdvdb_bin()
+            .args(["collection", "blog", "--where", expr, "--json"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let newer = run("status = published and date > 2024-04-01");
+    assert_eq!(newer["total_rows"].as_u64().unwrap(), 1);
+    assert_eq!(newer["rows"][0]["path"].as_str().unwrap(), "blog/launch.md");
+
+    let either = run("tags contains news or date <= 2024-01-15");
+    assert_eq!(either["total_rows"].as_u64().unwrap(), 2);
+
+    // Negation also matches rows without frontmatter (blog/plain.md).
+    let not_draft = run("status != draft");
+    assert_eq!(not_draft["total_rows"].as_u64().unwrap(), 3);
+}
+
+#[test]
+fn test_search_where_expression_cli() {
+    let dir = setup_and_ingest();
+
+    let output = mdvdb_bin()
+        .args([
+            "search",
+            "document",
+            "--where",
+            "status in (published, review) and not title contains Rust",
+            "--json",
+        ])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    for result in json["results"].as_array().unwrap() {
+        assert_eq!(result["file"]["path"], "hello.md");
+    }
+
+    let output = mdvdb_bin()
+        .args(["search", "document", "--where", "status = (", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mdvdb");
+    assert!(!output.status.success(), "invalid expression must fail");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid filter expression"));
+}
+
 #[test]
 fn test_collection_limit_offset_cli() {
     let dir = setup_collection_cli();
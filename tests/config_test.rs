@@ -1,7 +1,8 @@
 use std::fs;
 use std::path::PathBuf;
 
-use mdvdb::config::{Config, EmbeddingProviderType, VectorQuantization};
+use mdvdb::config::{Config, EmbeddingProviderType, RerankProviderType, VectorQuantization};
+use mdvdb::fts::LexicalLanguage;
 use mdvdb::Error;
 use serial_test::serial;
 use tempfile::TempDir;
@@ -35,10 +36,30 @@ const ALL_ENV_VARS: &[&str] = &[
     "MDVDB_NO_USER_CONFIG",
     "MDVDB_VECTOR_QUANTIZATION",
     "MDVDB_INDEX_COMPRESSION",
+    "MDVDB_INDEX_WAL_MAX_BYTES",
+    "MDVDB_INDEX_WAL_MAX_AGE_SECS",
+    "MDVDB_INDEX_RESCORE_FACTOR",
+    "MDVDB_INDEX_SNAPSHOT_RETENTION",
+    "MDVDB_INDEX_SNAPSHOT_BEFORE_REINDEX",
     "MDVDB_SEARCH_BOOST_LINKS",
     "MDVDB_SEARCH_BOOST_HOPS",
     "MDVDB_SEARCH_EXPAND_GRAPH",
     "MDVDB_SEARCH_EXPAND_LIMIT",
+    "MDVDB_SEARCH_FUSION",
+    "MDVDB_SEARCH_SEMANTIC_WEIGHT",
+    "MDVDB_SEARCH_DIVERSITY",
+    "MDVDB_SEARCH_MAX_PER_FILE",
+    "MDVDB_SEARCH_LEXICAL_BOOSTS",
+    "MDVDB_SEARCH_LEXICAL_LANGUAGE",
+    "MDVDB_SEARCH_LEXICAL_PATHS",
+    "MDVDB_SEARCH_LEXICAL_STOPWORDS",
+    "MDVDB_RERANK_PROVIDER",
+    "MDVDB_RERANK_MODEL",
+    "MDVDB_RERANK_ENDPOINT",
+    "MDVDB_RERANK_TOP_N",
+    "MDVDB_QUERY_CACHE_MAX_ENTRIES",
+    "MDVDB_QUERY_CACHE_TTL_SECS",
+    "MDVDB_CHUNK_EMBEDDING_CACHE",
     "MDVDB_CUSTOM_CLUSTERS",
     "MDVDB_CLUSTERING_ALGORITHM",
     "MDVDB_CLUSTERING_KNN",
@@ -101,6 +122,8 @@ fn defaults_applied_when_no_config() {
     assert_eq!(config.search_min_score, 0.0);
     assert_eq!(config.search_default_mode, mdvdb::SearchMode::Hybrid);
     assert_eq!(config.search_rrf_k, 60.0);
+    assert_eq!(config.search_fusion, mdvdb::FusionMethod::Rrf);
+    assert_eq!(config.search_semantic_weight, 0.7);
     assert_eq!(config.bm25_norm_k, 1.5);
     assert!(
         !config.search_decay_enabled,
@@ -406,6 +429,24 @@ fn search_config_from_yaml() {
     clear_env();
 }
 
+#[test]
+#[serial]
+fn fusion_config_from_yaml() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(
+        tmp.path(),
+        "search:\n  fusion: linear\n  semantic_weight: 0.6\n",
+    );
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_fusion, mdvdb::FusionMethod::Linear);
+    assert_eq!(config.search_semantic_weight, 0.6);
+
+    clear_env();
+}
+
 #[test]
 #[serial]
 fn search_mode_case_insensitive() {
@@ -929,6 +970,80 @@ fn quantization_and_compression_in_yaml() {
     clear_env();
 }
 
+#[test]
+#[serial]
+fn index_wal_thresholds_from_yaml_and_env() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(
+        tmp.path(),
+        "index:\n  wal_max_bytes: 1048576\n  wal_max_age_secs: 60\n",
+    );
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.index_wal_max_bytes, 1_048_576);
+    assert_eq!(config.index_wal_max_age_secs, 60);
+
+    std::env::set_var("MDVDB_INDEX_WAL_MAX_BYTES", "0");
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.index_wal_max_bytes, 0, "0 disables the log");
+    assert_eq!(config.index_wal_max_age_secs, 60);
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn quantized_index_and_rescore_factor_from_yaml_and_env() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(
+        tmp.path(),
+        "index:\n  quantization: b1\n  rescore_factor: 8\n",
+    );
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.vector_quantization, VectorQuantization::B1);
+    assert_eq!(config.index_rescore_factor, 8);
+
+    std::env::set_var("MDVDB_VECTOR_QUANTIZATION", "i8");
+    std::env::set_var("MDVDB_INDEX_RESCORE_FACTOR", "0");
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.vector_quantization, VectorQuantization::I8);
+    assert_eq!(config.index_rescore_factor, 0, "0 disables rescoring");
+
+    std::env::set_var("MDVDB_INDEX_RESCORE_FACTOR", "65");
+    assert!(Config::load(tmp.path()).is_err());
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn index_snapshot_settings_from_yaml_and_env() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(
+        tmp.path(),
+        "index:\n  snapshot_retention: 2\n  snapshot_before_reindex: false\n",
+    );
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.index_snapshot_retention, 2);
+    assert!(!config.index_snapshot_before_reindex);
+
+    std::env::set_var("MDVDB_INDEX_SNAPSHOT_RETENTION", "0");
+    std::env::set_var("MDVDB_INDEX_SNAPSHOT_BEFORE_REINDEX", "true");
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.index_snapshot_retention, 0, "0 keeps every snapshot");
+    assert!(config.index_snapshot_before_reindex);
+
+    clear_env();
+}
+
 // ---------------------------------------------------------------------------
 // Graph traversal config tests
 // ---------------------------------------------------------------------------
@@ -1041,6 +1156,280 @@ fn config_expand_limit_rejects_eleven() {
     clear_env();
 }
 
+#[test]
+#[serial]
+fn diversity_config_from_yaml() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(tmp.path(), "search:\n  diversity: 0.7\n  max_per_file: 2\n");
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_diversity, Some(0.7));
+    assert_eq!(config.search_max_per_file, Some(2));
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn diversity_defaults_to_disabled() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_diversity, None);
+    assert_eq!(config.search_max_per_file, None);
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn config_diversity_rejects_out_of_range() {
+    clear_env();
+    let tmp = TempDir::new().unwrap();
+    std::env::set_var("MDVDB_SEARCH_DIVERSITY", "1.5");
+
+    let result = Config::load(tmp.path());
+    assert!(result.is_err(), "diversity of 1.5 should be rejected");
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("search_diversity"),
+        "error should mention search_diversity: {}",
+        err_msg
+    );
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn config_max_per_file_rejects_zero() {
+    clear_env();
+    let tmp = TempDir::new().unwrap();
+    std::env::set_var("MDVDB_SEARCH_MAX_PER_FILE", "0");
+
+    let result = Config::load(tmp.path());
+    assert!(result.is_err(), "max_per_file of 0 should be rejected");
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("search_max_per_file"),
+        "error should mention search_max_per_file: {}",
+        err_msg
+    );
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn lexical_boosts_from_yaml_and_env() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert!(config.search_lexical_boosts.is_empty());
+
+    write_project_yaml(
+        tmp.path(),
+        "search:\n  lexical:\n    boosts:\n      title: 3.0\n      heading: 2.0\n",
+    );
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_lexical_boosts.get("title"), Some(&3.0));
+    assert_eq!(config.search_lexical_boosts.get("heading"), Some(&2.0));
+
+    std::env::set_var("MDVDB_SEARCH_LEXICAL_BOOSTS", "tags=2.5, frontmatter=0.5");
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_lexical_boosts.len(), 2);
+    assert_eq!(config.search_lexical_boosts.get("tags"), Some(&2.5));
+    assert_eq!(config.search_lexical_boosts.get("frontmatter"), Some(&0.5));
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn lexical_language_from_yaml_and_env() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_lexical_language, LexicalLanguage::English);
+    assert!(config.search_lexical_paths.is_empty());
+    assert!(!config.search_lexical_stopwords);
+
+    write_project_yaml(
+        tmp.path(),
+        "search:\n  lexical:\n    language: de\n    stopwords: true\n    paths:\n      notes/ja: japanese\n",
+    );
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.search_lexical_language, LexicalLanguage::German);
+    assert!(config.search_lexical_stopwords);
+    assert_eq!(
+        config.search_lexical_paths.get("notes/ja"),
+        Some(&LexicalLanguage::Cjk)
+    );
+
+    std::env::set_var("MDVDB_SEARCH_LEXICAL_LANGUAGE", "french");
+    std::env::set_var("MDVDB_SEARCH_LEXICAL_PATHS", "de=german, es=es");
+    std::env::set_var("MDVDB_SEARCH_LEXICAL_STOPWORDS", "false");
+    let settings = Config::load(tmp.path()).unwrap().lexical_settings();
+    assert_eq!(settings.language, LexicalLanguage::French);
+    assert_eq!(settings.paths.len(), 2);
+    assert_eq!(settings.paths.get("es"), Some(&LexicalLanguage::Spanish));
+    assert!(!settings.stopwords);
+
+    std::env::set_var("MDVDB_SEARCH_LEXICAL_LANGUAGE", "klingon");
+    let err_msg = Config::load(tmp.path()).unwrap_err().to_string();
+    assert!(err_msg.contains("klingon"), "{err_msg}");
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn config_lexical_boosts_reject_invalid_entries() {
+    clear_env();
+    let tmp = TempDir::new().unwrap();
+    for value in ["tags=0", "due-date=2"] {
+        std::env::set_var("MDVDB_SEARCH_LEXICAL_BOOSTS", value);
+        let err_msg = Config::load(tmp.path()).unwrap_err().to_string();
+        assert!(
+            err_msg.contains("search_lexical_boosts"),
+            "{value}: error should mention search_lexical_boosts: {err_msg}"
+        );
+    }
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn rerank_defaults_to_disabled() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.rerank_provider, RerankProviderType::None);
+    assert_eq!(config.rerank_model, None);
+    assert_eq!(config.rerank_endpoint, None);
+    assert_eq!(config.rerank_top_n, 50);
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn rerank_config_from_yaml() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(
+        tmp.path(),
+        "rerank:\n  provider: jina\n  model: jina-reranker-v2-base-multilingual\n  endpoint: https://api.jina.ai/v1/rerank\n  top_n: 20\n",
+    );
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.rerank_provider, RerankProviderType::Cohere);
+    assert_eq!(
+        config.rerank_model.as_deref(),
+        Some("jina-reranker-v2-base-multilingual")
+    );
+    assert_eq!(
+        config.rerank_endpoint.as_deref(),
+        Some("https://api.jina.ai/v1/rerank")
+    );
+    assert_eq!(config.rerank_top_n, 20);
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn rerank_env_overrides_yaml() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+    write_project_yaml(tmp.path(), "rerank:\n  provider: cohere\n  top_n: 20\n");
+    std::env::set_var("MDVDB_RERANK_PROVIDER", "tei");
+    std::env::set_var("MDVDB_RERANK_TOP_N", "5");
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.rerank_provider, RerankProviderType::Tei);
+    assert_eq!(config.rerank_top_n, 5);
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn query_cache_config_from_yaml_and_env() {
+    clear_env();
+    std::env::set_var("MDVDB_NO_USER_CONFIG", "1");
+    let tmp = TempDir::new().unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.query_cache_max_entries, 1000);
+    assert_eq!(config.query_cache_ttl_secs, 604_800);
+    assert!(config.chunk_embedding_cache);
+
+    write_project_yaml(
+        tmp.path(),
+        "cache:\n  query_max_entries: 50\n  query_ttl_secs: 3600\n  chunk_embeddings: false\n",
+    );
+    std::env::set_var("MDVDB_QUERY_CACHE_MAX_ENTRIES", "0");
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.query_cache_max_entries, 0);
+    assert_eq!(config.query_cache_ttl_secs, 3600);
+    assert!(!config.chunk_embedding_cache);
+
+    std::env::set_var("MDVDB_CHUNK_EMBEDDING_CACHE", "true");
+    assert!(Config::load(tmp.path()).unwrap().chunk_embedding_cache);
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn config_rerank_rejects_unknown_provider() {
+    clear_env();
+    let tmp = TempDir::new().unwrap();
+    std::env::set_var("MDVDB_RERANK_PROVIDER", "bogus");
+
+    let result = Config::load(tmp.path());
+    assert!(
+        result.is_err(),
+        "unknown rerank provider should be rejected"
+    );
+    assert!(result.unwrap_err().to_string().contains("bogus"));
+
+    clear_env();
+}
+
+#[test]
+#[serial]
+fn config_rerank_top_n_rejects_zero() {
+    clear_env();
+    let tmp = TempDir::new().unwrap();
+    std::env::set_var("MDVDB_RERANK_TOP_N", "0");
+
+    let result = Config::load(tmp.path());
+    assert!(result.is_err(), "rerank top_n of 0 should be rejected");
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("rerank_top_n"),
+        "error should mention rerank_top_n: {}",
+        err_msg
+    );
+
+    clear_env();
+}
+
 #[test]
 #[serial]
 fn granularity_from_env() {
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use mdvdb::config::{Config, EmbeddingProviderType};
-use mdvdb::embedding::batch::{embed_chunks, Chunk};
+use mdvdb::embedding::batch::{embed_chunks, embed_chunks_cached, Chunk};
+use mdvdb::embedding::chunk_cache::ChunkEmbeddingCache;
 use mdvdb::embedding::mock::MockProvider;
 use mdvdb::embedding::provider::{create_provider, EmbeddingProvider};
 
@@ -42,6 +43,8 @@ fn base_config() -> Config {
         search_min_score: 0.0,
         search_default_mode: mdvdb::SearchMode::Hybrid,
         search_rrf_k: 60.0,
+        search_fusion: mdvdb::search::FusionMethod::Rrf,
+        search_semantic_weight: 0.7,
         bm25_norm_k: 1.5,
         search_decay_enabled: false,
         search_decay_half_life: 90.0,
@@ -51,8 +54,26 @@ fn base_config() -> Config {
         search_boost_hops: 1,
         search_expand_graph: 0,
         search_expand_limit: 3,
+        search_diversity: None,
+        search_max_per_file: None,
+        search_lexical_boosts: Default::default(),
+        search_lexical_language: Default::default(),
+        search_lexical_paths: Default::default(),
+        search_lexical_stopwords: false,
+        rerank_provider: mdvdb::config::RerankProviderType::None,
+        rerank_model: None,
+        rerank_endpoint: None,
+        rerank_top_n: 50,
+        query_cache_max_entries: 1000,
+        query_cache_ttl_secs: 604800,
+        chunk_embedding_cache: true,
         vector_quantization: mdvdb::VectorQuantization::F16,
         index_compression: true,
+        index_wal_max_bytes: 64 * 1024 * 1024,
+        index_wal_max_age_secs: 3600,
+        index_rescore_factor: 4,
+        index_snapshot_retention: 5,
+        index_snapshot_before_reindex: true,
         edge_embeddings: true,
         edge_boost_weight: 0.15,
         edge_cluster_rebalance: 50,
@@ -145,6 +166,113 @@ async fn test_embed_chunks_hash_skip() {
     assert_eq!(provider.call_count(), 1);
 }
 
+#[tokio::test]
+async fn test_embed_chunks_cached_reuses_identical_text() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let cache = ChunkEmbeddingCache::new(dir.path());
+    let provider = MockProvider::new(16);
+    let chunks = vec![
+        make_chunk("a.md#0", "a.md", "shared paragraph"),
+        make_chunk("a.md#1", "a.md", "only in a"),
+    ];
+    let existing = HashMap::new();
+    let current = HashMap::new();
+
+    let first = embed_chunks_cached(
+        &provider,
+        &chunks,
+        &existing,
+        &current,
+        10,
+        Some(&cache),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(first.cache_hits, 0);
+    assert_eq!(provider.call_count(), 1);
+
+    // A renamed file with one reused paragraph only embeds the new text.
+    let moved = vec![
+        make_chunk("b.md#0", "b.md", "shared paragraph"),
+        make_chunk("b.md#1", "b.md", "only in b"),
+    ];
+    let second = embed_chunks_cached(
+        &provider,
+        &moved,
+        &existing,
+        &current,
+        10,
+        Some(&cache),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(second.cache_hits, 1);
+    assert_eq!(second.api_calls, 1);
+    assert_eq!(second.embeddings["b.md#0"], first.embeddings["a.md#0"]);
+    assert_eq!(second.embeddings.len(), 2);
+
+    let spaces = cache.stats().unwrap();
+    assert_eq!(spaces.len(), 1);
+    assert_eq!(spaces[0].entries, 3);
+}
+
+#[tokio::test]
+async fn test_embed_chunks_cached_keeps_batches_that_succeeded_before_a_failure() {
+    use async_trait::async_trait;
+
+    /// Fails any batch containing "fail", after the other batches finish.
+    struct FlakyProvider;
+
+    #[async_trait]
+    impl EmbeddingProvider for FlakyProvider {
+        async fn embed_batch(&self, texts: &[String]) -> mdvdb::Result<Vec<Vec<f32>>> {
+            if texts.iter().any(|text| text.contains("fail")) {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                return Err(mdvdb::Error::EmbeddingProvider("503 unavailable".into()));
+            }
+            Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+
+        fn model(&self) -> &str {
+            "flaky-model"
+        }
+
+        fn name(&self) -> &str {
+            "flaky"
+        }
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let cache = ChunkEmbeddingCache::new(dir.path());
+    let chunks = vec![
+        make_chunk("a.md#0", "a.md", "fail here"),
+        make_chunk("a.md#1", "a.md", "first paragraph"),
+        make_chunk("a.md#2", "a.md", "second paragraph"),
+    ];
+
+    let result = embed_chunks_cached(
+        &FlakyProvider,
+        &chunks,
+        &HashMap::new(),
+        &HashMap::new(),
+        1,
+        Some(&cache),
+        None,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let spaces = cache.stats().unwrap();
+    assert_eq!(spaces.len(), 1);
+    assert_eq!(spaces[0].entries, 2, "both successful batches are cached");
+}
+
 #[test]
 fn test_provider_factory_from_config() {
     let mut config = base_config();
@@ -1,4 +1,4 @@
-use mdvdb::fts::{FtsChunkData, FtsIndex};
+use mdvdb::fts::{FtsChunkData, FtsIndex, LexicalLanguage, LexicalSettings};
 use tempfile::TempDir;
 
 // ---------------------------------------------------------------------------
@@ -285,3 +285,245 @@ fn test_fts_readonly_open_does_not_conflict_with_writer() {
         "read-only open must not hit the writer lock"
     );
 }
+
+fn syntax_fixture() -> (TempDir, FtsIndex) {
+    let (dir, fts) = create_fts_dir();
+    let chunks = vec![
+        chunk_data(
+            "bg.md#0",
+            "Blue green deployments swap traffic between two environments",
+            &["Deploy"],
+        ),
+        chunk_data(
+            "k8s.md#0",
+            "Kubernetes supports blue green rollouts with two services",
+            &["Cluster"],
+        ),
+        chunk_data(
+            "canary.md#0",
+            "Green fields and a blue sky over the deployment team",
+            &["Notes"],
+        ),
+    ];
+    for chunk in &chunks {
+        fts.upsert_chunks(&chunk.source_path, std::slice::from_ref(chunk))
+            .unwrap();
+    }
+    fts.commit().unwrap();
+    (dir, fts)
+}
+
+fn ids(fts: &FtsIndex, query: &str) -> Vec<String> {
+    let mut ids: Vec<String> = fts
+        .search(query, 10)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.chunk_id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_fts_spelling_suggestions_for_unknown_words() {
+    let (_dir, fts) = syntax_fixture();
+    let suggest = |query: &str| -> Vec<(String, String, u64)> {
+        fts.spelling_suggestions(query, 3)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.term, s.suggestion, s.doc_freq))
+            .collect()
+    };
+    let owned = |term: &str, suggestion: &str, doc_freq: u64| {
+        (term.to_string(), suggestion.to_string(), doc_freq)
+    };
+
+    assert_eq!(suggest("blue gren"), vec![owned("gren", "green", 3)]);
+    assert_eq!(
+        suggest("Kubernets rollouts"),
+        vec![owned("kubernets", "kubernetes", 1)]
+    );
+    // Ties in document frequency go to the closer word.
+    assert_eq!(
+        suggest("\"deploymnts\""),
+        vec![
+            owned("deploymnts", "deployments", 1),
+            owned("deploymnts", "deployment", 1)
+        ]
+    );
+    assert!(suggest("blue green").is_empty());
+    assert!(suggest("-kubernets blue").is_empty());
+    assert!(suggest("bleu*").is_empty());
+}
+
+#[test]
+fn test_fts_phrase_and_excluded_terms() {
+    let (_dir, fts) = syntax_fixture();
+
+    assert_eq!(ids(&fts, r#""blue green""#), vec!["bg.md#0", "k8s.md#0"]);
+    assert_eq!(ids(&fts, r#""blue green" -kubernetes"#), vec!["bg.md#0"]);
+    assert_eq!(ids(&fts, "+team blue"), vec!["canary.md#0"]);
+}
+
+#[test]
+fn test_fts_fuzzy_prefix_and_field_scope() {
+    let (_dir, fts) = syntax_fixture();
+
+    assert!(ids(&fts, "kubrenetes").is_empty());
+    assert_eq!(ids(&fts, "kubrenetes~1"), vec!["k8s.md#0"]);
+    assert_eq!(ids(&fts, "environ*"), vec!["bg.md#0"]);
+    assert_eq!(ids(&fts, "heading:deploy"), vec!["bg.md#0"]);
+    assert_eq!(ids(&fts, "content:deploy"), vec!["bg.md#0", "canary.md#0"]);
+}
+
+#[test]
+fn test_fts_invalid_syntax_is_a_typed_error() {
+    let (_dir, fts) = syntax_fixture();
+
+    match fts.search(r#"deploy "blue green"#, 10) {
+        Err(mdvdb::error::Error::LexicalQuery { position, .. }) => assert_eq!(position, 7),
+        other => panic!("expected LexicalQuery error, got {other:?}"),
+    }
+    assert!(matches!(
+        fts.search("deploy~5", 10),
+        Err(mdvdb::error::Error::LexicalQuery { position: 7, .. })
+    ));
+    // A `name:` followed by whitespace is ordinary text, not a field scope.
+    assert_eq!(
+        ids(&fts, "notes: deployment"),
+        vec!["bg.md#0", "canary.md#0"]
+    );
+}
+
+fn frontmatter_fixture(boosts: &[(&str, f32)]) -> (TempDir, FtsIndex) {
+    let dir = TempDir::new().unwrap();
+    let fts = FtsIndex::open_or_create(&dir.path().join("fts"))
+        .unwrap()
+        .with_field_boosts(boosts.iter().map(|(k, v)| (k.to_string(), *v)).collect());
+    let frontmatter = serde_json::json!({
+        "title": "Database tuning",
+        "tags": ["postgres", "performance", 3],
+        "author": "Jane Doe",
+        "draft": true,
+    });
+    fts.upsert_file(
+        "pg.md",
+        &[
+            chunk_data("pg.md#0", "Connection pooling tips", &["Pools"]),
+            chunk_data("pg.md#1", "Vacuum schedules", &["Maintenance"]),
+        ],
+        Some(&frontmatter),
+    )
+    .unwrap();
+    fts.upsert_chunks(
+        "compare.md",
+        &[chunk_data(
+            "compare.md#0",
+            "Postgres compared with MySQL for database tuning work",
+            &["Comparison"],
+        )],
+    )
+    .unwrap();
+    fts.commit().unwrap();
+    (dir, fts)
+}
+
+#[test]
+fn test_fts_frontmatter_values_are_searchable() {
+    let (_dir, fts) = frontmatter_fixture(&[]);
+
+    // "postgres" only appears in pg.md's tags, indexed with each chunk.
+    let pg = vec!["pg.md#0", "pg.md#1"];
+    assert_eq!(
+        ids(&fts, "postgres"),
+        vec!["compare.md#0", "pg.md#0", "pg.md#1"]
+    );
+    assert_eq!(ids(&fts, "tags:postgres"), pg);
+    assert_eq!(ids(&fts, "tags:postg*"), pg);
+    assert_eq!(ids(&fts, "tags:postgers~1"), pg);
+    assert_eq!(ids(&fts, r#"title:"database tuning""#), pg);
+    assert_eq!(ids(&fts, "author:jane"), pg);
+    assert_eq!(ids(&fts, "frontmatter:jane"), pg);
+    assert_eq!(ids(&fts, "postgres -tags:postgres"), vec!["compare.md#0"]);
+    // Non-text values and missing keys are not indexed.
+    assert!(ids(&fts, "tags:3").is_empty());
+    assert!(ids(&fts, "draft:true").is_empty());
+    assert!(ids(&fts, "tags:mysql").is_empty());
+    // Unknown keys are plain text rather than fields.
+    assert_eq!(ids(&fts, "pooling:tips"), vec!["pg.md#0"]);
+}
+
+#[test]
+fn test_fts_frontmatter_field_boosts_change_ranking() {
+    // Both pg.md chunks carry the same frontmatter, so compare files.
+    let top = |boosts: &[(&str, f32)], query: &str| {
+        let (_dir, fts) = frontmatter_fixture(boosts);
+        let chunk_id = fts.search(query, 10).unwrap()[0].chunk_id.clone();
+        chunk_id.split('#').next().unwrap().to_string()
+    };
+
+    assert_eq!(top(&[("title", 10.0)], "database tuning"), "pg.md");
+    assert_eq!(
+        top(
+            &[("frontmatter", 0.1), ("content", 10.0)],
+            "database tuning"
+        ),
+        "compare.md"
+    );
+    assert_eq!(top(&[("tags", 10.0)], "postgres"), "pg.md");
+}
+
+#[test]
+fn test_fts_frontmatter_clauses_apply_to_every_chunk() {
+    let (_dir, fts) = frontmatter_fixture(&[]);
+
+    // "vacuum" is only in pg.md's second chunk, "tips" only in its first.
+    assert_eq!(ids(&fts, "+tags:postgres +vacuum"), vec!["pg.md#1"]);
+    assert_eq!(ids(&fts, "+author:jane +tips"), vec!["pg.md#0"]);
+    assert!(ids(&fts, "vacuum -tags:postgres").is_empty());
+    assert!(ids(&fts, "schedules -author:jane").is_empty());
+    assert_eq!(ids(&fts, "database -tags:postgres"), vec!["compare.md#0"]);
+}
+
+fn language_fixture(settings: &LexicalSettings) -> (TempDir, FtsIndex) {
+    let dir = TempDir::new().unwrap();
+    let fts = FtsIndex::open_or_create_with_settings(&dir.path().join("fts"), settings).unwrap();
+    for chunk in [
+        chunk_data("de/haus.md#0", "Die alten Häuser stehen am Fluss", &[]),
+        chunk_data("en/house.md#0", "The old houses stand by the river", &[]),
+    ] {
+        fts.upsert_chunks(&chunk.source_path, std::slice::from_ref(&chunk))
+            .unwrap();
+    }
+    fts.commit().unwrap();
+    (dir, fts)
+}
+
+#[test]
+fn test_fts_path_language_override_uses_its_stemmer() {
+    let (_dir, fts) = language_fixture(&LexicalSettings::default());
+    assert!(ids(&fts, "haus").is_empty());
+
+    let settings = LexicalSettings {
+        paths: [("de".to_string(), LexicalLanguage::German)].into(),
+        ..Default::default()
+    };
+    let (_dir, fts) = language_fixture(&settings);
+    assert_eq!(ids(&fts, "haus"), vec!["de/haus.md#0"]);
+    assert_eq!(ids(&fts, "house"), vec!["en/house.md#0"]);
+    assert_eq!(ids(&fts, r#""old houses""#), vec!["en/house.md#0"]);
+}
+
+#[test]
+fn test_fts_stopwords_are_optional() {
+    let (_dir, fts) = language_fixture(&LexicalSettings::default());
+    assert_eq!(ids(&fts, "the"), vec!["en/house.md#0"]);
+
+    let settings = LexicalSettings {
+        stopwords: true,
+        ..Default::default()
+    };
+    let (_dir, fts) = language_fixture(&settings);
+    assert!(ids(&fts, "the").is_empty());
+    assert_eq!(ids(&fts, "+the river"), vec!["en/house.md#0"]);
+}
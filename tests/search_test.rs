@@ -2424,3 +2424,727 @@ async fn test_multihop_boost_reorders() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_reranker_reorders_and_rescores_results() {
+    use std::sync::Arc;
+
+    use mdvdb::rerank::mock::MockReranker;
+    use mdvdb::search::RerankOptions;
+
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let provider = mock_provider();
+
+    let file = fake_markdown_file("doc.md", "h1", None);
+    let chunks = vec![
+        Chunk {
+            id: "doc.md#0".into(),
+            source_path: PathBuf::from("doc.md"),
+            heading_hierarchy: vec!["Heading".to_string()],
+            content: "Rust programming language is fast and safe".into(),
+            start_line: 1,
+            end_line: 10,
+            chunk_index: 0,
+            is_sub_split: false,
+        },
+        Chunk {
+            id: "doc.md#1".into(),
+            source_path: PathBuf::from("doc.md"),
+            heading_hierarchy: vec!["Heading".to_string()],
+            content: "Python is great for data science".into(),
+            start_line: 11,
+            end_line: 20,
+            chunk_index: 1,
+            is_sub_split: false,
+        },
+    ];
+    index.upsert(&file, &chunks, &fake_embeddings(2)).unwrap();
+
+    let reranker = Arc::new(MockReranker::new());
+    let query = SearchQuery::new("python data science")
+        .with_limit(1)
+        .with_reranker(RerankOptions {
+            reranker: reranker.clone(),
+            top_n: 10,
+        });
+    let response = search(
+        &query,
+        &index,
+        &provider,
+        None,
+        60.0,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(reranker.call_count(), 1);
+    assert_eq!(response.results.len(), 1, "truncated to limit after rerank");
+    assert_eq!(response.results[0].chunk.chunk_id, "doc.md#1");
+    assert_eq!(response.results[0].score, 1.0);
+    assert!(response.timings.rerank_secs >= 0.0);
+}
+
+#[tokio::test]
+async fn test_failing_reranker_keeps_fused_ranking() {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use mdvdb::rerank::provider::Reranker;
+    use mdvdb::search::RerankOptions;
+
+    struct FailingReranker;
+
+    #[async_trait]
+    impl Reranker for FailingReranker {
+        async fn rerank(&self, _query: &str, _documents: &[String]) -> mdvdb::Result<Vec<f64>> {
+            Err(mdvdb::Error::Reranker("service unavailable".into()))
+        }
+
+        fn model(&self) -> &str {
+            "failing"
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let provider = mock_provider();
+    populate_index(&index, "doc.md", "h1", None, 2);
+
+    let query = SearchQuery::new("test query").with_limit(2);
+    let fused = search(
+        &query,
+        &index,
+        &provider,
+        None,
+        60.0,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap();
+    let query = query.with_reranker(RerankOptions {
+        reranker: Arc::new(FailingReranker),
+        top_n: 10,
+    });
+    let reranked = search(
+        &query,
+        &index,
+        &provider,
+        None,
+        60.0,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap();
+
+    let ranking = |response: &mdvdb::search::SearchResponse| {
+        response
+            .results
+            .iter()
+            .map(|r| (r.chunk.chunk_id.clone(), r.score))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(reranked.results.len(), 2);
+    assert_eq!(ranking(&reranked), ranking(&fused));
+}
+
+/// Linear fusion with semantic weight 0 reduces to normalized BM25, so its
+/// top hit and score match lexical mode exactly.
+#[tokio::test]
+async fn test_hybrid_linear_fusion_zero_weight_matches_lexical() {
+    use mdvdb::search::FusionMethod;
+
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let provider = mock_provider();
+
+    let fts_dir = _dir.path().join("fts");
+    let fts = FtsIndex::open_or_create(&fts_dir).unwrap();
+
+    let chunks = vec![
+        Chunk {
+            id: "doc.md#0".into(),
+            source_path: PathBuf::from("doc.md"),
+            heading_hierarchy: vec!["Heading".to_string()],
+            content: "Rust programming language is fast and safe for systems development".into(),
+            start_line: 1,
+            end_line: 10,
+            chunk_index: 0,
+            is_sub_split: false,
+        },
+        Chunk {
+            id: "doc.md#1".into(),
+            source_path: PathBuf::from("doc.md"),
+            heading_hierarchy: vec!["Heading".to_string()],
+            content: "Python is great for data science and machine learning tasks".into(),
+            start_line: 11,
+            end_line: 20,
+            chunk_index: 1,
+            is_sub_split: false,
+        },
+    ];
+    let embs = fake_embeddings(2);
+    populate_both(&index, &fts, "doc.md", "h1", None, &chunks, &embs);
+
+    let query_linear = SearchQuery::new("Rust programming")
+        .with_mode(SearchMode::Hybrid)
+        .with_fusion(FusionMethod::Linear)
+        .with_semantic_weight(0.0);
+    let linear_results = search(
+        &query_linear,
+        &index,
+        &provider,
+        Some(&fts),
+        60.0,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap()
+    .results;
+
+    let query_lexical = SearchQuery::new("Rust programming").with_mode(SearchMode::Lexical);
+    let lexical_results = search(
+        &query_lexical,
+        &index,
+        &provider,
+        Some(&fts),
+        60.0,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap()
+    .results;
+
+    assert_eq!(linear_results[0].chunk.chunk_id, "doc.md#0");
+    assert_eq!(lexical_results[0].chunk.chunk_id, "doc.md#0");
+    assert!((linear_results[0].score - lexical_results[0].score).abs() < 1e-9);
+    for r in &linear_results {
+        assert!((0.0..=1.0).contains(&r.score));
+    }
+}
+
+/// `search_similar` queries with a stored document vector (no provider is
+/// involved) and drops excluded seed chunks before assembly.
+#[tokio::test]
+async fn test_search_similar_uses_stored_vector_and_excludes_seed() {
+    use mdvdb::search::search_similar;
+    use std::collections::HashSet;
+
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+
+    populate_index(&index, "seed.md", "h1", None, 2);
+    populate_index(&index, "near.md", "h2", None, 1);
+    let far = fake_markdown_file("far.md", "h3", None);
+    let mut far_embedding = vec![0.0f32; DIMS];
+    far_embedding[5] = 1.0;
+    index
+        .upsert(&far, &fake_chunks("far.md", 1), &[far_embedding])
+        .unwrap();
+
+    let seed = index.get_file_vector("seed.md").unwrap();
+    assert!((seed[0] - 0.5).abs() < 1e-6 && (seed[1] - 0.5).abs() < 1e-6);
+    assert!(index.get_file_vector("missing.md").is_none());
+
+    let query = SearchQuery::new("seed.md").with_limit(5);
+    let similar = |exclude: HashSet<String>| {
+        let query = query.clone();
+        let seed = seed.clone();
+        let index = &index;
+        async move {
+            search_similar(
+                &query,
+                index,
+                seed,
+                &exclude,
+                false,
+                90.0,
+                &[],
+                &[],
+                false,
+                1,
+                0,
+                3,
+                0.15,
+            )
+            .await
+            .unwrap()
+            .results
+        }
+    };
+
+    let all = similar(HashSet::new()).await;
+    assert!(all.iter().any(|r| r.file.path == "seed.md"));
+
+    let exclude: HashSet<String> = ["seed.md#0".to_string(), "seed.md#1".to_string()].into();
+    let results = similar(exclude).await;
+    assert!(results.iter().all(|r| r.file.path != "seed.md"));
+    assert_eq!(results[0].file.path, "near.md");
+}
+
+/// In hybrid RRF mode the explanation carries both raw signals and their RRF
+/// terms, and the retrieval score is their normalized sum.
+#[tokio::test]
+async fn test_explain_hybrid_rrf_breakdown() {
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let provider = mock_provider();
+    let fts = FtsIndex::open_or_create(&_dir.path().join("fts")).unwrap();
+    populate_both(
+        &index,
+        &fts,
+        "doc.md",
+        "h1",
+        None,
+        &fake_chunks("doc.md", 3),
+        &fake_embeddings(3),
+    );
+
+    let rrf_k = 60.0;
+    let query = SearchQuery::new("Chunk content")
+        .with_mode(SearchMode::Hybrid)
+        .with_explain(true);
+    let results = search(
+        &query,
+        &index,
+        &provider,
+        Some(&fts),
+        rrf_k,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap()
+    .results;
+
+    assert!(!results.is_empty());
+    for result in &results {
+        let explain = result.explain.as_ref().expect("explain requested");
+        assert!(explain.semantic_score.is_some() && explain.semantic_rank.is_some());
+        assert!(explain.bm25_score.unwrap() > 0.0);
+        let rrf = explain.rrf_semantic.unwrap() + explain.rrf_lexical.unwrap();
+        assert!((explain.retrieval_score - rrf / (2.0 / (rrf_k + 1.0))).abs() < 1e-9);
+        assert!(explain.decay_multiplier.is_none());
+        assert!(explain.link_boost.is_none());
+        assert_eq!(explain.final_score, result.score);
+    }
+
+    // Without the flag nothing is attached.
+    let plain = search(
+        &SearchQuery::new("Chunk content").with_mode(SearchMode::Hybrid),
+        &index,
+        &provider,
+        Some(&fts),
+        rrf_k,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap()
+    .results;
+    assert!(plain.iter().all(|r| r.explain.is_none()));
+}
+
+/// Link-boosted results report the hop distance, the boosting source file,
+/// and a delta that accounts for the score change.
+#[tokio::test]
+async fn test_explain_reports_link_boost_and_decay() {
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let provider = mock_provider();
+
+    // hub.md links to every leaf, so whichever three files rank highest, at
+    // least one remaining result is a 1-hop neighbor of a seed.
+    let leaves = ["a.md", "b.md", "c.md", "d.md"];
+    let mut files = vec![populate_index_with_links(
+        &index, "hub.md", "h0", None, 1, &leaves,
+    )];
+    for (i, leaf) in leaves.iter().enumerate() {
+        files.push(populate_index_with_links(
+            &index,
+            leaf,
+            &format!("h{}", i + 1),
+            None,
+            1,
+            &[],
+        ));
+    }
+    let graph = links::build_link_graph(&files, &mdvdb::relations::RelationContext::empty());
+    index.update_link_graph(Some(graph));
+
+    let query = SearchQuery::new("test query")
+        .with_boost_links(true)
+        .with_boost_hops(1)
+        .with_decay(true)
+        .with_explain(true);
+    let results = search(
+        &query,
+        &index,
+        &provider,
+        None,
+        60.0,
+        1.5,
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap()
+    .results;
+
+    let paths: Vec<&str> = results.iter().map(|r| r.file.path.as_str()).collect();
+    let mut boosted = 0;
+    for result in &results {
+        let explain = result.explain.as_ref().unwrap();
+        let multiplier = explain.decay_multiplier.expect("decay enabled");
+        assert!(multiplier > 0.0 && multiplier <= 1.0);
+        let decayed = explain.retrieval_score * multiplier;
+        match &explain.link_boost {
+            Some(boost) => {
+                boosted += 1;
+                assert_eq!(boost.hops, 1);
+                assert_ne!(boost.source, result.file.path);
+                assert!(paths.contains(&boost.source.as_str()));
+                assert!(
+                    (boost.edge_boost - 0.15).abs() < 1e-9,
+                    "flat boost without edges"
+                );
+                assert!((decayed + boost.delta - result.score).abs() < 1e-9);
+            }
+            None => assert!((decayed - result.score).abs() < 1e-9),
+        }
+        assert_eq!(explain.final_score, result.score);
+    }
+    assert!(boosted > 0, "at least one result should be link-boosted");
+}
+
+/// A file with an intro chunk, one heading section sub-split into three
+/// overlapping chunks, and a trailing section. Every chunk shares the same
+/// embedding so all of them match a seed vector equally.
+fn populate_sectioned_file(index: &Index) -> Vec<f32> {
+    let chunk = |i: usize, heading: &str, lines: (usize, usize), sub_split: bool| Chunk {
+        id: format!("guide.md#{i}"),
+        source_path: PathBuf::from("guide.md"),
+        heading_hierarchy: vec!["Guide".to_string(), heading.to_string()],
+        content: format!("Chunk {i} of the {heading} section"),
+        start_line: lines.0,
+        end_line: lines.1,
+        chunk_index: i,
+        is_sub_split: sub_split,
+    };
+    let chunks = vec![
+        chunk(0, "Intro", (1, 3), false),
+        chunk(1, "Setup", (4, 10), true),
+        chunk(2, "Setup", (10, 16), true),
+        chunk(3, "Setup", (16, 20), true),
+        chunk(4, "Usage", (21, 25), false),
+    ];
+    let mut embedding = vec![0.0f32; DIMS];
+    embedding[0] = 1.0;
+    let file = fake_markdown_file("guide.md", "h1", None);
+    index
+        .upsert(&file, &chunks, &vec![embedding.clone(); chunks.len()])
+        .unwrap();
+    embedding
+}
+
+async fn similar_results(
+    index: &Index,
+    query: &SearchQuery,
+    seed: Vec<f32>,
+) -> Vec<mdvdb::search::SearchResult> {
+    mdvdb::search::search_similar(
+        query,
+        index,
+        seed,
+        &std::collections::HashSet::new(),
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap()
+    .results
+}
+
+/// Parent mode widens sub-split fragments to their heading section and
+/// collapses sibling fragments into a single result.
+#[tokio::test]
+async fn test_parent_mode_returns_full_section() {
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let seed = populate_sectioned_file(&index);
+
+    let query = SearchQuery::new("guide").with_parent(true);
+    let results = similar_results(&index, &query, seed).await;
+
+    assert_eq!(
+        results.len(),
+        3,
+        "three sub-splits collapse into one section"
+    );
+    let section: Vec<_> = results
+        .iter()
+        .filter(|r| !r.chunk.section_chunk_ids.is_empty())
+        .collect();
+    assert_eq!(section.len(), 1);
+    let section = &section[0].chunk;
+    assert_eq!(
+        section.section_chunk_ids,
+        vec!["guide.md#1", "guide.md#2", "guide.md#3"]
+    );
+    assert_eq!((section.start_line, section.end_line), (4, 20));
+    for i in 1..=3 {
+        assert!(section.content.contains(&format!("Chunk {i} of the Setup")));
+    }
+    for r in results
+        .iter()
+        .filter(|r| r.chunk.heading_hierarchy[1] != "Setup")
+    {
+        assert!(r.chunk.section_chunk_ids.is_empty());
+        assert!(!r.chunk.content.contains("Setup"));
+    }
+}
+
+/// The window attaches neighboring chunks by chunk index, around the whole
+/// section when parent mode widened the result.
+#[tokio::test]
+async fn test_window_attaches_neighbor_chunks() {
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+    let seed = populate_sectioned_file(&index);
+    let ids = |chunks: &[mdvdb::search::SearchResultChunk]| -> Vec<String> {
+        chunks.iter().map(|c| c.chunk_id.clone()).collect()
+    };
+
+    let query = SearchQuery::new("guide").with_window(1);
+    let results = similar_results(&index, &query, seed.clone()).await;
+    let middle = results
+        .iter()
+        .find(|r| r.chunk.chunk_id == "guide.md#2")
+        .unwrap();
+    assert_eq!(ids(&middle.chunk.before), vec!["guide.md#1"]);
+    assert_eq!(ids(&middle.chunk.after), vec!["guide.md#3"]);
+    let first = results
+        .iter()
+        .find(|r| r.chunk.chunk_id == "guide.md#0")
+        .unwrap();
+    assert!(first.chunk.before.is_empty());
+
+    let query = SearchQuery::new("guide").with_window(2).with_parent(true);
+    let results = similar_results(&index, &query, seed.clone()).await;
+    let section = results
+        .iter()
+        .find(|r| !r.chunk.section_chunk_ids.is_empty())
+        .unwrap();
+    assert_eq!(ids(&section.chunk.before), vec!["guide.md#0"]);
+    assert_eq!(ids(&section.chunk.after), vec!["guide.md#4"]);
+
+    // An oversized window stops at the file's last chunk instead of probing
+    // every index up to `usize::MAX`.
+    let query = SearchQuery::new("guide").with_window(usize::MAX);
+    let results = similar_results(&index, &query, seed).await;
+    let middle = results
+        .iter()
+        .find(|r| r.chunk.chunk_id == "guide.md#2")
+        .unwrap();
+    assert_eq!(ids(&middle.chunk.before), vec!["guide.md#0", "guide.md#1"]);
+    assert_eq!(ids(&middle.chunk.after), vec!["guide.md#3", "guide.md#4"]);
+}
+
+/// A selective metadata filter is resolved before vector retrieval, so a
+/// matching chunk ranked far below the over-fetch window is still found.
+#[tokio::test]
+async fn test_selective_filter_uses_exact_prefiltered_scan() {
+    use mdvdb::search::VectorStrategy;
+
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+
+    let mut near = vec![0.0f32; DIMS];
+    near[0] = 1.0;
+    for i in 0..20 {
+        let name = format!("near{i}.md");
+        let file = fake_markdown_file(&name, &format!("h{i}"), Some(json!({"status": "draft"})));
+        index
+            .upsert(&file, &fake_chunks(&name, 1), &[near.clone()])
+            .unwrap();
+    }
+    let mut far = vec![0.0f32; DIMS];
+    far[0] = 0.1;
+    far[1] = 1.0;
+    let file = fake_markdown_file("far.md", "hf", Some(json!({"status": "published"})));
+    index
+        .upsert(&file, &fake_chunks("far.md", 1), &[far])
+        .unwrap();
+
+    let query = SearchQuery::new("status")
+        .with_limit(1)
+        .with_filter(MetadataFilter::Equals {
+            field: "status".into(),
+            value: json!("published"),
+        });
+    let response = mdvdb::search::search_similar(
+        &query,
+        &index,
+        near,
+        &std::collections::HashSet::new(),
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.results.len(), 1);
+    assert_eq!(response.results[0].file.path, "far.md");
+    assert_eq!(
+        response.timings.vector_strategy,
+        Some(VectorStrategy::Exact)
+    );
+    let selectivity = response.timings.filter_selectivity.unwrap();
+    assert!((selectivity - 1.0 / 21.0).abs() < 1e-9);
+}
+
+/// Facets count every file passing the filters, not just the returned page.
+#[tokio::test]
+async fn test_facets_count_filtered_files_beyond_limit() {
+    let (_dir, path) = create_index_dir();
+    let index = Index::create(&path, &test_config()).unwrap();
+
+    let mut embedding = vec![0.0f32; DIMS];
+    embedding[0] = 1.0;
+    let docs = [
+        (
+            "a.md",
+            json!({"status": "published", "tags": ["rust", "cli"]}),
+        ),
+        ("b.md", json!({"status": "published", "tags": ["rust"]})),
+        ("c.md", json!({"status": "draft", "tags": []})),
+        ("d.md", json!({"status": "archived", "tags": ["rust"]})),
+    ];
+    for (name, frontmatter) in docs {
+        let file = fake_markdown_file(name, name, Some(frontmatter));
+        let chunks = fake_chunks(name, 2);
+        index
+            .upsert(&file, &chunks, &vec![embedding.clone(); chunks.len()])
+            .unwrap();
+    }
+
+    let query = SearchQuery::new("status")
+        .with_limit(1)
+        .with_filter(MetadataFilter::Not(Box::new(MetadataFilter::Equals {
+            field: "status".into(),
+            value: json!("archived"),
+        })))
+        .with_facets(vec!["status".into(), "tags".into()]);
+    let response = mdvdb::search::search_similar(
+        &query,
+        &index,
+        embedding,
+        &std::collections::HashSet::new(),
+        false,
+        90.0,
+        &[],
+        &[],
+        false,
+        1,
+        0,
+        3,
+        0.15,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.results.len(), 1);
+    let status = &response.facets["status"];
+    assert_eq!(status["published"], 2);
+    assert_eq!(status["draft"], 1);
+    assert!(!status.contains_key("archived"));
+    assert_eq!(response.facets["tags"]["rust"], 2);
+    assert_eq!(response.facets["tags"]["cli"], 1);
+}